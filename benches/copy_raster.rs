@@ -0,0 +1,73 @@
+#[macro_use]
+extern crate criterion;
+
+use criterion::Criterion;
+use pix::rgb::Rgba8p;
+use pix::Raster;
+
+// Full-width regions are copied as one contiguous slice (faster, especially
+// for small rasters)
+fn copy_full_width(c: &mut Criterion, sz: u32) {
+    let s = format!("copy_full_width_{}", sz);
+    c.bench_function(&s, move |b| {
+        let mut r = Raster::<Rgba8p>::with_clear(sz, sz);
+        let src = Raster::with_color(sz, sz, Rgba8p::new(1, 2, 3, 4));
+        b.iter(|| r.copy_raster((), &src, ()))
+    });
+}
+
+// Offset regions are copied one row at a time
+fn copy_offset(c: &mut Criterion, sz: u32) {
+    let s = format!("copy_offset_{}", sz);
+    c.bench_function(&s, move |b| {
+        let mut r = Raster::<Rgba8p>::with_clear(sz + 1, sz);
+        let src = Raster::with_color(sz, sz, Rgba8p::new(1, 2, 3, 4));
+        b.iter(|| r.copy_raster((1, 0, sz, sz), &src, ()))
+    });
+}
+
+// A 4K frame, which is large enough to be limited by memory bandwidth
+fn copy_4k(c: &mut Criterion) {
+    c.bench_function("copy_4k", move |b| {
+        let mut r = Raster::<Rgba8p>::with_clear(3840, 2160);
+        let src = Raster::with_color(3840, 2160, Rgba8p::new(1, 2, 3, 4));
+        b.iter(|| r.copy_raster((), &src, ()))
+    });
+}
+
+// Offset 4K copy, one row at a time
+fn copy_offset_4k(c: &mut Criterion) {
+    c.bench_function("copy_offset_4k", move |b| {
+        let mut r = Raster::<Rgba8p>::with_clear(3841, 2160);
+        let src = Raster::with_color(3840, 2160, Rgba8p::new(1, 2, 3, 4));
+        b.iter(|| r.copy_raster((1, 0, 3840, 2160), &src, ()))
+    });
+}
+
+fn copy_full_width_16(c: &mut Criterion) {
+    copy_full_width(c, 16);
+}
+
+fn copy_full_width_256(c: &mut Criterion) {
+    copy_full_width(c, 256);
+}
+
+fn copy_offset_16(c: &mut Criterion) {
+    copy_offset(c, 16);
+}
+
+fn copy_offset_256(c: &mut Criterion) {
+    copy_offset(c, 256);
+}
+
+criterion_group!(
+    benches,
+    copy_full_width_16,
+    copy_full_width_256,
+    copy_offset_16,
+    copy_offset_256,
+    copy_4k,
+    copy_offset_4k,
+);
+
+criterion_main!(benches);
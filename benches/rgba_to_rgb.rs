@@ -13,6 +13,15 @@ fn rgba_to_rgb(c: &mut Criterion, sz: u32) {
     });
 }
 
+// Copying channels directly is about twice as fast as `with_raster`
+fn drop_alpha(c: &mut Criterion, sz: u32) {
+    let s = format!("drop_alpha_{}", sz);
+    c.bench_function(&s, move |b| {
+        let r = Raster::<Rgba8p>::with_clear(sz, sz);
+        b.iter(|| r.drop_alpha::<Rgb8>())
+    });
+}
+
 fn rgba_to_rgb_16(c: &mut Criterion) {
     rgba_to_rgb(c, 16);
 }
@@ -21,6 +30,20 @@ fn rgba_to_rgb_256(c: &mut Criterion) {
     rgba_to_rgb(c, 256);
 }
 
-criterion_group!(benches, rgba_to_rgb_16, rgba_to_rgb_256,);
+fn drop_alpha_16(c: &mut Criterion) {
+    drop_alpha(c, 16);
+}
+
+fn drop_alpha_256(c: &mut Criterion) {
+    drop_alpha(c, 256);
+}
+
+criterion_group!(
+    benches,
+    rgba_to_rgb_16,
+    rgba_to_rgb_256,
+    drop_alpha_16,
+    drop_alpha_256,
+);
 
 criterion_main!(benches);
@@ -0,0 +1,34 @@
+#[macro_use]
+extern crate criterion;
+
+use criterion::Criterion;
+use pix::rgb::{Rgba16p, Rgba8p};
+use pix::Raster;
+
+fn same_format(c: &mut Criterion, sz: u32) {
+    let s = format!("with_raster_same_{}", sz);
+    c.bench_function(&s, move |b| {
+        let r = Raster::<Rgba8p>::with_clear(sz, sz);
+        b.iter(|| Raster::<Rgba8p>::with_raster(&r))
+    });
+}
+
+fn bit_depth(c: &mut Criterion, sz: u32) {
+    let s = format!("with_raster_bit_depth_{}", sz);
+    c.bench_function(&s, move |b| {
+        let r = Raster::<Rgba8p>::with_clear(sz, sz);
+        b.iter(|| Raster::<Rgba16p>::with_raster(&r))
+    });
+}
+
+fn same_format_256(c: &mut Criterion) {
+    same_format(c, 256);
+}
+
+fn bit_depth_256(c: &mut Criterion) {
+    bit_depth(c, 256);
+}
+
+criterion_group!(benches, same_format_256, bit_depth_256);
+
+criterion_main!(benches);
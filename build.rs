@@ -9,37 +9,40 @@ use std::path::Path;
 
 include!("src/srgb_gamma.rs");
 
-/// Create sRGB gamma look-up tables
-fn gamma_lut() {
-    let out_dir = env::var_os("OUT_DIR").unwrap();
-    let dest_path = Path::new(&out_dir).join("gamma_lut.rs");
-    let mut w = BufWriter::new(File::create(dest_path).unwrap());
-    writeln!(w, "const ENCODE_SRGB_U8: &[u8] = &[").unwrap();
-    for i in 0..256 {
-        if i % 8 == 0 {
-            write!(w, "    ").unwrap();
-        }
-        let s = i as f32 / 255.0;
-        let v = (srgb_gamma_encode(s) * 255.0).round() as u8;
-        write!(w, "0x{v:02X?}, ").unwrap();
-        if i % 8 == 7 {
-            writeln!(w).unwrap();
-        }
-    }
-    writeln!(w, "];").unwrap();
-    writeln!(w, "const DECODE_SRGB_U8: &[u8] = &[").unwrap();
-    for i in 0..256 {
+/// Write one look-up table
+fn write_lut<W: Write>(
+    w: &mut W,
+    name: &str,
+    ty: &str,
+    max: f64,
+    gamma: fn(f64) -> f64,
+) {
+    let digits = if ty == "u8" { 2 } else { 4 };
+    let len = max as usize + 1;
+    writeln!(w, "const {name}: &[{ty}] = &[").unwrap();
+    for i in 0..len {
         if i % 8 == 0 {
             write!(w, "    ").unwrap();
         }
-        let s = i as f32 / 255.0;
-        let v = (srgb_gamma_decode(s) * 255.0).round() as u8;
-        write!(w, "0x{v:02X?}, ").unwrap();
+        let s = i as f64 / max;
+        let v = (gamma(s) * max).round() as u32;
+        write!(w, "0x{v:0digits$X?}, ").unwrap();
         if i % 8 == 7 {
             writeln!(w).unwrap();
         }
     }
     writeln!(w, "];").unwrap();
+}
+
+/// Create sRGB gamma look-up tables
+fn gamma_lut() {
+    let out_dir = env::var_os("OUT_DIR").unwrap();
+    let dest_path = Path::new(&out_dir).join("gamma_lut.rs");
+    let mut w = BufWriter::new(File::create(dest_path).unwrap());
+    write_lut(&mut w, "ENCODE_SRGB_U8", "u8", 255.0, srgb_encode_f64);
+    write_lut(&mut w, "DECODE_SRGB_U8", "u8", 255.0, srgb_decode_f64);
+    write_lut(&mut w, "ENCODE_SRGB_U16", "u16", 65535.0, srgb_encode_f64);
+    write_lut(&mut w, "DECODE_SRGB_U16", "u16", 65535.0, srgb_decode_f64);
 
     println!("cargo:rerun-if-changed=src/srgb_gamma.rs");
 }
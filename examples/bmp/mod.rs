@@ -45,9 +45,8 @@ fn write_info_header(buf: &mut Vec<u8>, raster: &Raster<SBgr8>) {
 
 fn write_pixel_data(buf: &mut Vec<u8>, raster: &Raster<SBgr8>) {
     let padding = (4 - 3 * raster.width() % 4) % 4;
-    let mut rows: Vec<_> = raster.rows(()).collect();
-    rows.reverse();
-    for row in rows {
+    // BMP rows are stored bottom-to-top
+    for row in raster.rows(()).rev() {
         for p in row {
             buf.push(u8::from(Bgr::blue(*p)));
             buf.push(u8::from(Bgr::green(*p)));
@@ -9,7 +9,7 @@ fn main() -> Result<(), io::Error> {
     let mut r = Raster::with_clear(16, 16);
     for y in 0..16 {
         for x in 0..16 {
-            if x + y & 1 != 0 {
+            if (x + y) & 1 != 0 {
                 *r.pixel_mut(x, y) = v;
             }
         }
@@ -0,0 +1,295 @@
+// argb.rs      ARGB color model.
+//
+// Copyright (c) 2024  Douglas P Lau
+//
+//! ARGB color model and types.
+use crate::chan::{Ch16, Ch32, Ch8, Linear, Premultiplied, Srgb, Straight};
+use crate::el::{Pix4, PixRgba, Pixel};
+use crate::ColorModel;
+use core::ops::Range;
+
+/// ARGB arrangement of [RGB] [color model].
+///
+/// The components are *[alpha]*, *[red]*, *[green]* and *[blue]*.  Unlike
+/// other color models, *alpha* is first, so there are no opaque formats.
+///
+/// [alpha]: ../el/trait.Pixel.html#method.alpha
+/// [blue]: #method.blue
+/// [color model]: ../trait.ColorModel.html
+/// [green]: #method.green
+/// [red]: #method.red
+/// [rgb]: ../rgb/struct.Rgb.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Argb {}
+
+impl Argb {
+    /// Get the *red* component.
+    ///
+    /// # Example: Get ARGB Red
+    /// ```
+    /// use pix::argb::{Argb, Argb8};
+    /// use pix::chan::Ch8;
+    ///
+    /// let p = Argb8::new(0xFF, 0x93, 0x80, 0xA0);
+    /// assert_eq!(Argb::red(p), Ch8::new(0x93));
+    /// ```
+    pub fn red<P>(p: P) -> P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.two()
+    }
+
+    /// Get a mutable reference to the *red* component.
+    ///
+    /// # Example: Modify ARGB Red
+    /// ```
+    /// use pix::argb::{Argb, Argb8};
+    /// use pix::chan::Ch8;
+    ///
+    /// let mut p = Argb8::new(0xFF, 0x88, 0x77, 0x66);
+    /// *Argb::red_mut(&mut p) = 0x55.into();
+    /// assert_eq!(Argb::red(p), Ch8::new(0x55));
+    /// ```
+    pub fn red_mut<P>(p: &mut P) -> &mut P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.two_mut()
+    }
+
+    /// Get the *green* component.
+    ///
+    /// # Example: ARGB Green
+    /// ```
+    /// use pix::argb::{Argb, Argb16};
+    /// use pix::chan::Ch16;
+    ///
+    /// let p = Argb16::new(0xFFFF, 0x2000, 0x1234, 0x8000);
+    /// assert_eq!(Argb::green(p), Ch16::new(0x1234));
+    /// ```
+    pub fn green<P>(p: P) -> P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.three()
+    }
+
+    /// Get a mutable reference to the *green* component.
+    ///
+    /// # Example: Modify ARGB Green
+    /// ```
+    /// use pix::argb::{Argb, Argb16};
+    /// use pix::chan::Ch16;
+    ///
+    /// let mut p = Argb16::new(0xFFFF, 0x2000, 0x1234, 0x8000);
+    /// *Argb::green_mut(&mut p) = 0x4321.into();
+    /// assert_eq!(Argb::green(p), Ch16::new(0x4321));
+    /// ```
+    pub fn green_mut<P>(p: &mut P) -> &mut P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.three_mut()
+    }
+
+    /// Get the *blue* component.
+    ///
+    /// # Example: ARGB Blue
+    /// ```
+    /// use pix::argb::{Argb, Argb32};
+    /// use pix::chan::Ch32;
+    ///
+    /// let p = Argb32::new(1.0, 0.25, 0.5, 0.75);
+    /// assert_eq!(Argb::blue(p), Ch32::new(0.75));
+    /// ```
+    pub fn blue<P>(p: P) -> P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.four()
+    }
+
+    /// Get a mutable reference to the *blue* component.
+    ///
+    /// # Example: Modify ARGB Blue
+    /// ```
+    /// use pix::argb::{Argb, Argb32};
+    /// use pix::chan::Ch32;
+    ///
+    /// let mut p = Argb32::new(1.0, 0.25, 0.5, 0.75);
+    /// *Argb::blue_mut(&mut p) = Ch32::new(0.125);
+    /// assert_eq!(Argb::blue(p), Ch32::new(0.125));
+    /// ```
+    pub fn blue_mut<P>(p: &mut P) -> &mut P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.four_mut()
+    }
+}
+
+impl ColorModel for Argb {
+    const CIRCULAR: Range<usize> = 0..0;
+    const LINEAR: Range<usize> = 1..4;
+    const ALPHA: usize = 0;
+    const NAME: &'static str = "rgb";
+    const CHANNEL_NAMES: &'static [&'static str] =
+        &["alpha", "red", "green", "blue"];
+
+    /// Convert into *red*, *green*, *blue* and *alpha* components
+    fn into_rgba<P>(p: P) -> PixRgba<P>
+    where
+        P: Pixel<Model = Self>,
+    {
+        let red = Argb::red(p);
+        let green = Argb::green(p);
+        let blue = Argb::blue(p);
+        PixRgba::<P>::new::<P::Chan>(red, green, blue, p.alpha())
+    }
+
+    /// Convert from *red*, *green*, *blue* and *alpha* components
+    fn from_rgba<P>(rgba: PixRgba<P>) -> P
+    where
+        P: Pixel<Model = Self>,
+    {
+        let chan = rgba.channels();
+        let red = chan[0];
+        let green = chan[1];
+        let blue = chan[2];
+        let alpha = chan[3];
+        P::from_channels(&[alpha, red, green, blue])
+    }
+}
+
+/// [Argb](struct.Argb.html) 8-bit [straight](../chan/struct.Straight.html)
+/// alpha [linear](../chan/struct.Linear.html)
+/// gamma [pixel](../el/trait.Pixel.html) format.
+pub type Argb8 = Pix4<Ch8, Argb, Straight, Linear>;
+
+/// [Argb](struct.Argb.html) 16-bit [straight](../chan/struct.Straight.html)
+/// alpha [linear](../chan/struct.Linear.html) gamma
+/// [pixel](../el/trait.Pixel.html) format.
+pub type Argb16 = Pix4<Ch16, Argb, Straight, Linear>;
+
+/// [Argb](struct.Argb.html) 32-bit [straight](../chan/struct.Straight.html)
+/// alpha [linear](../chan/struct.Linear.html) gamma
+/// [pixel](../el/trait.Pixel.html) format.
+pub type Argb32 = Pix4<Ch32, Argb, Straight, Linear>;
+
+/// [Argb](struct.Argb.html) 8-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Argb8p = Pix4<Ch8, Argb, Premultiplied, Linear>;
+
+/// [Argb](struct.Argb.html) 16-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Argb16p = Pix4<Ch16, Argb, Premultiplied, Linear>;
+
+/// [Argb](struct.Argb.html) 32-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Argb32p = Pix4<Ch32, Argb, Premultiplied, Linear>;
+
+/// [Argb](struct.Argb.html) 8-bit [straight](../chan/struct.Straight.html)
+/// alpha [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type SArgb8 = Pix4<Ch8, Argb, Straight, Srgb>;
+
+/// [Argb](struct.Argb.html) 16-bit [straight](../chan/struct.Straight.html)
+/// alpha [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type SArgb16 = Pix4<Ch16, Argb, Straight, Srgb>;
+
+/// [Argb](struct.Argb.html) 32-bit [straight](../chan/struct.Straight.html)
+/// alpha [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type SArgb32 = Pix4<Ch32, Argb, Straight, Srgb>;
+
+/// [Argb](struct.Argb.html) 8-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type SArgb8p = Pix4<Ch8, Argb, Premultiplied, Srgb>;
+
+/// [Argb](struct.Argb.html) 16-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type SArgb16p = Pix4<Ch16, Argb, Premultiplied, Srgb>;
+
+/// [Argb](struct.Argb.html) 32-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type SArgb32p = Pix4<Ch32, Argb, Premultiplied, Srgb>;
+
+#[cfg(test)]
+mod test {
+    use crate::argb::*;
+    use crate::chan::{Ch8, Channel};
+    use crate::el::{ChannelKind, Pixel};
+    use crate::ops::SrcOver;
+    use crate::rgb::*;
+    use crate::Raster;
+
+    #[test]
+    fn rgba_reshuffle() {
+        for v in 0..=255u8 {
+            let (a, r, g, b) = (v, v ^ 0x5A, v.wrapping_mul(7), !v);
+            let argb = Argb8::new(a, r, g, b);
+            let rgba = Rgba8::new(r, g, b, a);
+            assert_eq!(argb.convert::<Rgba8>(), rgba);
+            assert_eq!(rgba.convert::<Argb8>(), argb);
+            let argb = SArgb8p::new(a, r, g, b);
+            let rgba = SRgba8p::new(r, g, b, a);
+            assert_eq!(argb.convert::<SRgba8p>(), rgba);
+            assert_eq!(rgba.convert::<SArgb8p>(), argb);
+        }
+        let r0 =
+            Raster::<Rgba8>::with_u8_buffer(2, 1, [1, 2, 3, 4, 5, 6, 7, 8]);
+        let r1 = Raster::<Argb8>::with_raster(&r0);
+        assert_eq!(r1.as_u8_slice(), [4, 1, 2, 3, 8, 5, 6, 7]);
+        assert_eq!(
+            Raster::<Rgba8>::with_raster(&r1).as_u8_slice(),
+            r0.as_u8_slice()
+        );
+    }
+
+    #[test]
+    fn alpha_first() {
+        let p = Argb8::new(0x80, 0x10, 0x20, 0x30);
+        assert_eq!(u8::from(p.alpha()), 0x80);
+        assert_eq!(Argb8::channel_layout()[0], ChannelKind::Alpha);
+        assert_eq!(format!("{p}"), "argb(128, 16, 32, 48)");
+        // alpha is encoded / decoded without touching the first channel
+        let p: Argb8p = p.convert();
+        assert_eq!(p, Argb8p::new(0x80, 0x08, 0x10, 0x18));
+        assert!(p.is_premultiplied_valid());
+        let p: Argb8 = p.convert();
+        assert_eq!(p, Argb8::new(0x80, 0x10, 0x20, 0x30));
+        let p = Argb8::new(0x40, 0xFF, 0x00, 0x80).map_color(|c| Ch8::MAX - c);
+        assert_eq!(p, Argb8::new(0x40, 0x00, 0xFF, 0x7F));
+    }
+
+    #[test]
+    fn composite() {
+        let dst = Rgba8p::new(0x20, 0x40, 0x60, 0x80);
+        let src = Rgba8p::new(0x40, 0x00, 0x20, 0x40);
+        let mut r0 = Raster::with_color(2, 2, dst);
+        r0.composite_color((), src, SrcOver);
+        let mut r1 = Raster::<Argb8p>::with_color(2, 2, dst.convert());
+        r1.composite_color((), src.convert(), SrcOver);
+        assert_eq!(r1.pixel(1, 1).convert::<Rgba8p>(), r0.pixel(1, 1));
+        let m = Raster::<Argb8p>::with_color(2, 2, src.convert());
+        let mut r2 = Raster::<Argb8p>::with_color(2, 2, dst.convert());
+        r2.composite_raster((), &m, (), SrcOver);
+        assert_eq!(r2.pixel(0, 0), r1.pixel(0, 0));
+        let h = r2.halve(crate::Light::Native);
+        assert_eq!(h.pixel(0, 0), r2.pixel(0, 0));
+    }
+}
@@ -0,0 +1,196 @@
+// atlas.rs     Texture atlas packing
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use crate::el::Pixel;
+use crate::raster::{Raster, Region};
+
+/// Texture atlas, packing small tiles into one `Raster`.
+///
+/// Tiles are placed left-to-right on horizontal shelves.  A new shelf is
+/// started below the tallest tile of the current shelf when a tile does not
+/// fit in the remaining width.  Tiles are never rotated or moved once
+/// placed.
+///
+/// ### Pack glyph mattes
+/// ```
+/// use pix::matte::Matte8;
+/// use pix::{Atlas, Raster, Region};
+///
+/// let mut atlas = Atlas::<Matte8>::with_padding(64, 64, 1);
+/// let glyph = Raster::with_color(10, 12, Matte8::new(255));
+/// let a = atlas.add(&glyph).unwrap();
+/// let b = atlas.add(&glyph).unwrap();
+/// assert_eq!(a, Region::new(0, 0, 10, 12));
+/// assert_eq!(b, Region::new(11, 0, 10, 12));
+/// assert_eq!(atlas.raster().pixel(11, 0), Matte8::new(255));
+/// ```
+#[derive(Clone)]
+pub struct Atlas<P: Pixel> {
+    /// Packed raster
+    raster: Raster<P>,
+    /// Gap between tiles
+    padding: u32,
+    /// Left edge of free space on current shelf
+    x: u32,
+    /// Top of current shelf
+    y: u32,
+    /// Height of current shelf
+    shelf_height: u32,
+}
+
+impl<P: Pixel> Atlas<P> {
+    /// Create a new empty atlas, without padding.
+    ///
+    /// # Panics
+    ///
+    /// If `width` or `height` is greater than `std::i32::MAX`
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::with_padding(width, height, 0)
+    }
+
+    /// Create a new empty atlas, with padding between tiles.
+    ///
+    /// * `padding` Number of pixels between adjacent tiles, to avoid
+    ///   bleeding when sampling with filtering.  The padding is left as
+    ///   the default pixel value.
+    ///
+    /// # Panics
+    ///
+    /// If `width` or `height` is greater than `std::i32::MAX`
+    pub fn with_padding(width: u32, height: u32, padding: u32) -> Self {
+        Atlas {
+            raster: Raster::with_clear(width, height),
+            padding,
+            x: 0,
+            y: 0,
+            shelf_height: 0,
+        }
+    }
+
+    /// Get the packed `Raster`.
+    pub fn raster(&self) -> &Raster<P> {
+        &self.raster
+    }
+
+    /// Convert into the packed `Raster`.
+    pub fn into_raster(self) -> Raster<P> {
+        self.raster
+    }
+
+    /// Add a tile to the atlas.
+    ///
+    /// Returns the region where the tile was copied, or `None` if there is
+    /// not enough space left.  Empty tiles take no space, and are placed at
+    /// the origin.
+    pub fn add(&mut self, tile: &Raster<P>) -> Option<Region> {
+        let (x, y) = self.place(tile.width(), tile.height())?;
+        let reg = Region::new(x as i32, y as i32, tile.width(), tile.height());
+        self.raster.copy_raster(reg, tile, ());
+        Some(reg)
+    }
+
+    /// Find a place for a tile, updating shelf state
+    fn place(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (aw, ah) = (self.raster.width(), self.raster.height());
+        if width == 0 || height == 0 {
+            return Some((0, 0));
+        }
+        let mut x = self.x;
+        let mut y = self.y;
+        let mut shelf_height = self.shelf_height;
+        if x > 0 {
+            x = x.checked_add(self.padding)?;
+        }
+        if u64::from(x) + u64::from(width) > u64::from(aw) {
+            if shelf_height == 0 {
+                return None;
+            }
+            x = 0;
+            y = y.checked_add(shelf_height)?.checked_add(self.padding)?;
+            shelf_height = 0;
+            if width > aw {
+                return None;
+            }
+        }
+        if u64::from(y) + u64::from(height) > u64::from(ah) {
+            return None;
+        }
+        self.x = x + width;
+        self.y = y;
+        self.shelf_height = shelf_height.max(height);
+        Some((x, y))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::matte::Matte8;
+    use alloc::vec::Vec;
+
+    fn tile(width: u32, height: u32, v: u8) -> Raster<Matte8> {
+        let mut r = Raster::with_clear(width, height);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            *p = Matte8::new(v.wrapping_add(i as u8));
+        }
+        r
+    }
+
+    fn overlaps(a: Region, b: Region) -> bool {
+        !a.intersection(b).is_empty()
+    }
+
+    #[test]
+    fn pack() {
+        for padding in [0, 1] {
+            let mut atlas = Atlas::with_padding(40, 30, padding);
+            let mut placed = Vec::new();
+            for i in 0..50 {
+                let t = tile(
+                    3 + i % 7,
+                    2 + (i * 5) % 9,
+                    (i as u8).wrapping_mul(31),
+                );
+                if let Some(reg) = atlas.add(&t) {
+                    assert_eq!(
+                        (reg.width(), reg.height()),
+                        (t.width(), t.height())
+                    );
+                    assert_eq!(atlas.raster().intersection(reg), reg);
+                    placed.push((reg, t));
+                }
+            }
+            assert!(placed.len() > 10);
+            for (i, (a, ta)) in placed.iter().enumerate() {
+                let packed = atlas.raster().crop(*a);
+                assert_eq!(packed.pixels(), ta.pixels());
+                for (b, _) in &placed[i + 1..] {
+                    assert!(!overlaps(*a, *b), "{a:?} {b:?}");
+                    if padding > 0 {
+                        let grown = Region::new(
+                            a.left() - 1,
+                            a.top() - 1,
+                            a.width() + 2,
+                            a.height() + 2,
+                        );
+                        assert!(!overlaps(grown, *b), "{a:?} {b:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn full() {
+        let mut atlas = Atlas::new(10, 10);
+        assert!(atlas.add(&tile(11, 1, 0)).is_none());
+        assert!(atlas.add(&tile(1, 11, 0)).is_none());
+        assert_eq!(atlas.add(&tile(6, 6, 0)), Some(Region::new(0, 0, 6, 6)));
+        assert_eq!(atlas.add(&tile(4, 3, 0)), Some(Region::new(6, 0, 4, 3)));
+        assert!(atlas.add(&tile(5, 5, 0)).is_none());
+        assert_eq!(atlas.add(&tile(5, 4, 0)), Some(Region::new(0, 6, 5, 4)));
+        assert!(atlas.add(&tile(6, 1, 0)).is_none());
+        assert_eq!(atlas.add(&tile(5, 1, 0)), Some(Region::new(5, 6, 5, 1)));
+    }
+}
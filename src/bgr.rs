@@ -6,7 +6,7 @@
 use crate::chan::{Ch16, Ch32, Ch8, Linear, Premultiplied, Srgb, Straight};
 use crate::el::{Pix3, Pix4, PixRgba, Pixel};
 use crate::ColorModel;
-use std::ops::Range;
+use core::ops::Range;
 
 /// BGR arrangement of [RGB] [color model].
 ///
@@ -132,6 +132,9 @@ impl ColorModel for Bgr {
     const CIRCULAR: Range<usize> = 0..0;
     const LINEAR: Range<usize> = 0..3;
     const ALPHA: usize = 3;
+    const NAME: &'static str = "bgr";
+    const CHANNEL_NAMES: &'static [&'static str] =
+        &["blue", "green", "red", "alpha"];
 
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
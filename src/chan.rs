@@ -4,10 +4,12 @@
 // Copyright (c) 2019-2020  Jeron Aldaron Lau
 //
 //! Component channels
+#[cfg(not(feature = "std"))]
+use crate::float::Float;
 use crate::private::Sealed;
-use std::cmp::Ordering;
-use std::fmt::Debug;
-use std::ops::{Add, Div, Mul, Sub};
+use core::cmp::Ordering;
+use core::fmt::{self, Debug};
+use core::ops::{Add, Div, Mul, Sub};
 
 /// *Alpha* encoding mode.
 ///
@@ -60,6 +62,41 @@ impl Alpha for Premultiplied {
 // Include functions to convert gamma between linear and sRGB
 include!("srgb_gamma.rs");
 
+/// Encode an sRGB gamma value from linear intensity.
+///
+/// This is the sRGB transfer function (IEC 61966-2-1), with a linear
+/// segment below 0.0031308.  Values are clamped to the range 0 to 1.  It is
+/// the same curve used by [Srgb](struct.Srgb.html) gamma channels; the
+/// `Ch8` / `Ch16` look-up tables are this function, correctly rounded.
+///
+/// ## Example
+/// ```
+/// use pix::chan::srgb_gamma_encode;
+///
+/// assert_eq!(srgb_gamma_encode(0.0), 0.0);
+/// assert!((srgb_gamma_encode(0.5) - 0.7354).abs() < 0.0001);
+/// assert_eq!(srgb_gamma_encode(1.0), 1.0);
+/// ```
+pub fn srgb_gamma_encode(v: f32) -> f32 {
+    srgb_encode_f64(f64::from(v)) as f32
+}
+
+/// Decode an sRGB gamma value into linear intensity.
+///
+/// This is the inverse of [srgb_gamma_encode](fn.srgb_gamma_encode.html),
+/// with a linear segment below 0.04045.  Values are clamped to the range 0
+/// to 1.
+///
+/// ## Example
+/// ```
+/// use pix::chan::srgb_gamma_decode;
+///
+/// assert!((srgb_gamma_decode(0.7354) - 0.5).abs() < 0.0001);
+/// ```
+pub fn srgb_gamma_decode(v: f32) -> f32 {
+    srgb_decode_f64(f64::from(v)) as f32
+}
+
 // Include build-time sRGB gamma look-up tables
 include!(concat!(env!("OUT_DIR"), "/gamma_lut.rs"));
 
@@ -111,13 +148,14 @@ impl Gamma for Srgb {
 
 /// *Component* of a [color model], such as *red*, *green*, *etc*.
 ///
-/// Existing `Channel`s are [Ch8], [Ch16] and [Ch32].
+/// Existing `Channel`s are [Ch8], [Ch16], [Ch32] and [Ch64].
 ///
 /// This trait is *sealed*, and cannot be implemented outside of this crate.
 ///
 /// [Ch8]: struct.Ch8.html
 /// [Ch16]: struct.Ch16.html
 /// [Ch32]: struct.Ch32.html
+/// [Ch64]: struct.Ch64.html
 /// [color model]: ../trait.ColorModel.html
 pub trait Channel:
     Copy
@@ -131,6 +169,17 @@ pub trait Channel:
     + Sub<Output = Self>
     + Sealed
 {
+    /// Type for sums of raw values.
+    ///
+    /// This is `u64` for integer channels, so that sums are exact, or `f64`
+    /// for floating point channels.
+    type Sum: Copy
+        + Debug
+        + Default
+        + PartialEq
+        + Add<Output = Self::Sum>
+        + Sub<Output = Self::Sum>;
+
     /// Minimum intensity (*zero*)
     const MIN: Self;
 
@@ -140,13 +189,25 @@ pub trait Channel:
     /// Maximum intensity (*one*)
     const MAX: Self;
 
+    /// Number of bits for integer channels (zero for floating point)
+    const INTEGER_BITS: u32;
+
     /// Convert to `f32`
     fn to_f32(self) -> f32;
 
     /// Wrapping addition
+    ///
+    /// The `+` operator saturates at [MAX](#associatedconstant.MAX), but this
+    /// wraps around instead, as needed for circular channels such as *hue*.
+    /// See [rotate_circular].
+    ///
+    /// [rotate_circular]: ../el/trait.Pixel.html#method.rotate_circular
     fn wrapping_add(self, rhs: Self) -> Self;
 
     /// Wrapping subtraction
+    ///
+    /// The `-` operator saturates at [MIN](#associatedconstant.MIN), but this
+    /// wraps around instead, as needed for circular channels such as *hue*.
     fn wrapping_sub(self, rhs: Self) -> Self;
 
     /// Encode an sRGB gamma value from linear intensity
@@ -157,12 +218,56 @@ pub trait Channel:
 
     /// Linear interpolation
     fn lerp(self, rhs: Self, t: Self) -> Self;
+
+    /// Average of a slice of values, rounded to nearest.
+    ///
+    /// # Panics
+    ///
+    /// If `values` is empty.
+    fn average(values: &[Self]) -> Self {
+        Self::mean(values.iter().copied()).expect("No values to average")
+    }
+
+    /// Mean of values from an iterator, rounded to nearest.
+    ///
+    /// Values are summed using a wide accumulator ([Sum]), so any number of
+    /// values can be used without overflow.
+    ///
+    /// Returns `None` if there are no values.
+    ///
+    /// [Sum]: #associatedtype.Sum
+    fn mean<I>(values: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let (n, sum) = values
+            .into_iter()
+            .fold((0u64, Self::Sum::default()), |(n, sum), v| {
+                (n + 1, sum + v.to_sum())
+            });
+        (n > 0).then(|| Self::from_sum(sum, n))
+    }
+
+    /// Get the raw value, for summing (such as 0 to 255 for `Ch8`)
+    fn to_sum(self) -> Self::Sum;
+
+    /// Get the mean of raw values from their sum.
+    ///
+    /// * `sum` Sum of raw values.
+    /// * `n` Number of values (must not be zero).
+    ///
+    /// Integer channels are rounded to nearest, with ties rounding up.
+    fn from_sum(sum: Self::Sum, n: u64) -> Self;
 }
 
 /// 8-bit color [Channel](trait.Channel.html).
 ///
 /// The `Channel` is represented by a `u8`, but multiplication and division
-/// treat values as though they range between 0 and 1.
+/// treat values as though they range between 0 and 1.  Results are rounded
+/// to nearest, with ties rounding up:
+///
+/// * `x * y` = round(`x` × `y` / 255)
+/// * `x / y` = round(`x` × 255 / `y`), clamped to 255 (or 0 if `y` is 0)
 ///
 /// ```
 /// use pix::chan::{Ch16, Ch32, Ch8, Channel};
@@ -176,13 +281,17 @@ pub trait Channel:
 /// let c: Ch32 = c.into();
 /// assert_eq!(c, Ch32::MAX);
 /// ```
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Ch8(u8);
 
 /// 16-bit color [Channel](trait.Channel.html).
 ///
 /// The `Channel` is represented by a `u16`, but multiplication and division
-/// treat values as though they range between 0 and 1.
+/// treat values as though they range between 0 and 1.  Results are rounded
+/// to nearest, with ties rounding up:
+///
+/// * `x * y` = round(`x` × `y` / 65535)
+/// * `x / y` = round(`x` × 65535 / `y`), clamped to 65535 (or 0 if `y` is 0)
 ///
 /// ```
 /// use pix::chan::{Ch16, Ch32, Ch8, Channel};
@@ -196,7 +305,7 @@ pub struct Ch8(u8);
 /// let c: Ch32 = c.into();
 /// assert_eq!(c, Ch32::MAX);
 /// ```
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Ch16(u16);
 
 /// 32-bit color [Channel](trait.Channel.html).
@@ -204,6 +313,14 @@ pub struct Ch16(u16);
 /// The `Channel` is represented by an `f32`, but values are guaranteed to be
 /// between 0 and 1, inclusive.
 ///
+/// Raw buffers written as `Ch32` (such as with
+/// [Raster::as_u8_slice_mut](../struct.Raster.html#method.as_u8_slice_mut))
+/// can contain non-finite values.  These never cause a panic; `NaN` is
+/// ordered and compared as equal to [MIN].  Use
+/// [Raster::sanitize](../struct.Raster.html#method.sanitize) to clean them up.
+///
+/// [MIN]: trait.Channel.html#associatedconstant.MIN
+///
 /// ```
 /// use pix::chan::{Ch16, Ch32, Ch8, Channel};
 ///
@@ -216,9 +333,30 @@ pub struct Ch16(u16);
 /// let c: Ch16 = c.into();
 /// assert_eq!(c, Ch16::MAX);
 /// ```
-#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct Ch32(f32);
 
+/// 64-bit color [Channel](trait.Channel.html).
+///
+/// The `Channel` is represented by an `f64`, but values are guaranteed to be
+/// between 0 and 1, inclusive.  This is useful for accumulating values
+/// where `Ch32` would lose precision.
+///
+/// ```
+/// use pix::chan::{Ch32, Ch64, Ch8, Channel};
+///
+/// let c: Ch64 = 0.0.into();
+/// assert_eq!(c, Ch64::MIN);
+/// let c: Ch8 = c.into();
+/// assert_eq!(c, Ch8::MIN);
+/// let c: Ch64 = 1.0.into();
+/// assert_eq!(c, Ch64::MAX);
+/// let c: Ch32 = c.into();
+/// assert_eq!(c, Ch32::MAX);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ch64(f64);
+
 impl Ch8 {
     /// Create a new 8-bit `Channel` value.
     pub const fn new(value: u8) -> Self {
@@ -227,12 +365,16 @@ impl Ch8 {
 }
 
 impl Channel for Ch8 {
+    type Sum = u64;
+
     const MIN: Ch8 = Ch8(0);
 
     const MID: Ch8 = Ch8(128);
 
     const MAX: Ch8 = Ch8(0xFF);
 
+    const INTEGER_BITS: u32 = 8;
+
     /// Convert to `f32`
     fn to_f32(self) -> f32 {
         Ch32::from(self).0
@@ -270,6 +412,15 @@ impl Channel for Ch8 {
         let r = v0 + scale_i32(u8::from(t), v1 - v0);
         Self::new(r as u8)
     }
+    /// Get the raw value, for summing
+    fn to_sum(self) -> u64 {
+        u64::from(self.0)
+    }
+
+    /// Get the mean of raw values from their sum
+    fn from_sum(sum: u64, n: u64) -> Self {
+        Self::new(((sum + n / 2) / n) as u8)
+    }
 }
 
 /// Scale an i32 value by a u8 (for lerp)
@@ -292,6 +443,20 @@ impl From<Ch8> for u8 {
     }
 }
 
+impl fmt::Debug for Ch8 {
+    /// Format as hexadecimal, such as `Ch8(0xAB)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ch8({:#04X})", self.0)
+    }
+}
+
+impl fmt::Display for Ch8 {
+    /// Format as an integer
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
 impl From<f32> for Ch8 {
     fn from(value: f32) -> Self {
         Ch32::new(value).into()
@@ -333,12 +498,8 @@ where
     type Output = Self;
     fn mul(self, rhs: R) -> Self {
         let rhs = Self::from(rhs);
-        let l = u32::from(self.0);
-        let l = (l << 4) | (l >> 4);
-        let r = u32::from(rhs.0);
-        let r = (r << 4) | (r >> 4);
-        let value = ((l * r) >> 16) as u8;
-        Ch8(value)
+        let value = u32::from(self.0) * u32::from(rhs.0);
+        Ch8(((value + 127) / 255) as u8)
     }
 }
 
@@ -351,9 +512,9 @@ where
         #![allow(clippy::single_match, clippy::suspicious_arithmetic_impl)]
         let rhs = Self::from(rhs);
         if rhs.0 > 0 {
-            let ss = u32::from(self.0) << 8;
+            let ss = u32::from(self.0) * 255;
             let rr = u32::from(rhs.0);
-            let value = (ss / rr).min(255) as u8;
+            let value = ((ss + rr / 2) / rr).min(255) as u8;
             Ch8(value)
         } else {
             Ch8(0)
@@ -369,12 +530,16 @@ impl Ch16 {
 }
 
 impl Channel for Ch16 {
+    type Sum = u64;
+
     const MIN: Ch16 = Ch16(0);
 
     const MID: Ch16 = Ch16(0x8000);
 
     const MAX: Ch16 = Ch16(0xFFFF);
 
+    const INTEGER_BITS: u32 = 16;
+
     /// Convert to `f32`
     fn to_f32(self) -> f32 {
         Ch32::from(self).0
@@ -394,15 +559,13 @@ impl Channel for Ch16 {
 
     /// Encode an sRGB gamma value from linear intensity
     fn encode_srgb(self) -> Self {
-        let s = f32::from(u16::from(self)) / 65535.0;
-        let s = (srgb_gamma_encode(s) * 65535.0).round() as u16;
+        let s = ENCODE_SRGB_U16[usize::from(u16::from(self))];
         Self::new(s)
     }
 
     /// Decode an sRGB gamma value into linear intensity
     fn decode_srgb(self) -> Self {
-        let s = f32::from(u16::from(self)) / 65535.0;
-        let s = (srgb_gamma_decode(s) * 65535.0).round() as u16;
+        let s = DECODE_SRGB_U16[usize::from(u16::from(self))];
         Self::new(s)
     }
 
@@ -414,6 +577,15 @@ impl Channel for Ch16 {
         let r = v0 + scale_i64(u16::from(t), v1 - v0);
         Self::new(r as u16)
     }
+    /// Get the raw value, for summing
+    fn to_sum(self) -> u64 {
+        u64::from(self.0)
+    }
+
+    /// Get the mean of raw values from their sum
+    fn from_sum(sum: u64, n: u64) -> Self {
+        Self::new(((sum + n / 2) / n) as u16)
+    }
 }
 
 /// Scale an i64 value by a u16 (for lerp)
@@ -424,6 +596,17 @@ fn scale_i64(t: u16, v: i64) -> i64 {
     ((c + 1) + (c >> 16)) >> 16
 }
 
+/// Widen an 8-bit channel by bit replication.
+///
+/// Each value `v` maps to `v * 257` (`v << 8 | v`), so `MIN` and `MAX` are
+/// preserved, and every value is exact.  Narrowing back to `Ch8` always gives
+/// the original value.
+///
+/// ```
+/// use pix::chan::{Ch16, Ch8};
+///
+/// assert_eq!(Ch16::from(Ch8::new(0x7F)), Ch16::new(0x7F7F));
+/// ```
 impl From<Ch8> for Ch16 {
     fn from(c: Ch8) -> Self {
         let value = u16::from(c.0);
@@ -455,9 +638,37 @@ impl From<Ch16> for u16 {
     }
 }
 
+impl fmt::Debug for Ch16 {
+    /// Format as hexadecimal, such as `Ch16(0xABCD)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ch16({:#06X})", self.0)
+    }
+}
+
+impl fmt::Display for Ch16 {
+    /// Format as an integer
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Narrow a 16-bit channel, rounding to nearest.
+///
+/// Each value `v` maps to `v / 257`, rounded to the nearest integer.  Ties
+/// are not possible, since 257 is odd.  Widening the result back to `Ch16`
+/// gives the closest value representable by `Ch8`.  This is the same as
+/// converting through `Ch32`.
+///
+/// ```
+/// use pix::chan::{Ch16, Ch8};
+///
+/// assert_eq!(Ch8::from(Ch16::new(0x7FFF)), Ch8::new(0x7F));
+/// assert_eq!(Ch8::from(Ch16::new(0x0081)), Ch8::new(0x01));
+/// assert_eq!(Ch8::from(Ch16::new(0xFF00)), Ch8::new(0xFE));
+/// ```
 impl From<Ch16> for Ch8 {
     fn from(c: Ch16) -> Self {
-        Ch8::new((c.0 >> 8) as u8)
+        Ch8::new(((u32::from(c.0) + 128) / 257) as u8)
     }
 }
 
@@ -490,12 +701,8 @@ where
     type Output = Self;
     fn mul(self, rhs: R) -> Self {
         let rhs = Self::from(rhs);
-        let l = u64::from(self.0);
-        let l = (l << 8) | (l >> 8);
-        let r = u64::from(rhs.0);
-        let r = (r << 8) | (r >> 8);
-        let value = ((l * r) >> 32) as u16;
-        Ch16(value)
+        let value = u32::from(self.0) * u32::from(rhs.0);
+        Ch16(((u64::from(value) + 32767) / 65535) as u16)
     }
 }
 
@@ -508,9 +715,9 @@ where
         #![allow(clippy::single_match, clippy::suspicious_arithmetic_impl)]
         let rhs = Self::from(rhs);
         if rhs.0 > 0 {
-            let ss = u64::from(self.0) << 16;
+            let ss = u64::from(self.0) * 65535;
             let rr = u64::from(rhs.0);
-            let value = (ss / rr).min(65535) as u16;
+            let value = ((ss + rr / 2) / rr).min(65535) as u16;
             Ch16(value)
         } else {
             Ch16(0)
@@ -535,15 +742,41 @@ impl Ch32 {
         };
         Ch32(v)
     }
+
+    /// Check whether the value is finite (not `NaN` or infinite).
+    ///
+    /// Values created with [new](#method.new) are always finite.
+    ///
+    /// ```
+    /// use pix::chan::Ch32;
+    ///
+    /// assert!(Ch32::new(f32::NAN).is_finite());
+    /// ```
+    pub fn is_finite(self) -> bool {
+        self.0.is_finite()
+    }
+
+    /// Get value for comparisons, with `NaN` as 0.0
+    fn cmp_value(self) -> f32 {
+        if self.0.is_nan() {
+            0.0
+        } else {
+            self.0
+        }
+    }
 }
 
 impl Channel for Ch32 {
+    type Sum = f64;
+
     const MIN: Ch32 = Ch32(0.0);
 
     const MID: Ch32 = Ch32(0.5);
 
     const MAX: Ch32 = Ch32(1.0);
 
+    const INTEGER_BITS: u32 = 0;
+
     /// Convert to `f32`
     fn to_f32(self) -> f32 {
         self.0
@@ -589,6 +822,15 @@ impl Channel for Ch32 {
         let r = v0 + f32::from(t) * (v1 - v0);
         Self::new(r)
     }
+    /// Get the raw value, for summing
+    fn to_sum(self) -> f64 {
+        f64::from(self.0)
+    }
+
+    /// Get the mean of raw values from their sum
+    fn from_sum(sum: f64, n: u64) -> Self {
+        Self::new((sum / n as f64) as f32)
+    }
 }
 
 impl From<Ch8> for Ch32 {
@@ -609,23 +851,24 @@ impl From<Ch32> for f32 {
     }
 }
 
+impl fmt::Display for Ch32 {
+    /// Format as a floating point value, such as `1.0`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
 impl From<Ch32> for Ch8 {
     fn from(c: Ch32) -> Self {
-        let value = c.0;
-        debug_assert!((0.0..=1.0).contains(&value));
-        // this cast is not UB since the value is guaranteed
-        // to be between 0.0 and 1.0 (see bug #10184)
-        Ch8::new((value * 255.0).round() as u8)
+        // float to int casts saturate, with NaN becoming 0
+        Ch8::new((c.0 * 255.0).round() as u8)
     }
 }
 
 impl From<Ch32> for Ch16 {
     fn from(c: Ch32) -> Self {
-        let value = c.0;
-        debug_assert!((0.0..=1.0).contains(&value));
-        // this cast is not UB since the value is guaranteed
-        // to be between 0.0 and 1.0 (see bug #10184)
-        Ch16::new((value * 65535.0).round() as u16)
+        // float to int casts saturate, with NaN becoming 0
+        Ch16::new((c.0 * 65535.0).round() as u16)
     }
 }
 
@@ -635,12 +878,26 @@ impl From<Ch16> for Ch32 {
     }
 }
 
+impl PartialEq for Ch32 {
+    fn eq(&self, other: &Ch32) -> bool {
+        self.cmp_value() == other.cmp_value()
+    }
+}
+
 impl Eq for Ch32 {}
 
-#[allow(clippy::derive_ord_xor_partial_ord)]
+impl PartialOrd for Ch32 {
+    fn partial_cmp(&self, other: &Ch32) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Ord for Ch32 {
+    /// Compare values, treating `NaN` as equal to 0.0
     fn cmp(&self, other: &Ch32) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.cmp_value()
+            .partial_cmp(&other.cmp_value())
+            .unwrap_or(Ordering::Equal)
     }
 }
 
@@ -691,11 +948,236 @@ where
     }
 }
 
+impl Ch64 {
+    /// Create a new 64-bit `Channel` value.
+    ///
+    /// Returns [MIN](trait.Channel.html#associatedconstant.MIN) if value is
+    ///         less than 0.0, or `NaN`.
+    /// Returns [MAX](trait.Channel.html#associatedconstant.MAX) if value is
+    ///         greater than 1.0.
+    pub fn new(value: f64) -> Self {
+        let v = if value.is_nan() || value < 0.0 {
+            0.0
+        } else if value > 1.0 {
+            1.0
+        } else {
+            value
+        };
+        Ch64(v)
+    }
+
+    /// Get value for comparisons, with `NaN` as 0.0
+    fn cmp_value(self) -> f64 {
+        if self.0.is_nan() {
+            0.0
+        } else {
+            self.0
+        }
+    }
+}
+
+impl Channel for Ch64 {
+    type Sum = f64;
+
+    const MIN: Ch64 = Ch64(0.0);
+
+    const MID: Ch64 = Ch64(0.5);
+
+    const MAX: Ch64 = Ch64(1.0);
+
+    const INTEGER_BITS: u32 = 0;
+
+    /// Convert to `f32`
+    fn to_f32(self) -> f32 {
+        self.0 as f32
+    }
+
+    /// Wrapping addition
+    fn wrapping_add(self, rhs: Self) -> Self {
+        let v = self.0 + rhs.0;
+        if v <= 1.0 {
+            Self::new(v)
+        } else {
+            Self::new(v - 1.0)
+        }
+    }
+
+    /// Wrapping subtraction
+    fn wrapping_sub(self, rhs: Self) -> Self {
+        let v = self.0 - rhs.0;
+        if v >= 0.0 {
+            Self::new(v)
+        } else {
+            Self::new(v + 1.0)
+        }
+    }
+
+    /// Encode an sRGB gamma value from linear intensity
+    fn encode_srgb(self) -> Self {
+        Self::new(srgb_encode_f64(self.0))
+    }
+
+    /// Decode an sRGB gamma value into linear intensity
+    fn decode_srgb(self) -> Self {
+        Self::new(srgb_decode_f64(self.0))
+    }
+
+    /// Linear interpolation
+    #[inline]
+    fn lerp(self, rhs: Self, t: Self) -> Self {
+        Self::new(self.0 + t.0 * (rhs.0 - self.0))
+    }
+
+    /// Get the raw value, for summing
+    fn to_sum(self) -> f64 {
+        self.0
+    }
+
+    /// Get the mean of raw values from their sum
+    fn from_sum(sum: f64, n: u64) -> Self {
+        Self::new(sum / n as f64)
+    }
+}
+
+impl From<f32> for Ch64 {
+    fn from(value: f32) -> Self {
+        Ch64::new(f64::from(value))
+    }
+}
+
+impl From<f64> for Ch64 {
+    fn from(value: f64) -> Self {
+        Ch64::new(value)
+    }
+}
+
+impl From<Ch64> for f64 {
+    fn from(c: Ch64) -> f64 {
+        c.0
+    }
+}
+
+impl fmt::Display for Ch64 {
+    /// Format as a floating point value, such as `1.0`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl From<Ch8> for Ch64 {
+    fn from(c: Ch8) -> Self {
+        Ch64(f64::from(c.0) / 255.0)
+    }
+}
+
+impl From<Ch16> for Ch64 {
+    fn from(c: Ch16) -> Self {
+        Ch64(f64::from(c.0) / 65535.0)
+    }
+}
+
+impl From<Ch32> for Ch64 {
+    fn from(c: Ch32) -> Self {
+        Ch64(f64::from(c.0))
+    }
+}
+
+impl From<Ch64> for Ch8 {
+    fn from(c: Ch64) -> Self {
+        // float to int casts saturate, with NaN becoming 0
+        Ch8::new((c.0 * 255.0).round() as u8)
+    }
+}
+
+impl From<Ch64> for Ch16 {
+    fn from(c: Ch64) -> Self {
+        // float to int casts saturate, with NaN becoming 0
+        Ch16::new((c.0 * 65535.0).round() as u16)
+    }
+}
+
+impl From<Ch64> for Ch32 {
+    fn from(c: Ch64) -> Self {
+        Ch32(c.0 as f32)
+    }
+}
+
+impl PartialEq for Ch64 {
+    fn eq(&self, other: &Ch64) -> bool {
+        self.cmp_value() == other.cmp_value()
+    }
+}
+
+impl Eq for Ch64 {}
+
+impl PartialOrd for Ch64 {
+    fn partial_cmp(&self, other: &Ch64) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ch64 {
+    /// Compare values, treating `NaN` as equal to 0.0
+    fn cmp(&self, other: &Ch64) -> Ordering {
+        self.cmp_value()
+            .partial_cmp(&other.cmp_value())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl<R> Add<R> for Ch64
+where
+    Self: From<R>,
+{
+    type Output = Self;
+    fn add(self, rhs: R) -> Self {
+        let value = self.0 + Self::from(rhs).0;
+        Ch64(value.min(1.0))
+    }
+}
+
+impl<R> Sub<R> for Ch64
+where
+    Self: From<R>,
+{
+    type Output = Self;
+    fn sub(self, rhs: R) -> Self {
+        let value = self.0 - Self::from(rhs).0;
+        Ch64(value.max(0.0))
+    }
+}
+
+impl<R> Mul<R> for Ch64
+where
+    Self: From<R>,
+{
+    type Output = Self;
+    fn mul(self, rhs: R) -> Self {
+        Ch64(self.0 * Self::from(rhs).0)
+    }
+}
+
+impl<R> Div<R> for Ch64
+where
+    Self: From<R>,
+{
+    type Output = Self;
+    fn div(self, rhs: R) -> Self {
+        let v = Self::from(rhs).0;
+        if v > 0.0 {
+            Ch64((self.0 / v).min(1.0))
+        } else {
+            Ch64(0.0)
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::chan::*;
 
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn lut_encode_u8() {
         for i in 0..=255 {
             let s = i as f32 / 255.0;
@@ -705,6 +1187,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn lut_decode_u8() {
         for i in 0..=255 {
             let s = i as f32 / 255.0;
@@ -713,6 +1196,54 @@ mod test {
         }
     }
 
+    // canonical sRGB formulas, written independently of srgb_gamma.rs
+    fn canonical_encode(v: f64) -> f64 {
+        if v <= 0.003_130_8 {
+            v * 12.92
+        } else {
+            v.powf(1.0 / 2.4) * 1.055 - 0.055
+        }
+    }
+
+    fn canonical_decode(v: f64) -> f64 {
+        if v <= 0.04045 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    fn lut_check<T>(lut: &[T], max: f64, gamma: fn(f64) -> f64)
+    where
+        T: Copy + Into<f64>,
+    {
+        assert_eq!(lut.len(), max as usize + 1);
+        for (i, v) in lut.iter().enumerate() {
+            let c = (gamma(i as f64 / max) * max).round();
+            assert_eq!(c, (*v).into(), "index {i}");
+        }
+    }
+
+    #[test]
+    fn lut_canonical() {
+        lut_check(ENCODE_SRGB_U8, 255.0, canonical_encode);
+        lut_check(DECODE_SRGB_U8, 255.0, canonical_decode);
+        lut_check(ENCODE_SRGB_U16, 65535.0, canonical_encode);
+        lut_check(DECODE_SRGB_U16, 65535.0, canonical_decode);
+    }
+
+    #[test]
+    fn ch16_srgb_round_trip() {
+        // Decoding is many-to-one near black (slope 1/12.92), so
+        // encode(decode(x)) cannot be exact there; encoding first keeps
+        // enough precision to round trip within 1 LSB everywhere.
+        for i in 0..=u16::MAX {
+            let c = Ch16::new(i);
+            let v = u16::from(c.encode_srgb().decode_srgb());
+            assert!(v.abs_diff(i) <= 1, "{i} => {v}");
+        }
+    }
+
     #[test]
     fn ch8_into() {
         assert_eq!(Ch8::new(255), 255.into());
@@ -738,6 +1269,25 @@ mod test {
         assert_eq!(Ch16::new(65535), Ch8::new(255).into());
     }
 
+    #[test]
+    fn ch8_ch16_exhaustive() {
+        for v in 0..=255 {
+            let c16 = Ch16::from(Ch8::new(v));
+            assert_eq!(u16::from(c16), u16::from(v) * 257);
+            assert_eq!(Ch8::from(c16), Ch8::new(v));
+        }
+        let mut prev = Ch8::MIN;
+        for v in 0..=u16::MAX {
+            let c8 = Ch8::from(Ch16::new(v));
+            // nearest value, and the same as through Ch32
+            let w = i32::from(u16::from(Ch16::from(c8)));
+            assert!((i32::from(v) - w).abs() <= 128, "{v:04X}");
+            assert_eq!(c8, Ch8::from(Ch32::from(Ch16::new(v))), "{v:04X}");
+            assert!(c8 >= prev);
+            prev = c8;
+        }
+    }
+
     #[test]
     fn ch32_into() {
         assert_eq!(Ch32::new(1.0), 1.0.into());
@@ -776,6 +1326,46 @@ mod test {
         assert_eq!(Ch8::new(64), Ch8::new(32) / 0.5);
         assert_eq!(Ch8::new(64), Ch8::new(16) / 0.25);
     }
+    #[test]
+    fn ch8_mul_exact() {
+        assert_eq!(Ch8::new(0x02), Ch8::new(0xFF) * Ch8::new(0x02));
+        assert_eq!(Ch8::new(0x40), Ch8::new(0x80) * Ch8::new(0x80));
+        for x in 0..=255u8 {
+            for y in 0..=255u8 {
+                let v = (f64::from(x) * f64::from(y) / 255.0).round();
+                assert_eq!(Ch8::new(x) * Ch8::new(y), Ch8::new(v as u8));
+            }
+        }
+    }
+
+    #[test]
+    fn ch8_div_exact() {
+        for x in 0..=255u8 {
+            assert_eq!(Ch8::new(x) / Ch8::new(0), Ch8::new(0));
+            for y in 1..=255u8 {
+                let v = (f64::from(x) * 255.0 / f64::from(y)).round();
+                let v = v.min(255.0) as u8;
+                assert_eq!(Ch8::new(x) / Ch8::new(y), Ch8::new(v));
+            }
+        }
+    }
+
+    #[test]
+    fn ch16_mul_div_exact() {
+        for x in (0..=65535u16).step_by(251) {
+            for y in (1..=65535u16).step_by(257) {
+                let (fx, fy) = (f64::from(x), f64::from(y));
+                let v = (fx * fy / 65535.0).round() as u16;
+                assert_eq!(Ch16::new(x) * Ch16::new(y), Ch16::new(v));
+                let v = (fx * 65535.0 / fy).round().min(65535.0) as u16;
+                assert_eq!(Ch16::new(x) / Ch16::new(y), Ch16::new(v));
+            }
+        }
+        let max = Ch16::MAX;
+        assert_eq!(max * max, max);
+        assert_eq!(max * Ch16::new(2), Ch16::new(2));
+    }
+
     #[test]
     fn ch16_mul() {
         assert_eq!(Ch16::new(65535), Ch16::new(65535) * 1.0);
@@ -815,4 +1405,127 @@ mod test {
         assert_eq!(Ch32::new(0.0625), Ch32::new(0.5) * 0.125);
         assert_eq!(Ch32::new(0.03125), Ch32::new(0.5) * 0.0625);
     }
+
+    #[test]
+    fn ch64_into() {
+        assert_eq!(Ch64::new(1.0), 1.0.into());
+        assert_eq!(Ch64::new(0.5), 0.5.into());
+        assert_eq!(Ch64::new(0.25), 0.25f32.into());
+        assert_eq!(Ch64::new(0.0), Ch8::new(0).into());
+        assert_eq!(Ch64::new(1.0), Ch8::new(255).into());
+        assert_eq!(Ch64::new(1.0), Ch16::new(65535).into());
+        assert_eq!(Ch64::new(0.5), Ch32::new(0.5).into());
+        assert_eq!(Ch64::new(-1.0), Ch64::MIN);
+        assert_eq!(Ch64::new(f64::NAN), Ch64::MIN);
+        assert_eq!(Ch64::new(2.0), Ch64::MAX);
+        for i in 0..=255 {
+            let c8 = Ch8::new(i);
+            let c64: Ch64 = c8.into();
+            assert_eq!(c8, c64.into());
+        }
+        for i in (0..=65535).step_by(7) {
+            let c16 = Ch16::new(i);
+            let c64: Ch64 = c16.into();
+            assert_eq!(c16, c64.into());
+        }
+        assert_eq!(Ch32::new(0.25), Ch64::new(0.25).into());
+    }
+
+    #[test]
+    fn ch64_mul() {
+        assert_eq!(Ch64::new(1.0), Ch64::new(1.0) * 1.0);
+        assert_eq!(Ch64::new(0.5), Ch64::new(1.0) * 0.5);
+        assert_eq!(Ch64::new(0.25), Ch64::new(1.0) * 0.25);
+        assert_eq!(Ch64::new(0.125), Ch64::new(1.0) * 0.125);
+        assert_eq!(Ch64::new(0.0625), Ch64::new(1.0) * 0.0625);
+        assert_eq!(Ch64::new(0.25), Ch64::new(0.5) * 0.5);
+        assert_eq!(Ch64::new(0.125), Ch64::new(0.5) * 0.25);
+        assert_eq!(Ch64::new(0.0625), Ch64::new(0.5) * 0.125);
+        assert_eq!(Ch64::new(0.03125), Ch64::new(0.5) * 0.0625);
+    }
+
+    #[test]
+    fn ch64_div() {
+        assert_eq!(Ch64::new(1.0), Ch64::new(0.5) / 0.5);
+        assert_eq!(Ch64::new(1.0), Ch64::new(0.5) / 0.25);
+        assert_eq!(Ch64::new(0.5), Ch64::new(0.25) / 0.5);
+        assert_eq!(Ch64::new(0.0), Ch64::new(0.25) / 0.0);
+        assert_eq!(Ch64::new(1.0), Ch64::new(0.75) + 0.5);
+        assert_eq!(Ch64::new(0.0), Ch64::new(0.25) - 0.5);
+    }
+
+    #[test]
+    fn ch64_srgb() {
+        for i in 0..=100 {
+            let v = f64::from(i) / 100.0;
+            let c = Ch64::new(v);
+            let e = c.encode_srgb();
+            let e32 = Ch32::new(v as f32).encode_srgb();
+            assert!((e.to_f32() - e32.to_f32()).abs() < 1e-6, "{v}");
+            let d = f64::from(e.decode_srgb());
+            assert!((d - v).abs() < 1e-12, "{v} => {d}");
+        }
+        let c = Ch64::new(0.75).wrapping_add(Ch64::new(0.5));
+        assert_eq!(c, Ch64::new(0.25));
+        let c = Ch64::new(0.25).wrapping_sub(Ch64::new(0.5));
+        assert_eq!(c, Ch64::new(0.75));
+        assert_eq!(Ch64::MIN.lerp(Ch64::MAX, Ch64::MID), Ch64::MID);
+    }
+
+    #[test]
+    fn average() {
+        assert_eq!(Ch8::average(&[Ch8::new(0), Ch8::new(255)]), Ch8::new(128));
+        assert_eq!(
+            Ch8::average(&[Ch8::new(1), Ch8::new(1), Ch8::new(2)]),
+            Ch8::new(1)
+        );
+        assert_eq!(
+            Ch16::average(&[Ch16::new(0), Ch16::new(0xFFFF)]),
+            Ch16::new(0x8000)
+        );
+        assert_eq!(
+            Ch32::average(&[Ch32::new(0.25), Ch32::new(0.75)]),
+            Ch32::new(0.5)
+        );
+    }
+
+    #[test]
+    fn mean() {
+        assert_eq!(Ch8::mean([]), None);
+        let v = (0..=255).map(Ch8::new);
+        assert_eq!(Ch8::mean(v), Some(Ch8::new(128)));
+        let v = std::iter::repeat(Ch16::new(0xFFFF)).take(100_000);
+        assert_eq!(Ch16::mean(v), Some(Ch16::new(0xFFFF)));
+        assert_eq!(Ch32::mean([Ch32::new(0.5)]), Some(Ch32::new(0.5)));
+        let v = std::iter::repeat(Ch64::new(0.1)).take(1_000_000);
+        let m = f64::from(Ch64::mean(v).unwrap());
+        assert!((m - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn debug_hex() {
+        assert_eq!(format!("{:?}", Ch8::new(0xAB)), "Ch8(0xAB)");
+        assert_eq!(format!("{:?}", Ch8::new(0x05)), "Ch8(0x05)");
+        assert_eq!(format!("{:?}", Ch16::new(0xAB)), "Ch16(0x00AB)");
+        assert_eq!(format!("{:?}", Ch16::MAX), "Ch16(0xFFFF)");
+        assert_eq!(format!("{}", Ch8::new(0xAB)), "171");
+    }
+
+    #[test]
+    fn nan_order() {
+        let nan = Ch32(f32::NAN);
+        assert!(!nan.is_finite());
+        assert_eq!(nan, Ch32::MIN);
+        assert_eq!(nan.cmp(&Ch32::new(0.5)), Ordering::Less);
+        assert_eq!(Ch32::MIN.cmp(&nan), Ordering::Equal);
+        let mut v = [Ch32::new(0.5), nan, Ch32(f32::INFINITY), Ch32::MIN];
+        v.sort();
+        assert_eq!(v.iter().max(), Some(&Ch32(f32::INFINITY)));
+        assert_eq!(v[3], Ch32(f32::INFINITY));
+        assert_eq!(Ch8::from(nan), Ch8::MIN);
+        assert_eq!(Ch16::from(Ch32(f32::INFINITY)), Ch16::MAX);
+        let nan = Ch64(f64::NAN);
+        assert_eq!(nan.max(Ch64::new(0.25)), Ch64::new(0.25));
+        assert_eq!(Ch8::from(nan), Ch8::MIN);
+    }
 }
@@ -10,7 +10,7 @@ use crate::chan::{
 };
 use crate::el::{Pix3, Pix4, PixRgba, Pixel};
 use crate::ColorModel;
-use std::ops::Range;
+use core::ops::Range;
 
 /// [CMY] subtractive [color model].
 ///
@@ -136,6 +136,9 @@ impl ColorModel for Cmy {
     const CIRCULAR: Range<usize> = 0..0;
     const LINEAR: Range<usize> = 0..3;
     const ALPHA: usize = 3;
+    const NAME: &'static str = "cmy";
+    const CHANNEL_NAMES: &'static [&'static str] =
+        &["cyan", "magenta", "yellow", "alpha"];
 
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
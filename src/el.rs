@@ -4,15 +4,91 @@
 // Copyright (c) 2019-2020  Jeron Aldaron Lau
 //
 //! Module for `pix::el` items
-use crate::chan::{Alpha, Channel, Gamma, Linear, Premultiplied};
+use crate::bgr::Bgr;
+use crate::chan::{Alpha, Ch32, Channel, Gamma, Linear, Premultiplied};
+use crate::cmy::Cmy;
+use crate::gray::Gray;
 use crate::matte::Matte;
 use crate::ops::Blend;
 use crate::private::Sealed;
 use crate::rgb::Rgb;
 use crate::ColorModel;
-use std::any::TypeId;
-use std::fmt::Debug;
-use std::marker::PhantomData;
+use core::any::TypeId;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug};
+use core::iter::Copied;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::ops::Range;
+use core::slice::Iter;
+
+/// Assert that two pixels are approximately equal.
+///
+/// Uses [Pixel::approx_eq](el/trait.Pixel.html#method.approx_eq) with the
+/// given `epsilon`.
+///
+/// ## Example
+/// ```
+/// use pix::assert_pixel_approx_eq;
+/// use pix::rgb::Rgb32;
+///
+/// let p = Rgb32::new(0.1 + 0.2, 0.5, 0.5);
+/// assert_pixel_approx_eq!(p, Rgb32::new(0.3, 0.5, 0.5), 0.0001);
+/// ```
+#[macro_export]
+macro_rules! assert_pixel_approx_eq {
+    ($left:expr, $right:expr, $epsilon:expr $(,)?) => {{
+        let (left, right) = ($left, $right);
+        if !$crate::el::Pixel::approx_eq(left, right, $epsilon) {
+            panic!(
+                "pixels not approximately equal (epsilon: {})\n  left: {}\n \
+                 right: {}",
+                $epsilon, left, right,
+            );
+        }
+    }};
+}
+
+/// Kind of a [channel] within a [pixel].
+///
+/// [channel]: ../chan/trait.Channel.html
+/// [pixel]: trait.Pixel.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelKind {
+    /// Linear color component, such as *red* or *saturation*
+    Linear,
+    /// Circular color component (*hue*), which wraps around
+    Circular,
+    /// *Alpha* component
+    Alpha,
+}
+
+/// Get kinds of up to four channels of a color model
+const fn channel_kinds(
+    circular: Range<usize>,
+    alpha: usize,
+) -> [ChannelKind; 4] {
+    let mut kinds = [ChannelKind::Linear; 4];
+    let mut i = 0;
+    while i < kinds.len() {
+        if i == alpha {
+            kinds[i] = ChannelKind::Alpha;
+        } else if i >= circular.start && i < circular.end {
+            kinds[i] = ChannelKind::Circular;
+        }
+        i += 1;
+    }
+    kinds
+}
+
+/// Channel layout of a pixel format
+struct Layout<P>(PhantomData<P>);
+
+impl<P: Pixel> Layout<P> {
+    /// Kinds of up to four channels
+    const KINDS: [ChannelKind; 4] =
+        channel_kinds(P::Model::CIRCULAR, P::Model::ALPHA);
+}
 
 /// Pixel [channel], [color model], [alpha] and [gamma] mode.
 ///
@@ -27,13 +103,15 @@ use std::marker::PhantomData;
 /// ### Type Alias Naming Scheme
 ///
 /// * _Gamma_: `S` for [sRGB] gamma encoding; [linear] if omitted.
-/// * _Color model_: [`Rgb`] / [`Bgr`] / [`Gray`] / [`Cmy`] / [`Hsv`] /
-///                  [`Hsl`] / [`Hwb`] / [`YCbCr`] / [`Matte`].
+/// * _Color model_: [`Rgb`] / [`Bgr`] / [`Argb`] / [`Gray`] / [`Cmy`] /
+///   [`Hsv`] / [`Hsl`] / [`Hwb`] / [`YCbCr`] / [`Matte`].  `Argb` and
+///   `Matte` always include *alpha*, so they omit `a`.
 /// * _Alpha_: `a` to include alpha channel enabling translucent pixels.
 /// * _Bit depth_: `8` / `16` / `32` for 8-bit integer, 16-bit integer and
 ///   32-bit floating-point [channels].
 /// * _Alpha mode_: `p` for [premultiplied]; [straight] if omitted.
 ///
+/// [`argb`]: ../argb/struct.Argb.html
 /// [`bgr`]: ../bgr/struct.Bgr.html
 /// [channels]: ../chan/trait.Channel.html
 /// [`cmy`]: ../cmy/struct.Cmy.html
@@ -63,6 +141,51 @@ pub trait Pixel: Clone + Copy + Debug + Default + PartialEq + Sealed {
     /// Gamma mode
     type Gamma: Gamma;
 
+    /// Number of channels
+    ///
+    /// ## Example
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::gray::SGray8;
+    /// use pix::rgb::Rgba8;
+    ///
+    /// assert_eq!(Rgba8::CHANNELS, 4);
+    /// assert_eq!(SGray8::CHANNELS, 1);
+    /// ```
+    const CHANNELS: usize;
+
+    /// Size of a pixel, in bytes
+    ///
+    /// ## Example
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::matte::Matte16;
+    /// use pix::rgb::Rgba32;
+    ///
+    /// assert_eq!(Matte16::BYTES, 2);
+    /// assert_eq!(Rgba32::BYTES, 16);
+    /// ```
+    const BYTES: usize = Self::CHANNELS * size_of::<Self::Chan>();
+
+    /// Get the kind of each channel, in order.
+    ///
+    /// ## Example
+    /// ```
+    /// use pix::el::{ChannelKind, Pixel};
+    /// use pix::hsv::Hsva8;
+    ///
+    /// assert_eq!(Hsva8::channel_layout(), [
+    ///     ChannelKind::Circular,
+    ///     ChannelKind::Linear,
+    ///     ChannelKind::Linear,
+    ///     ChannelKind::Alpha,
+    /// ]);
+    /// ```
+    fn channel_layout() -> &'static [ChannelKind] {
+        let kinds: &'static [ChannelKind; 4] = &Layout::<Self>::KINDS;
+        &kinds[..Self::CHANNELS]
+    }
+
     /// Make a pixel from a slice of channels.
     fn from_channels(ch: &[Self::Chan]) -> Self;
 
@@ -78,6 +201,21 @@ pub trait Pixel: Clone + Copy + Debug + Default + PartialEq + Sealed {
     /// Get the channels mutably.
     fn channels_mut(&mut self) -> &mut [Self::Chan];
 
+    /// Iterate over the channels, in order.
+    ///
+    /// ## Example
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::rgb::Rgba8;
+    ///
+    /// let p = Rgba8::new(1, 2, 3, 4);
+    /// let sum: u32 = p.iter_channels().map(|c| u32::from(u8::from(c))).sum();
+    /// assert_eq!(sum, 10);
+    /// ```
+    fn iter_channels(&self) -> Copied<Iter<'_, Self::Chan>> {
+        self.channels().iter().copied()
+    }
+
     /// Get the first channel.
     fn one(self) -> Self::Chan {
         *self.channels().first().unwrap_or(&Self::Chan::MAX)
@@ -155,6 +293,291 @@ pub trait Pixel: Clone + Copy + Debug + Default + PartialEq + Sealed {
         chan.get_mut(Self::Model::ALPHA).unwrap()
     }
 
+    /// Rotate circular channels, such as *hue*.
+    ///
+    /// * `delta` Amount to add, with wrapping.  Integer channels wrap one
+    ///   step past [Channel::MAX] (at 256 for `Ch8`), while floating point
+    ///   channels wrap at 1.0.
+    ///
+    /// Only channels in the color model's `CIRCULAR` range are changed, so
+    /// this is a no-op for models without *hue*, such as `Rgb` or `Gray`.
+    ///
+    /// # Example: Rotate Hue
+    /// ```
+    /// use pix::chan::Ch8;
+    /// use pix::el::Pixel;
+    /// use pix::hsv::Hsv8;
+    ///
+    /// let p = Hsv8::new(0xC0, 0x80, 0xFF).rotate_circular(Ch8::new(0x80));
+    /// assert_eq!(p, Hsv8::new(0x40, 0x80, 0xFF));
+    /// ```
+    ///
+    /// [Channel::MAX]: ../chan/trait.Channel.html#associatedconstant.MAX
+    fn rotate_circular(mut self, delta: Self::Chan) -> Self {
+        for c in self.channels_mut()[Self::Model::CIRCULAR].iter_mut() {
+            *c = c.wrapping_add(delta);
+        }
+        self
+    }
+
+    /// Make a pixel with a different *alpha* value.
+    ///
+    /// * `alpha` New *alpha* value.
+    ///
+    /// For [premultiplied] formats, color channels are rescaled so that the
+    /// pixel keeps the same color, as it would when converting to and from
+    /// [straight] alpha.  With *alpha* of zero, color is lost.  For
+    /// [straight] formats, only the *alpha* channel is changed.
+    ///
+    /// Formats without an *alpha* channel are returned unchanged.
+    ///
+    /// [premultiplied]: ../chan/struct.Premultiplied.html
+    /// [straight]: ../chan/struct.Straight.html
+    ///
+    /// # Example: Half Transparent
+    /// ```
+    /// use pix::chan::Ch8;
+    /// use pix::el::Pixel;
+    /// use pix::rgb::{Rgb8, Rgba8, Rgba8p};
+    ///
+    /// let p = Rgba8::new(0xFF, 0x80, 0x40, 0xFF).with_alpha(Ch8::new(0x80));
+    /// assert_eq!(p, Rgba8::new(0xFF, 0x80, 0x40, 0x80));
+    /// let p = Rgba8p::new(0xFF, 0x80, 0x40, 0xFF).with_alpha(Ch8::new(0x80));
+    /// assert_eq!(p, Rgba8p::new(0x80, 0x40, 0x20, 0x80));
+    /// let p = Rgb8::new(0xFF, 0x80, 0x40).with_alpha(Ch8::new(0x80));
+    /// assert_eq!(p, Rgb8::new(0xFF, 0x80, 0x40));
+    /// ```
+    fn with_alpha(mut self, alpha: Self::Chan) -> Self {
+        if Self::Model::ALPHA >= Self::CHANNELS {
+            return self;
+        }
+        if TypeId::of::<Self::Alpha>() == TypeId::of::<Premultiplied>() {
+            let old = self.alpha();
+            for c in self.channels_mut()[Self::Model::LINEAR].iter_mut() {
+                *c = convert_channel::<
+                    Premultiplied,
+                    Self::Gamma,
+                    Premultiplied,
+                    Self::Gamma,
+                    _,
+                >(*c, old, alpha);
+            }
+        }
+        *self.alpha_mut() = alpha;
+        self
+    }
+
+    /// Make a pixel with a different channel value.
+    ///
+    /// * `CH` Channel index, in the order of [CHANNEL_NAMES].
+    /// * `value` New channel value.
+    ///
+    /// The value is stored as-is; premultiplied channels are not rescaled.
+    ///
+    /// # Panics
+    ///
+    /// If `CH` is not less than [CHANNELS](#associatedconstant.CHANNELS).
+    ///
+    /// [CHANNEL_NAMES]: ../trait.ColorModel.html#associatedconstant.CHANNEL_NAMES
+    ///
+    /// # Example: Replace Green
+    /// ```
+    /// use pix::chan::Ch8;
+    /// use pix::el::Pixel;
+    /// use pix::rgb::Rgb8;
+    ///
+    /// let p = Rgb8::new(0x10, 0x20, 0x30).with_channel::<1>(Ch8::new(0xFF));
+    /// assert_eq!(p, Rgb8::new(0x10, 0xFF, 0x30));
+    /// ```
+    fn with_channel<const CH: usize>(mut self, value: Self::Chan) -> Self {
+        self.channels_mut()[CH] = value;
+        self
+    }
+
+    /// Check whether all channels are approximately equal.
+    ///
+    /// Channels are compared as `f32` values (ranging from 0 to 1).  Circular
+    /// channels (*hue*) wrap around, so 0.999 and 0.001 are 0.002 apart.
+    ///
+    /// * `rhs` Pixel to compare.
+    /// * `epsilon` Maximum difference for each channel.
+    ///
+    /// # Example: Approximately Equal
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::rgb::Rgba32;
+    ///
+    /// let p = Rgba32::new(0.5, 0.25, 0.125, 1.0);
+    /// assert!(p.approx_eq(Rgba32::new(0.5001, 0.25, 0.125, 1.0), 0.001));
+    /// assert!(!p.approx_eq(Rgba32::new(0.6, 0.25, 0.125, 1.0), 0.001));
+    /// ```
+    fn approx_eq(self, rhs: Self, epsilon: f32) -> bool {
+        self.channels().iter().zip(rhs.channels()).enumerate().all(
+            |(i, (a, b))| {
+                let d = (a.to_f32() - b.to_f32()).abs();
+                let d = if Self::Model::CIRCULAR.contains(&i) {
+                    d.min(1.0 - d)
+                } else {
+                    d
+                };
+                d <= epsilon
+            },
+        )
+    }
+
+    /// Map all channels, including *alpha*, using a function.
+    ///
+    /// * `f` Function called with each channel value, returning the new value.
+    ///
+    /// To leave *alpha* unchanged, use [map_color](#method.map_color).
+    ///
+    /// # Example: Map Channels
+    /// ```
+    /// use pix::chan::{Ch8, Channel};
+    /// use pix::el::Pixel;
+    /// use pix::rgb::Rgba8;
+    ///
+    /// let p = Rgba8::new(0x10, 0x20, 0x30, 0xFF).map(|c| Ch8::MAX - c);
+    /// assert_eq!(p, Rgba8::new(0xEF, 0xDF, 0xCF, 0x00));
+    /// ```
+    fn map<F>(mut self, mut f: F) -> Self
+    where
+        F: FnMut(Self::Chan) -> Self::Chan,
+    {
+        for c in self.channels_mut() {
+            *c = f(*c);
+        }
+        self
+    }
+
+    /// Map color channels using a function, leaving *alpha* unchanged.
+    ///
+    /// * `f` Function called with each color channel value, returning the new
+    ///   value.
+    ///
+    /// # Example: Map Color Channels
+    /// ```
+    /// use pix::chan::{Ch8, Channel};
+    /// use pix::el::Pixel;
+    /// use pix::rgb::Rgba8;
+    ///
+    /// let p = Rgba8::new(0x10, 0x20, 0x30, 0xFF).map_color(|c| Ch8::MAX - c);
+    /// assert_eq!(p, Rgba8::new(0xEF, 0xDF, 0xCF, 0xFF));
+    /// ```
+    fn map_color<F>(mut self, mut f: F) -> Self
+    where
+        F: FnMut(Self::Chan) -> Self::Chan,
+    {
+        for (i, c) in self.channels_mut().iter_mut().enumerate() {
+            if i != Self::Model::ALPHA {
+                *c = f(*c);
+            }
+        }
+        self
+    }
+
+    /// Combine all channels, including *alpha*, with another pixel.
+    ///
+    /// * `rhs` Other pixel.
+    /// * `f` Function called with each pair of channel values, returning the
+    ///   new value.
+    ///
+    /// To leave *alpha* unchanged, use [zip_map_color](#method.zip_map_color).
+    ///
+    /// # Example: Average Two Pixels
+    /// ```
+    /// use pix::chan::Ch32;
+    /// use pix::el::Pixel;
+    /// use pix::rgb::Rgba32;
+    ///
+    /// let half = Ch32::new(0.5);
+    /// let p0 = Rgba32::new(0.2, 0.4, 0.6, 1.0);
+    /// let p1 = Rgba32::new(0.4, 0.0, 0.2, 0.5);
+    /// let p = p0.zip_map(p1, |a, b| a * half + b * half);
+    /// assert!(p.approx_eq(Rgba32::new(0.3, 0.2, 0.4, 0.75), 0.0001));
+    /// ```
+    fn zip_map<F>(mut self, rhs: Self, mut f: F) -> Self
+    where
+        F: FnMut(Self::Chan, Self::Chan) -> Self::Chan,
+    {
+        for (c, r) in self.channels_mut().iter_mut().zip(rhs.channels()) {
+            *c = f(*c, *r);
+        }
+        self
+    }
+
+    /// Combine color channels with another pixel, leaving *alpha* unchanged.
+    ///
+    /// * `rhs` Other pixel.
+    /// * `f` Function called with each pair of color channel values,
+    ///   returning the new value.
+    ///
+    /// # Example: Lighten Color Channels
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::rgb::Rgba8;
+    ///
+    /// let p0 = Rgba8::new(0x10, 0x80, 0x30, 0xFF);
+    /// let p1 = Rgba8::new(0x40, 0x20, 0x30, 0x00);
+    /// let p = p0.zip_map_color(p1, |a, b| a.max(b));
+    /// assert_eq!(p, Rgba8::new(0x40, 0x80, 0x30, 0xFF));
+    /// ```
+    fn zip_map_color<F>(mut self, rhs: Self, mut f: F) -> Self
+    where
+        F: FnMut(Self::Chan, Self::Chan) -> Self::Chan,
+    {
+        let chan = self.channels_mut().iter_mut();
+        for (i, (c, r)) in chan.zip(rhs.channels()).enumerate() {
+            if i != Self::Model::ALPHA {
+                *c = f(*c, *r);
+            }
+        }
+        self
+    }
+
+    /// Check whether a *premultiplied* pixel is valid.
+    ///
+    /// A valid pixel has no linear color channel greater than *alpha*.
+    ///
+    /// # Example: Check Premultiplied
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::rgb::Rgba8p;
+    ///
+    /// assert!(Rgba8p::new(0x20, 0x40, 0x80, 0x80).is_premultiplied_valid());
+    /// assert!(!Rgba8p::new(0xFF, 0xFF, 0xFF, 0x00).is_premultiplied_valid());
+    /// ```
+    fn is_premultiplied_valid(&self) -> bool
+    where
+        Self: Pixel<Alpha = Premultiplied>,
+    {
+        let alpha = self.alpha();
+        self.channels()[Self::Model::LINEAR]
+            .iter()
+            .all(|c| *c <= alpha)
+    }
+
+    /// Clamp linear color channels of a *premultiplied* pixel to *alpha*.
+    ///
+    /// # Example: Clamp Premultiplied
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::rgb::Rgba8p;
+    ///
+    /// let p = Rgba8p::new(0xFF, 0x40, 0x80, 0x60).clamp_premultiplied();
+    /// assert_eq!(p, Rgba8p::new(0x60, 0x40, 0x60, 0x60));
+    /// ```
+    fn clamp_premultiplied(mut self) -> Self
+    where
+        Self: Pixel<Alpha = Premultiplied>,
+    {
+        let alpha = self.alpha();
+        for c in self.channels_mut()[Self::Model::LINEAR].iter_mut() {
+            *c = (*c).min(alpha);
+        }
+        self
+    }
+
     /// Convert a pixel to another format
     ///
     /// * `D` Destination format.
@@ -165,11 +588,35 @@ pub trait Pixel: Clone + Copy + Debug + Default + PartialEq + Sealed {
     {
         if TypeId::of::<Self::Model>() == TypeId::of::<D::Model>() {
             convert_same_model::<D, Self>(self)
+        } else if let Some(dst) = convert_direct::<D, Self>(self) {
+            dst
         } else {
             convert_thru_rgba::<D, Self>(self)
         }
     }
 
+    /// Get the perceptual distance to another pixel.
+    ///
+    /// This is the Euclidean distance in Oklab; see
+    /// [oklab::delta_e](../oklab/fn.delta_e.html).
+    ///
+    /// ### Perceptual distance
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::rgb::{Rgb16, SRgb8};
+    ///
+    /// let gray = SRgb8::new(0x80, 0x80, 0x80);
+    /// let d = gray.distance_oklab(Rgb16::new(0x3000, 0x3000, 0x3000));
+    /// assert!(d < 0.1);
+    /// ```
+    fn distance_oklab<R>(self, rhs: R) -> f32
+    where
+        R: Pixel,
+        Ch32: From<Self::Chan> + From<R::Chan>,
+    {
+        crate::oklab::delta_e(self, rhs)
+    }
+
     /// Copy a color to a pixel slice
     fn copy_color(dst: &mut [Self], clr: &Self) {
         for d in dst.iter_mut() {
@@ -178,10 +625,13 @@ pub trait Pixel: Clone + Copy + Debug + Default + PartialEq + Sealed {
     }
 
     /// Copy a slice to another
+    ///
+    /// If the slices have different lengths, only the shorter length is
+    /// copied, and any remaining `dst` pixels are unchanged.  The copy is
+    /// done with `copy_from_slice`, which compiles to a `memcpy`.
     fn copy_slice(dst: &mut [Self], src: &[Self]) {
-        for (d, s) in dst.iter_mut().zip(src) {
-            *d = *s;
-        }
+        let len = dst.len().min(src.len());
+        dst[..len].copy_from_slice(&src[..len]);
     }
 
     /// Composite a color with a pixel slice
@@ -196,14 +646,68 @@ pub trait Pixel: Clone + Copy + Debug + Default + PartialEq + Sealed {
     }
 
     /// Composite matte with color to destination pixel slice
+    ///
+    /// The matte may have a different bit depth than the destination.  Its
+    /// values are interpreted as linear coverage, regardless of `Gamma`.
     fn composite_matte<M, O>(dst: &mut [Self], src: &[M], clr: &Self, op: O)
     where
         Self: Pixel<Alpha = Premultiplied, Gamma = Linear>,
-        M: Pixel<Chan = Self::Chan, Model = Matte, Gamma = Linear>,
+        M: Pixel<Model = Matte>,
+        Self::Chan: From<M::Chan>,
         O: Blend,
     {
         for (d, s) in dst.iter_mut().zip(src) {
-            d.composite_channels_alpha(clr, op, &s.alpha());
+            let alpha = Self::Chan::from(s.alpha());
+            d.composite_channels_alpha(clr, op, &alpha);
+        }
+    }
+
+    /// Composite matte with two colors to destination pixel slice
+    ///
+    /// `inside` is weighted by the matte value, then `outside` by its
+    /// inverse.
+    fn composite_matte_pair<M, O>(
+        dst: &mut [Self],
+        src: &[M],
+        inside: &Self,
+        outside: &Self,
+        op: O,
+    ) where
+        Self: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+        M: Pixel<Model = Matte>,
+        Self::Chan: From<M::Chan>,
+        O: Blend,
+    {
+        for (d, s) in dst.iter_mut().zip(src) {
+            let alpha = Self::Chan::from(s.alpha());
+            d.composite_channels_alpha(inside, op, &alpha);
+            let alpha = Self::Chan::from(M::Chan::MAX - s.alpha());
+            d.composite_channels_alpha(outside, op, &alpha);
+        }
+    }
+
+    /// Composite single-channel coverage with color to destination pixel
+    /// slice
+    ///
+    /// The first (only) channel of each source pixel is used as coverage,
+    /// whether its model is `Matte` or `Gray`.
+    fn composite_coverage<C, M, A, G, O>(
+        dst: &mut [Self],
+        src: &[Pix1<C, M, A, G>],
+        clr: &Self,
+        op: O,
+    ) where
+        Self: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+        C: Channel,
+        M: ColorModel,
+        A: Alpha,
+        G: Gamma,
+        Self::Chan: From<C>,
+        O: Blend,
+    {
+        for (d, s) in dst.iter_mut().zip(src) {
+            let alpha = Self::Chan::from(s.one());
+            d.composite_channels_alpha(clr, op, &alpha);
         }
     }
 
@@ -218,29 +722,36 @@ pub trait Pixel: Clone + Copy + Debug + Default + PartialEq + Sealed {
         }
     }
 
+    /// Composite a source slice of another pixel format
+    ///
+    /// The formats must have the same color model and channel type, but
+    /// either may lack an *alpha* channel.  Missing source *alpha* is
+    /// treated as opaque, and missing destination *alpha* is not written.
+    fn composite_slice_from<S, O>(dst: &mut [Self], src: &[S], op: O)
+    where
+        Self: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+        S: Pixel<
+            Chan = Self::Chan,
+            Model = Self::Model,
+            Alpha = Premultiplied,
+            Gamma = Linear,
+        >,
+        O: Blend,
+    {
+        for (d, s) in dst.iter_mut().zip(src) {
+            composite_pixel(d, s, op);
+        }
+    }
+
     /// Composite the channels of two pixels
+    ///
+    /// If the pixel format has no *alpha* channel, it is treated as opaque.
     fn composite_channels<O>(&mut self, src: &Self, op: O)
     where
         Self: Pixel<Alpha = Premultiplied, Gamma = Linear>,
         O: Blend,
     {
-        let da1 = Self::Chan::MAX - self.alpha();
-        let sa1 = Self::Chan::MAX - src.alpha();
-        // circular channels
-        let d_chan = &mut self.channels_mut()[Self::Model::CIRCULAR];
-        let s_chan = &src.channels()[Self::Model::CIRCULAR];
-        d_chan
-            .iter_mut()
-            .zip(s_chan)
-            .for_each(|(d, s)| circ_composite(d, da1, *s, sa1, op));
-        // linear channels
-        let d_chan = &mut self.channels_mut()[Self::Model::LINEAR];
-        let s_chan = &src.channels()[Self::Model::LINEAR];
-        d_chan
-            .iter_mut()
-            .zip(s_chan)
-            .for_each(|(d, s)| O::composite(d, da1, s, sa1));
-        O::composite(self.alpha_mut(), da1, &src.alpha(), sa1);
+        composite_pixel(self, src, op);
     }
 
     /// Composite the channels of two pixels with alpha
@@ -254,7 +765,7 @@ pub trait Pixel: Clone + Copy + Debug + Default + PartialEq + Sealed {
         O: Blend,
     {
         let da1 = Self::Chan::MAX - self.alpha();
-        let sa1 = Self::Chan::MAX - *alpha;
+        let sa1 = Self::Chan::MAX - src.alpha() * *alpha;
         // circular channels
         let d_chan = &mut self.channels_mut()[Self::Model::CIRCULAR];
         let s_chan = &src.channels()[Self::Model::CIRCULAR];
@@ -268,14 +779,80 @@ pub trait Pixel: Clone + Copy + Debug + Default + PartialEq + Sealed {
         d_chan
             .iter_mut()
             .zip(s_chan)
-            .for_each(|(d, s)| O::composite(d, da1, &(*s * *alpha), sa1));
-        O::composite(self.alpha_mut(), da1, &(src.alpha() * *alpha), sa1);
+            .for_each(|(d, s)| op.composite(d, da1, &(*s * *alpha), sa1));
+        if Self::Model::ALPHA < Self::CHANNELS {
+            let sa = src.alpha() * *alpha;
+            op.composite_alpha(self.alpha_mut(), da1, &sa, sa1);
+        }
+    }
+}
+
+/// [Pixel] format with an *alpha* channel.
+///
+/// Implemented for all pixels with four channels, as well as two channel
+/// `Gray` and one channel `Matte`.
+///
+/// [pixel]: trait.Pixel.html
+pub trait AlphaPixel: Pixel {}
+
+impl<C, M, A, G> AlphaPixel for Pix4<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+}
+
+impl<C, A, G> AlphaPixel for Pix2<C, Gray, A, G>
+where
+    C: Channel,
+    A: Alpha,
+    G: Gamma,
+{
+}
+
+impl<C, A, G> AlphaPixel for Pix1<C, Matte, A, G>
+where
+    C: Channel,
+    A: Alpha,
+    G: Gamma,
+{
+}
+
+/// Composite the channels of a source pixel onto a destination pixel
+///
+/// The destination *alpha* is only written if it has an *alpha* channel.
+fn composite_pixel<D, S, O>(dst: &mut D, src: &S, op: O)
+where
+    D: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    S: Pixel<Chan = D::Chan, Model = D::Model>,
+    O: Blend,
+{
+    let da1 = D::Chan::MAX - dst.alpha();
+    let sa1 = D::Chan::MAX - src.alpha();
+    // circular channels
+    let d_chan = &mut dst.channels_mut()[D::Model::CIRCULAR];
+    let s_chan = &src.channels()[D::Model::CIRCULAR];
+    d_chan
+        .iter_mut()
+        .zip(s_chan)
+        .for_each(|(d, s)| circ_composite(d, da1, *s, sa1, op));
+    // linear channels
+    let d_chan = &mut dst.channels_mut()[D::Model::LINEAR];
+    let s_chan = &src.channels()[D::Model::LINEAR];
+    d_chan
+        .iter_mut()
+        .zip(s_chan)
+        .for_each(|(d, s)| op.composite(d, da1, s, sa1));
+    if D::Model::ALPHA < D::CHANNELS {
+        op.composite_alpha(dst.alpha_mut(), da1, &src.alpha(), sa1);
     }
 }
 
 /// Calculate composite for a circular channel
 #[inline]
-fn circ_composite<C, O>(d: &mut C, da1: C, mut s: C, sa1: C, _op: O)
+fn circ_composite<C, O>(d: &mut C, da1: C, mut s: C, sa1: C, op: O)
 where
     C: Channel,
     O: Blend,
@@ -283,7 +860,14 @@ where
     // Circular channels are not premultiplied, so here's the algorithm:
     // 1. Calcualte `t`, ranging from MIN (dst) to MAX (src), using composite
     let mut t = C::MIN;
-    O::composite(&mut t, da1, &(C::MAX - sa1), sa1);
+    op.composite(&mut t, da1, &(C::MAX - sa1), sa1);
+    // A transparent destination contributes nothing, so the source is used
+    // outright (and vice versa)
+    if da1 == C::MAX && t > C::MIN {
+        t = C::MAX;
+    } else if sa1 == C::MAX {
+        t = C::MIN;
+    }
     // 2. If difference > 180 degrees, rotate both by 180 degrees
     let rotate = s.max(*d) - s.min(*d) > C::MID;
     if rotate {
@@ -340,12 +924,115 @@ where
     S: Pixel,
 {
     for c in channels[D::Model::LINEAR].iter_mut() {
-        *c = S::Gamma::to_linear(*c);
-        if TypeId::of::<S::Alpha>() != TypeId::of::<D::Alpha>() {
-            *c = S::Alpha::decode(*c, alpha);
-            *c = D::Alpha::encode(*c, alpha);
-        }
-        *c = D::Gamma::from_linear(*c);
+        *c = convert_channel::<S::Alpha, S::Gamma, D::Alpha, D::Gamma, _>(
+            *c, alpha, alpha,
+        );
+    }
+}
+
+/// Convert one channel to another *alpha* / *gamma* mode.
+///
+/// When *alpha* changes and either side is premultiplied with non-linear
+/// *gamma*, integer channels are converted through `Ch32`, so that the
+/// linear value is only rounded once.  Otherwise, those channels could lose
+/// several bits of precision.  All other conversions use the channel type
+/// directly, with its look-up tables.
+///
+/// * `c` Channel value.
+/// * `src_alpha` Source *alpha* value.
+/// * `dst_alpha` Destination *alpha* value.
+pub(crate) fn convert_channel<SA, SG, DA, DG, C>(
+    c: C,
+    src_alpha: C,
+    dst_alpha: C,
+) -> C
+where
+    SA: Alpha,
+    SG: Gamma,
+    DA: Alpha,
+    DG: Gamma,
+    C: Channel,
+{
+    let alpha =
+        TypeId::of::<SA>() != TypeId::of::<DA>() || src_alpha != dst_alpha;
+    if !alpha {
+        return DG::from_linear(SG::to_linear(c));
+    }
+    if C::INTEGER_BITS > 0 && premultiplied_gamma::<SA, SG, DA, DG>() {
+        let v = convert_channel::<SA, SG, DA, DG, Ch32>(
+            Ch32::new(c.to_f32()),
+            Ch32::new(src_alpha.to_f32()),
+            Ch32::new(dst_alpha.to_f32()),
+        );
+        return C::from(v.to_f32());
+    }
+    let v = DA::encode(SA::decode(SG::to_linear(c), src_alpha), dst_alpha);
+    DG::from_linear(v)
+}
+
+/// Check if either side is premultiplied with non-linear *gamma*
+fn premultiplied_gamma<SA, SG, DA, DG>() -> bool
+where
+    SA: Alpha,
+    SG: Gamma,
+    DA: Alpha,
+    DG: Gamma,
+{
+    let premul = TypeId::of::<Premultiplied>();
+    let linear = TypeId::of::<Linear>();
+    (TypeId::of::<SA>() == premul && TypeId::of::<SG>() != linear)
+        || (TypeId::of::<DA>() == premul && TypeId::of::<DG>() != linear)
+}
+
+/// Convert a pixel directly to another color model, if possible.
+///
+/// Some model pairs have exact formulas which can be computed at the source
+/// bit depth, rounding only once to the destination channel.  These are only
+/// used when *alpha* mode and *gamma* mode are the same.
+///
+/// * `D` Destination pixel format.
+/// * `S` Source pixel format.
+/// * `src` Source pixel.
+fn convert_direct<D, S>(src: S) -> Option<D>
+where
+    D: Pixel,
+    S: Pixel,
+    D::Chan: From<S::Chan>,
+{
+    if TypeId::of::<S::Alpha>() != TypeId::of::<D::Alpha>()
+        || TypeId::of::<S::Gamma>() != TypeId::of::<D::Gamma>()
+    {
+        return None;
+    }
+    let sm = TypeId::of::<S::Model>();
+    let dm = TypeId::of::<D::Model>();
+    let rgb = TypeId::of::<Rgb>();
+    let alpha = D::Chan::from(src.alpha());
+    if sm == TypeId::of::<Gray>() && dm == rgb {
+        let value = D::Chan::from(src.one());
+        Some(D::from_channels(&[value, value, value, alpha]))
+    } else if sm == rgb && dm == TypeId::of::<Gray>() {
+        let red = src.one().to_f32();
+        let green = src.two().to_f32();
+        let blue = src.three().to_f32();
+        let value = <D::Chan as From<f32>>::from(Gray::luma(red, green, blue));
+        Some(D::from_channels(&[value, alpha]))
+    } else if (sm == TypeId::of::<Cmy>() && dm == rgb)
+        || (sm == rgb && dm == TypeId::of::<Cmy>())
+    {
+        let one = D::Chan::from(S::Chan::MAX - src.one());
+        let two = D::Chan::from(S::Chan::MAX - src.two());
+        let three = D::Chan::from(S::Chan::MAX - src.three());
+        Some(D::from_channels(&[one, two, three, alpha]))
+    } else if (sm == TypeId::of::<Bgr>() && dm == rgb)
+        || (sm == rgb && dm == TypeId::of::<Bgr>())
+    {
+        let one = D::Chan::from(src.three());
+        let two = D::Chan::from(src.two());
+        let three = D::Chan::from(src.one());
+        Some(D::from_channels(&[one, two, three, alpha]))
+    } else {
+        None
     }
 }
 
@@ -365,12 +1052,37 @@ where
     D::Model::from_rgba::<D>(rgba)
 }
 
+/// Format pixel channels, such as `rgb(255, 0, 128)`
+fn fmt_channels<P>(p: &P, f: &mut fmt::Formatter<'_>) -> fmt::Result
+where
+    P: Pixel,
+    P::Chan: fmt::Display,
+{
+    let has_alpha = P::Model::ALPHA < P::CHANNELS;
+    let only_alpha = P::CHANNELS == 1 && has_alpha;
+    if has_alpha && !only_alpha && P::Model::ALPHA == 0 {
+        write!(f, "a")?;
+    }
+    write!(f, "{}", P::Model::NAME)?;
+    if has_alpha && !only_alpha && P::Model::ALPHA > 0 {
+        write!(f, "a")?;
+    }
+    write!(f, "(")?;
+    for (i, c) in p.channels().iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        fmt::Display::fmt(c, f)?;
+    }
+    write!(f, ")")
+}
+
 /// [Pixel] with one [channel] in its [color model].
 ///
 /// [channel]: ../chan/trait.Channel.html
 /// [color model]: ../trait.ColorModel.html
 /// [pixel]: trait.Pixel.html
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default)]
 #[repr(C)]
 pub struct Pix1<C, M, A, G>
 where
@@ -426,6 +1138,8 @@ where
     type Alpha = A;
     type Gamma = G;
 
+    const CHANNELS: usize = 1;
+
     fn from_channels(ch: &[C]) -> Self {
         let one = ch[0];
         Self::new::<C>(one)
@@ -450,12 +1164,110 @@ where
     }
 }
 
+impl<'a, C, M, A, G> IntoIterator for &'a Pix1<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    type Item = C;
+    type IntoIter = Copied<Iter<'a, C>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.channels.iter().copied()
+    }
+}
+
+impl<C, M, A, G, H> From<(H,)> for Pix1<C, M, A, G>
+where
+    C: Channel + From<H>,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn from((one,): (H,)) -> Self {
+        Self::new(one)
+    }
+}
+
+impl<C, M, A, G, T> From<Pix1<C, M, A, G>> for [T; 1]
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+    T: From<C>,
+{
+    fn from(p: Pix1<C, M, A, G>) -> Self {
+        p.channels.map(T::from)
+    }
+}
+
+impl<C, M, A, G> fmt::Display for Pix1<C, M, A, G>
+where
+    C: Channel + fmt::Display,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_channels(self, f)
+    }
+}
+
+impl<C, M, A, G> PartialEq for Pix1<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.channels == other.channels
+    }
+}
+
+impl<C, M, A, G> Eq for Pix1<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+}
+
+impl<C, M, A, G> PartialOrd for Pix1<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C, M, A, G> Ord for Pix1<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    /// Compare channels in order (for sorting, not perceptual)
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.channels.cmp(&other.channels)
+    }
+}
+
 /// [Pixel] with two [channel]s in its [color model].
 ///
 /// [channel]: ../chan/trait.Channel.html
 /// [color model]: ../trait.ColorModel.html
 /// [pixel]: trait.Pixel.html
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default)]
 #[repr(C)]
 pub struct Pix2<C, M, A, G>
 where
@@ -513,6 +1325,8 @@ where
     type Alpha = A;
     type Gamma = G;
 
+    const CHANNELS: usize = 2;
+
     fn from_channels(ch: &[C]) -> Self {
         let one = ch[0];
         let two = ch[1];
@@ -534,8 +1348,126 @@ where
         &self.channels
     }
 
-    fn channels_mut(&mut self) -> &mut [Self::Chan] {
-        &mut self.channels
+    fn channels_mut(&mut self) -> &mut [Self::Chan] {
+        &mut self.channels
+    }
+}
+
+impl<'a, C, M, A, G> IntoIterator for &'a Pix2<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    type Item = C;
+    type IntoIter = Copied<Iter<'a, C>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.channels.iter().copied()
+    }
+}
+
+/// Convert a tuple of channel values into a pixel.
+///
+/// Channels are in the same order as `new`, with *alpha* last.
+///
+/// ## Example
+/// ```
+/// use pix::gray::Graya16;
+///
+/// let p: Graya16 = (0x4000, 0xFFFF).into();
+/// assert_eq!(p, Graya16::new(0x4000, 0xFFFF));
+/// ```
+impl<C, M, A, G, H> From<(H, H)> for Pix2<C, M, A, G>
+where
+    C: Channel + From<H>,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn from((one, two): (H, H)) -> Self {
+        Self::new(one, two)
+    }
+}
+
+/// Convert a pixel into an array of channel values.
+///
+/// ## Example
+/// ```
+/// use pix::gray::Graya16;
+///
+/// let [v, a]: [u16; 2] = Graya16::new(0x4000, 0xFFFF).into();
+/// assert_eq!((v, a), (0x4000, 0xFFFF));
+/// ```
+impl<C, M, A, G, T> From<Pix2<C, M, A, G>> for [T; 2]
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+    T: From<C>,
+{
+    fn from(p: Pix2<C, M, A, G>) -> Self {
+        p.channels.map(T::from)
+    }
+}
+
+impl<C, M, A, G> fmt::Display for Pix2<C, M, A, G>
+where
+    C: Channel + fmt::Display,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_channels(self, f)
+    }
+}
+
+impl<C, M, A, G> PartialEq for Pix2<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.channels == other.channels
+    }
+}
+
+impl<C, M, A, G> Eq for Pix2<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+}
+
+impl<C, M, A, G> PartialOrd for Pix2<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C, M, A, G> Ord for Pix2<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    /// Compare channels in order (for sorting, not perceptual)
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.channels.cmp(&other.channels)
     }
 }
 
@@ -544,7 +1476,7 @@ where
 /// [channel]: ../chan/trait.Channel.html
 /// [color model]: ../trait.ColorModel.html
 /// [pixel]: trait.Pixel.html
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default)]
 #[repr(C)]
 pub struct Pix3<C, M, A, G>
 where
@@ -603,6 +1535,8 @@ where
     type Alpha = A;
     type Gamma = G;
 
+    const CHANNELS: usize = 3;
+
     fn from_channels(ch: &[C]) -> Self {
         let one = ch[0];
         let two = ch[1];
@@ -631,12 +1565,136 @@ where
     }
 }
 
+impl<'a, C, M, A, G> IntoIterator for &'a Pix3<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    type Item = C;
+    type IntoIter = Copied<Iter<'a, C>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.channels.iter().copied()
+    }
+}
+
+/// Convert a tuple of channel values into a pixel.
+///
+/// Channels are in the same order as `new`.
+///
+/// ## Example
+/// ```
+/// use pix::hsv::Hsv32;
+/// use pix::rgb::Rgb8;
+///
+/// let c: Rgb8 = (255, 0, 128).into();
+/// assert_eq!(c, Rgb8::new(255, 0, 128));
+/// let h: Hsv32 = (0.5, 1.0, 0.25).into();
+/// assert_eq!(h, Hsv32::new(0.5, 1.0, 0.25));
+/// ```
+impl<C, M, A, G, H> From<(H, H, H)> for Pix3<C, M, A, G>
+where
+    C: Channel + From<H>,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn from((one, two, three): (H, H, H)) -> Self {
+        Self::new(one, two, three)
+    }
+}
+
+/// Convert a pixel into an array of channel values.
+///
+/// ## Example
+/// ```
+/// use pix::hsv::Hsv32;
+/// use pix::rgb::Rgb8;
+///
+/// let [r, g, b]: [u8; 3] = Rgb8::new(255, 0, 128).into();
+/// assert_eq!((r, g, b), (255, 0, 128));
+/// let [h, s, v]: [f32; 3] = Hsv32::new(0.5, 1.0, 0.25).into();
+/// assert_eq!((h, s, v), (0.5, 1.0, 0.25));
+/// ```
+impl<C, M, A, G, T> From<Pix3<C, M, A, G>> for [T; 3]
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+    T: From<C>,
+{
+    fn from(p: Pix3<C, M, A, G>) -> Self {
+        p.channels.map(T::from)
+    }
+}
+
+impl<C, M, A, G> fmt::Display for Pix3<C, M, A, G>
+where
+    C: Channel + fmt::Display,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_channels(self, f)
+    }
+}
+
+impl<C, M, A, G> PartialEq for Pix3<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.channels == other.channels
+    }
+}
+
+impl<C, M, A, G> Eq for Pix3<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+}
+
+impl<C, M, A, G> PartialOrd for Pix3<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C, M, A, G> Ord for Pix3<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    /// Compare channels in order (for sorting, not perceptual)
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.channels.cmp(&other.channels)
+    }
+}
+
 /// [Pixel] with four [channel]s in its [color model].
 ///
 /// [channel]: ../chan/trait.Channel.html
 /// [color model]: ../trait.ColorModel.html
 /// [pixel]: trait.Pixel.html
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default)]
 #[repr(C)]
 pub struct Pix4<C, M, A, G>
 where
@@ -696,6 +1754,8 @@ where
     type Alpha = A;
     type Gamma = G;
 
+    const CHANNELS: usize = 4;
+
     fn from_channels(ch: &[C]) -> Self {
         let one = ch[0];
         let two = ch[1];
@@ -726,10 +1786,132 @@ where
     }
 }
 
+impl<'a, C, M, A, G> IntoIterator for &'a Pix4<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    type Item = C;
+    type IntoIter = Copied<Iter<'a, C>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.channels.iter().copied()
+    }
+}
+
+/// Convert a tuple of channel values into a pixel.
+///
+/// Channels are in the same order as `new`, with *alpha* last.
+///
+/// ## Example
+/// ```
+/// use pix::rgb::SRgba8;
+///
+/// let c: SRgba8 = (255, 0, 128, 64).into();
+/// assert_eq!(c, SRgba8::new(255, 0, 128, 64));
+/// ```
+impl<C, M, A, G, H> From<(H, H, H, H)> for Pix4<C, M, A, G>
+where
+    C: Channel + From<H>,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn from((one, two, three, four): (H, H, H, H)) -> Self {
+        Self::new(one, two, three, four)
+    }
+}
+
+/// Convert a pixel into an array of channel values.
+///
+/// ## Example
+/// ```
+/// use pix::rgb::SRgba8;
+///
+/// let [r, g, b, a]: [u8; 4] = SRgba8::new(255, 0, 128, 64).into();
+/// assert_eq!((r, g, b, a), (255, 0, 128, 64));
+/// ```
+impl<C, M, A, G, T> From<Pix4<C, M, A, G>> for [T; 4]
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+    T: From<C>,
+{
+    fn from(p: Pix4<C, M, A, G>) -> Self {
+        p.channels.map(T::from)
+    }
+}
+
+impl<C, M, A, G> fmt::Display for Pix4<C, M, A, G>
+where
+    C: Channel + fmt::Display,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_channels(self, f)
+    }
+}
+
+impl<C, M, A, G> PartialEq for Pix4<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.channels == other.channels
+    }
+}
+
+impl<C, M, A, G> Eq for Pix4<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+}
+
+impl<C, M, A, G> PartialOrd for Pix4<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C, M, A, G> Ord for Pix4<C, M, A, G>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    /// Compare channels in order (for sorting, not perceptual)
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.channels.cmp(&other.channels)
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use crate::bgr::*;
+    use crate::chan::{Ch8, Channel};
+    use crate::cmy::*;
     use crate::el::*;
     use crate::gray::*;
+    use crate::hsv::*;
     use crate::matte::*;
     use crate::rgb::*;
 
@@ -806,7 +1988,7 @@ mod test {
             SRgb8::new(0xEF, 0x8C, 0xC7).convert(),
         );
         assert_eq!(
-            Rgb8::new(0x22, 0xE7, 0x76),
+            Rgb8::new(0x22, 0xE5, 0x76),
             SRgb16::new(0x6673, 0xF453, 0xB593).convert(),
         );
         assert_eq!(
@@ -815,6 +1997,28 @@ mod test {
         );
     }
 
+    #[test]
+    fn gamma_only_tables() {
+        for v in 0..=255 {
+            let c = Ch8::new(v);
+            let lin: Rgb8 = SRgb8::new(v, v, v).convert();
+            assert_eq!(lin.one(), c.decode_srgb());
+            let srgb: SRgb8 = Rgb8::new(v, v, v).convert();
+            assert_eq!(srgb.one(), c.encode_srgb());
+        }
+    }
+
+    #[test]
+    fn srgb_straight_to_linear_premultiplied() {
+        for v in (0..=255).step_by(5) {
+            for a in (0..=255).step_by(15) {
+                let c = Ch8::new(v).decode_srgb() * Ch8::new(a);
+                let p: Rgba8p = SRgba8::new(v, v, v, a).convert();
+                assert_eq!(p, Rgba8p::new(c, c, c, Ch8::new(a)));
+            }
+        }
+    }
+
     #[test]
     fn straight_to_premultiplied() {
         assert_eq!(
@@ -847,19 +2051,301 @@ mod test {
         );
     }
 
+    #[test]
+    fn premultiplied_valid() {
+        assert!(Rgba8p::new(0x80, 0x80, 0x80, 0x80).is_premultiplied_valid());
+        assert!(Rgba8p::new(0, 0, 0, 0).is_premultiplied_valid());
+        assert!(!Rgba8p::new(0x81, 0x00, 0x00, 0x80).is_premultiplied_valid());
+        assert!(!Rgba8p::new(0, 0, 1, 0).is_premultiplied_valid());
+        assert!(Graya16p::new(0x1000, 0x1000).is_premultiplied_valid());
+        assert!(!Graya16p::new(0x1001, 0x1000).is_premultiplied_valid());
+        assert!(Matte8::new(0x00).is_premultiplied_valid());
+    }
+
+    #[test]
+    fn premultiplied_clamp() {
+        assert_eq!(
+            Rgba8p::new(0x10, 0x20, 0x18, 0x20),
+            Rgba8p::new(0x10, 0x40, 0x18, 0x20).clamp_premultiplied(),
+        );
+        assert_eq!(
+            Rgba8p::new(0, 0, 0, 0),
+            Rgba8p::new(0xFF, 0xFF, 0xFF, 0).clamp_premultiplied(),
+        );
+        assert_eq!(
+            Graya16p::new(0x8000, 0x8000),
+            Graya16p::new(0xFFFF, 0x8000).clamp_premultiplied(),
+        );
+    }
+
+    #[test]
+    fn iter_channels() {
+        let p = Rgba8::new(0x10, 0x20, 0x30, 0x40);
+        let v: Vec<u8> = p.iter_channels().map(u8::from).collect();
+        assert_eq!(v, [0x10, 0x20, 0x30, 0x40]);
+        let v: Vec<u8> = (&p).into_iter().map(u8::from).collect();
+        assert_eq!(v, [0x10, 0x20, 0x30, 0x40]);
+        assert_eq!(Gray8::new(0x10).iter_channels().count(), 1);
+        assert_eq!(Graya8::new(0x10, 0x20).iter_channels().count(), 2);
+        assert_eq!(Rgb8::new(0x10, 0x20, 0x30).iter_channels().count(), 3);
+        let mut n = 0;
+        for _c in &p {
+            n += 1;
+        }
+        assert_eq!(n, 4);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Rgb8::new(147, 128, 160).to_string(), "rgb(147, 128, 160)");
+        assert_eq!(
+            Rgba16::new(1, 2, 3, 0xFFFF).to_string(),
+            "rgba(1, 2, 3, 65535)"
+        );
+        assert_eq!(
+            Hsva32::new(0.25, 0.5, 1.0, 1.0).to_string(),
+            "hsva(0.25, 0.5, 1.0, 1.0)"
+        );
+        assert_eq!(SGraya8::new(0x80, 0xFF).to_string(), "graya(128, 255)");
+        assert_eq!(Matte8::new(0x40).to_string(), "matte(64)");
+        assert_eq!(SRgb8::new(0x80, 0, 0xFF).to_string(), "rgb(128, 0, 255)");
+        assert_eq!(SRgba8::new(1, 2, 3, 4).to_string(), "rgba(1, 2, 3, 4)");
+        assert_eq!(Rgba8::new(1, 2, 3, 4).to_string(), "rgba(1, 2, 3, 4)");
+    }
+
+    #[test]
+    fn approx_eq() {
+        let p = Rgba32::new(0.5, 0.25, 0.125, 1.0);
+        assert!(p.approx_eq(p, 0.0));
+        assert!(p.approx_eq(Rgba32::new(0.505, 0.25, 0.125, 0.995), 0.01));
+        assert!(!p.approx_eq(Rgba32::new(0.52, 0.25, 0.125, 1.0), 0.01));
+        let h0 = Hsv32::new(0.999, 0.5, 0.5);
+        let h1 = Hsv32::new(0.001, 0.5, 0.5);
+        assert!(h0.approx_eq(h1, 0.01));
+        assert!(!h0.approx_eq(Hsv32::new(0.5, 0.5, 0.5), 0.01));
+        let s0 = Hsv32::new(0.5, 0.999, 0.5);
+        let s1 = Hsv32::new(0.5, 0.001, 0.5);
+        assert!(!s0.approx_eq(s1, 0.01));
+        assert_pixel_approx_eq!(h0, h1, 0.01);
+    }
+
+    #[test]
+    #[should_panic(expected = "pixels not approximately equal")]
+    fn assert_approx_eq() {
+        assert_pixel_approx_eq!(Gray32::new(0.5), Gray32::new(0.6), 0.01);
+    }
+
+    const _: () = assert!(Rgba8::CHANNELS == 4);
+    const _: () = assert!(SGray16::CHANNELS == 1);
+    const _: () = assert!(Matte16::BYTES == 2);
+    const _: () = assert!(SRgb8::BYTES == 3);
+    const _: () = assert!(Hsva32::BYTES == 16);
+
+    #[test]
+    fn channel_layout() {
+        use ChannelKind::*;
+        assert_eq!(Matte8::channel_layout(), [Alpha]);
+        assert_eq!(SGray8::channel_layout(), [Linear]);
+        assert_eq!(SGraya16::channel_layout(), [Linear, Alpha]);
+        assert_eq!(Rgb32::channel_layout(), [Linear, Linear, Linear]);
+        assert_eq!(Hsv8::channel_layout(), [Circular, Linear, Linear]);
+        for kinds in [Rgba8::channel_layout(), Hsva32::channel_layout()] {
+            assert_eq!(kinds.len(), 4);
+            assert_eq!(kinds[3], Alpha);
+        }
+    }
+
+    #[test]
+    fn map_identity() {
+        let p = SRgba8::new(0x12, 0x34, 0x56, 0x78);
+        assert_eq!(p.map(|c| c), p);
+        assert_eq!(p.map_color(|c| c), p);
+        let p = Hsv32::new(0.25, 0.5, 0.75);
+        assert_eq!(p.map(|c| c), p);
+        let p = Matte16::new(0x1234);
+        assert_eq!(p.map(|c| c), p);
+        assert_eq!(p.map_color(|_| 0.into()), p);
+        let p = Gray8::new(0x40).map_color(|_| 0x80.into());
+        assert_eq!(p, Gray8::new(0x80));
+    }
+
+    #[test]
+    fn zip_map_max() {
+        let p0 = Rgba8::new(0x10, 0x80, 0x30, 0xFF);
+        let p1 = Rgba8::new(0x40, 0x20, 0x30, 0x00);
+        assert_eq!(
+            p0.zip_map(p1, |a, b| a.max(b)),
+            Rgba8::new(0x40, 0x80, 0x30, 0xFF)
+        );
+        assert_eq!(
+            p1.zip_map_color(p0, |a, b| a.max(b)),
+            Rgba8::new(0x40, 0x80, 0x30, 0x00)
+        );
+        let p0 = Gray16::new(0x1000);
+        let p1 = Gray16::new(0x2000);
+        assert_eq!(p0.zip_map(p1, |a, b| a.max(b)), p1);
+        let p0 = SGraya32::new(0.5, 0.25);
+        let p1 = SGraya32::new(0.25, 0.75);
+        assert_eq!(p0.zip_map(p1, |a, b| a.max(b)), SGraya32::new(0.5, 0.75));
+        assert_eq!(p0.zip_map_color(p1, |a, b| a.max(b)), p0);
+    }
+
+    #[test]
+    fn tuple_array_conversions() {
+        let m: Matte8 = (0x80,).into();
+        assert_eq!(m, Matte8::new(0x80));
+        assert_eq!(<[u8; 1]>::from(m), [0x80]);
+        let p: SGraya8 = (0x20, 0xC0).into();
+        assert_eq!(u8::from(p.alpha()), 0xC0);
+        assert_eq!(<[u8; 2]>::from(p), [0x20, 0xC0]);
+        let p: Rgba16 = (1, 2, 3, 4).into();
+        assert_eq!(<[u16; 4]>::from(p), [1, 2, 3, 4]);
+        assert_eq!(<[f32; 4]>::from(p)[3], 4.0 / 65535.0);
+    }
+
     #[test]
     fn straight_to_premultiplied_srgb() {
         assert_eq!(
-            SRgba8p::new(0x16, 0x2A, 0x5C, 0x80),
+            SRgba8p::new(0x15, 0x2D, 0x5D, 0x80),
             SRgba8::new(0x20, 0x40, 0x80, 0x80).convert(),
         );
         assert_eq!(
-            SRgba8p::new(0x0D, 0x1C, 0x40, 0x40),
+            SRgba8p::new(0x0C, 0x1E, 0x42, 0x40),
             SRgba16::new(0x2000, 0x4000, 0x8000, 0x4000).convert(),
         );
         assert_eq!(
-            SRgba8p::new(0x70, 0xE0, 0xA7, 0xBF),
+            SRgba8p::new(0x70, 0xE0, 0xA8, 0xBF),
             SRgba32::new(0.5, 1.0, 0.75, 0.75).convert(),
         );
     }
+
+    #[test]
+    fn direct_round_trip() {
+        for v in 0..=255 {
+            let p = Gray8::new(v);
+            assert_eq!(p, p.convert::<Rgb8>().convert());
+            let p = SGraya8::new(v, 255 - v);
+            assert_eq!(p, p.convert::<SRgba8>().convert());
+            let p = Cmy8::new(v, v / 2, 255 - v);
+            assert_eq!(p, p.convert::<Rgb8>().convert());
+            let p = Bgra8::new(v, v / 2, 255 - v, v / 3);
+            assert_eq!(p, p.convert::<Rgba8>().convert());
+        }
+    }
+
+    #[test]
+    fn direct_single_rounding() {
+        for v in (0..=0xFFFF).step_by(7) {
+            let p = Rgb16::new(v, v / 3, 0xFFFF - v);
+            let wide: Gray8 = p.convert::<Gray32>().convert();
+            assert_eq!(p.convert::<Gray8>(), wide);
+            let p = SRgb16::new(0xFFFF - v, v, v / 5);
+            let wide: SGray8 = p.convert::<SGray32>().convert();
+            assert_eq!(p.convert::<SGray8>(), wide);
+        }
+    }
+
+    #[test]
+    fn direct_other_models() {
+        for v in (0..=0xFFFF).step_by(7) {
+            let p = Rgb16::new(v, v / 3, 0xFFFF - v);
+            let hsv: Hsv8 = convert_thru_rgba(p);
+            assert_eq!(p.convert::<Hsv8>(), hsv);
+            let gray: SGray8 = convert_thru_rgba(p);
+            assert_eq!(p.convert::<SGray8>(), gray);
+        }
+    }
+
+    #[test]
+    fn with_alpha() {
+        let p = Rgba16::new(0x1000, 0x2000, 0x3000, 0x4000);
+        assert_eq!(
+            p.with_alpha(0xFFFF.into()),
+            Rgba16::new(0x1000, 0x2000, 0x3000, 0xFFFF)
+        );
+        let p = Rgba8p::new(0x40, 0x20, 0x10, 0x80);
+        assert_eq!(
+            p.with_alpha(0xFF.into()),
+            Rgba8p::new(0x80, 0x40, 0x20, 0xFF)
+        );
+        assert_eq!(p.with_alpha(0.into()), Rgba8p::new(0, 0, 0, 0));
+        let p = SRgba8p::new(0x60, 0x40, 0x20, 0xFF);
+        let q = p.with_alpha(0x80.into());
+        let r: SRgba8 = q.convert();
+        assert!(r.approx_eq(SRgba8::new(0x60, 0x40, 0x20, 0x80), 0.01));
+        let p = Graya32p::new(0.25, 0.5).with_alpha(1.0.into());
+        assert_eq!(p, Graya32p::new(0.5, 1.0));
+        let p = Hsva8p::new(0x40, 0x40, 0x40, 0x80).with_alpha(0xFF.into());
+        assert_eq!(p, Hsva8p::new(0x40, 0x80, 0x80, 0xFF));
+        let p = Matte8::new(0x40).with_alpha(0x80.into());
+        assert_eq!(p, Matte8::new(0x80));
+        assert_eq!(Gray8::new(0x40).with_alpha(0.into()), Gray8::new(0x40));
+    }
+
+    #[test]
+    fn with_channel() {
+        let p = Rgba8p::new(0x10, 0x20, 0x30, 0x40);
+        assert_eq!(
+            p.with_channel::<3>(0xFF.into()),
+            Rgba8p::new(0x10, 0x20, 0x30, 0xFF)
+        );
+        assert_eq!(
+            p.with_channel::<0>(0x00.into()),
+            Rgba8p::new(0x00, 0x20, 0x30, 0x40)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_channel_out_of_range() {
+        let _ = Rgb8::new(1, 2, 3).with_channel::<3>(0.into());
+    }
+
+    #[test]
+    fn rotate_circular() {
+        let p = Hsv8::new(0x30, 0x80, 0xFF);
+        let half = Ch8::new(0x80);
+        assert_eq!(p.rotate_circular(half), Hsv8::new(0xB0, 0x80, 0xFF));
+        assert_eq!(p.rotate_circular(half).rotate_circular(half), p);
+        let p = Rgb8::new(0x30, 0x80, 0xFF);
+        assert_eq!(p.rotate_circular(half), p);
+        let p = Hsva32::new(0.75, 0.5, 1.0, 1.0).rotate_circular(0.5.into());
+        assert!(p.approx_eq(Hsva32::new(0.25, 0.5, 1.0, 1.0), 1e-6));
+    }
+
+    #[test]
+    fn copy_slice() {
+        let src = [Rgb8::new(1, 2, 3); 3];
+        let mut dst = [Rgb8::new(9, 9, 9); 5];
+        Rgb8::copy_slice(&mut dst, &src);
+        assert_eq!(dst[..3], src);
+        assert_eq!(dst[3..], [Rgb8::new(9, 9, 9); 2]);
+        let mut dst = [Rgb8::new(9, 9, 9); 2];
+        Rgb8::copy_slice(&mut dst, &src);
+        assert_eq!(dst, [Rgb8::new(1, 2, 3); 2]);
+    }
+
+    #[test]
+    fn ordering() {
+        let values = [0x00, 0x01, 0x7F, 0x80, 0xFF];
+        let mut pixels = Vec::new();
+        for r in values {
+            for g in values {
+                for b in values {
+                    pixels.push(Rgb8::new(r, g, b));
+                }
+            }
+        }
+        pixels.reverse();
+        pixels.sort();
+        let tuples: Vec<(u8, u8, u8)> = pixels
+            .iter()
+            .map(|p| (p.one().into(), p.two().into(), p.three().into()))
+            .collect();
+        let mut sorted = tuples.clone();
+        sorted.sort();
+        assert_eq!(tuples, sorted);
+        assert!(Rgba16::new(1, 0, 0, 0) > Rgba16::new(0, 0xFFFF, 0xFFFF, 0));
+        assert!(Gray32::new(0.25) < Gray32::new(0.5));
+    }
 }
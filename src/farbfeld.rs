@@ -0,0 +1,168 @@
+// farbfeld.rs  Farbfeld image encoding
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+//! Reading and writing [farbfeld] images.
+//!
+//! [farbfeld]: https://tools.suckless.org/farbfeld/
+use crate::chan::Ch16;
+use crate::el::Pixel;
+use crate::raster::Raster;
+use crate::rgb::SRgba16;
+use std::io::{self, Read, Write};
+
+/// Magic bytes at start of farbfeld header
+const MAGIC: &[u8; 8] = b"farbfeld";
+
+/// Size of each pixel (16-bit RGBA)
+const PIXEL_BYTES: usize = 8;
+
+impl<P: Pixel> Raster<P> {
+    /// Write the `Raster` as a [farbfeld] image.
+    ///
+    /// Farbfeld is a simple lossless format: an 8-byte magic, big-endian
+    /// `u32` width and height, then 16-bit big-endian *sRGB* *straight*
+    /// RGBA pixels.  Pixels are converted to [SRgba16] first.
+    ///
+    /// [farbfeld]: https://tools.suckless.org/farbfeld/
+    /// [SRgba16]: rgb/type.SRgba16.html
+    ///
+    /// ### Dump a raster for debugging
+    /// ```
+    /// use pix::rgb::SRgb8;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::with_color(4, 4, SRgb8::new(0xFF, 0x80, 0x00));
+    /// let mut buf = Vec::new();
+    /// r.write_farbfeld(&mut buf).unwrap();
+    /// assert_eq!(buf.len(), 16 + 4 * 4 * 8);
+    /// ```
+    pub fn write_farbfeld<W: Write>(&self, mut writer: W) -> io::Result<()>
+    where
+        Ch16: From<P::Chan>,
+    {
+        let raster = Raster::<SRgba16>::with_raster(self);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&self.width().to_be_bytes())?;
+        writer.write_all(&self.height().to_be_bytes())?;
+        writer.write_all(&raster.to_be_bytes())
+    }
+}
+
+impl Raster<SRgba16> {
+    /// Read a [farbfeld] image.
+    ///
+    /// Returns an error of kind `InvalidData` if the magic bytes are wrong
+    /// or the image is too big, and `UnexpectedEof` if pixel data is
+    /// truncated.
+    ///
+    /// [farbfeld]: https://tools.suckless.org/farbfeld/
+    ///
+    /// ### Round trip
+    /// ```
+    /// use pix::rgb::SRgba16;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::with_color(3, 2, SRgba16::new(1, 2, 3, 4));
+    /// let mut buf = Vec::new();
+    /// r.write_farbfeld(&mut buf).unwrap();
+    /// let r2 = Raster::read_farbfeld(&buf[..]).unwrap();
+    /// assert_eq!(r.pixels(), r2.pixels());
+    /// ```
+    pub fn read_farbfeld<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut header = [0; 16];
+        reader.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            return Err(invalid_data("Invalid farbfeld magic"));
+        }
+        let width =
+            u32::from_be_bytes([header[8], header[9], header[10], header[11]]);
+        let height = u32::from_be_bytes([
+            header[12], header[13], header[14], header[15],
+        ]);
+        let len = pixel_bytes(width, height)
+            .ok_or_else(|| invalid_data("Farbfeld image too big"))?;
+        // Read without allocating everything up front, in case the header
+        // is bogus
+        let mut bytes = Vec::new();
+        reader.take(len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() < len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(Raster::from_be_bytes(width, height, &bytes))
+    }
+}
+
+/// Get the number of pixel bytes for an image, if not too big
+fn pixel_bytes(width: u32, height: u32) -> Option<usize> {
+    let w = i32::try_from(width).ok()?;
+    let h = i32::try_from(height).ok()?;
+    let len = usize::try_from(w.checked_mul(h)?).ok()?;
+    len.checked_mul(PIXEL_BYTES)
+        .filter(|bytes| *bytes <= isize::MAX as usize)
+}
+
+/// Make an invalid data error
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::gray::SGray8;
+    use crate::rgb::*;
+    use crate::Raster;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn header() {
+        let r = Raster::with_color(3, 2, SRgba16::new(0x1234, 2, 3, 0xFFFF));
+        let mut buf = Vec::new();
+        r.write_farbfeld(&mut buf).unwrap();
+        assert_eq!(&buf[..8], b"farbfeld");
+        assert_eq!(buf[8..16], [0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(buf[16..24], [0x12, 0x34, 0, 2, 0, 3, 0xFF, 0xFF]);
+        assert_eq!(buf.len(), 16 + 3 * 2 * 8);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut r = Raster::<SRgba8>::with_clear(5, 3);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = i as u8 * 16;
+            *p = SRgba8::new(v, 0xFF - v, v / 2, 0x80 + v / 2);
+        }
+        let mut buf = Vec::new();
+        r.write_farbfeld(&mut buf).unwrap();
+        let r16 = Raster::read_farbfeld(&buf[..]).unwrap();
+        assert_eq!(r16.width(), 5);
+        assert_eq!(r16.height(), 3);
+        assert_eq!(Raster::<SRgba8>::with_raster(&r16).pixels(), r.pixels());
+        let g = Raster::with_color(2, 2, SGray8::new(0x40));
+        let mut buf = Vec::new();
+        g.write_farbfeld(&mut buf).unwrap();
+        let r16 = Raster::read_farbfeld(&buf[..]).unwrap();
+        assert_eq!(
+            r16.pixel(1, 1),
+            SRgba16::new(0x4040, 0x4040, 0x4040, 0xFFFF)
+        );
+    }
+
+    fn read_err(buf: &[u8]) -> ErrorKind {
+        Raster::read_farbfeld(buf).err().unwrap().kind()
+    }
+
+    #[test]
+    fn errors() {
+        let r = Raster::with_color(2, 2, SRgba16::new(1, 2, 3, 4));
+        let mut buf = Vec::new();
+        r.write_farbfeld(&mut buf).unwrap();
+        assert_eq!(read_err(&buf[..buf.len() - 1]), ErrorKind::UnexpectedEof);
+        assert_eq!(read_err(&buf[..10]), ErrorKind::UnexpectedEof);
+        buf[0] = b'F';
+        assert_eq!(read_err(&buf[..]), ErrorKind::InvalidData);
+        let mut big = b"farbfeld".to_vec();
+        big.extend_from_slice(&[0xFF; 8]);
+        assert_eq!(read_err(&big[..]), ErrorKind::InvalidData);
+    }
+}
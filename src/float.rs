@@ -0,0 +1,105 @@
+// float.rs     Floating-point math for no_std
+//
+// Copyright (c) 2024  Douglas P Lau
+//
+//! Floating-point functions which are not available in `core`.
+//!
+//! With the `std` feature, this module is not used, since the inherent
+//! methods take precedence.
+
+/// Floating-point math using `libm`
+pub(crate) trait Float: Sized {
+    fn atan2(self, other: Self) -> Self;
+    fn cbrt(self) -> Self;
+    fn cos(self) -> Self;
+    fn floor(self) -> Self;
+    fn fract(self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+    fn round(self) -> Self;
+    fn sin(self) -> Self;
+    fn sqrt(self) -> Self;
+}
+
+impl Float for f32 {
+    fn atan2(self, other: Self) -> Self {
+        libm::atan2f(self, other)
+    }
+    fn cbrt(self) -> Self {
+        libm::cbrtf(self)
+    }
+    fn cos(self) -> Self {
+        libm::cosf(self)
+    }
+    fn floor(self) -> Self {
+        libm::floorf(self)
+    }
+    fn fract(self) -> Self {
+        self - libm::truncf(self)
+    }
+    fn hypot(self, other: Self) -> Self {
+        libm::hypotf(self, other)
+    }
+    fn powf(self, n: Self) -> Self {
+        libm::powf(self, n)
+    }
+    fn rem_euclid(self, rhs: Self) -> Self {
+        let r = self % rhs;
+        if r < 0.0 {
+            r + rhs.abs()
+        } else {
+            r
+        }
+    }
+    fn round(self) -> Self {
+        libm::roundf(self)
+    }
+    fn sin(self) -> Self {
+        libm::sinf(self)
+    }
+    fn sqrt(self) -> Self {
+        libm::sqrtf(self)
+    }
+}
+
+impl Float for f64 {
+    fn atan2(self, other: Self) -> Self {
+        libm::atan2(self, other)
+    }
+    fn cbrt(self) -> Self {
+        libm::cbrt(self)
+    }
+    fn cos(self) -> Self {
+        libm::cos(self)
+    }
+    fn floor(self) -> Self {
+        libm::floor(self)
+    }
+    fn fract(self) -> Self {
+        self - libm::trunc(self)
+    }
+    fn hypot(self, other: Self) -> Self {
+        libm::hypot(self, other)
+    }
+    fn powf(self, n: Self) -> Self {
+        libm::pow(self, n)
+    }
+    fn rem_euclid(self, rhs: Self) -> Self {
+        let r = self % rhs;
+        if r < 0.0 {
+            r + rhs.abs()
+        } else {
+            r
+        }
+    }
+    fn round(self) -> Self {
+        libm::round(self)
+    }
+    fn sin(self) -> Self {
+        libm::sin(self)
+    }
+    fn sqrt(self) -> Self {
+        libm::sqrt(self)
+    }
+}
@@ -7,11 +7,11 @@
 //!
 //! [`gray`]: https://en.wikipedia.org/wiki/Grayscale
 use crate::chan::{
-    Ch16, Ch32, Ch8, Channel, Linear, Premultiplied, Srgb, Straight,
+    Ch16, Ch32, Ch64, Ch8, Channel, Linear, Premultiplied, Srgb, Straight,
 };
 use crate::el::{Pix1, Pix2, PixRgba, Pixel};
 use crate::ColorModel;
-use std::ops::Range;
+use core::ops::Range;
 
 /// Gray [color model].
 ///
@@ -62,12 +62,23 @@ impl Gray {
     {
         p.one_mut()
     }
+
+    /// Get the luma of *red*, *green* and *blue* components
+    pub(crate) fn luma(red: f32, green: f32, blue: f32) -> f32 {
+        const RED_COEF: f32 = 0.212_6;
+        const GREEN_COEF: f32 = 0.715_2;
+        const BLUE_COEF: f32 = 0.072_2;
+
+        red * RED_COEF + green * GREEN_COEF + blue * BLUE_COEF
+    }
 }
 
 impl ColorModel for Gray {
     const CIRCULAR: Range<usize> = 0..0;
     const LINEAR: Range<usize> = 0..1;
     const ALPHA: usize = 1;
+    const NAME: &'static str = "gray";
+    const CHANNEL_NAMES: &'static [&'static str] = &["value", "alpha"];
 
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
@@ -83,15 +94,11 @@ impl ColorModel for Gray {
     where
         P: Pixel<Model = Self>,
     {
-        const RED_COEF: f32 = 0.212_6;
-        const GREEN_COEF: f32 = 0.715_2;
-        const BLUE_COEF: f32 = 0.072_2;
-
         let chan = rgba.channels();
-        let red = chan[0].to_f32() * RED_COEF;
-        let green = chan[1].to_f32() * GREEN_COEF;
-        let blue = chan[2].to_f32() * BLUE_COEF;
-        let value = P::Chan::from(red + green + blue);
+        let red = chan[0].to_f32();
+        let green = chan[1].to_f32();
+        let blue = chan[2].to_f32();
+        let value = P::Chan::from(Self::luma(red, green, blue));
         let alpha = chan[3];
         P::from_channels(&[value, alpha])
     }
@@ -112,6 +119,11 @@ pub type Gray16 = Pix1<Ch16, Gray, Straight, Linear>;
 /// [pixel](../el/trait.Pixel.html) format.
 pub type Gray32 = Pix1<Ch32, Gray, Straight, Linear>;
 
+/// [Gray](struct.Gray.html) 64-bit opaque (no *alpha* channel)
+/// [linear](../chan/struct.Linear.html) gamma
+/// [pixel](../el/trait.Pixel.html) format.
+pub type Gray64 = Pix1<Ch64, Gray, Straight, Linear>;
+
 /// [Gray](struct.Gray.html) 8-bit [straight](../chan/struct.Straight.html)
 /// alpha [linear](../chan/struct.Linear.html) gamma
 /// [pixel](../el/trait.Pixel.html) format.
@@ -127,6 +139,11 @@ pub type Graya16 = Pix2<Ch16, Gray, Straight, Linear>;
 /// [pixel](../el/trait.Pixel.html) format.
 pub type Graya32 = Pix2<Ch32, Gray, Straight, Linear>;
 
+/// [Gray](struct.Gray.html) 64-bit [straight](../chan/struct.Straight.html)
+/// alpha [linear](../chan/struct.Linear.html) gamma
+/// [pixel](../el/trait.Pixel.html) format.
+pub type Graya64 = Pix2<Ch64, Gray, Straight, Linear>;
+
 /// [Gray](struct.Gray.html) 8-bit
 /// [premultiplied](../chan/struct.Premultiplied.html) alpha
 /// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
@@ -145,6 +162,12 @@ pub type Graya16p = Pix2<Ch16, Gray, Premultiplied, Linear>;
 /// format.
 pub type Graya32p = Pix2<Ch32, Gray, Premultiplied, Linear>;
 
+/// [Gray](struct.Gray.html) 64-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Graya64p = Pix2<Ch64, Gray, Premultiplied, Linear>;
+
 /// [Gray](struct.Gray.html) 8-bit opaque (no *alpha* channel)
 /// [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
 /// format.
@@ -160,6 +183,11 @@ pub type SGray16 = Pix1<Ch16, Gray, Straight, Srgb>;
 /// format.
 pub type SGray32 = Pix1<Ch32, Gray, Straight, Srgb>;
 
+/// [Gray](struct.Gray.html) 64-bit opaque (no *alpha* channel)
+/// [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type SGray64 = Pix1<Ch64, Gray, Straight, Srgb>;
+
 /// [Gray](struct.Gray.html) 8-bit [straight](../chan/struct.Straight.html)
 /// alpha [sRGB](../chan/struct.Srgb.html) gamma
 /// [pixel](../el/trait.Pixel.html) format.
@@ -175,6 +203,11 @@ pub type SGraya16 = Pix2<Ch16, Gray, Straight, Srgb>;
 /// [pixel](../el/trait.Pixel.html) format.
 pub type SGraya32 = Pix2<Ch32, Gray, Straight, Srgb>;
 
+/// [Gray](struct.Gray.html) 64-bit [straight](../chan/struct.Straight.html)
+/// alpha [sRGB](../chan/struct.Srgb.html) gamma
+/// [pixel](../el/trait.Pixel.html) format.
+pub type SGraya64 = Pix2<Ch64, Gray, Straight, Srgb>;
+
 /// [Gray](struct.Gray.html) 8-bit
 /// [premultiplied](../chan/struct.Premultiplied.html) alpha
 /// [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
@@ -193,6 +226,12 @@ pub type SGraya16p = Pix2<Ch16, Gray, Premultiplied, Srgb>;
 /// format.
 pub type SGraya32p = Pix2<Ch32, Gray, Premultiplied, Srgb>;
 
+/// [Gray](struct.Gray.html) 64-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type SGraya64p = Pix2<Ch64, Gray, Premultiplied, Srgb>;
+
 #[cfg(test)]
 mod test {
     use crate::el::Pixel;
@@ -234,4 +273,12 @@ mod test {
         assert_eq!(Matte16::new(0xA2A2), SGraya8::new(0xBA, 0xA2).convert());
         assert_eq!(Matte8::new(0x80), SGraya32::new(0.75, 0.5).convert());
     }
+
+    #[test]
+    fn matte_from_gray() {
+        assert_eq!(Graya8::new(0xFF, 0xAB), Matte8::new(0xAB).into());
+        assert_eq!(Graya16::new(0xFFFF, 0xABCD), Matte16::new(0xABCD).into());
+        assert_eq!(Matte8::new(0x67), Gray8::new(0x67).into());
+        assert_eq!(Matte16::new(0x9876), Gray16::new(0x9876).into());
+    }
 }
@@ -12,7 +12,7 @@ use crate::chan::{
 use crate::el::{Pix3, Pix4, PixRgba, Pixel};
 use crate::hue::{rgb_to_hue_chroma_value, Hexcone};
 use crate::ColorModel;
-use std::ops::Range;
+use core::ops::Range;
 
 /// [HSL] bi-hexcone [color model].
 ///
@@ -162,6 +162,9 @@ impl ColorModel for Hsl {
     const CIRCULAR: Range<usize> = 0..1;
     const LINEAR: Range<usize> = 1..3;
     const ALPHA: usize = 3;
+    const NAME: &'static str = "hsl";
+    const CHANNEL_NAMES: &'static [&'static str] =
+        &["hue", "saturation", "lightness", "alpha"];
 
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
@@ -12,7 +12,7 @@ use crate::chan::{
 use crate::el::{Pix3, Pix4, PixRgba, Pixel};
 use crate::hue::{rgb_to_hue_chroma_value, Hexcone};
 use crate::ColorModel;
-use std::ops::Range;
+use core::ops::Range;
 
 /// [HSV] hexcone [color model], also known as HSB.
 ///
@@ -162,6 +162,9 @@ impl ColorModel for Hsv {
     const CIRCULAR: Range<usize> = 0..1;
     const LINEAR: Range<usize> = 1..3;
     const ALPHA: usize = 3;
+    const NAME: &'static str = "hsv";
+    const CHANNEL_NAMES: &'static [&'static str] =
+        &["hue", "saturation", "value", "alpha"];
 
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
@@ -431,6 +434,22 @@ mod test {
     fn composite_hsv() {
         let mut a = Hsva8p::new(0, 64, 64, 128);
         a.composite_channels(&Hsva8p::new(32, 128, 64, 128), SrcOver);
-        assert_eq!(a, Hsva8p::new(16, 159, 95, 191));
+        assert_eq!(a, Hsva8p::new(16, 160, 96, 192));
+    }
+
+    #[test]
+    fn hsva8_transparent() {
+        let mut dst = Hsva8p::new(0, 0, 0, 0);
+        let src = Hsva8p::new(100, 40, 80, 160);
+
+        dst.composite_channels(&src, SrcOver);
+        assert_eq!(dst, src);
+
+        dst.composite_channels(&Hsva8p::new(0, 0, 0, 0), SrcOver);
+        assert_eq!(dst, src);
+
+        dst = Hsva8p::new(0xC0, 0xFF, 0xFF, 0x00);
+        dst.composite_channels(&Hsva8p::new(0x40, 0, 0, 0), SrcOver);
+        assert_eq!(dst, Hsva8p::new(0xC0, 0xFF, 0xFF, 0x00));
     }
 }
@@ -4,6 +4,8 @@
 // Copyright (c) 2020  Douglas P Lau
 //
 use crate::chan::Channel;
+#[cfg(not(feature = "std"))]
+use crate::float::Float;
 
 /// Hexcone for color hue
 #[derive(Clone, Copy, Debug)]
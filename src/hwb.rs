@@ -11,7 +11,7 @@ use crate::chan::{
 use crate::el::{Pix3, Pix4, PixRgba, Pixel};
 use crate::hue::{rgb_to_hue_chroma_value, Hexcone};
 use crate::ColorModel;
-use std::ops::Range;
+use core::ops::Range;
 
 /// [HWB] [color model].
 ///
@@ -173,6 +173,9 @@ impl ColorModel for Hwb {
     const CIRCULAR: Range<usize> = 0..1;
     const LINEAR: Range<usize> = 1..3;
     const ALPHA: usize = 3;
+    const NAME: &'static str = "hwb";
+    const CHANNEL_NAMES: &'static [&'static str] =
+        &["hue", "whiteness", "blackness", "alpha"];
 
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
@@ -307,6 +310,7 @@ pub type SHwba32p = Pix4<Ch32, Hwb, Premultiplied, Srgb>;
 mod test {
     use crate::el::Pixel;
     use crate::hwb::*;
+    use crate::ops::SrcOver;
     use crate::rgb::*;
 
     #[test]
@@ -359,4 +363,20 @@ mod test {
             Rgb8::new(255, 0, 255).convert(),
         );
     }
+
+    #[test]
+    fn hwba8_transparent() {
+        let mut dst = Hwba8p::new(0, 0, 0, 0);
+        let src = Hwba8p::new(200, 40, 80, 160);
+
+        dst.composite_channels(&src, SrcOver);
+        assert_eq!(dst, src);
+
+        dst.composite_channels(&Hwba8p::new(0, 0, 0, 0), SrcOver);
+        assert_eq!(dst, src);
+
+        dst = Hwba8p::new(0x30, 0xFF, 0xFF, 0x00);
+        dst.composite_channels(&Hwba8p::new(0xB0, 0, 0, 0), SrcOver);
+        assert_eq!(dst, Hwba8p::new(0x30, 0xFF, 0xFF, 0x00));
+    }
 }
@@ -0,0 +1,220 @@
+// indexed.rs   Indexed images
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use crate::chan::{Ch8, Srgb, Straight};
+use crate::el::{Pix3, Pixel};
+use crate::palette::Palette;
+use crate::raster::Raster;
+use crate::rgb::{Rgb, SRgb8};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Indexed image, with one `u8` [Palette] index per pixel.
+///
+/// Created with [Raster::to_indexed] or [new](#method.new).
+///
+/// ### Round trip through an indexed image
+/// ```
+/// use pix::rgb::SRgb8;
+/// use pix::{Indexed, Raster};
+///
+/// let mut r = Raster::with_color(4, 4, SRgb8::new(0x20, 0x40, 0x80));
+/// *r.pixel_mut(1, 2) = SRgb8::new(0xFF, 0xFF, 0xFF);
+/// let indexed = r.to_indexed(|_| SRgb8::default());
+/// assert_eq!(indexed.palette().len(), 2);
+/// assert_eq!(indexed.index(1, 2), 1);
+/// assert_eq!(indexed.to_raster::<SRgb8>().pixels(), r.pixels());
+/// ```
+///
+/// [Palette]: struct.Palette.html
+/// [Raster::to_indexed]: struct.Raster.html#method.to_indexed
+#[derive(Clone)]
+pub struct Indexed {
+    /// Width in pixels
+    width: u32,
+    /// Height in pixels
+    height: u32,
+    /// Palette index of each pixel
+    indices: Box<[u8]>,
+    /// Color palette
+    palette: Palette,
+}
+
+impl Indexed {
+    /// Create a new indexed image.
+    ///
+    /// * `width` Width in pixels.
+    /// * `height` Height in pixels.
+    /// * `indices` Palette index of each pixel, in row-major order.
+    /// * `palette` Color palette.
+    ///
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    /// * If `indices` length is not equal to `width` * `height`
+    /// * If any index is not less than the palette length
+    pub fn new<B>(width: u32, height: u32, indices: B, palette: Palette) -> Self
+    where
+        B: Into<Box<[u8]>>,
+    {
+        assert!(width <= i32::MAX as u32, "Width too large");
+        assert!(height <= i32::MAX as u32, "Height too large");
+        let indices = indices.into();
+        assert_eq!(
+            indices.len(),
+            width as usize * height as usize,
+            "Invalid indices length"
+        );
+        assert!(
+            indices.iter().all(|i| usize::from(*i) < palette.len()),
+            "Index out of range"
+        );
+        Indexed {
+            width,
+            height,
+            indices,
+            palette,
+        }
+    }
+
+    /// Get the width, in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height, in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the color palette.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Get a slice of all palette indices.
+    pub fn indices(&self) -> &[u8] {
+        &self.indices
+    }
+
+    /// Get the palette index of one pixel.
+    ///
+    /// # Panics
+    ///
+    /// * If `x` or `y` is outside the image
+    pub fn index(&self, x: i32, y: i32) -> u8 {
+        assert!(x >= 0 && (x as u32) < self.width, "Invalid x: {x}");
+        assert!(y >= 0 && (y as u32) < self.height, "Invalid y: {y}");
+        self.indices[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Get the color of one pixel.
+    ///
+    /// # Panics
+    ///
+    /// * If `x` or `y` is outside the image
+    pub fn pixel(&self, x: i32, y: i32) -> SRgb8 {
+        self.palette.colors()[usize::from(self.index(x, y))]
+    }
+
+    /// Convert to a `Raster`.
+    ///
+    /// * `P` `Pixel` format of resulting `Raster`.
+    ///
+    /// Each palette entry is converted once, then copied to its pixels.
+    pub fn to_raster<P>(&self) -> Raster<P>
+    where
+        P: Pixel,
+        P::Chan: From<Ch8>,
+    {
+        let colors: Vec<P> = self.palette.iter().map(|c| c.convert()).collect();
+        let pixels: Vec<P> = self
+            .indices
+            .iter()
+            .map(|i| colors[usize::from(*i)])
+            .collect();
+        Raster::with_pixels(self.width, self.height, pixels)
+    }
+}
+
+impl<P: Pixel<Chan = Ch8>> Raster<P> {
+    /// Convert to an [Indexed](struct.Indexed.html) image.
+    ///
+    /// * `threshold_fn` Palette threshold function, as in
+    ///   [Palette::set_threshold_fn](struct.Palette.html#method.set_threshold_fn).
+    ///
+    /// Colors are quantized with
+    /// [Palette::make_indexed](struct.Palette.html#method.make_indexed),
+    /// into a palette of up to 256 entries.  When every color matches
+    /// exactly (such as an image with 256 or fewer colors, using a threshold
+    /// of zero), the conversion is lossless.
+    pub fn to_indexed(&self, threshold_fn: fn(usize) -> SRgb8) -> Indexed
+    where
+        <Pix3<Ch8, Rgb, Straight, Srgb> as Pixel>::Chan: From<P::Chan>,
+    {
+        let mut palette = Palette::default();
+        palette.set_threshold_fn(threshold_fn);
+        let indices = palette.index_pixels(self.pixels());
+        Indexed::new(self.width(), self.height(), indices, palette)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rgb::SRgba8;
+
+    #[test]
+    fn lossless() {
+        let mut r = Raster::<SRgb8>::with_clear(32, 8);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = i as u8;
+            *p = SRgb8::new(v, v.wrapping_mul(7), 255 - v);
+        }
+        let indexed = r.to_indexed(|_| SRgb8::default());
+        assert_eq!((indexed.width(), indexed.height()), (32, 8));
+        assert_eq!(indexed.palette().len(), 256);
+        assert_eq!(indexed.to_raster::<SRgb8>().pixels(), r.pixels());
+        for y in 0..8 {
+            for x in 0..32 {
+                assert_eq!(indexed.pixel(x, y), r.pixel(x, y));
+            }
+        }
+        let rgba = indexed.to_raster::<SRgba8>();
+        assert_eq!(rgba.pixel(5, 3), SRgba8::new(101, 195, 154, 255));
+        let indexed = Indexed::new(
+            32,
+            8,
+            indexed.indices().to_vec(),
+            indexed.palette().clone(),
+        );
+        assert_eq!(indexed.to_raster::<SRgb8>().pixels(), r.pixels());
+    }
+
+    #[test]
+    fn full_palette() {
+        let mut r = Raster::<SRgb8>::with_clear(20, 20);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            *p = SRgb8::new((i % 20) as u8, (i / 20) as u8, 0);
+        }
+        let indexed = r.to_indexed(|_| SRgb8::default());
+        assert_eq!(indexed.palette().len(), 256);
+        // colors beyond the palette use the best match
+        assert_eq!(indexed.pixel(19, 19), SRgb8::new(19, 11, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of range")]
+    fn out_of_range() {
+        let palette = Palette::from_colors(&[SRgb8::default()]);
+        Indexed::new(2, 1, [0, 1], palette);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid x: 2")]
+    fn index_outside() {
+        let palette = Palette::from_colors(&[SRgb8::default()]);
+        Indexed::new(2, 1, [0, 0], palette).index(2, 0);
+    }
+}
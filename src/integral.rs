@@ -0,0 +1,199 @@
+// integral.rs  Integral image (summed-area table)
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use crate::chan::Channel;
+use crate::el::Pixel;
+use crate::raster::{Raster, Region};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Integral image, or summed-area table, of a [Raster] region.
+///
+/// Created with [Raster::integral].  After building the table in one pass,
+/// the sum of channel values within any rectangle can be found in constant
+/// time, which is useful for box filters and adaptive thresholding.
+///
+/// Sums are of *raw* channel values, using the channel's [Sum] type: `u64`
+/// integers from 0 to 255 for `Ch8` or 0 to 65535 for `Ch16`, and `f64`
+/// from 0.0 to 1.0 for `Ch32` / `Ch64`.  Integer sums are exact.  Circular
+/// channels, such as *hue*, are summed as plain values.
+///
+/// [Raster]: struct.Raster.html
+/// [Raster::integral]: struct.Raster.html#method.integral
+/// [Sum]: chan/trait.Channel.html#associatedtype.Sum
+#[derive(Clone, Debug)]
+pub struct Integral<P: Pixel> {
+    /// Region of raster covered
+    region: Region,
+    /// Sums, with a leading row and column of zeros
+    sums: Vec<<P::Chan as Channel>::Sum>,
+}
+
+impl<P: Pixel> Integral<P> {
+    /// Build an integral image from a raster region
+    pub(crate) fn new(raster: &Raster<P>, reg: Region) -> Self {
+        let region = raster.intersection(reg);
+        let (w, h) = (region.width() as usize, region.height() as usize);
+        let stride = (w + 1) * P::CHANNELS;
+        let zero = <P::Chan as Channel>::Sum::default();
+        let mut sums = vec![zero; stride * (h + 1)];
+        if !region.is_empty() {
+            for (y, row) in raster.rows(region).enumerate() {
+                let mut row_sum = [zero; 4];
+                let (above, below) = sums.split_at_mut((y + 1) * stride);
+                let above = &above[y * stride..];
+                for (x, p) in row.iter().enumerate() {
+                    let i = (x + 1) * P::CHANNELS;
+                    for (c, v) in p.channels().iter().enumerate() {
+                        row_sum[c] = row_sum[c] + v.to_sum();
+                        below[i + c] = above[i + c] + row_sum[c];
+                    }
+                }
+            }
+        }
+        Integral { region, sums }
+    }
+
+    /// Get the region covered, in raster coordinates.
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    /// Get the sum of each channel within a region.
+    ///
+    /// * `reg` Region in raster coordinates.  It can be a `Region` struct,
+    ///   tuple of (*x*, *y*, *width*, *height*) or the unit type `()`.
+    ///
+    /// The region is clipped to the integral region.  The array has one
+    /// sum for each channel, with zero for any past `P::CHANNELS`.
+    ///
+    /// ### Sum of a region
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::with_color(8, 8, Gray8::new(3));
+    /// let integral = r.integral(());
+    /// assert_eq!(integral.sum((2, 2, 4, 5)), [60, 0, 0, 0]);
+    /// ```
+    pub fn sum<R>(&self, reg: R) -> [<P::Chan as Channel>::Sum; 4]
+    where
+        R: Into<Region>,
+    {
+        let mut sum = [<P::Chan as Channel>::Sum::default(); 4];
+        let reg = self.region.intersection(reg);
+        if reg.is_empty() {
+            return sum;
+        }
+        let x0 = (reg.left() - self.region.left()) as usize;
+        let y0 = (reg.top() - self.region.top()) as usize;
+        let x1 = x0 + reg.width() as usize;
+        let y1 = y0 + reg.height() as usize;
+        let stride = (self.region.width() as usize + 1) * P::CHANNELS;
+        let at = |x: usize, y: usize, c: usize| {
+            self.sums[y * stride + x * P::CHANNELS + c]
+        };
+        for (c, s) in sum.iter_mut().enumerate().take(P::CHANNELS) {
+            *s = at(x1, y1, c) + at(x0, y0, c) - at(x0, y1, c) - at(x1, y0, c);
+        }
+        sum
+    }
+
+    /// Get the mean of each channel within a region.
+    ///
+    /// * `reg` Region in raster coordinates.
+    ///
+    /// The mean is rounded to nearest, as with
+    /// [Raster::mean](struct.Raster.html#method.mean) (except for circular
+    /// channels).  An empty region gives the default pixel.
+    pub fn mean<R>(&self, reg: R) -> P
+    where
+        R: Into<Region>,
+    {
+        let reg = self.region.intersection(reg);
+        let mut mean = P::default();
+        if reg.is_empty() {
+            return mean;
+        }
+        let n = u64::from(reg.width()) * u64::from(reg.height());
+        for (c, s) in mean.channels_mut().iter_mut().zip(self.sum(reg)) {
+            *c = P::Chan::from_sum(s, n);
+        }
+        mean
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gray::Gray16;
+    use crate::rgb::{Rgba32, Rgba8};
+
+    fn brute_sum<P: Pixel>(
+        r: &Raster<P>,
+        reg: Region,
+    ) -> [<P::Chan as Channel>::Sum; 4] {
+        let mut sums = [<P::Chan as Channel>::Sum::default(); 4];
+        for row in r.rows(reg) {
+            for p in row {
+                for (s, c) in sums.iter_mut().zip(p.channels()) {
+                    *s = *s + c.to_sum();
+                }
+            }
+        }
+        sums
+    }
+
+    fn regions(width: u32, height: u32) -> Vec<Region> {
+        let mut state = 0x1234_5678_u32;
+        let mut next = |m: u32| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state % m
+        };
+        (0..200)
+            .map(|_| {
+                let x = next(width + 4) as i32 - 2;
+                let y = next(height + 4) as i32 - 2;
+                Region::new(x, y, next(width + 2), next(height + 2))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sums_match() {
+        let r = Raster::<Rgba8>::with_noise(23, 17, 5);
+        let integral = r.integral(());
+        for reg in regions(23, 17) {
+            let reg = r.intersection(reg);
+            assert_eq!(integral.sum(reg), brute_sum(&r, reg), "{reg:?}");
+            if !reg.is_empty() {
+                assert_eq!(integral.mean(reg), r.mean(reg), "{reg:?}");
+            }
+        }
+        let r = Raster::<Gray16>::with_noise(31, 9, 6);
+        let integral = r.integral((3, 2, 20, 6));
+        assert_eq!(integral.region(), Region::new(3, 2, 20, 6));
+        for reg in regions(31, 9) {
+            let clipped = integral.region().intersection(reg);
+            assert_eq!(integral.sum(reg), brute_sum(&r, clipped), "{reg:?}");
+        }
+    }
+
+    #[test]
+    fn float_sums() {
+        let r = Raster::<Rgba32>::with_noise(12, 12, 7);
+        let integral = r.integral(());
+        for reg in regions(12, 12) {
+            let reg = r.intersection(reg);
+            let sum = integral.sum(reg);
+            for (a, b) in sum.iter().zip(brute_sum(&r, reg)) {
+                assert!((a - b).abs() < 1e-9, "{reg:?}");
+            }
+        }
+        assert_eq!(integral.sum((20, 20, 5, 5)), [0.0; 4]);
+        assert_eq!(integral.mean((20, 20, 5, 5)), Rgba32::default());
+    }
+}
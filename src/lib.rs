@@ -10,11 +10,11 @@
 //!
 //! Many image formats are supported:
 //!
-//! * Bit depth: 8- or 16-bit integer and 32-bit float
+//! * Bit depth: 8- or 16-bit integer and 32- or 64-bit float
 //! * Alpha: *premultiplied* or *straight*
 //! * Gamma: *linear* or *sRGB*
 //! * Color models:
-//!   - [`RGB`] / [`BGR`] (*red*, *green*, *blue*)
+//!   - [`RGB`] / [`BGR`] / [`ARGB`] (*red*, *green*, *blue*)
 //!   - [`CMY`] (*cyan*, *magenta*, *yellow*)
 //!   - [`Gray`] (*luma* / *relative luminance*)
 //!   - [`HSV`] (*hue*, *saturation*, *value*)
@@ -23,12 +23,14 @@
 //!   - [`YCbCr`] (used by JPEG)
 //!   - [`Matte`] (*alpha* only)
 //!   - [`Oklab`] (*lightness*, *green/red*, *blue/yellow*)
+//!   - [`Oklch`] (*lightness*, *chroma*, *hue*)
 //!   - [`XYZ`] (CIE 1931 XYZ)
 //!
 //! Compositing with blending [operations] is supported for *premultiplied*
 //! images with *linear* gamma.
 //!
 //! [alpha]: chan/trait.Alpha.html
+//! [`argb`]: argb/index.html
 //! [`bgr`]: bgr/index.html
 //! [channel]: chan/trait.Channel.html
 //! [`cmy`]: cmy/index.html
@@ -40,6 +42,7 @@
 //! [`hwb`]: hwb/index.html
 //! [`matte`]: matte/index.html
 //! [`oklab`]: oklab/index.html
+//! [`oklch`]: oklch/index.html
 //! [operations]: ops/index.html
 //! [raster]: struct.Raster.html
 //! [`rgb`]: rgb/index.html
@@ -67,28 +70,63 @@
 //!
 //! ![Colors](https://raw.githubusercontent.com/DougLau/pix/master/res/colors.png)
 //!
+//! ### Features
+//!
+//! * `std` (default): Use the standard library, enabling [farbfeld] image
+//!   reading and writing, and `std::error::Error` for error types.  Without
+//!   it, the crate is `no_std`, but still requires `alloc`.
+//! * `libm`: Use [libm] for floating-point math when `std` is disabled.
+//!   Either `std` or `libm` must be enabled.
+//!
+//! [farbfeld]: https://tools.suckless.org/farbfeld/
+//! [libm]: https://crates.io/crates/libm
+//!
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("Either the `std` or `libm` feature must be enabled");
+
+extern crate alloc;
+
+pub mod argb;
+mod atlas;
 pub mod bgr;
 pub mod chan;
 pub mod cmy;
 pub mod el;
+#[cfg(feature = "std")]
+mod farbfeld;
+#[cfg(not(feature = "std"))]
+mod float;
 pub mod gray;
 pub mod hsl;
 pub mod hsv;
 mod hue;
 pub mod hwb;
+mod indexed;
+mod integral;
 pub mod matte;
 mod model;
 pub mod oklab;
+pub mod oklch;
 pub mod ops;
 mod palette;
 mod private;
 mod raster;
+mod raw;
 pub mod rgb;
 pub mod xyz;
 pub mod ycc;
 
+pub use crate::atlas::Atlas;
+pub use crate::indexed::Indexed;
+pub use crate::integral::Integral;
 pub use crate::model::ColorModel;
 pub use crate::palette::Palette;
-pub use crate::raster::{Raster, Region, Rows, RowsMut};
+pub use crate::raster::{
+    Anchor, Axis, ClipStats, Columns, DitherMode, Light, Raster, RasterBuilder,
+    RasterError, RecolorMode, Region, RegionSet, Rows, RowsMut, ShiftMode,
+    Spans, SpansMut,
+};
+pub use crate::raw::{PixFormat, RawImage, RawImageMut};
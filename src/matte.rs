@@ -4,15 +4,24 @@
 // Copyright (c) 2019-2020  Jeron Aldaron Lau
 //
 //! Matte color model and types.
-use crate::chan::{Ch16, Ch32, Ch8, Channel, Linear, Premultiplied};
-use crate::el::{Pix1, PixRgba, Pixel};
+use crate::chan::{
+    Ch16, Ch32, Ch64, Ch8, Channel, Linear, Premultiplied, Straight,
+};
+use crate::el::{Pix1, Pix2, PixRgba, Pixel};
+use crate::gray::Gray;
 use crate::ColorModel;
-use std::ops::Range;
+use core::ops::Range;
 
 /// Matte [color model].
 ///
-/// The component is *[alpha]* only.
+/// The component is *[alpha]* only.  Matte pixel formats have
+/// [premultiplied] alpha and [linear] gamma, so matte rasters can be
+/// composited directly, such as accumulating coverage masks with
+/// [SrcOver].
 ///
+/// [linear]: ../chan/struct.Linear.html
+/// [premultiplied]: ../chan/struct.Premultiplied.html
+/// [SrcOver]: ../ops/struct.SrcOver.html
 /// [alpha]: ../el/trait.Pixel.html#method.alpha
 /// [color model]: ../trait.ColorModel.html
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -22,6 +31,8 @@ impl ColorModel for Matte {
     const CIRCULAR: Range<usize> = 0..0;
     const LINEAR: Range<usize> = 0..0;
     const ALPHA: usize = 0;
+    const NAME: &'static str = "matte";
+    const CHANNEL_NAMES: &'static [&'static str] = &["alpha"];
 
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
@@ -53,3 +64,51 @@ pub type Matte16 = Pix1<Ch16, Matte, Premultiplied, Linear>;
 /// [Matte](struct.Matte.html) 32-bit alpha [linear](../chan/struct.Linear.html)
 /// gamma [pixel](../el/trait.Pixel.html) format.
 pub type Matte32 = Pix1<Ch32, Matte, Premultiplied, Linear>;
+
+/// [Matte](struct.Matte.html) 64-bit alpha [linear](../chan/struct.Linear.html)
+/// gamma [pixel](../el/trait.Pixel.html) format.
+pub type Matte64 = Pix1<Ch64, Matte, Premultiplied, Linear>;
+
+/// Convert a matte to [linear] gray with *alpha*.
+///
+/// *Value* is set to white, and *alpha* is the matte coverage.  Unlike
+/// [convert](../el/trait.Pixel.html#method.convert), this does not go
+/// through RGBA.
+///
+/// [linear]: ../chan/struct.Linear.html
+///
+/// ## Example
+/// ```
+/// use pix::gray::Graya8;
+/// use pix::matte::Matte8;
+///
+/// assert_eq!(Graya8::from(Matte8::new(0x80)), Graya8::new(0xFF, 0x80));
+/// ```
+impl<C: Channel> From<Pix1<C, Matte, Premultiplied, Linear>>
+    for Pix2<C, Gray, Straight, Linear>
+{
+    fn from(p: Pix1<C, Matte, Premultiplied, Linear>) -> Self {
+        Self::new::<C>(C::MAX, p.alpha())
+    }
+}
+
+/// Convert opaque [linear] gray to a matte.
+///
+/// The matte coverage is the gray *value*.
+///
+/// [linear]: ../chan/struct.Linear.html
+///
+/// ## Example
+/// ```
+/// use pix::gray::Gray8;
+/// use pix::matte::Matte8;
+///
+/// assert_eq!(Matte8::from(Gray8::new(0x40)), Matte8::new(0x40));
+/// ```
+impl<C: Channel> From<Pix1<C, Gray, Straight, Linear>>
+    for Pix1<C, Matte, Premultiplied, Linear>
+{
+    fn from(p: Pix1<C, Gray, Straight, Linear>) -> Self {
+        Self::new::<C>(p.one())
+    }
+}
@@ -4,9 +4,9 @@
 //
 //! Color models
 use crate::el::{PixRgba, Pixel};
-use std::any::Any;
-use std::fmt::Debug;
-use std::ops::Range;
+use core::any::Any;
+use core::fmt::Debug;
+use core::ops::Range;
 
 /// Model for pixel colors.
 ///
@@ -33,6 +33,18 @@ pub trait ColorModel: Clone + Copy + Debug + Default + PartialEq + Any {
     /// Alpha channel number
     const ALPHA: usize;
 
+    /// Model name, without *alpha*, such as `"rgb"`
+    ///
+    /// When a pixel is formatted with `Display`, this is used as a prefix,
+    /// with `a` added for the *alpha* channel, such as
+    /// `rgba(255, 0, 128, 255)`.  By default, it is empty.
+    const NAME: &'static str = "";
+
+    /// Channel names, in order (including *alpha*)
+    ///
+    /// By default, this is empty.
+    const CHANNEL_NAMES: &'static [&'static str] = &[];
+
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
     where
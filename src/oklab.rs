@@ -9,8 +9,11 @@
 
 use crate::chan::{Ch16, Ch32, Ch8, Channel, Linear, Premultiplied, Straight};
 use crate::el::{Pix3, Pix4, PixRgba, Pixel};
+#[cfg(not(feature = "std"))]
+use crate::float::Float;
+use crate::rgb::{Rgb, Rgb32};
 use crate::ColorModel;
-use std::ops::Range;
+use core::ops::Range;
 
 /// [Oklab] [color model]
 ///
@@ -136,6 +139,8 @@ impl ColorModel for Oklab {
     const CIRCULAR: Range<usize> = 0..0;
     const LINEAR: Range<usize> = 0..3;
     const ALPHA: usize = 3;
+    const NAME: &'static str = "oklab";
+    const CHANNEL_NAMES: &'static [&'static str] = &["l", "a", "b", "alpha"];
 
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
@@ -145,18 +150,7 @@ impl ColorModel for Oklab {
         let pl = Self::l(p).to_f32();
         let pa = Self::a(p).to_f32();
         let pb = Self::b(p).to_f32();
-
-        let l_ = pl + 0.3963377774 * pa + 0.2158037573 * pb;
-        let m_ = pl - 0.1055613458 * pa - 0.0638541728 * pb;
-        let s_ = pl - 0.0894841775 * pa - 1.2914855480 * pb;
-
-        let l = l_ * l_ * l_;
-        let m = m_ * m_ * m_;
-        let s = s_ * s_ * s_;
-
-        let red = 4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s;
-        let green = -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s;
-        let blue = -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s;
+        let (red, green, blue) = oklab_to_rgb(pl, pa, pb);
         PixRgba::<P>::new(red, green, blue, p.alpha().to_f32())
     }
 
@@ -171,20 +165,85 @@ impl ColorModel for Oklab {
         let blue = chan[2].to_f32();
         let alpha = chan[3];
 
-        let l = 0.4122214708 * red + 0.5363325363 * green + 0.0514459929 * blue;
-        let m = 0.2119034982 * red + 0.6806995451 * green + 0.1073969566 * blue;
-        let s = 0.0883024619 * red + 0.2817188376 * green + 0.6299787005 * blue;
+        let (pl, pa, pb) = rgb_to_oklab(red, green, blue);
+        P::from_channels(&[pl.into(), pa.into(), pb.into(), alpha])
+    }
+}
+
+/// Convert Oklab *L*, *a* and *b* to linear *red*, *green* and *blue*
+pub(crate) fn oklab_to_rgb(pl: f32, pa: f32, pb: f32) -> (f32, f32, f32) {
+    let l_ = pl + 0.3963377774 * pa + 0.2158037573 * pb;
+    let m_ = pl - 0.1055613458 * pa - 0.0638541728 * pb;
+    let s_ = pl - 0.0894841775 * pa - 1.2914855480 * pb;
 
-        let l_ = l.cbrt();
-        let m_ = m.cbrt();
-        let s_ = s.cbrt();
+    let l = l_ * l_ * l_;
+    let m = m_ * m_ * m_;
+    let s = s_ * s_ * s_;
 
-        let pl = 0.2104542553 * l_ + 0.7936177850 * m_ - 0.0040720468 * s_;
-        let pa = 1.9779984951 * l_ - 2.4285922050 * m_ + 0.4505937099 * s_;
-        let pb = 0.0259040371 * l_ + 0.7827717662 * m_ - 0.8086757660 * s_;
+    let red = 4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s;
+    let green = -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s;
+    let blue = -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s;
+    (red, green, blue)
+}
 
-        P::from_channels(&[pl.into(), pa.into(), pb.into(), alpha])
-    }
+/// Convert linear *red*, *green* and *blue* to Oklab *L*, *a* and *b*
+pub(crate) fn rgb_to_oklab(red: f32, green: f32, blue: f32) -> (f32, f32, f32) {
+    let l = 0.4122214708 * red + 0.5363325363 * green + 0.0514459929 * blue;
+    let m = 0.2119034982 * red + 0.6806995451 * green + 0.1073969566 * blue;
+    let s = 0.0883024619 * red + 0.2817188376 * green + 0.6299787005 * blue;
+
+    let l_ = l.cbrt();
+    let m_ = m.cbrt();
+    let s_ = s.cbrt();
+
+    let pl = 0.2104542553 * l_ + 0.7936177850 * m_ - 0.0040720468 * s_;
+    let pa = 1.9779984951 * l_ - 2.4285922050 * m_ + 0.4505937099 * s_;
+    let pb = 0.0259040371 * l_ + 0.7827717662 * m_ - 0.8086757660 * s_;
+    (pl, pa, pb)
+}
+
+/// Get the perceptual color difference between two pixels.
+///
+/// This is the Euclidean distance between the colors in Oklab, ranging from
+/// `0.0` for identical colors to about `1.0` for black and white.  *Alpha*
+/// is not compared.
+///
+/// Oklab components are calculated from linear RGB directly, so negative
+/// *a* / *b* values are not clamped as they would be in an `Oklab32` pixel.
+///
+/// ### Compare colors
+/// ```
+/// use pix::oklab::delta_e;
+/// use pix::rgb::SRgb8;
+///
+/// let blue = SRgb8::new(0, 0, 0xFF);
+/// let cyan = SRgb8::new(0, 0xFF, 0xFF);
+/// let yellow = SRgb8::new(0xFF, 0xFF, 0);
+/// assert!(delta_e(blue, cyan) < delta_e(blue, yellow));
+/// ```
+pub fn delta_e<A, B>(a: A, b: B) -> f32
+where
+    A: Pixel,
+    B: Pixel,
+    Ch32: From<A::Chan> + From<B::Chan>,
+{
+    let (l0, a0, b0) = pixel_to_oklab(a);
+    let (l1, a1, b1) = pixel_to_oklab(b);
+    let (dl, da, db) = (l0 - l1, a0 - a1, b0 - b1);
+    (dl * dl + da * da + db * db).sqrt()
+}
+
+/// Convert a pixel to Oklab *L*, *a* and *b*, without clamping
+fn pixel_to_oklab<P>(p: P) -> (f32, f32, f32)
+where
+    P: Pixel,
+    Ch32: From<P::Chan>,
+{
+    let rgb: Rgb32 = p.convert();
+    let red = Rgb::red(rgb).to_f32();
+    let green = Rgb::green(rgb).to_f32();
+    let blue = Rgb::blue(rgb).to_f32();
+    rgb_to_oklab(red, green, blue)
 }
 
 /// [Oklab](struct.Oklab.html) 8-bit opaque (no *alpha* channel)
@@ -240,8 +299,35 @@ pub type Oklaba32p = Pix4<Ch32, Oklab, Premultiplied, Linear>;
 
 #[cfg(test)]
 mod test {
+    use crate::el::Pixel;
+    use crate::oklab::delta_e;
+    use crate::rgb::*;
+
     #[test]
     fn oklab_to_rgb() {
         // TODO
     }
+
+    #[test]
+    fn delta_e_ordering() {
+        let black = SRgb8::new(0, 0, 0);
+        let white = SRgb8::new(0xFF, 0xFF, 0xFF);
+        let blue = SRgb8::new(0, 0, 0xFF);
+        let cyan = SRgb8::new(0, 0xFF, 0xFF);
+        let yellow = SRgb8::new(0xFF, 0xFF, 0);
+        let red = SRgb8::new(0xFF, 0, 0);
+        let orange = SRgb8::new(0xFF, 0x80, 0);
+        assert_eq!(delta_e(blue, blue), 0.0);
+        assert!((delta_e(black, white) - 1.0).abs() < 0.001);
+        assert_eq!(delta_e(blue, yellow), delta_e(yellow, blue));
+        assert!(delta_e(blue, cyan) < delta_e(blue, yellow));
+        assert!(delta_e(red, orange) < delta_e(red, cyan));
+        assert!(delta_e(red, orange) < delta_e(orange, blue));
+        // a negative, so this would be wrong if clamped
+        let green = SRgb8::new(0, 0xFF, 0);
+        assert!(delta_e(green, red) > delta_e(green, yellow));
+        // different pixel formats
+        let d = red.distance_oklab(Rgba16::new(0xFFFF, 0, 0, 0x8000));
+        assert!(d < 0.001);
+    }
 }
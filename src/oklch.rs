@@ -0,0 +1,273 @@
+// oklch.rs        Oklch color model.
+//
+// Copyright (c) 2024  Douglas P Lau
+//
+//! [Oklch] color model and types.
+//!
+//! [Oklch]: https://bottosson.github.io/posts/oklab/#the-oklab-color-space
+use crate::chan::{Ch16, Ch32, Ch8, Channel, Linear, Premultiplied, Straight};
+use crate::el::{Pix3, Pix4, PixRgba, Pixel};
+#[cfg(not(feature = "std"))]
+use crate::float::Float;
+use crate::oklab::{oklab_to_rgb, rgb_to_oklab};
+use crate::ColorModel;
+use core::f32::consts::TAU;
+use core::ops::Range;
+
+/// [Oklch] [color model]
+///
+/// This is the cylindrical form of [Oklab].  The components are *[L]*,
+/// *[C]*, *[h]* and optional *[alpha]*.
+///
+/// Like Oklab, it is defined in terms of linear light, so only
+/// [linear](../chan/struct.Linear.html) gamma pixel formats are provided.
+///
+/// [alpha]: ../el/trait.Pixel.html#method.alpha
+/// [C]: #method.c
+/// [color model]: ../trait.ColorModel.html
+/// [h]: #method.h
+/// [L]: #method.l
+/// [Oklab]: ../oklab/struct.Oklab.html
+/// [Oklch]: https://bottosson.github.io/posts/oklab/#the-oklab-color-space
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Oklch {}
+
+impl Oklch {
+    /// Get the *L* component (perceived lightness).
+    ///
+    /// # Example: Oklch *L*
+    /// ```
+    /// use pix::chan::Ch32;
+    /// use pix::oklch::{Oklch, Oklch32};
+    ///
+    /// let p = Oklch32::new(0.25, 0.5, 1.0);
+    /// assert_eq!(Oklch::l(p), Ch32::new(0.25));
+    /// ```
+    pub fn l<P>(p: P) -> P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.one()
+    }
+
+    /// Get a mutable reference to the *L* component.
+    ///
+    /// # Example: Modify Oklch *L*
+    /// ```
+    /// use pix::chan::Ch32;
+    /// use pix::oklch::{Oklch, Oklch32};
+    ///
+    /// let mut p = Oklch32::new(0.25, 0.5, 1.0);
+    /// *Oklch::l_mut(&mut p) = Ch32::new(0.75);
+    /// assert_eq!(Oklch::l(p), Ch32::new(0.75));
+    /// ```
+    pub fn l_mut<P>(p: &mut P) -> &mut P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.one_mut()
+    }
+
+    /// Get the *C* component (chroma).
+    ///
+    /// # Example: Oklch *C*
+    /// ```
+    /// use pix::chan::Ch16;
+    /// use pix::oklch::{Oklch, Oklch16};
+    ///
+    /// let p = Oklch16::new(0x2000, 0x1234, 0x8000);
+    /// assert_eq!(Oklch::c(p), Ch16::new(0x1234));
+    /// ```
+    pub fn c<P>(p: P) -> P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.two()
+    }
+
+    /// Get a mutable reference to the *C* component.
+    ///
+    /// # Example: Modify Oklch *C*
+    /// ```
+    /// use pix::chan::Ch16;
+    /// use pix::oklch::{Oklch, Oklch16};
+    ///
+    /// let mut p = Oklch16::new(0x2000, 0x1234, 0x8000);
+    /// *Oklch::c_mut(&mut p) = 0x4321.into();
+    /// assert_eq!(Oklch::c(p), Ch16::new(0x4321));
+    /// ```
+    pub fn c_mut<P>(p: &mut P) -> &mut P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.two_mut()
+    }
+
+    /// Get the *h* component (hue).
+    ///
+    /// The hue angle is mapped from [Channel::MIN] (0 degrees) to
+    /// [Channel::MAX] (360 degrees).
+    ///
+    /// # Example: Oklch *h*
+    /// ```
+    /// use pix::chan::Ch8;
+    /// use pix::oklch::{Oklch, Oklch8};
+    ///
+    /// let p = Oklch8::new(0x93, 0x80, 0xA0);
+    /// assert_eq!(Oklch::h(p), Ch8::new(0xA0));
+    /// ```
+    /// [Channel::MIN]: ../chan/trait.Channel.html#associatedconstant.MIN
+    /// [Channel::MAX]: ../chan/trait.Channel.html#associatedconstant.MAX
+    pub fn h<P>(p: P) -> P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.three()
+    }
+
+    /// Get a mutable reference to the *h* component.
+    ///
+    /// # Example: Modify Oklch *h*
+    /// ```
+    /// use pix::chan::{Ch32, Channel};
+    /// use pix::oklch::{Oklch, Oklch32};
+    ///
+    /// let mut p = Oklch32::new(0.5, 0.25, 0.75);
+    /// let mut h = Oklch::h_mut(&mut p);
+    /// *h = h.wrapping_add(0.5.into());
+    /// assert_eq!(Oklch::h(p), Ch32::new(0.25));
+    /// ```
+    pub fn h_mut<P>(p: &mut P) -> &mut P::Chan
+    where
+        P: Pixel<Model = Self>,
+    {
+        p.three_mut()
+    }
+}
+
+impl ColorModel for Oklch {
+    const CIRCULAR: Range<usize> = 2..3;
+    const LINEAR: Range<usize> = 0..2;
+    const ALPHA: usize = 3;
+    const NAME: &'static str = "oklch";
+    const CHANNEL_NAMES: &'static [&'static str] = &["l", "c", "h", "alpha"];
+
+    /// Convert into *red*, *green*, *blue* and *alpha* components
+    fn into_rgba<P>(p: P) -> PixRgba<P>
+    where
+        P: Pixel<Model = Self>,
+    {
+        let pl = Self::l(p).to_f32();
+        let pc = Self::c(p).to_f32();
+        let ph = Self::h(p).to_f32() * TAU;
+        let (red, green, blue) = oklab_to_rgb(pl, pc * ph.cos(), pc * ph.sin());
+        PixRgba::<P>::new(red, green, blue, p.alpha().to_f32())
+    }
+
+    /// Convert from *red*, *green*, *blue* and *alpha* components
+    fn from_rgba<P>(rgba: PixRgba<P>) -> P
+    where
+        P: Pixel<Model = Self>,
+    {
+        let chan = rgba.channels();
+        let red = chan[0].to_f32();
+        let green = chan[1].to_f32();
+        let blue = chan[2].to_f32();
+        let alpha = chan[3];
+
+        let (pl, pa, pb) = rgb_to_oklab(red, green, blue);
+        let pc = pa.hypot(pb);
+        let ph = pb.atan2(pa) / TAU;
+        let ph = if ph < 0.0 { ph + 1.0 } else { ph };
+        P::from_channels(&[pl.into(), pc.into(), ph.into(), alpha])
+    }
+}
+
+/// [Oklch](struct.Oklch.html) 8-bit opaque (no *alpha* channel)
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Oklch8 = Pix3<Ch8, Oklch, Straight, Linear>;
+
+/// [Oklch](struct.Oklch.html) 16-bit opaque (no *alpha* channel)
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Oklch16 = Pix3<Ch16, Oklch, Straight, Linear>;
+
+/// [Oklch](struct.Oklch.html) 32-bit opaque (no *alpha* channel)
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Oklch32 = Pix3<Ch32, Oklch, Straight, Linear>;
+
+/// [Oklch](struct.Oklch.html) 8-bit
+/// [straight](../chan/struct.Straight.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Oklcha8 = Pix4<Ch8, Oklch, Straight, Linear>;
+
+/// [Oklch](struct.Oklch.html) 16-bit
+/// [straight](../chan/struct.Straight.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Oklcha16 = Pix4<Ch16, Oklch, Straight, Linear>;
+
+/// [Oklch](struct.Oklch.html) 32-bit
+/// [straight](../chan/struct.Straight.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Oklcha32 = Pix4<Ch32, Oklch, Straight, Linear>;
+
+/// [Oklch](struct.Oklch.html) 8-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Oklcha8p = Pix4<Ch8, Oklch, Premultiplied, Linear>;
+
+/// [Oklch](struct.Oklch.html) 16-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Oklcha16p = Pix4<Ch16, Oklch, Premultiplied, Linear>;
+
+/// [Oklch](struct.Oklch.html) 32-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Oklcha32p = Pix4<Ch32, Oklch, Premultiplied, Linear>;
+
+#[cfg(test)]
+mod test {
+    use crate::el::Pixel;
+    use crate::oklch::*;
+    use crate::rgb::*;
+
+    fn assert_near(a: Ch32, b: f32) {
+        assert!((a.to_f32() - b).abs() < 0.001, "{a:?} != {b}");
+    }
+
+    #[test]
+    fn srgb_red_to_oklch() {
+        let p: Oklch32 = SRgb8::new(0xFF, 0x00, 0x00).convert();
+        assert_near(Oklch::l(p), 0.627_955);
+        assert_near(Oklch::c(p), 0.257_683);
+        assert_near(Oklch::h(p), 29.233_885 / 360.0);
+    }
+
+    #[test]
+    fn oklch_round_trip() {
+        for clr in [
+            SRgb8::new(0xFF, 0x00, 0x00),
+            SRgb8::new(0x00, 0xFF, 0x00),
+            SRgb8::new(0x00, 0x00, 0xFF),
+            SRgb8::new(0x20, 0x80, 0xC0),
+            SRgb8::new(0x80, 0x80, 0x80),
+        ] {
+            let p: Oklch32 = clr.convert();
+            let rgb: SRgb32 = p.convert();
+            let rgb: SRgb8 = rgb.convert();
+            for (a, b) in clr.channels().iter().zip(rgb.channels()) {
+                let (a, b) = (u8::from(*a), u8::from(*b));
+                assert!(a.abs_diff(b) <= 1, "{clr:?} != {rgb:?}");
+            }
+        }
+    }
+}
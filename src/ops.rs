@@ -5,17 +5,59 @@
 //! Compositing and blending operations.
 //!
 //! Used in `Raster` methods [composite_color], [composite_matte] and
-//! [composite_raster].
+//! [composite_raster].  To select an operation at runtime, use [BlendOp].
 //!
+//! [BlendOp]: enum.BlendOp.html
 //! [composite_color]: ../struct.Raster.html#method.composite_color
 //! [composite_matte]: ../struct.Raster.html#method.composite_matte
 //! [composite_raster]: ../struct.Raster.html#method.composite_raster
 use crate::chan::Channel;
-use std::any::Any;
+use core::any::Any;
 
 /// Blending operation for compositing.
 ///
-/// This trait is *sealed*, and cannot be implemented outside of this crate.
+/// Custom operations can be implemented outside of this crate, and used
+/// with any of the `Raster` composite methods.  For each pixel,
+/// [composite] is called once per color channel, then [composite_alpha]
+/// is called for the *alpha* channel (if there is one).
+///
+/// Implementations must follow these rules:
+///
+/// * Channels are *premultiplied* with linear gamma, so a color channel is
+///   never greater than *alpha* of the same pixel.
+/// * `da1` and `sa1` are one minus the *alpha* of the whole destination /
+///   source pixel.  They are the same for every channel.
+/// * Channel arithmetic saturates at `MIN` and `MAX` after each operation,
+///   so the order of operations matters.  For the result to be a valid
+///   premultiplied pixel, each color result must not be greater than the
+///   *alpha* result.
+/// * Circular channels, such as *hue*, are not premultiplied.  For these,
+///   `composite` is also called with a `MIN` destination and source *alpha*
+///   as the source, to get a weight for interpolating between the two.
+///
+/// ### Screen blend mode
+/// ```
+/// use pix::chan::Channel;
+/// use pix::ops::Blend;
+/// use pix::rgb::Rgba8p;
+/// use pix::Raster;
+///
+/// #[derive(Clone, Copy)]
+/// struct Screen;
+///
+/// impl Blend for Screen {
+///     fn composite<C: Channel>(&self, dst: &mut C, _: C, src: &C, _: C) {
+///         *dst = *src + *dst * (C::MAX - *src);
+///     }
+/// }
+///
+/// let mut r = Raster::with_color(2, 2, Rgba8p::new(0x80, 0, 0, 0xFF));
+/// r.composite_color((), Rgba8p::new(0x80, 0x80, 0, 0x80), Screen);
+/// assert_eq!(r.pixel(0, 0), Rgba8p::new(0xC0, 0x80, 0, 0xFF));
+/// ```
+///
+/// [composite]: #tymethod.composite
+/// [composite_alpha]: #method.composite_alpha
 pub trait Blend: Any + Copy + Clone {
     /// Composite a destination and source
     ///
@@ -23,7 +65,27 @@ pub trait Blend: Any + Copy + Clone {
     /// * `da1` One minus destination *alpha*
     /// * `src` Source channel
     /// * `sa1` One minus source *alpha*
-    fn composite<C: Channel>(dst: &mut C, da1: C, src: &C, sa1: C);
+    fn composite<C: Channel>(&self, dst: &mut C, da1: C, src: &C, sa1: C);
+
+    /// Composite a destination and source *alpha* channel
+    ///
+    /// By default, this is the same as `composite`.  Blend modes which only
+    /// change color override it, so that *alpha* is composited with
+    /// *Source Over*.
+    ///
+    /// * `dst` Destination *alpha*
+    /// * `da1` One minus destination *alpha*
+    /// * `src` Source *alpha*
+    /// * `sa1` One minus source *alpha*
+    fn composite_alpha<C: Channel>(
+        &self,
+        dst: &mut C,
+        da1: C,
+        src: &C,
+        sa1: C,
+    ) {
+        self.composite(dst, da1, src, sa1);
+    }
 }
 
 /// Source only (ignore destination)
@@ -78,84 +140,453 @@ pub struct Clear;
 #[derive(Clone, Copy)]
 pub struct Plus;
 
+/// Source Over compositing with opacity
+///
+/// The source contribution is scaled by an opacity factor, from `0.0`
+/// (transparent) to `1.0` (same as [SrcOver](struct.SrcOver.html)).  This
+/// is useful for layer opacity, without pre-scaling the source.
+#[derive(Clone, Copy)]
+pub struct SrcOverOpacity(pub f32);
+
+/// Darken blend mode (minimum of source and destination color)
+///
+/// Where source and destination overlap, the darker color is kept.
+/// Elsewhere, it is the same as [SrcOver](struct.SrcOver.html).
+#[derive(Clone, Copy)]
+pub struct Darken;
+
+/// Lighten blend mode (maximum of source and destination color)
+///
+/// Where source and destination overlap, the lighter color is kept.
+/// Elsewhere, it is the same as [SrcOver](struct.SrcOver.html).
+#[derive(Clone, Copy)]
+pub struct Lighten;
+
+/// Difference blend mode (absolute difference of source and destination)
+///
+/// Where source and destination overlap, the result is the difference of
+/// their colors.  Elsewhere, it is the same as
+/// [SrcOver](struct.SrcOver.html).  *Alpha* is composited as `SrcOver`, so
+/// the difference of two identical opaque images is opaque black.
+#[derive(Clone, Copy)]
+pub struct Difference;
+
+/// Blending operation selected at runtime
+///
+/// Each variant corresponds to a [Blend](trait.Blend.html) type.  It can be
+/// used with the `Raster` methods [composite_color_dyn],
+/// [composite_matte_dyn] and [composite_raster_dyn], which dispatch to the
+/// static operations.
+///
+/// [composite_color_dyn]: ../struct.Raster.html#method.composite_color_dyn
+/// [composite_matte_dyn]: ../struct.Raster.html#method.composite_matte_dyn
+/// [composite_raster_dyn]: ../struct.Raster.html#method.composite_raster_dyn
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendOp {
+    /// [Src](struct.Src.html)
+    Src,
+    /// [Dest](struct.Dest.html)
+    Dest,
+    /// [SrcOver](struct.SrcOver.html)
+    SrcOver,
+    /// [DestOver](struct.DestOver.html)
+    DestOver,
+    /// [SrcOut](struct.SrcOut.html)
+    SrcOut,
+    /// [DestOut](struct.DestOut.html)
+    DestOut,
+    /// [SrcIn](struct.SrcIn.html)
+    SrcIn,
+    /// [DestIn](struct.DestIn.html)
+    DestIn,
+    /// [SrcAtop](struct.SrcAtop.html)
+    SrcAtop,
+    /// [DestAtop](struct.DestAtop.html)
+    DestAtop,
+    /// [Xor](struct.Xor.html)
+    Xor,
+    /// [Clear](struct.Clear.html)
+    Clear,
+    /// [Plus](struct.Plus.html)
+    Plus,
+    /// [SrcOverOpacity](struct.SrcOverOpacity.html)
+    SrcOverOpacity(f32),
+    /// [Darken](struct.Darken.html)
+    Darken,
+    /// [Lighten](struct.Lighten.html)
+    Lighten,
+    /// [Difference](struct.Difference.html)
+    Difference,
+}
+
+/// Evaluate an expression with a static `Blend` for a `BlendOp`
+macro_rules! with_blend_op {
+    ($op:expr, $o:ident => $body:expr) => {
+        match $op {
+            $crate::ops::BlendOp::Src => {
+                let $o = $crate::ops::Src;
+                $body
+            }
+            $crate::ops::BlendOp::Dest => {
+                let $o = $crate::ops::Dest;
+                $body
+            }
+            $crate::ops::BlendOp::SrcOver => {
+                let $o = $crate::ops::SrcOver;
+                $body
+            }
+            $crate::ops::BlendOp::DestOver => {
+                let $o = $crate::ops::DestOver;
+                $body
+            }
+            $crate::ops::BlendOp::SrcOut => {
+                let $o = $crate::ops::SrcOut;
+                $body
+            }
+            $crate::ops::BlendOp::DestOut => {
+                let $o = $crate::ops::DestOut;
+                $body
+            }
+            $crate::ops::BlendOp::SrcIn => {
+                let $o = $crate::ops::SrcIn;
+                $body
+            }
+            $crate::ops::BlendOp::DestIn => {
+                let $o = $crate::ops::DestIn;
+                $body
+            }
+            $crate::ops::BlendOp::SrcAtop => {
+                let $o = $crate::ops::SrcAtop;
+                $body
+            }
+            $crate::ops::BlendOp::DestAtop => {
+                let $o = $crate::ops::DestAtop;
+                $body
+            }
+            $crate::ops::BlendOp::Xor => {
+                let $o = $crate::ops::Xor;
+                $body
+            }
+            $crate::ops::BlendOp::Clear => {
+                let $o = $crate::ops::Clear;
+                $body
+            }
+            $crate::ops::BlendOp::Plus => {
+                let $o = $crate::ops::Plus;
+                $body
+            }
+            $crate::ops::BlendOp::SrcOverOpacity(opacity) => {
+                let $o = $crate::ops::SrcOverOpacity(opacity);
+                $body
+            }
+            $crate::ops::BlendOp::Darken => {
+                let $o = $crate::ops::Darken;
+                $body
+            }
+            $crate::ops::BlendOp::Lighten => {
+                let $o = $crate::ops::Lighten;
+                $body
+            }
+            $crate::ops::BlendOp::Difference => {
+                let $o = $crate::ops::Difference;
+                $body
+            }
+        }
+    };
+}
+pub(crate) use with_blend_op;
+
 impl Blend for Src {
-    fn composite<C: Channel>(dst: &mut C, _da1: C, src: &C, _sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, _da1: C, src: &C, _sa1: C) {
         *dst = *src;
     }
 }
 
 impl Blend for Dest {
-    fn composite<C: Channel>(_dst: &mut C, _da1: C, _src: &C, _sa1: C) {
+    fn composite<C: Channel>(&self, _dst: &mut C, _da1: C, _src: &C, _sa1: C) {
         // leave _dst as is
     }
 }
 
 impl Blend for SrcOver {
-    fn composite<C: Channel>(dst: &mut C, _da1: C, src: &C, sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, _da1: C, src: &C, sa1: C) {
         *dst = *src + *dst * sa1;
     }
 }
 
 impl Blend for DestOver {
-    fn composite<C: Channel>(dst: &mut C, da1: C, src: &C, _sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, da1: C, src: &C, _sa1: C) {
         *dst = *src * da1 + *dst;
     }
 }
 
 impl Blend for SrcOut {
-    fn composite<C: Channel>(dst: &mut C, da1: C, src: &C, _sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, da1: C, src: &C, _sa1: C) {
         *dst = *src * da1;
     }
 }
 
 impl Blend for DestOut {
-    fn composite<C: Channel>(dst: &mut C, _da1: C, _src: &C, sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, _da1: C, _src: &C, sa1: C) {
         *dst = *dst * sa1;
     }
 }
 
 impl Blend for SrcIn {
-    fn composite<C: Channel>(dst: &mut C, da1: C, src: &C, _sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, da1: C, src: &C, _sa1: C) {
         let da = C::MAX - da1;
         *dst = *src * da;
     }
 }
 
 impl Blend for DestIn {
-    fn composite<C: Channel>(dst: &mut C, _da1: C, _src: &C, sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, _da1: C, _src: &C, sa1: C) {
         let sa = C::MAX - sa1;
         *dst = *dst * sa;
     }
 }
 
 impl Blend for SrcAtop {
-    fn composite<C: Channel>(dst: &mut C, da1: C, src: &C, sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, da1: C, src: &C, sa1: C) {
         let da = C::MAX - da1;
         *dst = *src * da + *dst * sa1;
     }
 }
 
 impl Blend for DestAtop {
-    fn composite<C: Channel>(dst: &mut C, da1: C, src: &C, sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, da1: C, src: &C, sa1: C) {
         let sa = C::MAX - sa1;
         *dst = *src * da1 + *dst * sa;
     }
 }
 
 impl Blend for Xor {
-    fn composite<C: Channel>(dst: &mut C, da1: C, src: &C, sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, da1: C, src: &C, sa1: C) {
         *dst = *src * da1 + *dst * sa1;
     }
 }
 
 impl Blend for Clear {
-    fn composite<C: Channel>(dst: &mut C, _da1: C, _src: &C, _sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, _da1: C, _src: &C, _sa1: C) {
         *dst = C::default();
     }
 }
 
 impl Blend for Plus {
-    fn composite<C: Channel>(dst: &mut C, _da1: C, src: &C, _sa1: C) {
+    fn composite<C: Channel>(&self, dst: &mut C, _da1: C, src: &C, _sa1: C) {
         *dst = *src + *dst;
     }
 }
+
+impl Blend for SrcOverOpacity {
+    fn composite<C: Channel>(&self, dst: &mut C, _da1: C, src: &C, sa1: C) {
+        let k = C::from(self.0);
+        let sa1 = C::MAX - (C::MAX - sa1) * k;
+        *dst = *src * k + *dst * sa1;
+    }
+}
+
+impl Blend for Darken {
+    fn composite<C: Channel>(&self, dst: &mut C, da1: C, src: &C, sa1: C) {
+        let s = *src * (C::MAX - da1);
+        let d = *dst * (C::MAX - sa1);
+        *dst = s.min(d) + *src * da1 + *dst * sa1;
+    }
+}
+
+impl Blend for Lighten {
+    fn composite<C: Channel>(&self, dst: &mut C, da1: C, src: &C, sa1: C) {
+        let s = *src * (C::MAX - da1);
+        let d = *dst * (C::MAX - sa1);
+        *dst = s.max(d) + *src * da1 + *dst * sa1;
+    }
+}
+
+impl Blend for Difference {
+    fn composite<C: Channel>(&self, dst: &mut C, da1: C, src: &C, sa1: C) {
+        let s = *src * (C::MAX - da1);
+        let d = *dst * (C::MAX - sa1);
+        *dst = s.max(d) - s.min(d) + *src * da1 + *dst * sa1;
+    }
+
+    fn composite_alpha<C: Channel>(
+        &self,
+        dst: &mut C,
+        da1: C,
+        src: &C,
+        sa1: C,
+    ) {
+        SrcOver.composite(dst, da1, src, sa1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chan::Ch8;
+    use crate::el::Pixel;
+    use crate::matte::Matte8;
+    use crate::ops::*;
+    use crate::rgb::{Rgba16p, Rgba8p};
+    use crate::Raster;
+
+    #[test]
+    fn src_over_opacity_one() {
+        let src = Rgba8p::new(0x40, 0x80, 0x20, 0x80);
+        for dst in [
+            Rgba8p::new(0x00, 0x00, 0x00, 0x00),
+            Rgba8p::new(0xFF, 0x40, 0x10, 0xFF),
+            Rgba8p::new(0x20, 0x30, 0x40, 0x60),
+        ] {
+            let mut a = [dst];
+            let mut b = [dst];
+            Rgba8p::composite_color(&mut a, &src, SrcOver);
+            Rgba8p::composite_color(&mut b, &src, SrcOverOpacity(1.0));
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn src_over_opacity_zero() {
+        let src = Rgba8p::new(0x40, 0x80, 0x20, 0x80);
+        let dst = Rgba8p::new(0x20, 0x30, 0x40, 0x60);
+        let mut a = [dst];
+        Rgba8p::composite_color(&mut a, &src, SrcOverOpacity(0.0));
+        assert_eq!(a, [dst]);
+    }
+
+    #[test]
+    fn src_over_opacity_half() {
+        let src = Rgba8p::new(0x40, 0x80, 0x20, 0x80);
+        let scaled = Rgba8p::from_channels(&[
+            Ch8::new(0x20),
+            Ch8::new(0x40),
+            Ch8::new(0x10),
+            Ch8::new(0x40),
+        ]);
+        let dst = Rgba8p::new(0xFF, 0x40, 0x10, 0xFF);
+        let mut a = [dst];
+        let mut b = [dst];
+        Rgba8p::composite_color(&mut a, &scaled, SrcOver);
+        Rgba8p::composite_color(&mut b, &src, SrcOverOpacity(0.5));
+        for (ca, cb) in a[0].channels().iter().zip(b[0].channels()) {
+            assert!(u8::from(*ca).abs_diff(u8::from(*cb)) <= 1);
+        }
+    }
+
+    #[test]
+    fn src_over_opacity_matte() {
+        let clr = Rgba8p::new(0x40, 0x80, 0x20, 0x80);
+        let dst = Rgba8p::new(0x20, 0x30, 0x40, 0x60);
+        let matte = [Matte8::new(0xFF)];
+        let mut a = [dst];
+        let mut b = [dst];
+        Rgba8p::composite_matte(&mut a, &matte, &clr, SrcOver);
+        Rgba8p::composite_matte(&mut b, &matte, &clr, SrcOverOpacity(1.0));
+        assert_eq!(a, b);
+        let mut c = [dst];
+        Rgba8p::composite_matte(&mut c, &matte, &clr, SrcOverOpacity(0.0));
+        assert_eq!(c, [dst]);
+    }
+
+    #[test]
+    fn difference_self() {
+        let mut r = Raster::<Rgba8p>::with_clear(3, 1);
+        *r.pixel_mut(0, 0) = Rgba8p::new(0x10, 0x80, 0xFF, 0xFF);
+        *r.pixel_mut(1, 0) = Rgba8p::new(0x40, 0x00, 0xC0, 0xFF);
+        *r.pixel_mut(2, 0) = Rgba8p::new(0x20, 0x10, 0x40, 0x80);
+        let mut d = r.clone();
+        d.composite_raster((), &r, (), Difference);
+        let black = Rgba8p::new(0, 0, 0, 0xFF);
+        assert_eq!(d.pixel(0, 0), black);
+        assert_eq!(d.pixel(1, 0), black);
+        // alpha is composited as source over
+        let mut a = [r.pixel(2, 0)];
+        Rgba8p::composite_color(&mut a, &r.pixel(2, 0), SrcOver);
+        assert_eq!(d.pixel(2, 0).alpha(), a[0].alpha());
+        // difference with transparent is source over
+        let src = Rgba8p::new(0x40, 0x30, 0x20, 0x80);
+        let mut a = [Rgba8p::default()];
+        Rgba8p::composite_color(&mut a, &src, Difference);
+        assert_eq!(a, [src]);
+    }
+
+    #[test]
+    fn difference_opaque() {
+        let mut a = [Rgba16p::new(0x1000, 0x8000, 0xF000, 0xFFFF)];
+        let src = Rgba16p::new(0x3000, 0x7000, 0x0000, 0xFFFF);
+        Rgba16p::composite_color(&mut a, &src, Difference);
+        assert_eq!(a, [Rgba16p::new(0x2000, 0x1000, 0xF000, 0xFFFF)]);
+    }
+
+    #[test]
+    fn darken_lighten() {
+        let dst = Rgba8p::new(0x10, 0x80, 0xFF, 0xFF);
+        let src = Rgba8p::new(0x40, 0x20, 0xC0, 0xFF);
+        let mut a = [dst];
+        Rgba8p::composite_color(&mut a, &src, Darken);
+        assert_eq!(a, [Rgba8p::new(0x10, 0x20, 0xC0, 0xFF)]);
+        let mut a = [dst];
+        Rgba8p::composite_color(&mut a, &src, Lighten);
+        assert_eq!(a, [Rgba8p::new(0x40, 0x80, 0xFF, 0xFF)]);
+        // translucent source over opaque destination
+        let src = Rgba8p::new(0x40, 0x00, 0x40, 0x80);
+        let mut a = [dst];
+        Rgba8p::composite_color(&mut a, &src, Darken);
+        let mut b = [dst];
+        Rgba8p::composite_color(&mut b, &src, SrcOver);
+        // channels darker than destination match source over
+        assert_eq!(a[0].two(), b[0].two());
+        assert_eq!(a[0].three(), b[0].three());
+        assert_eq!(a[0].alpha(), Ch8::MAX);
+    }
+
+    fn check_dyn<O: Blend>(op: O, dop: BlendOp) {
+        let mut dst = Raster::<Rgba8p>::with_clear(8, 8);
+        let mut src = Raster::<Rgba8p>::with_clear(8, 8);
+        let mut matte = Raster::<Matte8>::with_clear(8, 8);
+        for (i, p) in dst.pixels_mut().iter_mut().enumerate() {
+            let a = (i * 4) as u8;
+            *p = Rgba8p::new(a / 2, a / 3, a / 4, a);
+        }
+        for (i, p) in src.pixels_mut().iter_mut().enumerate() {
+            let a = 255 - (i * 3) as u8;
+            *p = Rgba8p::new(a / 4, a / 2, a, a);
+        }
+        for (i, p) in matte.pixels_mut().iter_mut().enumerate() {
+            *p = Matte8::new((i * 5) as u8);
+        }
+        let clr = Rgba8p::new(0x20, 0x40, 0x60, 0x80);
+        let mut r0 = dst.clone();
+        let mut r1 = dst.clone();
+        r0.composite_raster((1, 1, 6, 6), &src, (), op);
+        r1.composite_raster_dyn((1, 1, 6, 6), &src, (), dop);
+        assert_eq!(r0.pixels(), r1.pixels(), "{dop:?}");
+        r0.composite_color((2, 0, 4, 8), clr, op);
+        r1.composite_color_dyn((2, 0, 4, 8), clr, dop);
+        assert_eq!(r0.pixels(), r1.pixels(), "{dop:?}");
+        r0.composite_matte((), &matte, (), clr, op);
+        r1.composite_matte_dyn((), &matte, (), clr, dop);
+        assert_eq!(r0.pixels(), r1.pixels(), "{dop:?}");
+    }
+
+    #[test]
+    fn blend_op_dyn() {
+        check_dyn(Src, BlendOp::Src);
+        check_dyn(Dest, BlendOp::Dest);
+        check_dyn(SrcOver, BlendOp::SrcOver);
+        check_dyn(DestOver, BlendOp::DestOver);
+        check_dyn(SrcOut, BlendOp::SrcOut);
+        check_dyn(DestOut, BlendOp::DestOut);
+        check_dyn(SrcIn, BlendOp::SrcIn);
+        check_dyn(DestIn, BlendOp::DestIn);
+        check_dyn(SrcAtop, BlendOp::SrcAtop);
+        check_dyn(DestAtop, BlendOp::DestAtop);
+        check_dyn(Xor, BlendOp::Xor);
+        check_dyn(Clear, BlendOp::Clear);
+        check_dyn(Plus, BlendOp::Plus);
+        check_dyn(SrcOverOpacity(0.25), BlendOp::SrcOverOpacity(0.25));
+        check_dyn(Darken, BlendOp::Darken);
+        check_dyn(Lighten, BlendOp::Lighten);
+        check_dyn(Difference, BlendOp::Difference);
+    }
+}
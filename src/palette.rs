@@ -5,14 +5,40 @@
 use crate::chan::{Ch8, Srgb, Straight};
 use crate::el::{Pix3, Pixel};
 use crate::gray::Gray8;
+use crate::oklab::delta_e;
 use crate::raster::Raster;
 use crate::rgb::{Rgb, SRgb8};
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::Copied;
+use core::slice::Iter;
 
 /// Color table for use with indexed `Raster`s.
 #[derive(Clone)]
 pub struct Palette {
     table: Vec<SRgb8>,
+    /// Maximum number of entries
+    capacity: usize,
     threshold_fn: fn(usize) -> SRgb8,
+    /// First index of each color in the table, for exact matching
+    lookup: BTreeMap<[u8; 3], usize>,
+}
+
+impl Default for Palette {
+    /// Create a `Palette` with capacity for 256 entries.
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl<'a> IntoIterator for &'a Palette {
+    type Item = SRgb8;
+    type IntoIter = Copied<Iter<'a, SRgb8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 impl Palette {
@@ -24,10 +50,74 @@ impl Palette {
         let threshold_fn = |_| SRgb8::default();
         Palette {
             table,
+            capacity,
             threshold_fn,
+            lookup: BTreeMap::new(),
         }
     }
 
+    /// Create a `Palette` from a fixed list of colors.
+    ///
+    /// * `colors` Palette entries, in order.
+    ///
+    /// Colors are added without matching, so duplicates are kept.  The
+    /// palette is full, with capacity equal to the number of colors.
+    ///
+    /// ### Load a fixed palette
+    /// ```
+    /// use pix::rgb::SRgb8;
+    /// use pix::Palette;
+    ///
+    /// let black = SRgb8::new(0, 0, 0);
+    /// let white = SRgb8::new(0xFF, 0xFF, 0xFF);
+    /// let p = Palette::from_colors(&[black, white]);
+    /// assert_eq!(p.len(), 2);
+    /// assert_eq!(p.entry(1), Some(white));
+    /// ```
+    pub fn from_colors(colors: &[SRgb8]) -> Self {
+        let mut palette = Self::new(colors.len());
+        palette.table.extend_from_slice(colors);
+        palette.rebuild_lookup();
+        palette
+    }
+
+    /// Create a `Palette` with every distinct color in a `Raster`.
+    ///
+    /// * `raster` Source raster.
+    /// * `max_colors` Maximum number of entries (palette capacity).
+    ///
+    /// Entries are sorted in channel order (*red*, then *green*, then
+    /// *blue*).  Returns `None` if there are more than `max_colors` distinct
+    /// colors.
+    ///
+    /// ### Palette from an image
+    /// ```
+    /// use pix::rgb::SRgb8;
+    /// use pix::{Palette, Raster};
+    ///
+    /// let mut r = Raster::with_color(4, 4, SRgb8::new(0xFF, 0, 0));
+    /// *r.pixel_mut(2, 2) = SRgb8::new(0, 0, 0xFF);
+    /// let p = Palette::from_raster_dedup(&r, 16).unwrap();
+    /// let (red, blue) = (SRgb8::new(0xFF, 0, 0), SRgb8::new(0, 0, 0xFF));
+    /// assert_eq!(p.colors(), [blue, red]);
+    /// assert!(Palette::from_raster_dedup(&r, 1).is_none());
+    /// ```
+    pub fn from_raster_dedup(
+        raster: &Raster<SRgb8>,
+        max_colors: usize,
+    ) -> Option<Self> {
+        let mut colors = raster.pixels().to_vec();
+        colors.sort_unstable();
+        colors.dedup();
+        if colors.len() > max_colors {
+            return None;
+        }
+        let mut palette = Self::new(max_colors);
+        palette.table.extend_from_slice(&colors);
+        palette.rebuild_lookup();
+        Some(palette)
+    }
+
     /// Get the number of entries.
     pub fn len(&self) -> usize {
         self.table.len()
@@ -38,12 +128,24 @@ impl Palette {
         self.table.is_empty()
     }
 
+    /// Get the maximum number of entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Shrink the capacity to the number of entries.
+    ///
+    /// After this, no new entries can be added.
+    pub fn shrink_to_fit(&mut self) {
+        self.table.shrink_to_fit();
+        self.capacity = self.table.len();
+    }
+
     /// Set the threshold function for matching entries.
     ///
     /// * `threshold_fn` Called when checking whether a color matches an
-    ///                  existing entry.  The parameter is the palette table
-    ///                  size.  Returns the maximum `Channel`-wise difference
-    ///                  to match.
+    ///   existing entry.  The parameter is the palette table size.  Returns
+    ///   the maximum `Channel`-wise difference to match.
     pub fn set_threshold_fn(&mut self, threshold_fn: fn(usize) -> SRgb8) {
         self.threshold_fn = threshold_fn;
     }
@@ -53,6 +155,11 @@ impl Palette {
         &self.table
     }
 
+    /// Get an iterator of all colors.
+    pub fn iter(&self) -> Copied<Iter<'_, SRgb8>> {
+        self.table.iter().copied()
+    }
+
     /// Get a `Palette` entry.
     ///
     /// * `i` Index of entry.
@@ -75,6 +182,11 @@ impl Palette {
     /// Index of best matching or added entry if successful.  Otherwise, when
     /// no matches are found and the table is full, `None` is returned.
     pub fn set_entry(&mut self, clr: SRgb8) -> Option<usize> {
+        // An exact match is always the best, and within any threshold
+        let key = <[u8; 3]>::from(clr);
+        if let Some(i) = self.lookup.get(&key) {
+            return Some(*i);
+        }
         if let Some((i, dif)) = self.best_match(clr) {
             if Rgb::within_threshold(dif, (self.threshold_fn)(self.table.len()))
             {
@@ -82,14 +194,23 @@ impl Palette {
             }
         }
         let i = self.table.len();
-        if i < self.table.capacity() {
+        if i < self.capacity {
             self.table.push(clr);
+            self.lookup.insert(key, i);
             Some(i)
         } else {
             None
         }
     }
 
+    /// Rebuild exact match lookup from the table
+    fn rebuild_lookup(&mut self) {
+        self.lookup.clear();
+        for (i, c) in self.table.iter().enumerate() {
+            self.lookup.entry(<[u8; 3]>::from(*c)).or_insert(i);
+        }
+    }
+
     /// Find the best match for a color.
     ///
     /// The first of equal matches will be returned.
@@ -107,6 +228,40 @@ impl Palette {
         best
     }
 
+    /// Find the nearest entry to a color, by perceptual distance.
+    ///
+    /// Unlike [set_entry](#method.set_entry), no threshold is used; see
+    /// [oklab::delta_e](oklab/fn.delta_e.html).  The first of equal matches
+    /// will be returned.
+    ///
+    /// * `clr` Color to match.
+    ///
+    /// # Returns
+    /// Index of nearest entry, or `None` if the palette is empty.
+    ///
+    /// ### Find nearest color
+    /// ```
+    /// use pix::rgb::SRgb8;
+    /// use pix::Palette;
+    ///
+    /// let p = Palette::from_colors(&[
+    ///     SRgb8::new(0xFF, 0xFF, 0x00),
+    ///     SRgb8::new(0x00, 0xFF, 0xFF),
+    /// ]);
+    /// let blue = SRgb8::new(0x00, 0x00, 0xFF);
+    /// assert_eq!(p.nearest_perceptual(blue), Some(1));
+    /// ```
+    pub fn nearest_perceptual(&self, clr: SRgb8) -> Option<usize> {
+        let mut best: Option<(usize, f32)> = None;
+        for (i, c) in self.table.iter().enumerate() {
+            let dist = delta_e(clr, *c);
+            if best.map_or(true, |(_, d)| dist < d) {
+                best = Some((i, dist));
+            }
+        }
+        best.map(|(i, _)| i)
+    }
+
     /// Replace a `Palette` entry.
     ///
     /// * `i` Index of entry.
@@ -118,6 +273,7 @@ impl Palette {
         if i < self.table.len() {
             let old = self.table[i];
             self.table[i] = clr;
+            self.rebuild_lookup();
             Some(old)
         } else {
             None
@@ -145,28 +301,53 @@ impl Palette {
     }
 
     /// Make an indexed raster
+    ///
+    /// Each pixel is matched with [set_entry](#method.set_entry).  If the
+    /// table is full, the best matching entry is used instead.
     pub fn make_indexed<S>(&mut self, raster: Raster<S>) -> Raster<Gray8>
     where
         S: Pixel<Chan = Ch8>,
         <Pix3<Ch8, Rgb, Straight, Srgb> as Pixel>::Chan: From<S::Chan>,
     {
-        let mut indexed = Raster::with_clear(raster.width(), raster.height());
-        for (src, dst) in raster.pixels().iter().zip(indexed.pixels_mut()) {
-            let clr = src.convert();
-            if let Some(e) = self.set_entry(clr) {
-                *dst = Gray8::new(e as u8);
-            } else {
-                // FIXME: handle full palette
-            }
-        }
-        indexed
+        let indices = self.index_pixels(raster.pixels());
+        Raster::with_u8_buffer(raster.width(), raster.height(), indices)
+    }
+
+    /// Get entry indices for a slice of pixels, adding entries as needed
+    pub(crate) fn index_pixels<S>(&mut self, pixels: &[S]) -> Vec<u8>
+    where
+        S: Pixel<Chan = Ch8>,
+        <Pix3<Ch8, Rgb, Straight, Srgb> as Pixel>::Chan: From<S::Chan>,
+    {
+        // Neighboring pixels are often equal, and matching the same color
+        // twice in a row always gives the same entry
+        let mut last: Option<(SRgb8, u8)> = None;
+        pixels
+            .iter()
+            .map(|src| {
+                let clr = src.convert();
+                match last {
+                    Some((c, e)) if c == clr => e,
+                    _ => {
+                        let ent = self
+                            .set_entry(clr)
+                            .or_else(|| self.best_match(clr).map(|(i, _)| i))
+                            .unwrap_or(0)
+                            as u8;
+                        last = Some((clr, ent));
+                        ent
+                    }
+                }
+            })
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::gray::Gray8;
     use crate::rgb::*;
-    use crate::Palette;
+    use crate::{Palette, Raster};
 
     #[test]
     fn fill_16() {
@@ -206,6 +387,56 @@ mod test {
         assert_eq!(p.colors(), &v[..]);
     }
 
+    #[test]
+    fn from_colors() {
+        let colors = [
+            SRgb8::new(0x00, 0x00, 0x00),
+            SRgb8::new(0xFF, 0x00, 0x00),
+            SRgb8::new(0x00, 0xFF, 0x00),
+            SRgb8::new(0xFF, 0x00, 0x00),
+        ];
+        let mut p = Palette::from_colors(&colors);
+        assert_eq!(p.len(), 4);
+        assert!(!p.is_empty());
+        assert_eq!(p.colors(), &colors);
+        assert!(p.iter().eq(colors));
+        assert!((&p).into_iter().eq(colors));
+        assert_eq!(p.histogram(&[1u8, 3, 3, 0]), Some(vec![1, 1, 0, 2]));
+        assert_eq!(p.histogram(&[4u8]), None);
+        assert_eq!(p.set_entry(SRgb8::new(0xFF, 0x00, 0x00)), Some(1));
+        assert_eq!(p.set_entry(SRgb8::new(0x00, 0x00, 0xFF)), None);
+        assert!(Palette::default().is_empty());
+    }
+
+    #[test]
+    fn capacity() {
+        let mut p = Palette::new(16);
+        assert_eq!(p.capacity(), 16);
+        p.set_entry(SRgb8::new(1, 2, 3));
+        p.set_entry(SRgb8::new(40, 50, 60));
+        p.shrink_to_fit();
+        assert_eq!(p.capacity(), 2);
+        assert_eq!(p.len(), 2);
+        assert_eq!(p.set_entry(SRgb8::new(40, 50, 60)), Some(1));
+        assert_eq!(p.set_entry(SRgb8::new(99, 99, 99)), None);
+        assert_eq!(Palette::default().capacity(), 256);
+    }
+
+    #[test]
+    fn nearest_perceptual() {
+        let mut p = Palette::new(8);
+        assert_eq!(p.nearest_perceptual(SRgb8::new(1, 2, 3)), None);
+        p.set_entry(SRgb8::new(0x00, 0x00, 0x00));
+        p.set_entry(SRgb8::new(0xFF, 0xFF, 0xFF));
+        p.set_entry(SRgb8::new(0xFF, 0x00, 0x00));
+        p.set_entry(SRgb8::new(0x00, 0x80, 0x00));
+        assert_eq!(p.nearest_perceptual(SRgb8::new(0x20, 0x20, 0x20)), Some(0));
+        assert_eq!(p.nearest_perceptual(SRgb8::new(0xC0, 0xC0, 0xD0)), Some(1));
+        assert_eq!(p.nearest_perceptual(SRgb8::new(0xFF, 0x40, 0x20)), Some(2));
+        assert_eq!(p.nearest_perceptual(SRgb8::new(0x10, 0xA0, 0x30)), Some(3));
+        assert_eq!(p.nearest_perceptual(SRgb8::new(0xFF, 0x00, 0x00)), Some(2));
+    }
+
     #[test]
     fn check_hist() {
         let mut p = Palette::new(8);
@@ -222,6 +453,80 @@ mod test {
         assert_eq!(p.histogram(&v[..]), Some(vec![18, 6, 10, 4, 8, 0, 2]));
     }
 
+    /// Original linear matching, for comparison
+    fn set_entry_linear(p: &mut Palette, clr: SRgb8) -> Option<usize> {
+        let mut best: Option<(usize, SRgb8)> = None;
+        for (i, c) in p.colors().iter().enumerate() {
+            let dif = Rgb::difference(clr, *c);
+            if match best {
+                Some((_, d)) => Rgb::within_threshold(dif, d) && dif != d,
+                _ => true,
+            } {
+                best = Some((i, dif));
+            }
+        }
+        if let Some((i, dif)) = best {
+            if Rgb::within_threshold(dif, (p.threshold_fn)(p.len())) {
+                return Some(i);
+            }
+        }
+        let i = p.len();
+        if i < p.capacity() {
+            p.table.push(clr);
+            Some(i)
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn matching_same_as_linear() {
+        let thresholds: [fn(usize) -> SRgb8; 3] = [
+            |_| SRgb8::default(),
+            |_| SRgb8::new(8, 8, 8),
+            |n| SRgb8::new(n as u8 / 4, n as u8 / 8, n as u8 / 2),
+        ];
+        let mut seed = 0x1234_5678u32;
+        for threshold_fn in thresholds {
+            let mut r = Raster::<SRgb8>::with_clear(32, 32);
+            let mut clr = SRgb8::default();
+            for p in r.pixels_mut() {
+                // xorshift, with runs of equal pixels
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                if seed & 3 > 0 {
+                    let [r, g, b, _] = seed.to_le_bytes();
+                    clr = SRgb8::new(r / 4, g / 2, b);
+                }
+                *p = clr;
+            }
+            let mut p0 = Palette::new(200);
+            p0.set_threshold_fn(threshold_fn);
+            let mut p1 = Palette::new(200);
+            p1.set_threshold_fn(threshold_fn);
+            let indexed = p0.make_indexed(r.clone());
+            for (clr, idx) in r.pixels().iter().zip(indexed.pixels()) {
+                let e = set_entry_linear(&mut p1, *clr)
+                    .or_else(|| p1.best_match(*clr).map(|(i, _)| i))
+                    .unwrap_or(0);
+                assert_eq!(Gray8::new(e as u8), *idx);
+            }
+            assert_eq!(p0.colors(), p1.colors());
+        }
+    }
+
+    #[test]
+    fn replace_lookup() {
+        let red = SRgb8::new(0xFF, 0x00, 0x00);
+        let blue = SRgb8::new(0x00, 0x00, 0xFF);
+        let mut p = Palette::from_colors(&[red, blue, red]);
+        assert_eq!(p.set_entry(red), Some(0));
+        p.replace_entry(0, blue);
+        assert_eq!(p.set_entry(red), Some(2));
+        assert_eq!(p.set_entry(blue), Some(0));
+    }
+
     #[test]
     fn matching() {
         let mut p = Palette::new(8);
@@ -234,4 +539,21 @@ mod test {
         p.set_threshold_fn(|_| SRgb8::new(5, 5, 5));
         assert_eq!(p.set_entry(SRgb8::new(35, 35, 35)), Some(2));
     }
+
+    #[test]
+    fn from_raster_dedup() {
+        let mut r = Raster::<SRgb8>::with_clear(16, 16);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = (i % 37) as u8;
+            *p = SRgb8::new(v / 4, 0x80, v * 3);
+        }
+        let p = Palette::from_raster_dedup(&r, 37).unwrap();
+        assert_eq!(p.len(), 37);
+        assert_eq!(p.capacity(), 37);
+        assert!(p.colors().windows(2).all(|w| w[0] < w[1]));
+        for clr in r.pixels() {
+            assert!(p.colors().binary_search(clr).is_ok());
+        }
+        assert!(Palette::from_raster_dedup(&r, 36).is_none());
+    }
 }
@@ -13,12 +13,12 @@
 //! Hwb::hue(Rgb8::new(255, 255, 255));
 //! ```
 use crate::chan::{
-    Alpha, Ch16, Ch32, Ch8, Channel, Gamma, Linear, Premultiplied, Srgb,
+    Alpha, Ch16, Ch32, Ch64, Ch8, Channel, Gamma, Linear, Premultiplied, Srgb,
     Straight,
 };
 use crate::el::{Pix1, Pix2, Pix3, Pix4};
 use crate::ColorModel;
-use std::any::Any;
+use core::any::Any;
 
 /// Sealed trait to prevent outside crates from implementing traits
 pub trait Sealed: Any {}
@@ -29,6 +29,8 @@ impl Sealed for Ch16 {}
 
 impl Sealed for Ch32 {}
 
+impl Sealed for Ch64 {}
+
 impl Sealed for Straight {}
 
 impl Sealed for Premultiplied {}
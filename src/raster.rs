@@ -3,22 +3,179 @@
 // Copyright (c) 2017-2024  Douglas P Lau
 // Copyright (c) 2019-2020  Jeron Aldaron Lau
 //
-use crate::chan::{Ch16, Ch8, Linear, Premultiplied};
-use crate::el::Pixel;
-use crate::matte::Matte;
-use crate::ops::Blend;
-use std::convert::TryFrom;
-use std::ops::Range;
-use std::slice::{from_raw_parts_mut, ChunksExact, ChunksExactMut};
+use crate::chan::{
+    Alpha, Ch16, Ch32, Ch64, Ch8, Channel, Gamma, Linear, Premultiplied,
+    Straight,
+};
+use crate::el::{
+    convert_channel, AlphaPixel, ChannelKind, Pix1, Pix2, Pix3, Pix4, PixRgba,
+    Pixel,
+};
+#[cfg(not(feature = "std"))]
+use crate::float::Float;
+use crate::gray::Gray;
+use crate::integral::Integral;
+use crate::matte::{Matte, Matte8};
+use crate::ops::{with_blend_op, Blend, BlendOp, SrcOver};
+use crate::rgb::{Rgb, Rgb8, Rgba8p, SRgb8, SRgba8};
+use crate::ycc::{YCbCr, YCbCr32};
+use crate::ColorModel;
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::any::{Any, TypeId};
+use core::convert::TryFrom;
+use core::fmt;
+use core::iter::StepBy;
+use core::ops::Range;
+use core::ptr::slice_from_raw_parts_mut;
+use core::slice::{ChunksExact, ChunksExactMut, Iter};
 
-/// Message for width too big
-const WIDTH_TOO_BIG: &str = "Raster width too big";
+/// 4x4 Bayer matrix for ordered dithering
+const BAYER_4X4: [[u8; 4]; 4] =
+    [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
 
-/// Message for height too big
-const HEIGHT_TOO_BIG: &str = "Raster height too big";
+/// Dithering mode for reducing bit depth.
+///
+/// Used by [Raster::convert_dithered].
+///
+/// [Raster::convert_dithered]: struct.Raster.html#method.convert_dithered
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DitherMode {
+    /// No dithering (round to nearest)
+    #[default]
+    None,
+    /// Ordered dithering with a 4x4 Bayer matrix
+    Ordered,
+}
+
+/// Orientation of a stripe pattern.
+///
+/// Used by [Raster::fill_stripes].
+///
+/// [Raster::fill_stripes]: struct.Raster.html#method.fill_stripes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// Horizontal stripes (alternating rows)
+    Horizontal,
+    /// Vertical stripes (alternating columns)
+    Vertical,
+}
+
+/// Light mode for resampling color channels.
+///
+/// Used by [Raster::halve].
+///
+/// [Raster::halve]: struct.Raster.html#method.halve
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Light {
+    /// Filter channel values as encoded
+    #[default]
+    Native,
+    /// Filter in linear light, decoding and re-encoding sRGB gamma
+    Linear,
+}
+
+/// Mode for pixels vacated by shifting.
+///
+/// Used by [Raster::shift].
+///
+/// [Raster::shift]: struct.Raster.html#method.shift
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShiftMode<P> {
+    /// Fill vacated pixels with a color
+    Fill(P),
+    /// Wrap pixels shifted off one edge around to the opposite edge
+    Wrap,
+}
+
+/// Mode for replacing pixel colors.
+///
+/// Used by [Raster::recolor].
+///
+/// [Raster::recolor]: struct.Raster.html#method.recolor
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RecolorMode {
+    /// Replace color, keeping only the original *alpha*
+    #[default]
+    Flat,
+    /// Replace color, scaled by the original relative luminance
+    Luminance,
+}
+
+/// Position of a [Region] anchored within a parent.
+///
+/// Used by [Region::anchored].
+///
+/// [Region]: struct.Region.html
+/// [Region::anchored]: struct.Region.html#method.anchored
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Anchor {
+    /// Top-left corner
+    #[default]
+    TopLeft,
+    /// Top edge, centered horizontally
+    TopCenter,
+    /// Top-right corner
+    TopRight,
+    /// Left edge, centered vertically
+    CenterLeft,
+    /// Centered horizontally and vertically
+    Center,
+    /// Right edge, centered vertically
+    CenterRight,
+    /// Bottom-left corner
+    BottomLeft,
+    /// Bottom edge, centered horizontally
+    BottomCenter,
+    /// Bottom-right corner
+    BottomRight,
+}
+
+/// Counts of values clamped when building a [Raster] from `f32` data.
+///
+/// Returned from [Raster::from_f32_slice_clamping].
+///
+/// [Raster]: struct.Raster.html
+/// [Raster::from_f32_slice_clamping]: struct.Raster.html#method.from_f32_slice_clamping
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClipStats {
+    /// Number of values below 0.0
+    pub below: usize,
+    /// Number of values above 1.0
+    pub above: usize,
+    /// Number of `NaN` values (which become 0.0)
+    pub nan: usize,
+}
 
-/// Message for raster too big
-const TOO_BIG: &str = "Raster too big";
+/// Error building a [Raster] from rows.
+///
+/// Returned from [Raster::with_rows] and [RasterBuilder].
+///
+/// [Raster]: struct.Raster.html
+/// [Raster::with_rows]: struct.Raster.html#method.with_rows
+/// [RasterBuilder]: struct.RasterBuilder.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RasterError {
+    /// Row length not equal to raster width
+    RowWidth,
+
+    /// Number of rows not equal to raster height
+    RowCount,
+}
+
+impl fmt::Display for RasterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RasterError::RowWidth => write!(f, "invalid raster row width"),
+            RasterError::RowCount => write!(f, "invalid raster row count"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RasterError {}
 
 /// Image arranged as a rectangular array of pixels.  Rows are ordered top to
 /// bottom, and pixels within rows are left to right.
@@ -30,6 +187,19 @@ const TOO_BIG: &str = "Raster too big";
 /// * [with_pixels](#method.with_pixels)
 /// * [with_u8_buffer](#method.with_u8_buffer)
 /// * [with_u16_buffer](#method.with_u16_buffer)
+/// * [with_f64_buffer](#method.with_f64_buffer)
+///
+/// Separate channel planes can be joined with
+/// [from_planes](#method.from_planes).
+///
+/// ### Size limits
+///
+/// Width and height must each be no greater than `i32::MAX`, and so must the
+/// total pixel count.  The buffer size in bytes must also fit in `isize`,
+/// the limit for any allocation; this can be exceeded on 32-bit targets,
+/// such as a 40000 x 40000 [Rgba16](rgb/type.Rgba16.html) raster.
+/// Constructors panic if a limit is exceeded, so buffer conversions never
+/// need to check again.
 ///
 /// ### Working with byte buffers
 ///
@@ -47,7 +217,7 @@ const TOO_BIG: &str = "Raster too big";
 /// // A boxed slice can be turned back into Vec
 /// let v: Vec<u8> = slice.into();
 /// ```
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct Raster<P: Pixel> {
     width: i32,
     height: i32,
@@ -56,11 +226,14 @@ pub struct Raster<P: Pixel> {
 
 /// `Iterator` of *rows* in a [raster], as slices of [pixel]s.
 ///
-/// This struct is created by the [rows] method of [Raster].
+/// This struct is created by the [rows] method of [Raster].  Rows can be
+/// iterated bottom-to-top with `rev()`, which is useful for formats such as
+/// BMP or OpenGL textures, where row 0 is the bottom.
 ///
 /// [pixel]: el/trait.Pixel.html
 /// [raster]: struct.Raster.html
 /// [rows]: struct.Raster.html#method.rows
+#[derive(Clone)]
 pub struct Rows<'a, P: Pixel> {
     /// Chunks iterator of full rows
     chunks: ChunksExact<'a, P>,
@@ -70,7 +243,8 @@ pub struct Rows<'a, P: Pixel> {
 
 /// `Iterator` of *rows* in a [raster], as mutable slices of [pixel]s.
 ///
-/// This struct is created by the [rows_mut] method of [Raster].
+/// This struct is created by the [rows_mut] method of [Raster].  Rows can be
+/// iterated bottom-to-top with `rev()`.
 ///
 /// [pixel]: el/trait.Pixel.html
 /// [raster]: struct.Raster.html
@@ -82,6 +256,86 @@ pub struct RowsMut<'a, P: Pixel> {
     columns: Range<usize>,
 }
 
+/// `Iterator` of *columns* in a [raster].
+///
+/// This struct is created by the [columns] method of [Raster].  Each column
+/// is an `Iterator` of [pixel] references, from top to bottom.
+///
+/// [pixel]: el/trait.Pixel.html
+/// [raster]: struct.Raster.html
+/// [columns]: struct.Raster.html#method.columns
+pub struct Columns<'a, P: Pixel> {
+    /// Pixels of full rows within the region
+    pixels: &'a [P],
+    /// Raster width
+    width: usize,
+    /// Range of requested columns
+    columns: Range<usize>,
+}
+
+/// `Iterator` of *spans* covered by a [RegionSet], as slices of [pixel]s.
+///
+/// This struct is created by the [spans] method of [Raster].  Each item is
+/// the `(x, y)` position of a span, along with its pixels.
+///
+/// [pixel]: el/trait.Pixel.html
+/// [raster]: struct.Raster.html
+/// [RegionSet]: struct.RegionSet.html
+/// [spans]: struct.Raster.html#method.spans
+pub struct Spans<'a, P: Pixel> {
+    /// Pixels of the raster
+    pixels: &'a [P],
+    /// Raster width
+    width: usize,
+    /// Clipped spans (x, y, length)
+    spans: vec::IntoIter<(i32, i32, usize)>,
+}
+
+/// `Iterator` of *spans* covered by a [RegionSet], as mutable slices of
+/// [pixel]s.
+///
+/// This struct is created by the [spans_mut] method of [Raster].  Each item
+/// is the `(x, y)` position of a span, along with its pixels.
+///
+/// [pixel]: el/trait.Pixel.html
+/// [raster]: struct.Raster.html
+/// [RegionSet]: struct.RegionSet.html
+/// [spans_mut]: struct.Raster.html#method.spans_mut
+pub struct SpansMut<'a, P: Pixel> {
+    /// Remaining pixels, after the previous span
+    rest: &'a mut [P],
+    /// Index of first remaining pixel
+    pos: usize,
+    /// Raster width
+    width: usize,
+    /// Clipped spans (x, y, length)
+    spans: vec::IntoIter<(i32, i32, usize)>,
+}
+
+/// Builder for a [Raster], one row at a time.
+///
+/// This is useful for streaming decoders, since pixels are only copied once.
+///
+/// ### Build a `Raster` from decoded rows
+/// ```
+/// use pix::gray::Gray8;
+/// use pix::RasterBuilder;
+///
+/// let mut builder = RasterBuilder::new(3, 2);
+/// builder.push_row(&[Gray8::new(1), Gray8::new(2), Gray8::new(3)])?;
+/// builder.push_row(&[Gray8::new(4), Gray8::new(5), Gray8::new(6)])?;
+/// let r = builder.finish()?;
+/// assert_eq!(r.pixel(2, 1), Gray8::new(6));
+/// # Ok::<(), pix::RasterError>(())
+/// ```
+/// [Raster]: struct.Raster.html
+pub struct RasterBuilder<P: Pixel> {
+    width: i32,
+    height: i32,
+    rows: i32,
+    pixels: Vec<P>,
+}
+
 /// Location / dimensions of pixels relative to a [Raster](struct.Raster.html).
 ///
 /// ### Create directly
@@ -107,6 +361,60 @@ pub struct Region {
     height: i32,
 }
 
+/// Set of pixels covered by a union of [Region]s.
+///
+/// Overlapping regions are decomposed into disjoint horizontal *bands*, each
+/// with a sorted list of non-overlapping *spans*.  This is useful for
+/// tracking damage, since every covered pixel is visited exactly once by
+/// [Raster::spans].
+///
+/// [Raster::spans]: struct.Raster.html#method.spans
+/// [Region]: struct.Region.html
+///
+/// ### Union of damaged regions
+/// ```
+/// use pix::{Region, RegionSet};
+///
+/// let mut set = RegionSet::new();
+/// set.add((0, 0, 10, 10));
+/// set.add((5, 5, 10, 10));
+/// assert!(set.contains(12, 12));
+/// assert!(!set.contains(12, 2));
+/// assert_eq!(set.regions().count(), 3);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegionSet {
+    /// Disjoint bands, sorted from top to bottom
+    bands: Vec<Band>,
+}
+
+/// Horizontal band of a [RegionSet]
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Band {
+    /// Top row
+    top: i32,
+    /// Bottom row (exclusive)
+    bottom: i32,
+    /// Sorted, non-overlapping spans of columns (left, right exclusive)
+    spans: Vec<(i32, i32)>,
+}
+
+impl<P> fmt::Debug for Raster<P>
+where
+    P: Pixel + fmt::Display,
+{
+    /// Format dimensions, pixel type and a sample of up to 4x4 pixels from
+    /// the top-left corner, instead of the entire buffer.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Raster")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("pixel", &TypeName(core::any::type_name::<P>()))
+            .field("sample", &Sample(self))
+            .finish()
+    }
+}
+
 impl<P: Pixel> From<Raster<P>> for Box<[P]> {
     /// Get internal pixel data as boxed slice.
     fn from(raster: Raster<P>) -> Self {
@@ -127,12 +435,12 @@ where
 {
     /// Get internal pixel data as boxed slice of *u8*.
     fn from(raster: Raster<P>) -> Self {
-        let pixels = raster.pixels;
-        let capacity = pixels.len() * std::mem::size_of::<P>();
-        let slice = Box::<[P]>::into_raw(pixels);
+        // constructors checked that the byte count fits in `isize`
+        let capacity = raster.bytes();
+        let slice = Box::<[P]>::into_raw(raster.pixels);
         let buffer: Box<[u8]> = unsafe {
             let ptr = (*slice).as_mut_ptr() as *mut u8;
-            Box::from_raw(from_raw_parts_mut(ptr, capacity))
+            Box::from_raw(slice_from_raw_parts_mut(ptr, capacity))
         };
         buffer
     }
@@ -144,12 +452,29 @@ where
 {
     /// Get internal pixel data as boxed slice of *u16*.
     fn from(raster: Raster<P>) -> Self {
-        let pixels = raster.pixels;
-        let capacity = pixels.len() * std::mem::size_of::<P>() / 2;
-        let slice = Box::<[P]>::into_raw(pixels);
+        // constructors checked that the byte count fits in `isize`
+        let capacity = raster.bytes() / 2;
+        let slice = Box::<[P]>::into_raw(raster.pixels);
         let buffer: Box<[u16]> = unsafe {
             let ptr = (*slice).as_mut_ptr() as *mut u16;
-            Box::from_raw(from_raw_parts_mut(ptr, capacity))
+            Box::from_raw(slice_from_raw_parts_mut(ptr, capacity))
+        };
+        buffer
+    }
+}
+
+impl<P> From<Raster<P>> for Box<[f64]>
+where
+    P: Pixel<Chan = Ch64>,
+{
+    /// Get internal pixel data as boxed slice of *f64*.
+    fn from(raster: Raster<P>) -> Self {
+        // constructors checked that the byte count fits in `isize`
+        let capacity = raster.bytes() / 8;
+        let slice = Box::<[P]>::into_raw(raster.pixels);
+        let buffer: Box<[f64]> = unsafe {
+            let ptr = (*slice).as_mut_ptr() as *mut f64;
+            Box::from_raw(slice_from_raw_parts_mut(ptr, capacity))
         };
         buffer
     }
@@ -158,6 +483,20 @@ where
 impl<P: Pixel> Raster<P> {
     /// Construct a `Raster` with all pixels set to the default value.
     ///
+    /// The default pixel has every channel set to
+    /// [Channel::MIN](chan/trait.Channel.html#associatedconstant.MIN).  What
+    /// that means depends on the color model:
+    ///
+    /// * Formats with *alpha* (including `Matte`): fully transparent
+    /// * `Rgb`, `Bgr`, `Gray`, `Hsv`, `Hsl`, `Oklab`, `Oklch`, `Xyz`: opaque
+    ///   black
+    /// * `Hwb`: opaque red (zero *whiteness* and *blackness*)
+    /// * `Cmy`: opaque white
+    /// * `YCbCr`: opaque green (zero *chroma* is not neutral)
+    ///
+    /// Use [with_transparent](#method.with_transparent) or
+    /// [with_opaque](#method.with_opaque) to be explicit.
+    ///
     /// # Panics
     ///
     /// Panics if `width` or `height` is greater than `std::i32::MAX`.
@@ -178,6 +517,94 @@ impl<P: Pixel> Raster<P> {
         Self::with_color(width, height, P::default())
     }
 
+    /// Construct a fully transparent `Raster`.
+    ///
+    /// All channels, including *alpha*, are set to
+    /// [Channel::MIN](chan/trait.Channel.html#associatedconstant.MIN).
+    /// `P` must have an *alpha* channel; see
+    /// [AlphaPixel](el/trait.AlphaPixel.html).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is greater than `std::i32::MAX`.
+    ///
+    /// ## Example
+    /// ```
+    /// use pix::chan::{Ch8, Channel};
+    /// use pix::el::Pixel;
+    /// use pix::rgb::SRgba8;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::<SRgba8>::with_transparent(10, 10);
+    /// assert_eq!(r.pixel(0, 0).alpha(), Ch8::MIN);
+    /// ```
+    ///
+    /// Pixel formats without *alpha* are rejected at compile time:
+    /// ```compile_fail
+    /// use pix::rgb::SRgb8;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::<SRgb8>::with_transparent(10, 10);
+    /// ```
+    pub fn with_transparent(width: u32, height: u32) -> Self
+    where
+        P: AlphaPixel,
+    {
+        Self::with_color(width, height, P::default())
+    }
+
+    /// Construct an opaque `Raster` with all pixels set to one color.
+    ///
+    /// *Alpha* (if any) is set to
+    /// [Channel::MAX](chan/trait.Channel.html#associatedconstant.MAX),
+    /// regardless of `clr`.  Other channels are used as-is, so for
+    /// *premultiplied* formats the color is as if composited over black.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is greater than `std::i32::MAX`.
+    ///
+    /// ## Example
+    /// ```
+    /// use pix::chan::{Ch8, Channel};
+    /// use pix::el::Pixel;
+    /// use pix::rgb::Rgba8p;
+    /// use pix::Raster;
+    ///
+    /// let clr = Rgba8p::new(0x20, 0x40, 0x60, 0x80);
+    /// let r = Raster::<Rgba8p>::with_opaque(10, 10, clr);
+    /// assert_eq!(r.pixel(0, 0), Rgba8p::new(0x20, 0x40, 0x60, 0xFF));
+    /// ```
+    pub fn with_opaque(width: u32, height: u32, clr: P) -> Self {
+        let mut clr = clr;
+        if P::Model::ALPHA < P::CHANNELS {
+            *clr.alpha_mut() = P::Chan::MAX;
+        }
+        Self::with_color(width, height, clr)
+    }
+
+    /// Construct a `Raster` filled with deterministic noise.
+    ///
+    /// See [fill_noise](#method.fill_noise) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is greater than `std::i32::MAX`.
+    ///
+    /// ## Example
+    /// ```
+    /// use pix::rgb::SRgba8;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::<SRgba8>::with_noise(32, 32, 1);
+    /// assert_ne!(r.pixel(0, 0), r.pixel(1, 0));
+    /// ```
+    pub fn with_noise(width: u32, height: u32, seed: u64) -> Self {
+        let mut r = Self::with_clear(width, height);
+        r.fill_noise((), seed);
+        r
+    }
+
     /// Construct a `Raster` with all pixels set to one color.
     ///
     /// # Panics
@@ -193,9 +620,8 @@ impl<P: Pixel> Raster<P> {
     /// let r = Raster::<SRgb8>::with_color(15, 15, clr);
     /// ```
     pub fn with_color(width: u32, height: u32, clr: P) -> Self {
-        let width = i32::try_from(width).expect(WIDTH_TOO_BIG);
-        let height = i32::try_from(height).expect(HEIGHT_TOO_BIG);
-        let len = (width * height) as usize;
+        let (width, height) = raster_size(width, height);
+        let len = raster_len::<P>(width, height);
         let pixels = vec![clr; len].into_boxed_slice();
         Raster {
             width,
@@ -204,6 +630,28 @@ impl<P: Pixel> Raster<P> {
         }
     }
 
+    /// Construct a `Raster` with all pixels set to a color of any format.
+    ///
+    /// The color is converted to `P` once, so this is as fast as
+    /// [with_color](#method.with_color).  Since `P` cannot be inferred from
+    /// the color, it must be specified.
+    ///
+    /// ### Make a translucent raster from an opaque color
+    /// ```
+    /// use pix::rgb::{SRgb8, SRgba8};
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::<SRgba8>::with_color_from(4, 4, SRgb8::new(1, 2, 3));
+    /// assert_eq!(r.pixel(0, 0), SRgba8::new(1, 2, 3, 0xFF));
+    /// ```
+    pub fn with_color_from<C>(width: u32, height: u32, clr: C) -> Self
+    where
+        C: Pixel,
+        P::Chan: From<C::Chan>,
+    {
+        Self::with_color(width, height, clr.convert())
+    }
+
     /// Construct a `Raster` with another `Raster`.
     ///
     /// The pixel format can be converted using this method.
@@ -219,18 +667,142 @@ impl<P: Pixel> Raster<P> {
     /// // load pixels into raster
     /// let r1 = Raster::<Rgba16>::with_raster(&r0);
     /// ```
+    ///
+    /// When `S` and `P` are the same format, the pixel data is cloned
+    /// directly.  If only the bit depth differs, pixels are converted without
+    /// *alpha* / *gamma* processing.  Conversions between `Matte` and `Gray`
+    /// with *alpha* copy the *alpha* channel directly, rather than going
+    /// through RGBA.
     pub fn with_raster<S>(src: &Raster<S>) -> Self
     where
         S: Pixel,
         P::Chan: From<S::Chan>,
     {
+        if let Some(r) = (src as &dyn Any).downcast_ref::<Raster<P>>() {
+            return r.clone();
+        }
         let mut r = Raster::with_clear(src.width(), src.height());
-        let srows = src.rows(());
-        let drows = r.rows_mut(());
-        for (drow, srow) in drows.zip(srows) {
-            for (d, s) in drow.iter_mut().zip(srow) {
-                *d = s.convert();
-            }
+        convert_slice(&mut r.pixels, &src.pixels);
+        r
+    }
+
+    /// Convert to another pixel format, with dithering.
+    ///
+    /// When reducing to an integer channel type, an offset is added to color
+    /// channels just before rounding, which reduces banding in smooth
+    /// gradients.  The offset is applied in the destination *gamma* space, so
+    /// it is always less than one destination step.  *Alpha* is not
+    /// dithered.
+    ///
+    /// * `D` `Pixel` format of destination `Raster`.
+    /// * `dither` Dithering mode.
+    ///
+    /// ### Convert from Rgba32 to SRgba8
+    /// ```
+    /// use pix::rgb::{Rgba32, SRgba8};
+    /// use pix::{DitherMode, Raster};
+    ///
+    /// let r0 = Raster::<Rgba32>::with_clear(50, 50);
+    /// let r1 = r0.convert_dithered::<SRgba8>(DitherMode::Ordered);
+    /// ```
+    pub fn convert_dithered<D>(&self, dither: DitherMode) -> Raster<D>
+    where
+        D: Pixel,
+        D::Chan: From<P::Chan>,
+        Ch32: From<P::Chan>,
+    {
+        if dither == DitherMode::None || D::Chan::INTEGER_BITS == 0 {
+            return Raster::with_raster(self);
+        }
+        let mut r = Raster::with_clear(self.width(), self.height());
+        let width = self.width as usize;
+        for (i, (d, s)) in
+            r.pixels.iter_mut().zip(self.pixels.iter()).enumerate()
+        {
+            let (x, y) = (i % width, i / width);
+            *d = dither_pixel(*s, BAYER_4X4[y & 3][x & 3]);
+        }
+        r
+    }
+
+    /// Convert to a pixel format without an *alpha* channel.
+    ///
+    /// * `D` `Pixel` format of destination `Raster`.  It must have the same
+    ///   color model, channel type and gamma as `P`, without *alpha*.
+    ///
+    /// This is faster than [with_raster](#method.with_raster), since
+    /// channels are copied directly.  Premultiplied channels are taken as-is
+    /// (not divided by *alpha*), which is the same as compositing over black.
+    ///
+    /// # Panics
+    ///
+    /// If `P` does not have *alpha* as its last channel, or if `D` does not
+    /// have the other channels of `P`.
+    ///
+    /// ### Drop *alpha*
+    /// ```
+    /// use pix::rgb::{SRgb8, SRgba8};
+    /// use pix::Raster;
+    ///
+    /// let clr = SRgba8::new(0x10, 0x20, 0x30, 0x40);
+    /// let r0 = Raster::with_color(4, 4, clr);
+    /// let r1 = r0.drop_alpha::<SRgb8>();
+    /// assert_eq!(r1.pixel(0, 0), SRgb8::new(0x10, 0x20, 0x30));
+    /// ```
+    pub fn drop_alpha<D>(&self) -> Raster<D>
+    where
+        D: Pixel<Chan = P::Chan, Model = P::Model, Gamma = P::Gamma>,
+    {
+        assert!(
+            D::CHANNELS + 1 == P::CHANNELS && P::Model::ALPHA == D::CHANNELS,
+            "Pixel formats must differ only by alpha channel"
+        );
+        let mut r = Raster::<D>::with_clear(self.width(), self.height());
+        for (d, s) in r.pixels.iter_mut().zip(self.pixels.iter()) {
+            d.channels_mut()
+                .copy_from_slice(&s.channels()[..D::CHANNELS]);
+        }
+        r
+    }
+
+    /// Convert to a pixel format with an opaque *alpha* channel.
+    ///
+    /// * `D` `Pixel` format of destination `Raster`.  It must have the same
+    ///   color model, channel type and gamma as `P`, with *alpha* added.
+    ///
+    /// This is faster than [with_raster](#method.with_raster), since
+    /// channels are copied directly.  *Alpha* is set to
+    /// [Channel::MAX](chan/trait.Channel.html#associatedconstant.MAX), so
+    /// straight and premultiplied channels are the same.
+    ///
+    /// # Panics
+    ///
+    /// If `D` does not have *alpha* as its last channel, or if `P` does not
+    /// have the other channels of `D`.
+    ///
+    /// ### Add opaque *alpha*
+    /// ```
+    /// use pix::rgb::{Rgb16, Rgba16p};
+    /// use pix::Raster;
+    ///
+    /// let r0 = Raster::with_color(4, 4, Rgb16::new(0x1000, 0x2000, 0x3000));
+    /// let r1 = r0.with_opaque_alpha::<Rgba16p>();
+    /// let clr = Rgba16p::new(0x1000, 0x2000, 0x3000, 0xFFFF);
+    /// assert_eq!(r1.pixel(0, 0), clr);
+    /// ```
+    pub fn with_opaque_alpha<D>(&self) -> Raster<D>
+    where
+        D: Pixel<Chan = P::Chan, Model = P::Model, Gamma = P::Gamma>,
+    {
+        assert!(
+            P::CHANNELS + 1 == D::CHANNELS && D::Model::ALPHA == P::CHANNELS,
+            "Pixel formats must differ only by alpha channel"
+        );
+        let mut r = Raster::<D>::with_clear(self.width(), self.height());
+        for (d, s) in r.pixels.iter_mut().zip(self.pixels.iter()) {
+            let chan = d.channels_mut();
+            chan[..P::CHANNELS].copy_from_slice(s.channels());
+            chan[P::CHANNELS] = P::Chan::MAX;
         }
         r
     }
@@ -265,10 +837,8 @@ impl<P: Pixel> Raster<P> {
     where
         B: Into<Box<[P]>>,
     {
-        let width = i32::try_from(width).expect(WIDTH_TOO_BIG);
-        let height = i32::try_from(height).expect(HEIGHT_TOO_BIG);
-        let len = usize::try_from(width.checked_mul(height).expect(TOO_BIG))
-            .expect(TOO_BIG);
+        let (width, height) = raster_size(width, height);
+        let len = raster_len::<P>(width, height);
         let pixels = pixels.into();
         assert_eq!(len, pixels.len());
         Raster {
@@ -278,6 +848,48 @@ impl<P: Pixel> Raster<P> {
         }
     }
 
+    /// Construct a `Raster` from an `Iterator` of rows.
+    ///
+    /// * `I` Row `Iterator`.
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `rows` Rows of pixels, such as `Vec<P>` or `&[P]`.
+    ///
+    /// Returns an error if any row length is not equal to `width`, or the
+    /// number of rows is not equal to `height`.  For pushing rows one at a
+    /// time, use [RasterBuilder].
+    ///
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    ///
+    /// ### Construct from rows
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::Raster;
+    ///
+    /// let rows = (0..4).map(|y| vec![Gray8::new(y); 5]);
+    /// let r = Raster::with_rows(5, 4, rows)?;
+    /// assert_eq!(r.pixel(4, 3), Gray8::new(3));
+    /// # Ok::<(), pix::RasterError>(())
+    /// ```
+    /// [RasterBuilder]: struct.RasterBuilder.html
+    pub fn with_rows<I, R>(
+        width: u32,
+        height: u32,
+        rows: I,
+    ) -> Result<Self, RasterError>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[P]>,
+    {
+        let mut builder = RasterBuilder::new(width, height);
+        for row in rows {
+            builder.push_row(row.as_ref())?;
+        }
+        builder.finish()
+    }
+
     /// Construct a `Raster` from a `u8` buffer.
     ///
     /// * `B` Owned pixed type (`Vec` or boxed slice).
@@ -295,21 +907,18 @@ impl<P: Pixel> Raster<P> {
         B: Into<Box<[u8]>>,
         P: Pixel<Chan = Ch8>,
     {
-        let width = i32::try_from(width).expect(WIDTH_TOO_BIG);
-        let height = i32::try_from(height).expect(HEIGHT_TOO_BIG);
-        let len = usize::try_from(width.checked_mul(height).expect(TOO_BIG))
-            .expect(TOO_BIG);
-        assert!(len > 0);
+        let (width, height) = raster_size(width, height);
+        let len = raster_len::<P>(width, height);
         let buffer: Box<[u8]> = buffer.into();
         let capacity = buffer.len();
         assert_eq!(
-            len * std::mem::size_of::<P>(),
-            capacity * std::mem::size_of::<u8>()
+            len * core::mem::size_of::<P>(),
+            capacity * core::mem::size_of::<u8>()
         );
         let slice = Box::<[u8]>::into_raw(buffer);
         let pixels: Box<[P]> = unsafe {
             let ptr = (*slice).as_mut_ptr() as *mut P;
-            Box::from_raw(from_raw_parts_mut(ptr, len))
+            Box::from_raw(slice_from_raw_parts_mut(ptr, len))
         };
         Raster {
             width,
@@ -335,21 +944,18 @@ impl<P: Pixel> Raster<P> {
         B: Into<Box<[u16]>>,
         P: Pixel<Chan = Ch16>,
     {
-        let width = i32::try_from(width).expect(WIDTH_TOO_BIG);
-        let height = i32::try_from(height).expect(HEIGHT_TOO_BIG);
-        let len = usize::try_from(width.checked_mul(height).expect(TOO_BIG))
-            .expect(TOO_BIG);
-        assert!(len > 0);
+        let (width, height) = raster_size(width, height);
+        let len = raster_len::<P>(width, height);
         let buffer: Box<[u16]> = buffer.into();
         let capacity = buffer.len();
         assert_eq!(
-            len * std::mem::size_of::<P>(),
-            capacity * std::mem::size_of::<u16>()
+            len * core::mem::size_of::<P>(),
+            capacity * core::mem::size_of::<u16>()
         );
         let slice = Box::<[u16]>::into_raw(buffer);
         let pixels: Box<[P]> = unsafe {
             let ptr = (*slice).as_mut_ptr() as *mut P;
-            Box::from_raw(from_raw_parts_mut(ptr, len))
+            Box::from_raw(slice_from_raw_parts_mut(ptr, len))
         };
         Raster {
             width,
@@ -358,753 +964,6876 @@ impl<P: Pixel> Raster<P> {
         }
     }
 
-    /// Get width of `Raster`.
-    pub fn width(&self) -> u32 {
-        self.width as u32
-    }
-
-    /// Get height of `Raster`.
-    pub fn height(&self) -> u32 {
-        self.height as u32
-    }
-
-    /// Clear all pixels to default value.
-    pub fn clear(&mut self) {
-        for p in self.pixels.iter_mut() {
-            *p = P::default();
-        }
-    }
-
-    /// Get one pixel.
-    pub fn pixel(&self, x: i32, y: i32) -> P {
-        assert!(x >= 0 && x < self.width);
-        assert!(y >= 0 && y < self.height);
-        let i = (self.width * y + x) as usize;
-        self.pixels[i]
-    }
-
-    /// Get a mutable pixel.
-    pub fn pixel_mut(&mut self, x: i32, y: i32) -> &mut P {
-        assert!(x >= 0 && x < self.width);
-        assert!(y >= 0 && y < self.height);
-        let i = (self.width * y + x) as usize;
-        &mut self.pixels[i]
-    }
-
-    /// Get a slice of all pixels.
-    pub fn pixels(&self) -> &[P] {
-        &self.pixels
-    }
-
-    /// Get a mutable slice of all pixels.
-    pub fn pixels_mut(&mut self) -> &mut [P] {
-        &mut self.pixels
+    /// Construct a `Raster` from an `f64` buffer.
+    ///
+    /// * `B` Owned pixed type (`Vec` or boxed slice).
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `buffer` Buffer of pixel data.
+    ///
+    /// Values are clamped to the range 0 to 1, with `NaN` becoming 0.
+    ///
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    /// * If `buffer` length is not equal to `width` * `height` *
+    ///   `std::mem::size_of::<P>()`
+    pub fn with_f64_buffer<B>(width: u32, height: u32, buffer: B) -> Self
+    where
+        B: Into<Box<[f64]>>,
+        P: Pixel<Chan = Ch64>,
+    {
+        let (width, height) = raster_size(width, height);
+        let len = raster_len::<P>(width, height);
+        let mut buffer: Box<[f64]> = buffer.into();
+        let capacity = buffer.len();
+        assert_eq!(
+            len * core::mem::size_of::<P>(),
+            capacity * core::mem::size_of::<f64>()
+        );
+        for v in buffer.iter_mut() {
+            *v = Ch64::new(*v).into();
+        }
+        let slice = Box::<[f64]>::into_raw(buffer);
+        let pixels: Box<[P]> = unsafe {
+            let ptr = (*slice).as_mut_ptr() as *mut P;
+            Box::from_raw(slice_from_raw_parts_mut(ptr, len))
+        };
+        Raster {
+            width,
+            height,
+            pixels,
+        }
     }
 
-    /// Get an `Iterator` of rows within a `Raster`.
+    /// Construct a `Raster` from a slice of `f32` channel values.
     ///
-    /// * `reg` Region of the Raster to iterate.
-    pub fn rows<R>(&self, reg: R) -> Rows<P>
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `data` Channel values, in pixel order.
+    ///
+    /// Values are clamped to the range 0 to 1, with `NaN` becoming 0.  To
+    /// find out whether any values were clamped, use
+    /// [from_f32_slice_clamping](#method.from_f32_slice_clamping).
+    ///
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    /// * If `data` length is not equal to `width` * `height` *
+    ///   `P::CHANNELS`
+    pub fn from_f32_slice(width: u32, height: u32, data: &[f32]) -> Self
     where
-        R: Into<Region>,
+        P: Pixel<Chan = Ch32>,
     {
-        Rows::new(self, self.intersection(reg.into()))
+        Self::from_f32_slice_clamping(width, height, data).0
     }
 
-    /// Get an `Iterator` of mutable rows within a `Raster`.
+    /// Construct a `Raster` from a slice of `f32` channel values, counting
+    /// clamped values.
     ///
-    /// * `reg` Region of the Raster to iterate.
-    pub fn rows_mut<R>(&mut self, reg: R) -> RowsMut<P>
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `data` Channel values, in pixel order.
+    ///
+    /// Values are clamped to the range 0 to 1, with `NaN` becoming 0.  The
+    /// returned [ClipStats](struct.ClipStats.html) count values which were
+    /// out of range, for detecting clipping in HDR data.
+    ///
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    /// * If `data` length is not equal to `width` * `height` *
+    ///   `P::CHANNELS`
+    ///
+    /// ### Detect clipping
+    /// ```
+    /// use pix::rgb::Rgb32;
+    /// use pix::Raster;
+    ///
+    /// let data = [0.5, 1.5, -0.25, 1.0, 0.0, 2.0];
+    /// let (r, clip) = Raster::<Rgb32>::from_f32_slice_clamping(2, 1, &data);
+    /// assert_eq!(r.pixel(0, 0), Rgb32::new(0.5, 1.0, 0.0));
+    /// assert_eq!((clip.below, clip.above), (1, 2));
+    /// ```
+    pub fn from_f32_slice_clamping(
+        width: u32,
+        height: u32,
+        data: &[f32],
+    ) -> (Self, ClipStats)
     where
-        R: Into<Region>,
+        P: Pixel<Chan = Ch32>,
     {
-        RowsMut::new(self, self.intersection(reg.into()))
+        let mut r = Self::with_clear(width, height);
+        assert_eq!(r.pixels.len() * P::CHANNELS, data.len());
+        let mut clip = ClipStats::default();
+        let chans = r.pixels.iter_mut().flat_map(|p| p.channels_mut());
+        for (c, v) in chans.zip(data) {
+            if v.is_nan() {
+                clip.nan += 1;
+            } else if *v < 0.0 {
+                clip.below += 1;
+            } else if *v > 1.0 {
+                clip.above += 1;
+            }
+            *c = Ch32::new(*v);
+        }
+        (r, clip)
     }
 
-    /// Get `Region` of entire `Raster`.
-    pub fn region(&self) -> Region {
-        Region::new(0, 0, self.width(), self.height())
+    /// Construct a `Raster` from big-endian bytes.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `bytes` Pixel data, with each channel in big-endian byte order (as
+    ///   used by 16-bit PNG).
+    ///
+    /// For 8-bit channels, this is a plain copy.
+    ///
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    /// * If `bytes` length is not equal to `width` * `height` *
+    ///   `std::mem::size_of::<P>()`
+    ///
+    /// ### Read 16-bit PNG data
+    /// ```
+    /// use pix::gray::Gray16;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::<Gray16>::from_be_bytes(2, 1, &[0x12, 0x34, 0, 1]);
+    /// assert_eq!(r.pixel(0, 0), Gray16::new(0x1234));
+    /// assert_eq!(r.pixel(1, 0), Gray16::new(0x0001));
+    /// ```
+    pub fn from_be_bytes(width: u32, height: u32, bytes: &[u8]) -> Self {
+        Self::with_endian_bytes(
+            width,
+            height,
+            bytes,
+            cfg!(target_endian = "little"),
+        )
     }
 
-    /// Get intersection with a `Region`.
-    pub fn intersection<R>(&self, reg: R) -> Region
-    where
-        R: Into<Region>,
-    {
-        let reg = reg.into();
-        reg.intersection(self.region())
+    /// Construct a `Raster` from little-endian bytes.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `bytes` Pixel data, with each channel in little-endian byte order.
+    ///
+    /// For 8-bit channels, this is a plain copy.
+    ///
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    /// * If `bytes` length is not equal to `width` * `height` *
+    ///   `std::mem::size_of::<P>()`
+    pub fn from_le_bytes(width: u32, height: u32, bytes: &[u8]) -> Self {
+        Self::with_endian_bytes(
+            width,
+            height,
+            bytes,
+            cfg!(target_endian = "big"),
+        )
     }
 
-    /// Copy a color to a region of the `Raster`.
+    /// Construct a `Raster` from bytes, swapping channel byte order if needed
+    fn with_endian_bytes(
+        width: u32,
+        height: u32,
+        bytes: &[u8],
+        swap: bool,
+    ) -> Self {
+        let mut raster = Raster::with_clear(width, height);
+        let buf = raster.as_u8_slice_mut();
+        assert_eq!(buf.len(), bytes.len());
+        buf.copy_from_slice(bytes);
+        if swap {
+            swap_chan_bytes::<P::Chan>(buf);
+        }
+        raster
+    }
+
+    /// Construct a `Raster` by interleaving separate channel planes.
     ///
-    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
-    ///         (*x*, *y*, *width*, *height*) or the unit type `()`.  Using
-    ///         `()` has the same result as `Raster::region()`.
-    /// * `clr` Source `Pixel` color.
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `planes` One slice per channel, each containing `width` * `height`
+    ///   values.
     ///
-    /// ### Copy a color to a rectangle region
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    /// * If number of `planes` is not equal to the pixel channel count
+    /// * If any plane length is not equal to `width` * `height`
+    ///
+    /// ## Example
     /// ```
-    /// use pix::rgb::SRgb8;
+    /// use pix::rgb::Rgba8;
     /// use pix::Raster;
     ///
-    /// let mut r = Raster::with_clear(100, 100);
-    /// let clr = SRgb8::new(0xDD, 0x96, 0x70);
-    /// r.copy_color((20, 40, 25, 50), clr);
+    /// let r0 = Raster::with_color(4, 4, Rgba8::new(0x20, 0x40, 0x60, 0xFF));
+    /// let planes = r0.to_planes();
+    /// let planes: Vec<_> = planes.iter().map(|p| &p[..]).collect();
+    /// let r1 = Raster::<Rgba8>::from_planes(4, 4, &planes);
+    /// assert_eq!(r0.pixels(), r1.pixels());
     /// ```
-    pub fn copy_color<R>(&mut self, reg: R, clr: P)
-    where
-        R: Into<Region>,
-    {
-        let reg = self.intersection(reg.into());
-        let width = reg.width();
-        let height = reg.height();
-        if width > 0 && height > 0 {
-            let drows = self.rows_mut(reg);
-            for drow in drows {
-                P::copy_color(drow, &clr);
+    pub fn from_planes(width: u32, height: u32, planes: &[&[P::Chan]]) -> Self {
+        let mut r = Self::with_clear(width, height);
+        let len = r.pixels.len();
+        assert_eq!(planes.len(), P::CHANNELS);
+        for (i, plane) in planes.iter().enumerate() {
+            assert_eq!(len, plane.len());
+            for (p, c) in r.pixels.iter_mut().zip(plane.iter()) {
+                p.channels_mut()[i] = *c;
             }
         }
+        r
     }
 
-    /// Copy from a source `Raster`.
+    /// Reinterpret as another pixel format, reusing the pixel buffer.
     ///
-    /// * `to` Region within `self` (destination).
-    /// * `src` Source `Raster`.
-    /// * `from` Region within source `Raster`.
+    /// No conversion is done; channel values are unchanged.  This is useful
+    /// when pixel data has been mislabeled, for example as linear when it
+    /// is actually sRGB encoded.  To convert pixels, use
+    /// [with_raster](#method.with_raster) instead.
     ///
-    /// `to` / `from` can be `Region` structs, tuples of (*x*, *y*, *width*,
-    /// *height*) or the unit type `()`.  Using `()` has the same result as
-    /// `Raster::region()`.
+    /// * `D` `Pixel` format of resulting `Raster`.  It must have the same
+    ///   channel type as `P`.
     ///
-    /// ```bob
-    /// *------------+      *-------------+
-    /// |            |      |    *------+ |
-    /// | *------+   |      |    |      | |
-    /// | |      |   |      |    | from | |
-    /// | |  to  |   | <--- |    +------+ |
-    /// | +------+   |      |             |
-    /// |            |      |     src     |
-    /// |    self    |      +-------------+
-    /// +------------+
-    /// ```
-    /// The copied `Region` is clamped to the smaller of `to` and `from` in
-    /// both `X` and `Y` dimensions.  Also, `to` and `from` are clipped to
-    /// their respective `Raster` dimensions.
+    /// # Panics
     ///
-    /// ### Copy part of one `Raster` to another
+    /// At runtime, if `D` and `P` have a different number of channels.
+    ///
+    /// ### Relabel linear as sRGB
     /// ```
-    /// use pix::rgb::SRgb8;
+    /// use pix::rgb::{Rgba8, SRgba8};
     /// use pix::Raster;
     ///
-    /// let mut r0 = Raster::with_clear(100, 100);
-    /// let r1 = Raster::with_color(5, 5, SRgb8::new(80, 0, 80));
-    /// // ... load image data
-    /// r0.copy_raster((40, 40, 5, 5), &r1, ());
+    /// let r0 = Raster::with_color(4, 4, Rgba8::new(0x80, 0x40, 0x20, 0xFF));
+    /// let r1 = r0.reinterpret::<SRgba8>();
+    /// assert_eq!(r1.pixel(0, 0), SRgba8::new(0x80, 0x40, 0x20, 0xFF));
     /// ```
-    pub fn copy_raster<R0, R1>(&mut self, to: R0, src: &Raster<P>, from: R1)
+    pub fn reinterpret<D>(self) -> Raster<D>
     where
-        R0: Into<Region>,
-        R1: Into<Region>,
+        D: Pixel<Chan = P::Chan>,
     {
-        let (to, from) = self.clip_regions(to, src, from);
-        let srows = src.rows(from);
-        let drows = self.rows_mut(to);
-        for (drow, srow) in drows.zip(srows) {
-            P::copy_slice(drow, srow);
+        assert_eq!(core::mem::size_of::<P>(), core::mem::size_of::<D>());
+        let len = self.pixels.len();
+        let slice = Box::<[P]>::into_raw(self.pixels);
+        let pixels: Box<[D]> = unsafe {
+            let ptr = (*slice).as_mut_ptr() as *mut D;
+            Box::from_raw(slice_from_raw_parts_mut(ptr, len))
+        };
+        Raster {
+            width: self.width,
+            height: self.height,
+            pixels,
         }
     }
 
-    /// Clip `to` / `from` regions for source / destination rasters
-    fn clip_regions<R0, R1, Q>(
-        &self,
-        to: R0,
-        src: &Raster<Q>,
-        from: R1,
-    ) -> (Region, Region)
-    where
-        R0: Into<Region>,
-        R1: Into<Region>,
-        Q: Pixel,
-    {
-        let (to, from) = (to.into(), from.into());
-        let (tx, ty) = (to.x.min(0).abs(), to.y.min(0).abs());
-        let (fx, fy) = (from.x.min(0).abs(), from.y.min(0).abs());
-        let to = self.intersection(to);
-        let from = src.intersection(from);
-        let width = to.width().min(from.width());
-        let height = to.height().min(from.height());
-        let to = Region::new(to.x + fx, to.y + fy, width, height);
-        let from = Region::new(from.x + tx, from.y + ty, width, height);
-        (to, from)
-    }
-
-    /// Get view of pixels as a `u8` slice.
-    pub fn as_u8_slice(&self) -> &[u8] {
-        unsafe {
-            let (prefix, v, suffix) = self.pixels.align_to::<u8>();
-            debug_assert!(prefix.is_empty());
-            debug_assert!(suffix.is_empty());
-            v
+    /// Encode linear channels with the sRGB gamma formula, in place.
+    ///
+    /// **Warning**: the `Gamma` type parameter is *not* changed, so it no
+    /// longer describes the data.  This is only useful when downstream code
+    /// requires a fixed pixel format.  To relabel the raster, use
+    /// [reinterpret](#method.reinterpret) afterwards; to convert pixels
+    /// between gamma modes, use [with_raster](#method.with_raster) instead.
+    ///
+    /// Linear channels are encoded regardless of the `Gamma` tag.  *Alpha*
+    /// and circular (*hue*) channels are not changed.  Premultiplied
+    /// channels are encoded as-is, without dividing by *alpha*.
+    ///
+    /// ### Encode a linear raster, keeping its type
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::rgb::Rgb32;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_color(4, 4, Rgb32::new(0.0, 0.5, 1.0));
+    /// r.encode_srgb_in_place();
+    /// let p = r.pixel(0, 0);
+    /// assert!(p.approx_eq(Rgb32::new(0.0, 0.7354, 1.0), 0.001));
+    /// ```
+    pub fn encode_srgb_in_place(&mut self) {
+        for p in self.pixels.iter_mut() {
+            let chan = &mut p.channels_mut()[P::Model::LINEAR];
+            for c in chan.iter_mut() {
+                *c = c.encode_srgb();
+            }
         }
     }
 
-    /// Get view of pixels as a mutable `u8` slice.
-    pub fn as_u8_slice_mut(&mut self) -> &mut [u8] {
-        unsafe {
-            let (prefix, v, suffix) = self.pixels.align_to_mut::<u8>();
-            debug_assert!(prefix.is_empty());
-            debug_assert!(suffix.is_empty());
-            v
+    /// Decode linear channels with the sRGB gamma formula, in place.
+    ///
+    /// **Warning**: the `Gamma` type parameter is *not* changed, so it no
+    /// longer describes the data.  This is the inverse of
+    /// [encode_srgb_in_place](#method.encode_srgb_in_place).
+    ///
+    /// Linear channels are decoded regardless of the `Gamma` tag.  *Alpha*
+    /// and circular (*hue*) channels are not changed.
+    ///
+    /// ### Decode an sRGB raster, keeping its type
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::rgb::SRgb32;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_color(4, 4, SRgb32::new(0.0, 0.7354, 1.0));
+    /// r.decode_srgb_in_place();
+    /// let p = r.pixel(0, 0);
+    /// assert!(p.approx_eq(SRgb32::new(0.0, 0.5, 1.0), 0.001));
+    /// ```
+    pub fn decode_srgb_in_place(&mut self) {
+        for p in self.pixels.iter_mut() {
+            let chan = &mut p.channels_mut()[P::Model::LINEAR];
+            for c in chan.iter_mut() {
+                *c = c.decode_srgb();
+            }
         }
     }
-}
 
-impl<P> Raster<P>
-where
-    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
-{
-    /// Composite a source color to a region of the `Raster`.
+    /// Premultiply by *alpha*, reusing the pixel buffer.
     ///
-    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
-    ///         (*x*, *y*, *width*, *height*) or the unit type `()`.  Using
-    ///         `()` has the same result as `Raster::region()`.
-    /// * `clr` Source `Pixel` color.
-    /// * `op` Compositing operation.
+    /// This gives the same result as [with_raster](#method.with_raster),
+    /// without allocating a second `Raster`.  Linear channels are multiplied
+    /// by *alpha* (in linear light for sRGB gamma); circular (*hue*) channels
+    /// are not changed.
     ///
-    /// ### Example
+    /// * `D` `Pixel` format of resulting `Raster`.  It must be the same as
+    ///   `P`, but with [Premultiplied](chan/struct.Premultiplied.html) alpha.
+    ///
+    /// ### Premultiply a raster
     /// ```
-    /// use pix::ops::SrcOver;
-    /// use pix::bgr::Bgra8p;
+    /// use pix::rgb::{Rgba8, Rgba8p};
     /// use pix::Raster;
     ///
-    /// let mut r = Raster::with_color(100, 100, Bgra8p::new(99, 0, 99, 255));
-    /// let clr = Bgra8p::new(200, 200, 0, 128);
-    /// r.composite_color((20, 40, 25, 50), clr, SrcOver);
+    /// let r = Raster::with_color(4, 4, Rgba8::new(0xFF, 0x80, 0x40, 0x80));
+    /// let r = r.premultiply_in_place::<Rgba8p>();
+    /// assert_eq!(r.pixel(0, 0), Rgba8p::new(0x80, 0x40, 0x20, 0x80));
     /// ```
-    pub fn composite_color<R, O>(&mut self, reg: R, clr: P, op: O)
+    pub fn premultiply_in_place<D>(mut self) -> Raster<D>
     where
-        R: Into<Region>,
-        O: Blend,
+        P: Pixel<Alpha = Straight>,
+        D: Pixel<
+            Chan = P::Chan,
+            Model = P::Model,
+            Alpha = Premultiplied,
+            Gamma = P::Gamma,
+        >,
     {
-        let reg = self.intersection(reg.into());
-        let width = reg.width();
-        let height = reg.height();
-        if width > 0 && height > 0 {
-            let drows = self.rows_mut(reg);
-            for drow in drows {
-                P::composite_color(drow, &clr, op);
+        for p in self.pixels.iter_mut() {
+            let alpha = p.alpha();
+            for c in p.channels_mut()[P::Model::LINEAR].iter_mut() {
+                *c = convert_channel::<
+                    Straight,
+                    P::Gamma,
+                    Premultiplied,
+                    P::Gamma,
+                    _,
+                >(*c, alpha, alpha);
             }
         }
+        self.reinterpret()
     }
 
-    /// Composite from a matte `Raster` and color.
+    /// Divide premultiplied channels by *alpha*, reusing the pixel buffer.
     ///
-    /// * `to` Region within `self` (destination).
-    /// * `src` Source `Raster` matte.
-    /// * `from` Region within source `Raster`.
-    /// * `clr` Color to apply to the matte.
-    /// * `op` Compositing operation.
+    /// This is the inverse of
+    /// [premultiply_in_place](#method.premultiply_in_place), and gives the
+    /// same result as [with_raster](#method.with_raster).
     ///
-    /// `to` / `from` can be `Region` structs, tuples of (*x*, *y*, *width*,
-    /// *height*) or the unit type `()`.  Using `()` has the same result as
-    /// `Raster::region()`.
+    /// * `D` `Pixel` format of resulting `Raster`.  It must be the same as
+    ///   `P`, but with [Straight](chan/struct.Straight.html) alpha.
     ///
-    /// ### Example
+    /// ### Unpremultiply a raster
     /// ```
-    /// use pix::matte::Matte8;
-    /// use pix::ops::SrcOver;
-    /// use pix::rgb::Rgba8p;
+    /// use pix::rgb::{Rgba8, Rgba8p};
     /// use pix::Raster;
     ///
-    /// let mut r0 = Raster::with_clear(100, 100);
-    /// let r1 = Raster::with_color(10, 10, Matte8::new(37));
-    /// // ... load image data
-    /// let clr = Rgba8p::new(50, 100, 150, 200);
-    /// r0.composite_matte((30, 50, 10, 10), &r1, (), clr, SrcOver);
+    /// let r = Raster::with_color(4, 4, Rgba8p::new(0x80, 0x40, 0x20, 0x80));
+    /// let r = r.unpremultiply_in_place::<Rgba8>();
+    /// assert_eq!(r.pixel(0, 0), Rgba8::new(0xFF, 0x80, 0x40, 0x80));
     /// ```
-    pub fn composite_matte<R0, R1, M, O>(
-        &mut self,
-        to: R0,
-        src: &Raster<M>,
-        from: R1,
-        clr: P,
-        op: O,
-    ) where
-        R0: Into<Region>,
-        R1: Into<Region>,
-        M: Pixel<Chan = P::Chan, Model = Matte, Gamma = P::Gamma>,
-        O: Blend,
+    pub fn unpremultiply_in_place<D>(mut self) -> Raster<D>
+    where
+        P: Pixel<Alpha = Premultiplied>,
+        D: Pixel<
+            Chan = P::Chan,
+            Model = P::Model,
+            Alpha = Straight,
+            Gamma = P::Gamma,
+        >,
     {
-        let (to, from) = self.clip_regions(to, src, from);
-        let srows = src.rows(from);
-        let drows = self.rows_mut(to);
-        for (drow, srow) in drows.zip(srows) {
-            P::composite_matte(drow, srow, &clr, op);
+        for p in self.pixels.iter_mut() {
+            let alpha = p.alpha();
+            for c in p.channels_mut()[P::Model::LINEAR].iter_mut() {
+                *c = convert_channel::<
+                    Premultiplied,
+                    P::Gamma,
+                    Straight,
+                    P::Gamma,
+                    _,
+                >(*c, alpha, alpha);
+            }
         }
+        self.reinterpret()
     }
 
-    /// Composite from a source `Raster`.
-    ///
-    /// * `to` Region within `self` (destination).
-    /// * `src` Source `Raster`.
-    /// * `from` Region within source `Raster`.
-    /// * `op` Compositing operation.
-    ///
-    /// `to` / `from` can be `Region` structs, tuples of (*x*, *y*, *width*,
-    /// *height*) or the unit type `()`.  Using `()` has the same result as
-    /// `Raster::region()`.
+    /// Check whether all pixels are approximately equal to another `Raster`.
     ///
-    /// ```bob
-    /// *------------+      *-------------+
-    /// |            |      |    *------+ |
-    /// | *------+   |      |    |      | |
-    /// | |      |   |      |    | from | |
-    /// | |  to  |   | <--- |    +------+ |
-    /// | +------+   |      |             |
-    /// |            |      |     src     |
-    /// |    self    |      +-------------+
-    /// +------------+
-    /// ```
-    /// The composited `Region` is clamped to the smaller of `to` and `from` in
-    /// both `X` and `Y` dimensions.  Also, `to` and `from` are clipped to
-    /// their respective `Raster` dimensions.
+    /// Returns `false` if dimensions are different.  See
+    /// [Pixel::approx_eq](el/trait.Pixel.html#method.approx_eq).
+    pub fn approx_eq(&self, rhs: &Self, epsilon: f32) -> bool {
+        self.width == rhs.width
+            && self.height == rhs.height
+            && self
+                .pixels
+                .iter()
+                .zip(rhs.pixels.iter())
+                .all(|(a, b)| a.approx_eq(*b, epsilon))
+    }
+
+    /// Get width of `Raster`.
+    pub fn width(&self) -> u32 {
+        self.width as u32
+    }
+
+    /// Get height of `Raster`.
+    pub fn height(&self) -> u32 {
+        self.height as u32
+    }
+
+    /// Get size of pixel buffer, in bytes.
     ///
-    /// ### Blend one `Raster` onto another
+    /// ### Raster buffer size
     /// ```
-    /// use pix::ops::SrcOver;
-    /// use pix::rgb::Rgba8p;
+    /// use pix::rgb::Rgba16;
     /// use pix::Raster;
     ///
-    /// let mut r0 = Raster::with_clear(100, 100);
-    /// let r1 = Raster::with_color(5, 5, Rgba8p::new(80, 0, 80, 200));
-    /// // ... load image data
-    /// r0.composite_raster((40, 40), &r1, (), SrcOver);
+    /// let r = Raster::<Rgba16>::with_clear(100, 50);
+    /// assert_eq!(r.bytes(), 100 * 50 * 8);
     /// ```
-    pub fn composite_raster<R0, R1, O>(
-        &mut self,
-        to: R0,
-        src: &Raster<P>,
-        from: R1,
-        op: O,
-    ) where
-        R0: Into<Region>,
-        R1: Into<Region>,
-        O: Blend,
-    {
-        let (to, from) = self.clip_regions(to, src, from);
-        let srows = src.rows(from);
-        let drows = self.rows_mut(to);
-        for (drow, srow) in drows.zip(srows) {
-            P::composite_slice(drow, srow, op);
-        }
+    pub fn bytes(&self) -> usize {
+        self.pixels.len() * core::mem::size_of::<P>()
     }
-}
 
-impl<'a, P: Pixel> Rows<'a, P> {
-    /// Create a new row `Iterator`.
-    fn new(raster: &'a Raster<P>, reg: Region) -> Self {
-        let width = raster.width() as usize;
-        let start = reg.y as usize * width;
-        let end = reg.bottom() as usize * width;
-        let slice = &raster.pixels[start..end];
-        let chunks = slice.chunks_exact(width);
-        let x = reg.x as usize;
-        let w = reg.width as usize;
-        let columns = x..x + w;
-        Rows { chunks, columns }
+    /// Clear all pixels to default value.
+    pub fn clear(&mut self) {
+        for p in self.pixels.iter_mut() {
+            *p = P::default();
+        }
     }
-}
 
-impl<'a, P: Pixel> Iterator for Rows<'a, P> {
-    type Item = &'a [P];
+    /// Get one pixel.
+    pub fn pixel(&self, x: i32, y: i32) -> P {
+        assert!(x >= 0 && x < self.width);
+        assert!(y >= 0 && y < self.height);
+        let i = (self.width * y + x) as usize;
+        self.pixels[i]
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.chunks.next().map(|s| &s[self.columns.clone()])
+    /// Get a mutable pixel.
+    pub fn pixel_mut(&mut self, x: i32, y: i32) -> &mut P {
+        assert!(x >= 0 && x < self.width);
+        assert!(y >= 0 && y < self.height);
+        let i = (self.width * y + x) as usize;
+        &mut self.pixels[i]
     }
-}
 
-impl<'a, P: Pixel> RowsMut<'a, P> {
-    /// Create a new mutable row `Iterator`.
-    fn new(raster: &'a mut Raster<P>, reg: Region) -> Self {
-        let width = raster.width() as usize;
-        let start = reg.y as usize * width;
-        let end = reg.bottom() as usize * width;
-        let slice = &mut raster.pixels[start..end];
-        let chunks = slice.chunks_exact_mut(width);
-        let x = reg.x as usize;
-        let w = reg.width as usize;
-        let columns = x..x + w;
-        RowsMut { chunks, columns }
+    /// Get a slice of all pixels.
+    pub fn pixels(&self) -> &[P] {
+        &self.pixels
     }
-}
 
-impl<'a, P: Pixel> Iterator for RowsMut<'a, P> {
-    type Item = &'a mut [P];
+    /// Get a mutable slice of all pixels.
+    pub fn pixels_mut(&mut self) -> &mut [P] {
+        &mut self.pixels
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.chunks.next().map(|s| &mut s[self.columns.clone()])
+    /// Get an `Iterator` of rows within a `Raster`.
+    ///
+    /// * `reg` Region of the Raster to iterate.
+    pub fn rows<R>(&self, reg: R) -> Rows<'_, P>
+    where
+        R: Into<Region>,
+    {
+        Rows::new(self, self.intersection(reg.into()))
     }
-}
 
-impl From<(i32, i32, u32, u32)> for Region {
-    fn from(r: (i32, i32, u32, u32)) -> Self {
-        Region::new(r.0, r.1, r.2, r.3)
+    /// Get an `Iterator` of columns within a `Raster`.
+    ///
+    /// * `reg` Region of the Raster to iterate.
+    ///
+    /// Each column is an `Iterator` of pixels, from top to bottom.  For
+    /// mutable access, use [column_to_vec](#method.column_to_vec) and
+    /// [set_column](#method.set_column).
+    ///
+    /// ### Sum each column
+    /// ```
+    /// use pix::gray::{Gray, Gray8};
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::<Gray8>::with_u8_buffer(2, 2, [1, 2, 3, 4]);
+    /// let sums: Vec<u32> = r
+    ///     .columns(())
+    ///     .map(|col| col.map(|p| u32::from(u8::from(Gray::value(*p)))).sum())
+    ///     .collect();
+    /// assert_eq!(sums, [4, 6]);
+    /// ```
+    pub fn columns<R>(&self, reg: R) -> Columns<'_, P>
+    where
+        R: Into<Region>,
+    {
+        Columns::new(self, self.intersection(reg.into()))
     }
-}
 
-impl From<(i32, i32)> for Region {
-    fn from(r: (i32, i32)) -> Self {
-        const MAX: u32 = i32::MAX as u32;
-        Region::new(r.0, r.1, MAX, MAX)
+    /// Get a column of pixels as a `Vec`.
+    ///
+    /// * `x` Column number.
+    /// * `reg` Region of the Raster, limiting which rows are included.
+    ///
+    /// Pixels are ordered from top to bottom.  If column `x` is not within
+    /// `reg` (clipped to the `Raster`), the `Vec` is empty.
+    ///
+    /// ### Get a column
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::<Gray8>::with_u8_buffer(2, 3, [1, 2, 3, 4, 5, 6]);
+    /// let col = r.column_to_vec(1, (0, 1, 2, 2));
+    /// assert_eq!(col, [Gray8::new(4), Gray8::new(6)]);
+    /// ```
+    pub fn column_to_vec<R>(&self, x: i32, reg: R) -> Vec<P>
+    where
+        R: Into<Region>,
+    {
+        match self.column_range(x, reg.into()) {
+            Some(range) => range.map(|i| self.pixels[i]).collect(),
+            None => Vec::new(),
+        }
     }
-}
 
-impl From<()> for Region {
-    fn from(_: ()) -> Self {
-        const MAX: u32 = i32::MAX as u32;
-        Region::new(0, 0, MAX, MAX)
+    /// Set a column of pixels.
+    ///
+    /// * `x` Column number.
+    /// * `reg` Region of the Raster, limiting which rows are set.
+    /// * `pixels` Pixels to set, from top to bottom.
+    ///
+    /// This is the inverse of [column_to_vec](#method.column_to_vec).
+    ///
+    /// # Panics
+    ///
+    /// If `pixels` length is not equal to the number of pixels in column `x`
+    /// within `reg` (clipped to the `Raster`).
+    ///
+    /// ### Reverse a column
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::<Gray8>::with_u8_buffer(2, 3, [1, 2, 3, 4, 5, 6]);
+    /// let mut col = r.column_to_vec(0, ());
+    /// col.reverse();
+    /// r.set_column(0, (), &col);
+    /// assert_eq!(r.pixel(0, 0), Gray8::new(5));
+    /// ```
+    pub fn set_column<R>(&mut self, x: i32, reg: R, pixels: &[P])
+    where
+        R: Into<Region>,
+    {
+        let range = self.column_range(x, reg.into());
+        let len = range.as_ref().map_or(0, |r| r.len());
+        assert_eq!(len, pixels.len(), "Column length mismatch");
+        for (i, p) in range.into_iter().flatten().zip(pixels) {
+            self.pixels[i] = *p;
+        }
     }
-}
 
-impl Region {
-    /// Create a new `Region`
-    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
-        let width = i32::try_from(width).expect(WIDTH_TOO_BIG);
-        let height = i32::try_from(height).expect(HEIGHT_TOO_BIG);
-        Region {
-            x,
-            y,
-            width,
-            height,
+    /// Get pixel indices of a column within a region
+    fn column_range(
+        &self,
+        x: i32,
+        reg: Region,
+    ) -> Option<StepBy<Range<usize>>> {
+        let reg = self.intersection(reg);
+        if x < reg.x || x >= reg.right() {
+            return None;
         }
+        let width = self.width as usize;
+        let start = reg.y as usize * width + x as usize;
+        let end = reg.bottom() as usize * width;
+        Some((start..end).step_by(width))
     }
 
-    /// Get intersection with another `Region`
-    pub fn intersection<R>(self, rhs: R) -> Self
+    /// Get an `Iterator` of spans covered by a `RegionSet`.
+    ///
+    /// * `set` Regions of the Raster to iterate.
+    ///
+    /// Spans are clipped to the `Raster`, and ordered from top to bottom,
+    /// then left to right.  Each pixel in the set is visited exactly once.
+    ///
+    /// ### Sum pixels in damaged regions
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::{Raster, RegionSet};
+    ///
+    /// let r = Raster::with_color(8, 8, Gray8::new(1));
+    /// let set: RegionSet = [(0, 0, 4, 4), (2, 2, 4, 4)].into_iter().collect();
+    /// let n: usize = r.spans(&set).map(|(_x, _y, span)| span.len()).sum();
+    /// assert_eq!(n, 28);
+    /// ```
+    pub fn spans(&self, set: &RegionSet) -> Spans<'_, P> {
+        Spans::new(self, set)
+    }
+
+    /// Get an `Iterator` of mutable spans covered by a `RegionSet`.
+    ///
+    /// * `set` Regions of the Raster to iterate.
+    ///
+    /// Spans are clipped to the `Raster`, and ordered from top to bottom,
+    /// then left to right.  Each pixel in the set is visited exactly once.
+    pub fn spans_mut(&mut self, set: &RegionSet) -> SpansMut<'_, P> {
+        SpansMut::new(self, set)
+    }
+
+    /// Get an `Iterator` of mutable rows within a `Raster`.
+    ///
+    /// * `reg` Region of the Raster to iterate.
+    pub fn rows_mut<R>(&mut self, reg: R) -> RowsMut<'_, P>
     where
-        R: Into<Self>,
+        R: Into<Region>,
     {
-        let rhs = rhs.into();
-        let x0 = self.x.max(rhs.x);
-        let x1 = self.right().min(rhs.right());
-        let y0 = self.y.max(rhs.y);
-        let y1 = self.bottom().min(rhs.bottom());
-        if x0 < x1 && y0 < y1 {
-            let w = (x1 - x0) as u32;
-            let h = (y1 - y0) as u32;
-            Region::new(x0, y0, w, h)
-        } else {
-            Region::default()
-        }
+        RowsMut::new(self, self.intersection(reg.into()))
     }
 
-    /// Get the left side
-    pub fn left(self) -> i32 {
-        self.x
+    /// Get `Region` of entire `Raster`.
+    pub fn region(&self) -> Region {
+        Region::new(0, 0, self.width(), self.height())
+    }
+
+    /// Get intersection with a `Region`.
+    pub fn intersection<R>(&self, reg: R) -> Region
+    where
+        R: Into<Region>,
+    {
+        let reg = reg.into();
+        reg.intersection(self.region())
+    }
+
+    /// Copy a color to a region of the `Raster`.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.  Using `()` has
+    ///   the same result as `Raster::region()`.
+    /// * `clr` Source `Pixel` color.
+    ///
+    /// ### Copy a color to a rectangle region
+    /// ```
+    /// use pix::rgb::SRgb8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_clear(100, 100);
+    /// let clr = SRgb8::new(0xDD, 0x96, 0x70);
+    /// r.copy_color((20, 40, 25, 50), clr);
+    /// ```
+    pub fn copy_color<R>(&mut self, reg: R, clr: P)
+    where
+        R: Into<Region>,
+    {
+        let reg = self.intersection(reg.into());
+        if let Some(d) = self.full_width_range(reg) {
+            P::copy_color(&mut self.pixels[d], &clr);
+        } else if !reg.is_empty() {
+            let drows = self.rows_mut(reg);
+            for drow in drows {
+                P::copy_color(drow, &clr);
+            }
+        }
+    }
+
+    /// Copy a color of any format to a region of the `Raster`.
+    ///
+    /// The color is converted to `P` once, then copied as with
+    /// [copy_color](#method.copy_color).
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `clr` Source color.
+    ///
+    /// ### Copy an opaque color to a translucent raster
+    /// ```
+    /// use pix::rgb::{SRgb8, SRgba8};
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::<SRgba8>::with_clear(100, 100);
+    /// r.copy_color_from((20, 40, 25, 50), SRgb8::new(0xDD, 0x96, 0x70));
+    /// assert_eq!(r.pixel(20, 40), SRgba8::new(0xDD, 0x96, 0x70, 0xFF));
+    /// ```
+    pub fn copy_color_from<R, C>(&mut self, reg: R, clr: C)
+    where
+        R: Into<Region>,
+        C: Pixel,
+        P::Chan: From<C::Chan>,
+    {
+        self.copy_color(reg, clr.convert());
+    }
+
+    /// Fill a region with a checkerboard pattern.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `a` Color of cell containing the raster origin.
+    /// * `b` Alternate cell color.
+    /// * `cell` Width and height of each cell, in pixels.
+    ///
+    /// The pattern is anchored to the raster origin, not the region, so
+    /// adjacent regions line up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell` is zero.
+    ///
+    /// ### Draw a transparency checkerboard
+    /// ```
+    /// use pix::rgb::SRgba8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_clear(64, 64);
+    /// let light = SRgba8::new(0xCC, 0xCC, 0xCC, 0xFF);
+    /// let dark = SRgba8::new(0x99, 0x99, 0x99, 0xFF);
+    /// r.fill_checkerboard((), light, dark, 8);
+    /// assert_eq!(r.pixel(7, 0), light);
+    /// assert_eq!(r.pixel(8, 0), dark);
+    /// assert_eq!(r.pixel(8, 8), light);
+    /// ```
+    pub fn fill_checkerboard<R>(&mut self, reg: R, a: P, b: P, cell: u32)
+    where
+        R: Into<Region>,
+    {
+        assert!(cell > 0, "Checkerboard cell size must be nonzero");
+        let reg = self.intersection(reg.into());
+        if !reg.is_empty() {
+            let cell = cell as usize;
+            let x = reg.x as usize;
+            for (y, drow) in (reg.y as usize..).zip(self.rows_mut(reg)) {
+                if (y / cell) % 2 == 0 {
+                    fill_bands(drow, x, cell, &a, &b);
+                } else {
+                    fill_bands(drow, x, cell, &b, &a);
+                }
+            }
+        }
+    }
+
+    /// Fill a region with a stripe pattern.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `a` Color of stripe containing the raster origin.
+    /// * `b` Alternate stripe color.
+    /// * `width` Width of each stripe, in pixels.
+    /// * `axis` Orientation of stripes.
+    ///
+    /// The pattern is anchored to the raster origin, not the region, so
+    /// adjacent regions line up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero.
+    ///
+    /// ### Fill vertical stripes
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::{Axis, Raster};
+    ///
+    /// let mut r = Raster::with_clear(16, 16);
+    /// let white = Gray8::new(0xFF);
+    /// r.fill_stripes((), white, Gray8::new(0), 2, Axis::Vertical);
+    /// assert_eq!(r.pixel(1, 5), white);
+    /// assert_eq!(r.pixel(2, 5), Gray8::new(0));
+    /// ```
+    pub fn fill_stripes<R>(
+        &mut self,
+        reg: R,
+        a: P,
+        b: P,
+        width: u32,
+        axis: Axis,
+    ) where
+        R: Into<Region>,
+    {
+        assert!(width > 0, "Stripe width must be nonzero");
+        let reg = self.intersection(reg.into());
+        if !reg.is_empty() {
+            let width = width as usize;
+            let x = reg.x as usize;
+            for (y, drow) in (reg.y as usize..).zip(self.rows_mut(reg)) {
+                match axis {
+                    Axis::Horizontal => {
+                        let clr = if (y / width) % 2 == 0 { &a } else { &b };
+                        P::copy_color(drow, clr);
+                    }
+                    Axis::Vertical => fill_bands(drow, x, width, &a, &b),
+                }
+            }
+        }
+    }
+
+    /// Fill a region with deterministic noise.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `seed` Seed for the random number generator.
+    ///
+    /// Every channel is filled, including *alpha*, with uniformly distributed
+    /// values.  Pixels are filled row by row, then channel by channel, using
+    /// a [SplitMix64] generator.  The sequence for a given seed is stable
+    /// across platforms, so it is suitable for tests and dither patterns.
+    ///
+    /// For premultiplied formats, color channels can exceed *alpha*; use
+    /// [clamp_premultiplied] on each pixel if that matters.
+    ///
+    /// [clamp_premultiplied]: el/trait.Pixel.html#method.clamp_premultiplied
+    /// [SplitMix64]: https://prng.di.unimi.it/splitmix64.c
+    ///
+    /// ### Fill reproducible noise
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::Raster;
+    ///
+    /// let mut r0 = Raster::<Gray8>::with_clear(16, 16);
+    /// r0.fill_noise((), 42);
+    /// let mut r1 = Raster::<Gray8>::with_clear(16, 16);
+    /// r1.fill_noise((), 42);
+    /// assert_eq!(r0.pixels(), r1.pixels());
+    /// ```
+    pub fn fill_noise<R>(&mut self, reg: R, seed: u64)
+    where
+        R: Into<Region>,
+    {
+        let bits = (core::mem::size_of::<P::Chan>() * 8).min(24);
+        let max = ((1u32 << bits) - 1) as f32;
+        let mut rng = SplitMix64(seed);
+        for row in self.rows_mut(reg) {
+            for p in row.iter_mut() {
+                for c in p.channels_mut() {
+                    let v = (rng.next_u64() >> (64 - bits)) as f32;
+                    *c = P::Chan::from(v / max);
+                }
+            }
+        }
+    }
+
+    /// Copy from a source `Raster`.
+    ///
+    /// * `to` Region within `self` (destination).
+    /// * `src` Source `Raster`.
+    /// * `from` Region within source `Raster`.
+    ///
+    /// `to` / `from` can be `Region` structs, tuples of (*x*, *y*, *width*,
+    /// *height*) or the unit type `()`.  Using `()` has the same result as
+    /// `Raster::region()`.
+    ///
+    /// ```bob
+    /// *------------+      *-------------+
+    /// |            |      |    *------+ |
+    /// | *------+   |      |    |      | |
+    /// | |      |   |      |    | from | |
+    /// | |  to  |   | <--- |    +------+ |
+    /// | +------+   |      |             |
+    /// |            |      |     src     |
+    /// |    self    |      +-------------+
+    /// +------------+
+    /// ```
+    /// The top-left corners of `to` and `from` are aligned, so pixel
+    /// (`to.x + i`, `to.y + j`) is copied from (`from.x + i`, `from.y + j`).
+    /// Only pixels within both regions, and inside both rasters, are copied.
+    /// Negative `to` / `from` coordinates skip the corresponding offsets.
+    ///
+    /// ### Copy part of one `Raster` to another
+    /// ```
+    /// use pix::rgb::SRgb8;
+    /// use pix::Raster;
+    ///
+    /// let mut r0 = Raster::with_clear(100, 100);
+    /// let r1 = Raster::with_color(5, 5, SRgb8::new(80, 0, 80));
+    /// // ... load image data
+    /// r0.copy_raster((40, 40, 5, 5), &r1, ());
+    /// ```
+    pub fn copy_raster<R0, R1>(&mut self, to: R0, src: &Raster<P>, from: R1)
+    where
+        R0: Into<Region>,
+        R1: Into<Region>,
+    {
+        let (to, from) = self.clip_regions(to, src, from);
+        if let (Some(d), Some(s)) =
+            (self.full_width_range(to), src.full_width_range(from))
+        {
+            P::copy_slice(&mut self.pixels[d], &src.pixels[s]);
+            return;
+        }
+        let srows = src.rows(from);
+        let drows = self.rows_mut(to);
+        for (drow, srow) in drows.zip(srows) {
+            debug_assert_eq!(drow.len(), srow.len());
+            P::copy_slice(drow, srow);
+        }
+    }
+
+    /// Convert from a source `Raster` of another pixel format.
+    ///
+    /// * `to` Region within `self` (destination).
+    /// * `src` Source `Raster`.
+    /// * `from` Region within source `Raster`.
+    ///
+    /// Regions are clipped the same way as
+    /// [copy_raster](#method.copy_raster), and pixels are converted as by
+    /// [with_raster](#method.with_raster).  This is useful for updating only
+    /// a dirty rectangle of a display buffer.
+    ///
+    /// ### Convert a dirty rectangle for display
+    /// ```
+    /// use pix::bgr::SBgra8;
+    /// use pix::rgb::Rgba8p;
+    /// use pix::Raster;
+    ///
+    /// let work = Raster::with_color(64, 64, Rgba8p::new(0, 0x80, 0, 0xFF));
+    /// let mut display = Raster::<SBgra8>::with_clear(64, 64);
+    /// let dirty = (8, 8, 16, 16);
+    /// display.convert_region_from(dirty, &work, dirty);
+    /// assert_eq!(display.pixel(0, 0), SBgra8::default());
+    /// assert_ne!(display.pixel(8, 8), SBgra8::default());
+    /// ```
+    pub fn convert_region_from<R0, R1, S>(
+        &mut self,
+        to: R0,
+        src: &Raster<S>,
+        from: R1,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        S: Pixel,
+        P::Chan: From<S::Chan>,
+    {
+        let (to, from) = self.clip_regions(to, src, from);
+        let srows = src.rows(from);
+        let drows = self.rows_mut(to);
+        for (drow, srow) in drows.zip(srows) {
+            convert_slice(drow, srow);
+        }
+    }
+
+    /// Copy from a source `Raster`, scaling with nearest-neighbor sampling.
+    ///
+    /// * `to` Region within `self` (destination).
+    /// * `src` Source `Raster`.
+    /// * `from` Region within source `Raster`.
+    ///
+    /// The `from` region is stretched to fill the `to` region; scale factors
+    /// need not be integers.  Using `()` for either region has the same
+    /// result as `Raster::region()` of that raster.  Destination pixels
+    /// outside `self`, or which sample outside `src`, are left unchanged.
+    /// If either region is empty, nothing is copied.
+    ///
+    /// ### Draw a 16x16 sprite into a 64x64 rectangle
+    /// ```
+    /// use pix::rgb::SRgb8;
+    /// use pix::Raster;
+    ///
+    /// let mut r0 = Raster::with_clear(100, 100);
+    /// let sprite = Raster::with_color(16, 16, SRgb8::new(80, 0, 80));
+    /// r0.copy_raster_scaled((10, 10, 64, 64), &sprite, ());
+    /// assert_eq!(r0.pixel(73, 73), SRgb8::new(80, 0, 80));
+    /// assert_eq!(r0.pixel(74, 74), SRgb8::default());
+    /// ```
+    pub fn copy_raster_scaled<R0, R1>(
+        &mut self,
+        to: R0,
+        src: &Raster<P>,
+        from: R1,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+    {
+        self.for_each_scaled(to, src, from, |d, s| *d = *s);
+    }
+
+    /// Map channels within a region, using a function.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `f` Function called with channel number and value, returning the new
+    ///   value.
+    ///
+    /// ### Invert a 16-bit raster
+    /// ```
+    /// use pix::chan::{Ch16, Channel};
+    /// use pix::gray::Gray16;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_color(10, 10, Gray16::new(0x1000));
+    /// r.map_channels((), |_i, c| Ch16::MAX - c);
+    /// assert_eq!(r.pixel(5, 5), Gray16::new(0xEFFF));
+    /// ```
+    pub fn map_channels<R, F>(&mut self, reg: R, mut f: F)
+    where
+        R: Into<Region>,
+        F: FnMut(usize, P::Chan) -> P::Chan,
+    {
+        let reg = self.intersection(reg.into());
+        if !reg.is_empty() {
+            for row in self.rows_mut(reg) {
+                for p in row.iter_mut() {
+                    for (i, c) in p.channels_mut().iter_mut().enumerate() {
+                        *c = f(i, *c);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rotate *hue* within a region.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `delta` Amount to rotate.  A full turn is one step past
+    ///   [Channel::MAX](chan/trait.Channel.html#associatedconstant.MAX) for
+    ///   integer channels (256 for `Ch8`), or 1.0 for floating point.
+    ///
+    /// This works with any color model with circular channels, such as
+    /// `Hsv`, `Hsl`, `Hwb` or `Oklch`.  Other models are unchanged.  See
+    /// [Pixel::rotate_circular](el/trait.Pixel.html#method.rotate_circular).
+    ///
+    /// ### Rotate hue by 180 degrees
+    /// ```
+    /// use pix::hsl::Hsl8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_color(4, 4, Hsl8::new(0x20, 0xFF, 0x80));
+    /// r.rotate_hue((), 0x80.into());
+    /// assert_eq!(r.pixel(2, 2), Hsl8::new(0xA0, 0xFF, 0x80));
+    /// ```
+    pub fn rotate_hue<R>(&mut self, reg: R, delta: P::Chan)
+    where
+        R: Into<Region>,
+    {
+        for row in self.rows_mut(reg) {
+            for p in row.iter_mut() {
+                *p = p.rotate_circular(delta);
+            }
+        }
+    }
+
+    /// Replace the color of pixels within a region, keeping *alpha*.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `clr` Target color, which should be opaque.
+    /// * `mode` Recolor mode.
+    ///
+    /// With [RecolorMode::Luminance], the target color is multiplied in
+    /// linear light by the relative luminance of each original pixel, so
+    /// white becomes `clr` and black stays black.  Premultiplied formats
+    /// are rescaled to the original *alpha*.
+    ///
+    /// [RecolorMode::Luminance]: enum.RecolorMode.html#variant.Luminance
+    ///
+    /// ### Tint a sprite
+    /// ```
+    /// use pix::rgb::SRgba8;
+    /// use pix::{Raster, RecolorMode};
+    ///
+    /// let mut r = Raster::with_color(4, 4, SRgba8::new(255, 255, 255, 100));
+    /// r.recolor((), SRgba8::new(200, 40, 0, 255), RecolorMode::Luminance);
+    /// assert_eq!(r.pixel(1, 1), SRgba8::new(200, 40, 0, 100));
+    /// ```
+    pub fn recolor<R>(&mut self, reg: R, clr: P, mode: RecolorMode)
+    where
+        R: Into<Region>,
+    {
+        let opaque = clr.with_alpha(P::Chan::MAX);
+        let target = linear_rgb(opaque);
+        for row in self.rows_mut(reg) {
+            for p in row.iter_mut() {
+                let alpha = p.alpha();
+                let c = match mode {
+                    RecolorMode::Flat => opaque,
+                    RecolorMode::Luminance => {
+                        let [red, green, blue] =
+                            linear_rgb(p.with_alpha(P::Chan::MAX));
+                        let lum = Gray::luma(red, green, blue).clamp(0.0, 1.0);
+                        let [red, green, blue] = target.map(|c| {
+                            P::Gamma::from_linear(Ch32::new(c * lum)).to_f32()
+                        });
+                        P::Model::from_rgba::<P>(PixRgba::<P>::new(
+                            red, green, blue, 1.0,
+                        ))
+                    }
+                };
+                *p = c.with_alpha(alpha);
+            }
+        }
+    }
+
+    /// Sanitize channel values which are out of range.
+    ///
+    /// Non-finite values (`NaN` or infinite) are replaced with
+    /// [Channel::MIN], and other values are clamped between `MIN` and
+    /// `MAX`.  This is only needed after writing raw data, such as with
+    /// [as_u8_slice_mut](#method.as_u8_slice_mut).
+    ///
+    /// [Channel::MIN]: chan/trait.Channel.html#associatedconstant.MIN
+    ///
+    /// ### Clean up a buffer
+    /// ```
+    /// use pix::gray::Gray32;
+    /// use pix::Raster;
+    ///
+    /// let buf: Vec<u8> = [0.5, f32::NAN, f32::INFINITY, -2.0]
+    ///     .iter()
+    ///     .flat_map(|v| v.to_ne_bytes())
+    ///     .collect();
+    /// let mut r = Raster::<Gray32>::with_clear(4, 1);
+    /// r.as_u8_slice_mut().copy_from_slice(&buf);
+    /// r.sanitize();
+    /// assert_eq!(r.pixel(0, 0), Gray32::new(0.5));
+    /// assert_eq!(r.pixel(2, 0), Gray32::new(0.0));
+    /// ```
+    pub fn sanitize(&mut self)
+    where
+        P: Pixel<Chan = Ch32>,
+    {
+        for p in self.pixels.iter_mut() {
+            for c in p.channels_mut() {
+                let v = c.to_f32();
+                *c = if v.is_finite() {
+                    Ch32::new(v)
+                } else {
+                    Ch32::MIN
+                };
+            }
+        }
+    }
+
+    /// Reorder the channels of every pixel.
+    ///
+    /// * `order` Source channel index for each channel.  Indices may be
+    ///   repeated, to copy one channel into several others.
+    ///
+    /// # Panics
+    ///
+    /// If `order` length is not equal to the pixel channel count, or if any
+    /// index is out of range.
+    ///
+    /// ### Swap *red* and *blue*
+    /// ```
+    /// use pix::rgb::Rgba8;
+    /// use pix::Raster;
+    ///
+    /// let clr = Rgba8::new(0x10, 0x20, 0x30, 0x40);
+    /// let mut r = Raster::with_color(4, 4, clr);
+    /// r.swizzle([2, 1, 0, 3]);
+    /// assert_eq!(r.pixel(1, 1), Rgba8::new(0x30, 0x20, 0x10, 0x40));
+    /// ```
+    pub fn swizzle<const N: usize>(&mut self, order: [usize; N]) {
+        assert_eq!(N, P::CHANNELS, "Swizzle order length");
+        assert!(order.iter().all(|i| *i < N), "Swizzle index out of range");
+        for p in self.pixels.iter_mut() {
+            let src = *p;
+            for (c, i) in p.channels_mut().iter_mut().zip(order) {
+                *c = src.channels()[i];
+            }
+        }
+    }
+
+    /// Create a [Gray] `Raster` from one channel.
+    ///
+    /// * `idx` Channel index.
+    ///
+    /// The result has the same channel type and gamma mode as `self`.
+    ///
+    /// # Panics
+    ///
+    /// If `idx` is not less than the pixel channel count.
+    ///
+    /// ### Show *alpha* as gray
+    /// ```
+    /// use pix::gray::Gray16;
+    /// use pix::rgb::Rgba16;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::with_color(4, 4, Rgba16::new(0, 0, 0, 0x8000));
+    /// let g: Raster<Gray16> = r.broadcast_channel(3);
+    /// assert_eq!(g.pixel(2, 2), Gray16::new(0x8000));
+    /// ```
+    ///
+    /// [Gray]: ../gray/struct.Gray.html
+    pub fn broadcast_channel(
+        &self,
+        idx: usize,
+    ) -> Raster<Pix1<P::Chan, Gray, Straight, P::Gamma>> {
+        assert!(idx < P::CHANNELS, "Channel index out of range: {idx}");
+        self.map_into((), |p| Pix1::new(p.channels()[idx]))
+    }
+
+    /// Threshold one channel within a region, making a [Matte] `Raster`.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `idx` Channel index.
+    /// * `value` Threshold value.
+    ///
+    /// Each *alpha* value in the result is `MAX` where the channel is greater
+    /// than or equal to `value`, otherwise `MIN`.  The result is the size of
+    /// the region clipped to `self`, with the same channel type.
+    ///
+    /// # Panics
+    ///
+    /// If `idx` is not less than the pixel channel count.
+    ///
+    /// ### Segment a gradient
+    /// ```
+    /// use pix::chan::Ch8;
+    /// use pix::gray::Gray8;
+    /// use pix::matte::Matte8;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::<Gray8>::with_u8_buffer(4, 1, [0x00, 0x40, 0x80, 0xC0]);
+    /// let m: Raster<Matte8> = r.threshold((), 0, Ch8::new(0x80));
+    /// assert_eq!(m.as_u8_slice(), [0x00, 0x00, 0xFF, 0xFF]);
+    /// ```
+    ///
+    /// [Matte]: ../matte/struct.Matte.html
+    pub fn threshold<R>(
+        &self,
+        reg: R,
+        idx: usize,
+        value: P::Chan,
+    ) -> Raster<Pix1<P::Chan, Matte, Premultiplied, Linear>>
+    where
+        R: Into<Region>,
+    {
+        assert!(idx < P::CHANNELS, "Channel index out of range: {idx}");
+        self.map_into(reg, |p| {
+            if p.channels()[idx] >= value {
+                Pix1::new(P::Chan::MAX)
+            } else {
+                Pix1::new(P::Chan::MIN)
+            }
+        })
+    }
+
+    /// Clamp channels within a region.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `min` Pixel with minimum value for each channel.
+    /// * `max` Pixel with maximum value for each channel.
+    ///
+    /// If a `min` channel is greater than the `max` channel, the `max` value
+    /// is used.
+    ///
+    /// ### Limit *red* and *blue* levels
+    /// ```
+    /// use pix::rgb::Rgb8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_color(4, 4, Rgb8::new(0x10, 0x80, 0xF0));
+    /// let min = Rgb8::new(0x20, 0x00, 0x00);
+    /// let max = Rgb8::new(0xFF, 0xFF, 0xE0);
+    /// r.clamp_channels((), min, max);
+    /// assert_eq!(r.pixel(0, 0), Rgb8::new(0x20, 0x80, 0xE0));
+    /// ```
+    pub fn clamp_channels<R>(&mut self, reg: R, min: P, max: P)
+    where
+        R: Into<Region>,
+    {
+        self.map_channels(reg, |i, c| {
+            c.max(min.channels()[i]).min(max.channels()[i])
+        });
+    }
+
+    /// Map channels within a region, using one lookup table per channel.
+    ///
+    /// This can be used to adjust levels or curves in one pass.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `luts` Lookup tables, one for each channel (including *alpha*).
+    ///
+    /// # Panics
+    ///
+    /// If the number of `luts` is not equal to the pixel channel count.
+    pub fn map_channels_u8<R>(&mut self, reg: R, luts: &[[u8; 256]])
+    where
+        R: Into<Region>,
+        P: Pixel<Chan = Ch8>,
+    {
+        assert_eq!(luts.len(), P::CHANNELS);
+        let reg = self.intersection(reg.into());
+        if !reg.is_empty() {
+            for row in self.rows_mut(reg) {
+                for p in row.iter_mut() {
+                    for (c, lut) in p.channels_mut().iter_mut().zip(luts) {
+                        *c = Ch8::new(lut[usize::from(u8::from(*c))]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Map *linear* channels within a region, using one lookup table.
+    ///
+    /// *Alpha* and circular (*hue*) channels are not changed.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `lut` Lookup table.
+    ///
+    /// ### Invert colors
+    /// ```
+    /// use pix::rgb::Rgba8;
+    /// use pix::Raster;
+    ///
+    /// let mut lut = [0; 256];
+    /// for (i, v) in lut.iter_mut().enumerate() {
+    ///     *v = 255 - i as u8;
+    /// }
+    /// let mut r = Raster::with_color(10, 10, Rgba8::new(0, 64, 255, 128));
+    /// r.map_linear_u8((), &lut);
+    /// assert_eq!(r.pixel(0, 0), Rgba8::new(255, 191, 0, 128));
+    /// ```
+    pub fn map_linear_u8<R>(&mut self, reg: R, lut: &[u8; 256])
+    where
+        R: Into<Region>,
+        P: Pixel<Chan = Ch8>,
+    {
+        let reg = self.intersection(reg.into());
+        if !reg.is_empty() {
+            for row in self.rows_mut(reg) {
+                for p in row.iter_mut() {
+                    let chan = &mut p.channels_mut()[P::Model::LINEAR];
+                    for c in chan.iter_mut() {
+                        *c = Ch8::new(lut[usize::from(u8::from(*c))]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adjust white balance within a region.
+    ///
+    /// *Red* and *blue* channels are scaled by gains, which are usually
+    /// derived from a target color temperature.  Results are clamped to the
+    /// valid channel range.
+    ///
+    /// Gains should be applied to [linear] gamma rasters; for sRGB rasters,
+    /// convert to linear first.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `r_gain` Gain for *red* channel.
+    /// * `b_gain` Gain for *blue* channel.
+    ///
+    /// ### Warm an image
+    /// ```
+    /// use pix::rgb::Rgb32;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_color(10, 10, Rgb32::new(0.5, 0.5, 0.5));
+    /// r.white_balance((), 1.2, 0.8);
+    /// assert_eq!(r.pixel(0, 0), Rgb32::new(0.6, 0.5, 0.4));
+    /// ```
+    ///
+    /// [linear]: chan/struct.Linear.html
+    pub fn white_balance<R>(&mut self, reg: R, r_gain: f32, b_gain: f32)
+    where
+        R: Into<Region>,
+        P: Pixel<Model = Rgb>,
+    {
+        let reg = self.intersection(reg.into());
+        if !reg.is_empty() {
+            for row in self.rows_mut(reg) {
+                for p in row.iter_mut() {
+                    *p = Rgb::scale_channels(*p, r_gain, 1.0, b_gain);
+                }
+            }
+        }
+    }
+
+    /// Get the mean pixel of a region.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    ///
+    /// Channels are averaged as stored, without *alpha* or *gamma*
+    /// conversion.  Circular channels (*hue*) use the circular mean: the
+    /// angle of the sum of unit vectors for each value.  If those vectors
+    /// cancel out, the mean *hue* is zero.
+    ///
+    /// If the region is empty, the default pixel is returned.
+    ///
+    /// ### Mean of a gradient
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::Raster;
+    ///
+    /// let v: Vec<u8> = (0..=255).collect();
+    /// let r = Raster::<Gray8>::with_u8_buffer(16, 16, v);
+    /// assert_eq!(r.mean(()), Gray8::new(128));
+    /// assert_eq!(r.mean((0, 0, 16, 1)), Gray8::new(8));
+    /// ```
+    pub fn mean<R>(&self, reg: R) -> P
+    where
+        R: Into<Region>,
+    {
+        let reg = self.intersection(reg.into());
+        let mut mean = P::default();
+        if reg.is_empty() {
+            return mean;
+        }
+        for (i, c) in mean.channels_mut().iter_mut().enumerate() {
+            let values = self.rows(reg).flatten().map(|p| p.channels()[i]);
+            *c = if P::Model::CIRCULAR.contains(&i) {
+                circular_mean(circular_sums(values))
+            } else {
+                P::Chan::mean(values).unwrap_or_default()
+            };
+        }
+        mean
+    }
+
+    /// Get the variance of each channel in a region.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    ///
+    /// Returns the population variance of each channel, with values scaled
+    /// to range from 0 to 1 (see [Channel::to_f32]).  For circular channels
+    /// (*hue*), the circular variance is returned instead: one minus the
+    /// length of the mean unit vector.  Both are zero for a solid color.
+    ///
+    /// If the region is empty, all variances are zero.
+    ///
+    /// ### Variance of a checkerboard
+    /// ```
+    /// use pix::gray::Gray32;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_clear(8, 8);
+    /// r.fill_checkerboard((), Gray32::new(0.0), Gray32::new(1.0), 1);
+    /// assert_eq!(r.variance(()), vec![0.25]);
+    /// ```
+    /// [Channel::to_f32]: chan/trait.Channel.html#tymethod.to_f32
+    pub fn variance<R>(&self, reg: R) -> Vec<f32>
+    where
+        R: Into<Region>,
+    {
+        let reg = self.intersection(reg.into());
+        if reg.is_empty() {
+            return vec![0.0; P::CHANNELS];
+        }
+        let n = f64::from(reg.width) * f64::from(reg.height);
+        (0..P::CHANNELS)
+            .map(|i| {
+                let values = self.rows(reg).flatten().map(|p| p.channels()[i]);
+                let var = if P::Model::CIRCULAR.contains(&i) {
+                    let (sin, cos) = circular_sums(values);
+                    1.0 - sin.hypot(cos) / n
+                } else {
+                    let (sum, sum2) = values.fold((0.0, 0.0), |(s, s2), c| {
+                        let v = f64::from(c.to_f32());
+                        (s + v, s2 + v * v)
+                    });
+                    let mean = sum / n;
+                    sum2 / n - mean * mean
+                };
+                var.max(0.0) as f32
+            })
+            .collect()
+    }
+
+    /// Build an integral image (summed-area table) of a region.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    ///
+    /// ### Box filter sum
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::<Gray8>::with_clear(16, 16);
+    /// r.copy_color((4, 4, 4, 4), Gray8::new(10));
+    /// let integral = r.integral(());
+    /// assert_eq!(integral.sum((0, 0, 6, 6))[0], 40);
+    /// assert_eq!(integral.mean((4, 4, 2, 2)), Gray8::new(10));
+    /// ```
+    pub fn integral<R>(&self, reg: R) -> Integral<P>
+    where
+        R: Into<Region>,
+    {
+        Integral::new(self, reg.into())
+    }
+
+    /// Crop to a new `Raster`.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    ///
+    /// The region is clipped to the `Raster` dimensions.  If it does not
+    /// intersect at all, the result is an empty (0 x 0) `Raster`.
+    ///
+    /// ### Crop a `Raster`
+    /// ```
+    /// use pix::rgb::SRgb8;
+    /// use pix::Raster;
+    ///
+    /// let r0 = Raster::<SRgb8>::with_clear(100, 100);
+    /// let r1 = r0.crop((-10, 20, 50, 50));
+    /// assert_eq!((r1.width(), r1.height()), (40, 50));
+    /// ```
+    pub fn crop<R>(&self, reg: R) -> Self
+    where
+        R: Into<Region>,
+    {
+        let reg = self.intersection(reg);
+        let mut r = Raster::with_clear(reg.width(), reg.height());
+        if !reg.is_empty() {
+            r.copy_raster((), self, reg);
+        }
+        r
+    }
+
+    /// Find the bounding region of pixels matching a predicate.
+    ///
+    /// * `pred` Function called with pixels, returning `true` for pixels
+    ///   to include.
+    ///
+    /// Returns the smallest region containing all matching pixels, or
+    /// `None` if there are none.  Rows are scanned inward from the top and
+    /// bottom, and then only the columns outside the bounds found so far
+    /// are checked on each remaining row.
+    ///
+    /// ### Find pixels brighter than a threshold
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::gray::SGray8;
+    /// use pix::{Raster, Region};
+    ///
+    /// let mut r = Raster::<SGray8>::with_clear(10, 10);
+    /// *r.pixel_mut(3, 4) = SGray8::new(0x90);
+    /// *r.pixel_mut(6, 2) = SGray8::new(0x40);
+    /// let reg = r.bounds_where(|p| u8::from(p.one()) > 0x20);
+    /// assert_eq!(reg, Some(Region::new(3, 2, 4, 3)));
+    /// ```
+    pub fn bounds_where<F>(&self, pred: F) -> Option<Region>
+    where
+        F: Fn(P) -> bool,
+    {
+        let width = self.width() as usize;
+        if width == 0 {
+            return None;
+        }
+        let mut rows = self.pixels.chunks_exact(width);
+        let top = rows.position(|row| row.iter().any(|p| pred(*p)))?;
+        let bottom = top
+            + 1
+            + rows
+                .rposition(|row| row.iter().any(|p| pred(*p)))
+                .map_or(0, |i| i + 1);
+        let (mut left, mut right) = (width, 0);
+        for row in self.pixels[top * width..bottom * width].chunks_exact(width)
+        {
+            if let Some(x) = row[..left].iter().position(|p| pred(*p)) {
+                left = x;
+            }
+            if let Some(x) = row[right..].iter().rposition(|p| pred(*p)) {
+                right = right + x + 1;
+            }
+        }
+        Some(Region::new(
+            left as i32,
+            top as i32,
+            (right - left) as u32,
+            (bottom - top) as u32,
+        ))
+    }
+
+    /// Find the bounding region of pixels which are not fully transparent.
+    ///
+    /// Returns `None` if all pixels have *alpha* of zero.  This can be used
+    /// with [crop](#method.crop) to trim transparent borders.
+    ///
+    /// ### Trim a sprite
+    /// ```
+    /// use pix::rgb::SRgba8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::<SRgba8>::with_clear(16, 16);
+    /// r.copy_color((4, 5, 6, 3), SRgba8::new(0xFF, 0, 0, 0x80));
+    /// let reg = r.bounds_nontransparent().unwrap();
+    /// let sprite = r.crop(reg);
+    /// assert_eq!((sprite.width(), sprite.height()), (6, 3));
+    /// ```
+    pub fn bounds_nontransparent(&self) -> Option<Region> {
+        self.bounds_where(|p| p.alpha() > P::Chan::MIN)
+    }
+
+    /// Find the bounding region of pixels which differ from a color.
+    ///
+    /// * `clr` Background color to exclude.
+    ///
+    /// Returns `None` if all pixels are equal to `clr`.
+    pub fn bounds_not(&self, clr: P) -> Option<Region> {
+        self.bounds_where(|p| p != clr)
+    }
+
+    /// Map pixels of a region to a new `Raster`, using a function.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `f` Function called with each source pixel, returning the
+    ///   destination pixel.
+    ///
+    /// The region is clipped to the `Raster` dimensions, and the result has
+    /// the same size as the clipped region.  The destination pixel format
+    /// can be different from the source.
+    ///
+    /// ### Make a threshold mask
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::gray::SGray8;
+    /// use pix::matte::Matte8;
+    /// use pix::Raster;
+    ///
+    /// let v = [0x10, 0x90, 0x80, 0xF0];
+    /// let r = Raster::<SGray8>::with_u8_buffer(2, 2, v);
+    /// let mask = r.map_into((), |p| {
+    ///     Matte8::new(if u8::from(p.one()) >= 0x80 { 0xFF } else { 0 })
+    /// });
+    /// assert_eq!(mask.as_u8_slice(), [0, 0xFF, 0xFF, 0xFF]);
+    /// ```
+    pub fn map_into<D, R, F>(&self, reg: R, mut f: F) -> Raster<D>
+    where
+        D: Pixel,
+        R: Into<Region>,
+        F: FnMut(P) -> D,
+    {
+        let reg = self.intersection(reg);
+        let mut r = Raster::with_clear(reg.width(), reg.height());
+        if !reg.is_empty() {
+            for (drow, srow) in r.rows_mut(()).zip(self.rows(reg)) {
+                for (d, s) in drow.iter_mut().zip(srow) {
+                    *d = f(*s);
+                }
+            }
+        }
+        r
+    }
+
+    /// Map pixels from a source `Raster`, using a function.
+    ///
+    /// * `to` Region within `self` (destination).
+    /// * `src` Source `Raster`.
+    /// * `from` Region within source `Raster`.
+    /// * `f` Function called with each source pixel, returning the
+    ///   destination pixel.
+    ///
+    /// Regions are clipped as with [copy_raster](#method.copy_raster).  The
+    /// source pixel format can be different from the destination.
+    ///
+    /// ### Copy *alpha* from an `SRgba8` raster
+    /// ```
+    /// use pix::el::Pixel;
+    /// use pix::matte::Matte8;
+    /// use pix::rgb::SRgba8;
+    /// use pix::Raster;
+    ///
+    /// let src = Raster::with_color(4, 4, SRgba8::new(0xFF, 0, 0, 0x80));
+    /// let mut r = Raster::<Matte8>::with_clear(8, 8);
+    /// r.map_region_from((2, 2), &src, (), |p| Matte8::new(p.alpha()));
+    /// assert_eq!(r.pixel(1, 1), Matte8::new(0));
+    /// assert_eq!(r.pixel(2, 2), Matte8::new(0x80));
+    /// ```
+    pub fn map_region_from<S, R0, R1, F>(
+        &mut self,
+        to: R0,
+        src: &Raster<S>,
+        from: R1,
+        mut f: F,
+    ) where
+        S: Pixel,
+        R0: Into<Region>,
+        R1: Into<Region>,
+        F: FnMut(S) -> P,
+    {
+        let (to, from) = self.clip_regions(to, src, from);
+        if !to.is_empty() {
+            let srows = src.rows(from);
+            let drows = self.rows_mut(to);
+            for (drow, srow) in drows.zip(srows) {
+                for (d, s) in drow.iter_mut().zip(srow) {
+                    *d = f(*s);
+                }
+            }
+        }
+    }
+
+    /// Extend to a new `Raster` with a border on each side.
+    ///
+    /// * `left` Width of left border.
+    /// * `top` Height of top border.
+    /// * `right` Width of right border.
+    /// * `bottom` Height of bottom border.
+    /// * `fill` Color of border pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resulting width or height is greater than
+    /// `std::i32::MAX`.
+    ///
+    /// ### Add a border
+    /// ```
+    /// use pix::rgb::SRgb8;
+    /// use pix::Raster;
+    ///
+    /// let r0 = Raster::<SRgb8>::with_clear(100, 100);
+    /// let r1 = r0.with_border(5, 5, 10, 10, SRgb8::new(0xFF, 0xFF, 0xFF));
+    /// assert_eq!((r1.width(), r1.height()), (115, 115));
+    /// ```
+    pub fn with_border(
+        &self,
+        left: u32,
+        top: u32,
+        right: u32,
+        bottom: u32,
+        fill: P,
+    ) -> Self {
+        let (w, h) = (self.width(), self.height());
+        let width = w
+            .checked_add(left)
+            .and_then(|w| w.checked_add(right))
+            .unwrap_or_else(|| {
+                panic!("Raster width too big: {w} + {left} + {right}")
+            });
+        let height = h
+            .checked_add(top)
+            .and_then(|h| h.checked_add(bottom))
+            .unwrap_or_else(|| {
+                panic!("Raster height too big: {h} + {top} + {bottom}")
+            });
+        let mut r = Raster::with_color(width, height, fill);
+        if !self.region().is_empty() {
+            // border fits, since `r` dimensions are valid
+            r.copy_raster((left as i32, top as i32), self, ());
+        }
+        r
+    }
+
+    /// Shift pixels in place.
+    ///
+    /// * `dx` Columns to shift right (negative to shift left).
+    /// * `dy` Rows to shift down (negative to shift up).
+    /// * `mode` Fill vacated pixels with a color, or wrap around.
+    ///
+    /// ### Scroll up one row
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::{Raster, ShiftMode};
+    ///
+    /// let mut r = Raster::<Gray8>::with_u8_buffer(2, 3, [1, 2, 3, 4, 5, 6]);
+    /// r.shift(0, -1, ShiftMode::Fill(Gray8::new(0)));
+    /// assert_eq!(r.as_u8_slice(), [3, 4, 5, 6, 0, 0]);
+    /// r.shift(1, 0, ShiftMode::Wrap);
+    /// assert_eq!(r.as_u8_slice(), [4, 3, 6, 5, 0, 0]);
+    /// ```
+    pub fn shift(&mut self, dx: i32, dy: i32, mode: ShiftMode<P>) {
+        let width = self.width as usize;
+        if self.pixels.is_empty() {
+            return;
+        }
+        if dy != 0 {
+            // shift whole rows at once
+            let n = shift_count(dy, self.height as usize, mode) * width;
+            shift_slice(&mut self.pixels, n, dy > 0, mode);
+        }
+        if dx != 0 {
+            let n = shift_count(dx, width, mode);
+            for row in self.pixels.chunks_exact_mut(width) {
+                shift_slice(row, n, dx > 0, mode);
+            }
+        }
+    }
+
+    /// Rotate to a new `Raster`, using nearest-neighbor sampling.
+    ///
+    /// * `radians` Angle of rotation, clockwise.
+    /// * `background` Color of pixels not covered by the rotated image.
+    ///
+    /// Rotation is about the center of the `Raster`.  The result is sized to
+    /// fit the rotated bounding box, rounded to the nearest pixel.  Each
+    /// destination pixel center is mapped back to the source pixel which
+    /// contains it, so multiples of 90 degrees are exact transformations.
+    ///
+    /// ### Deskew a scanned image
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::Raster;
+    ///
+    /// let r0 = Raster::with_color(100, 50, Gray8::new(0x20));
+    /// let r1 = r0.rotate(0.02, Gray8::new(0xFF));
+    /// assert_eq!((r1.width(), r1.height()), (101, 52));
+    /// assert_eq!(r1.pixel(0, 0), Gray8::new(0xFF));
+    /// assert_eq!(r1.pixel(50, 26), Gray8::new(0x20));
+    /// ```
+    pub fn rotate(&self, radians: f32, background: P) -> Self {
+        let (sin, cos) = (radians.sin(), radians.cos());
+        let (w, h) = (self.width as f32, self.height as f32);
+        let width = (w * cos.abs() + h * sin.abs()).round() as u32;
+        let height = (w * sin.abs() + h * cos.abs()).round() as u32;
+        let mut r = Raster::with_color(width, height, background);
+        if r.region().is_empty() {
+            return r;
+        }
+        let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+        for (y, row) in r.rows_mut(()).enumerate() {
+            let dy = y as f32 + 0.5 - cy;
+            for (x, p) in row.iter_mut().enumerate() {
+                let dx = x as f32 + 0.5 - cx;
+                // inverse rotation, back to source coordinates
+                let sx = (cos * dx + sin * dy + w / 2.0).floor();
+                let sy = (cos * dy - sin * dx + h / 2.0).floor();
+                if sx >= 0.0 && sx < w && sy >= 0.0 && sy < h {
+                    *p = self.pixel(sx as i32, sy as i32);
+                }
+            }
+        }
+        r
+    }
+
+    /// Downsample by a factor of two, using a 2x2 box filter.
+    ///
+    /// * `light` Mode for filtering color channels.
+    ///
+    /// Color is weighted by *alpha*: implicitly for *premultiplied* pixels,
+    /// and explicitly for *straight* ones.  With `Light::Linear`, sRGB
+    /// encoded color channels are decoded to linear light before filtering,
+    /// then re-encoded.  *Hue* channels are averaged as angles, wrapping
+    /// around.  For odd dimensions, the last row / column is repeated.
+    ///
+    /// ## Example
+    /// ```
+    /// use pix::rgb::{Rgba8p, SRgb8};
+    /// use pix::{Light, Raster};
+    ///
+    /// let r0 = Raster::<Rgba8p>::with_clear(64, 48);
+    /// let r1 = r0.halve(Light::Native);
+    /// assert_eq!((r1.width(), r1.height()), (32, 24));
+    ///
+    /// let mut r0 = Raster::<SRgb8>::with_clear(2, 1);
+    /// *r0.pixel_mut(0, 0) = SRgb8::new(0xFF, 0xFF, 0xFF);
+    /// let r1 = r0.halve(Light::Linear);
+    /// assert_eq!(r1.pixel(0, 0), SRgb8::new(0xBC, 0xBC, 0xBC));
+    /// ```
+    pub fn halve(&self, light: Light) -> Self {
+        let width = (self.width + 1) / 2;
+        let height = (self.height + 1) / 2;
+        let mut r = Raster::<P>::with_clear(width as u32, height as u32);
+        for y in 0..height {
+            let y0 = y * 2;
+            let y1 = (y0 + 1).min(self.height - 1);
+            for x in 0..width {
+                let x0 = x * 2;
+                let x1 = (x0 + 1).min(self.width - 1);
+                let px = [
+                    self.pixel(x0, y0),
+                    self.pixel(x1, y0),
+                    self.pixel(x0, y1),
+                    self.pixel(x1, y1),
+                ];
+                *r.pixel_mut(x, y) = box_filter(&px, light);
+            }
+        }
+        r
+    }
+
+    /// Clip `to` / `from` regions for source / destination rasters
+    ///
+    /// Pixel (`to.x + i`, `to.y + j`) corresponds with (`from.x + i`,
+    /// `from.y + j`).  The result is the range of (`i`, `j`) offsets within
+    /// both regions, where both pixels are inside their rasters.
+    fn clip_regions<R0, R1, Q>(
+        &self,
+        to: R0,
+        src: &Raster<Q>,
+        from: R1,
+    ) -> (Region, Region)
+    where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        Q: Pixel,
+    {
+        let (to, from) = (to.into(), from.into());
+        let (x0, x1) = clip_span(
+            (to.x, to.width, self.width),
+            (from.x, from.width, src.width),
+        );
+        let (y0, y1) = clip_span(
+            (to.y, to.height, self.height),
+            (from.y, from.height, src.height),
+        );
+        if x0 < x1 && y0 < y1 {
+            let (w, h) = ((x1 - x0) as u32, (y1 - y0) as u32);
+            let tx = (i64::from(to.x) + x0) as i32;
+            let ty = (i64::from(to.y) + y0) as i32;
+            let fx = (i64::from(from.x) + x0) as i32;
+            let fy = (i64::from(from.y) + y0) as i32;
+            (Region::new(tx, ty, w, h), Region::new(fx, fy, w, h))
+        } else {
+            (Region::default(), Region::default())
+        }
+    }
+
+    /// Get the contiguous range of pixels in a region spanning full rows
+    ///
+    /// Returns `None` unless the region is non-empty, starts at `x` = 0 and
+    /// covers the full width.
+    fn full_width_range(&self, reg: Region) -> Option<Range<usize>> {
+        if reg.x == 0 && reg.width == self.width && !reg.is_empty() {
+            let start = (reg.y * self.width) as usize;
+            Some(start..start + (reg.height * self.width) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Visit pixels of a destination region, with nearest-neighbor source
+    /// pixels from a (differently sized) source region.
+    ///
+    /// Destination pixels outside `self`, or which map to a source pixel
+    /// outside `src`, are skipped.
+    fn for_each_scaled<R0, R1, F>(
+        &mut self,
+        to: R0,
+        src: &Raster<P>,
+        from: R1,
+        mut f: F,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        F: FnMut(&mut P, &P),
+    {
+        let (to, from) = (to.into(), from.into());
+        let to = if to == Region::from(()) {
+            self.region()
+        } else {
+            to
+        };
+        let from = if from == Region::from(()) {
+            src.region()
+        } else {
+            from
+        };
+        if to.width == 0
+            || to.height == 0
+            || from.width == 0
+            || from.height == 0
+        {
+            return;
+        }
+        let clip = self.intersection(to);
+        let (tw, th) = (i64::from(to.width), i64::from(to.height));
+        let (fw, fh) = (i64::from(from.width), i64::from(from.height));
+        for y in clip.y..clip.bottom() {
+            let j = i64::from(y - to.y);
+            let sy = i64::from(from.y) + (2 * j + 1) * fh / (2 * th);
+            if sy < 0 || sy >= i64::from(src.height) {
+                continue;
+            }
+            for x in clip.x..clip.right() {
+                let i = i64::from(x - to.x);
+                let sx = i64::from(from.x) + (2 * i + 1) * fw / (2 * tw);
+                if sx < 0 || sx >= i64::from(src.width) {
+                    continue;
+                }
+                let s = &src.pixels[(sy * i64::from(src.width) + sx) as usize];
+                let d = (self.width * y + x) as usize;
+                f(&mut self.pixels[d], s);
+            }
+        }
+    }
+
+    /// Find the first pixel which is not valid *premultiplied*.
+    ///
+    /// Returns the (*x*, *y*) coordinate of the first pixel with a linear
+    /// color channel greater than *alpha*, or `None` if all are valid.
+    pub fn validate_premultiplied(&self) -> Option<(i32, i32)>
+    where
+        P: Pixel<Alpha = Premultiplied>,
+    {
+        self.pixels
+            .iter()
+            .position(|p| !p.is_premultiplied_valid())
+            .map(|i| {
+                let i = i as i32;
+                (i % self.width, i / self.width)
+            })
+    }
+
+    /// Split pixels into separate channel planes.
+    ///
+    /// One `Vec` is returned for each channel, containing `width` * `height`
+    /// values in row-major order.
+    pub fn to_planes(&self) -> Vec<Vec<P::Chan>> {
+        (0..P::CHANNELS)
+            .map(|i| self.pixels.iter().map(|p| p.channels()[i]).collect())
+            .collect()
+    }
+
+    /// Split pixels into separate channel planes of `u8` values.
+    pub fn to_u8_planes(&self) -> Vec<Vec<u8>>
+    where
+        P: Pixel<Chan = Ch8>,
+    {
+        let bytes = self.as_u8_slice();
+        let n_chan = P::CHANNELS;
+        if n_chan == 1 {
+            return vec![bytes.to_vec()];
+        }
+        let mut planes: Vec<Vec<u8>> = (0..n_chan)
+            .map(|_| Vec::with_capacity(self.pixels.len()))
+            .collect();
+        for px in bytes.chunks_exact(n_chan) {
+            for (plane, v) in planes.iter_mut().zip(px) {
+                plane.push(*v);
+            }
+        }
+        planes
+    }
+
+    /// Get view of pixels as a `u8` slice.
+    pub fn as_u8_slice(&self) -> &[u8] {
+        unsafe {
+            let (prefix, v, suffix) = self.pixels.align_to::<u8>();
+            debug_assert!(prefix.is_empty());
+            debug_assert!(suffix.is_empty());
+            v
+        }
+    }
+
+    /// Get a stable hash of the `Raster` contents.
+    ///
+    /// The 64-bit [FNV-1a] hash covers:
+    /// * Width and height
+    /// * Pixel format: channel count, size and
+    ///   [layout](el/trait.Pixel.html#method.channel_layout), color model
+    ///   channel names, *alpha* mode and gamma
+    /// * Channel values, each in little-endian byte order
+    ///
+    /// Channel values are hashed bit-exactly, so `Ch32` values `0.0` and
+    /// `-0.0` differ.  The same contents give the same hash on any platform,
+    /// and the algorithm will not change in patch releases, making this
+    /// suitable for golden-image regression tests.
+    ///
+    /// [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/
+    ///
+    /// ### Compare rasters by hash
+    /// ```
+    /// use pix::rgb::SRgb8;
+    /// use pix::Raster;
+    ///
+    /// let r0 = Raster::with_color(4, 4, SRgb8::new(1, 2, 3));
+    /// let mut r1 = r0.clone();
+    /// assert_eq!(r0.content_hash(), r1.content_hash());
+    /// *r1.pixel_mut(3, 3) = SRgb8::new(1, 2, 4);
+    /// assert_ne!(r0.content_hash(), r1.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        let mut hash = Fnv1a::default();
+        hash.write(&self.width().to_le_bytes());
+        hash.write(&self.height().to_le_bytes());
+        let size = core::mem::size_of::<P::Chan>();
+        hash.write(&[P::CHANNELS as u8, size as u8]);
+        for kind in P::channel_layout() {
+            hash.write(&[match kind {
+                ChannelKind::Linear => 0,
+                ChannelKind::Circular => 1,
+                ChannelKind::Alpha => 2,
+            }]);
+        }
+        for name in P::Model::CHANNEL_NAMES.iter().take(P::CHANNELS) {
+            hash.write(name.as_bytes());
+            hash.write(&[0]);
+        }
+        // Probe alpha mode and gamma by behavior, since type names are not
+        // stable
+        let (mid, min) = (P::Chan::MID, P::Chan::MIN);
+        let premultiplied = P::Alpha::encode(mid, min) != mid;
+        let srgb = P::Gamma::to_linear(mid) != mid;
+        hash.write(&[u8::from(premultiplied), u8::from(srgb)]);
+        for c in self.as_u8_slice().chunks_exact(size) {
+            if cfg!(target_endian = "little") {
+                hash.write(c);
+            } else {
+                c.iter().rev().for_each(|b| hash.write(&[*b]));
+            }
+        }
+        hash.0
+    }
+
+    /// Get view of pixels as a mutable `u8` slice.
+    pub fn as_u8_slice_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            let (prefix, v, suffix) = self.pixels.align_to_mut::<u8>();
+            debug_assert!(prefix.is_empty());
+            debug_assert!(suffix.is_empty());
+            v
+        }
+    }
+
+    /// Get view of pixels as a `u16` slice.
+    ///
+    /// Values are in native-endian byte order.
+    pub fn as_u16_slice(&self) -> &[u16]
+    where
+        P: Pixel<Chan = Ch16>,
+    {
+        unsafe {
+            let (prefix, v, suffix) = self.pixels.align_to::<u16>();
+            debug_assert!(prefix.is_empty());
+            debug_assert!(suffix.is_empty());
+            v
+        }
+    }
+
+    /// Get view of pixels as a mutable `u16` slice.
+    ///
+    /// Values are in native-endian byte order.
+    pub fn as_u16_slice_mut(&mut self) -> &mut [u16]
+    where
+        P: Pixel<Chan = Ch16>,
+    {
+        unsafe {
+            let (prefix, v, suffix) = self.pixels.align_to_mut::<u16>();
+            debug_assert!(prefix.is_empty());
+            debug_assert!(suffix.is_empty());
+            v
+        }
+    }
+
+    /// Get pixel data as big-endian bytes.
+    ///
+    /// Each channel is written in big-endian byte order, as required by
+    /// 16-bit PNG or big-endian TIFF.  For 8-bit channels, this is a plain
+    /// copy of [as_u8_slice](#method.as_u8_slice).
+    ///
+    /// ### Write 16-bit PNG data
+    /// ```
+    /// use pix::gray::Gray16;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::with_color(1, 1, Gray16::new(0x1234));
+    /// assert_eq!(r.to_be_bytes(), [0x12, 0x34]);
+    /// ```
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        self.to_endian_bytes(cfg!(target_endian = "little"))
+    }
+
+    /// Get pixel data as little-endian bytes.
+    ///
+    /// Each channel is written in little-endian byte order.  For 8-bit
+    /// channels, this is a plain copy of [as_u8_slice](#method.as_u8_slice).
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        self.to_endian_bytes(cfg!(target_endian = "big"))
+    }
+
+    /// Get pixel data as bytes, swapping channel byte order if needed
+    fn to_endian_bytes(&self, swap: bool) -> Vec<u8> {
+        let mut bytes = self.as_u8_slice().to_vec();
+        if swap {
+            swap_chan_bytes::<P::Chan>(&mut bytes);
+        }
+        bytes
+    }
+}
+
+impl<P> Raster<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+{
+    /// Composite a source color to a region of the `Raster`.
+    ///
+    /// * `reg` Region within `self`.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.  Using `()` has
+    ///   the same result as `Raster::region()`.
+    /// * `clr` Source `Pixel` color.
+    /// * `op` Compositing operation.
+    ///
+    /// ### Example
+    /// ```
+    /// use pix::ops::SrcOver;
+    /// use pix::bgr::Bgra8p;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_color(100, 100, Bgra8p::new(99, 0, 99, 255));
+    /// let clr = Bgra8p::new(200, 200, 0, 128);
+    /// r.composite_color((20, 40, 25, 50), clr, SrcOver);
+    /// ```
+    pub fn composite_color<R, O>(&mut self, reg: R, clr: P, op: O)
+    where
+        R: Into<Region>,
+        O: Blend,
+    {
+        let reg = self.intersection(reg.into());
+        if !reg.is_empty() {
+            let drows = self.rows_mut(reg);
+            for drow in drows {
+                P::composite_color(drow, &clr, op);
+            }
+        }
+    }
+
+    /// Composite a source color of any format to a region of the `Raster`.
+    ///
+    /// The color is converted to `P` once, then composited as with
+    /// [composite_color](#method.composite_color).
+    ///
+    /// ### Composite a straight alpha color
+    /// ```
+    /// use pix::ops::SrcOver;
+    /// use pix::rgb::{Rgba8p, SRgb8};
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::<Rgba8p>::with_clear(10, 10);
+    /// r.composite_color_from((), SRgb8::new(0xFF, 0xFF, 0xFF), SrcOver);
+    /// assert_eq!(r.pixel(5, 5), Rgba8p::new(0xFF, 0xFF, 0xFF, 0xFF));
+    /// ```
+    pub fn composite_color_from<R, C, O>(&mut self, reg: R, clr: C, op: O)
+    where
+        R: Into<Region>,
+        C: Pixel,
+        P::Chan: From<C::Chan>,
+        O: Blend,
+    {
+        self.composite_color(reg, clr.convert(), op);
+    }
+
+    /// Composite from a matte `Raster` and color.
+    ///
+    /// * `to` Region within `self` (destination).
+    /// * `src` Source `Raster` matte.
+    /// * `from` Region within source `Raster`.
+    /// * `clr` Color to apply to the matte.
+    /// * `op` Compositing operation.
+    ///
+    /// `to` / `from` can be `Region` structs, tuples of (*x*, *y*, *width*,
+    /// *height*) or the unit type `()`.  Using `()` has the same result as
+    /// `Raster::region()`.
+    ///
+    /// This is a masked fill: each pixel of `clr` (including its *alpha*)
+    /// is multiplied by the matte value before compositing, without making a
+    /// temporary color raster.
+    ///
+    /// The matte can have a different bit depth than `self`; its *alpha* is
+    /// converted one pixel at a time.  Matte values are always interpreted as
+    /// linear coverage, regardless of the matte's `Gamma` type.
+    ///
+    /// ### Example
+    /// ```
+    /// use pix::matte::Matte8;
+    /// use pix::ops::SrcOver;
+    /// use pix::rgb::Rgba8p;
+    /// use pix::Raster;
+    ///
+    /// let mut r0 = Raster::with_clear(100, 100);
+    /// let r1 = Raster::with_color(10, 10, Matte8::new(37));
+    /// // ... load image data
+    /// let clr = Rgba8p::new(50, 100, 150, 200);
+    /// r0.composite_matte((30, 50, 10, 10), &r1, (), clr, SrcOver);
+    /// ```
+    pub fn composite_matte<R0, R1, M, O>(
+        &mut self,
+        to: R0,
+        src: &Raster<M>,
+        from: R1,
+        clr: P,
+        op: O,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        M: Pixel<Model = Matte>,
+        P::Chan: From<M::Chan>,
+        O: Blend,
+    {
+        let (to, from) = self.clip_regions(to, src, from);
+        let srows = src.rows(from);
+        let drows = self.rows_mut(to);
+        for (drow, srow) in drows.zip(srows) {
+            P::composite_matte(drow, srow, &clr, op);
+        }
+    }
+
+    /// Composite a color through a matte `Raster` (masked fill).
+    ///
+    /// * `reg` Region within `self` (destination).
+    /// * `clr` Color to fill.
+    /// * `matte` Matte `Raster` used as a clip mask.
+    /// * `matte_reg` Region within `matte`.
+    /// * `op` Compositing operation.
+    ///
+    /// The color (including its *alpha*) is multiplied by the matte value at
+    /// each pixel while compositing, without making a temporary color
+    /// raster.  Both regions are clipped the same way as
+    /// [composite_matte](#method.composite_matte), which this is equivalent
+    /// to, with arguments in fill order.
+    ///
+    /// ### Fill a circle
+    /// ```
+    /// use pix::matte::Matte8;
+    /// use pix::ops::SrcOver;
+    /// use pix::rgb::Rgba8p;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_clear(16, 16);
+    /// let mut circle = Raster::<Matte8>::with_clear(16, 16);
+    /// // ... render circle coverage
+    /// circle.copy_color((4, 4, 8, 8), Matte8::new(0xFF));
+    /// let clr = Rgba8p::new(0x40, 0x20, 0x80, 0xFF);
+    /// r.composite_color_masked((), clr, &circle, (), SrcOver);
+    /// assert_eq!(r.pixel(0, 0), Rgba8p::default());
+    /// assert_eq!(r.pixel(6, 6), clr);
+    /// ```
+    pub fn composite_color_masked<R0, R1, M, O>(
+        &mut self,
+        reg: R0,
+        clr: P,
+        matte: &Raster<M>,
+        matte_reg: R1,
+        op: O,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        M: Pixel<Model = Matte>,
+        P::Chan: From<M::Chan>,
+        O: Blend,
+    {
+        self.composite_matte(reg, matte, matte_reg, clr, op);
+    }
+
+    /// Composite from a matte `Raster` with inside and outside colors.
+    ///
+    /// * `to` Region within `self` (destination).
+    /// * `src` Source `Raster` matte.
+    /// * `from` Region within source `Raster`.
+    /// * `inside` Color to apply to the matte.
+    /// * `outside` Color to apply to the inverse of the matte.
+    /// * `op` Compositing operation.
+    ///
+    /// The result is the same as calling
+    /// [composite_matte](#method.composite_matte) with `inside`, then again
+    /// with `outside` and an inverted matte, but the destination is only
+    /// read and written once.  This is useful for two-tone rendering, such
+    /// as a shape cut out of a fill.
+    ///
+    /// ### Cut out a shape
+    /// ```
+    /// use pix::matte::Matte8;
+    /// use pix::ops::DestOut;
+    /// use pix::rgb::Rgba8p;
+    /// use pix::Raster;
+    ///
+    /// let fill = Rgba8p::new(0x20, 0x40, 0x80, 0xFF);
+    /// let mut r = Raster::with_color(8, 8, fill);
+    /// let mut shape = Raster::<Matte8>::with_clear(8, 8);
+    /// shape.copy_color((2, 2, 4, 4), Matte8::new(0xFF));
+    /// let cut = Rgba8p::new(0, 0, 0, 0xFF);
+    /// let keep = Rgba8p::default();
+    /// r.composite_matte_pair((), &shape, (), cut, keep, DestOut);
+    /// assert_eq!(r.pixel(0, 0), fill);
+    /// assert_eq!(r.pixel(3, 3), Rgba8p::default());
+    /// ```
+    pub fn composite_matte_pair<R0, R1, M, O>(
+        &mut self,
+        to: R0,
+        src: &Raster<M>,
+        from: R1,
+        inside: P,
+        outside: P,
+        op: O,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        M: Pixel<Model = Matte>,
+        P::Chan: From<M::Chan>,
+        O: Blend,
+    {
+        let (to, from) = self.clip_regions(to, src, from);
+        let srows = src.rows(from);
+        let drows = self.rows_mut(to);
+        for (drow, srow) in drows.zip(srows) {
+            P::composite_matte_pair(drow, srow, &inside, &outside, op);
+        }
+    }
+
+    /// Composite from a single-channel coverage `Raster` and color.
+    ///
+    /// * `to` Region within `self` (destination).
+    /// * `src` Source coverage `Raster`.
+    /// * `from` Region within source `Raster`.
+    /// * `clr` Color to apply to the coverage.
+    /// * `op` Compositing operation.
+    ///
+    /// This is like [composite_matte](#method.composite_matte), but accepts
+    /// any single-channel pixel format, such as [Gray8] output from a text
+    /// rasterizer.  The channel value is used as linear coverage, ignoring
+    /// its *luma* meaning and `Gamma` type.
+    ///
+    /// [Gray8]: gray/type.Gray8.html
+    ///
+    /// ### Example
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::ops::SrcOver;
+    /// use pix::rgb::Rgba8p;
+    /// use pix::Raster;
+    ///
+    /// let mut r0 = Raster::with_clear(100, 100);
+    /// let r1 = Raster::with_color(10, 10, Gray8::new(37));
+    /// let clr = Rgba8p::new(50, 100, 150, 200);
+    /// r0.composite_coverage((30, 50, 10, 10), &r1, (), clr, SrcOver);
+    /// ```
+    pub fn composite_coverage<R0, R1, C, M, A, G, O>(
+        &mut self,
+        to: R0,
+        src: &Raster<Pix1<C, M, A, G>>,
+        from: R1,
+        clr: P,
+        op: O,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        C: Channel,
+        M: ColorModel,
+        A: Alpha,
+        G: Gamma,
+        P::Chan: From<C>,
+        O: Blend,
+    {
+        let (to, from) = self.clip_regions(to, src, from);
+        let srows = src.rows(from);
+        let drows = self.rows_mut(to);
+        for (drow, srow) in drows.zip(srows) {
+            P::composite_coverage(drow, srow, &clr, op);
+        }
+    }
+
+    /// Composite from a source `Raster`.
+    ///
+    /// * `to` Region within `self` (destination).
+    /// * `src` Source `Raster`.
+    /// * `from` Region within source `Raster`.
+    /// * `op` Compositing operation.
+    ///
+    /// `to` / `from` can be `Region` structs, tuples of (*x*, *y*, *width*,
+    /// *height*) or the unit type `()`.  Using `()` has the same result as
+    /// `Raster::region()`.
+    ///
+    /// ```bob
+    /// *------------+      *-------------+
+    /// |            |      |    *------+ |
+    /// | *------+   |      |    |      | |
+    /// | |      |   |      |    | from | |
+    /// | |  to  |   | <--- |    +------+ |
+    /// | +------+   |      |             |
+    /// |            |      |     src     |
+    /// |    self    |      +-------------+
+    /// +------------+
+    /// ```
+    /// The top-left corners of `to` and `from` are aligned, so pixel
+    /// (`to.x + i`, `to.y + j`) is composited with
+    /// (`from.x + i`, `from.y + j`).  Only pixels within both regions, and
+    /// inside both rasters, are composited.
+    /// Negative `to` / `from` coordinates skip the corresponding offsets.
+    ///
+    /// ### Blend one `Raster` onto another
+    /// ```
+    /// use pix::ops::SrcOver;
+    /// use pix::rgb::Rgba8p;
+    /// use pix::Raster;
+    ///
+    /// let mut r0 = Raster::with_clear(100, 100);
+    /// let r1 = Raster::with_color(5, 5, Rgba8p::new(80, 0, 80, 200));
+    /// // ... load image data
+    /// r0.composite_raster((40, 40), &r1, (), SrcOver);
+    /// ```
+    pub fn composite_raster<R0, R1, O>(
+        &mut self,
+        to: R0,
+        src: &Raster<P>,
+        from: R1,
+        op: O,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        O: Blend,
+    {
+        let (to, from) = self.clip_regions(to, src, from);
+        if let (Some(d), Some(s)) =
+            (self.full_width_range(to), src.full_width_range(from))
+        {
+            P::composite_slice(&mut self.pixels[d], &src.pixels[s], op);
+            return;
+        }
+        let srows = src.rows(from);
+        let drows = self.rows_mut(to);
+        for (drow, srow) in drows.zip(srows) {
+            P::composite_slice(drow, srow, op);
+        }
+    }
+
+    /// Composite from a source `Raster` with or without an *alpha* channel.
+    ///
+    /// * `to` Region within `self` (destination).
+    /// * `src` Source `Raster`.
+    /// * `from` Region within source `Raster`.
+    /// * `op` Compositing operation.
+    ///
+    /// Regions are clipped and aligned as in
+    /// [composite_raster](#method.composite_raster).  The source must have
+    /// the same color model and channel type, but either format may lack an
+    /// *alpha* channel.  This allows compositing translucent sprites onto an
+    /// opaque destination, such as [Rgb8p](rgb/type.Rgb8p.html), which
+    /// is treated as having *alpha* of
+    /// [MAX](chan/trait.Channel.html#associatedconstant.MAX).
+    ///
+    /// ### Blend onto an opaque framebuffer
+    /// ```
+    /// use pix::ops::SrcOver;
+    /// use pix::rgb::{Rgb8p, Rgba8p};
+    /// use pix::Raster;
+    ///
+    /// let mut fb = Raster::with_color(100, 100, Rgb8p::new(0, 0, 100));
+    /// let sprite = Raster::with_color(5, 5, Rgba8p::new(100, 0, 0, 128));
+    /// fb.composite_raster_from((40, 40), &sprite, (), SrcOver);
+    /// assert_eq!(fb.pixel(42, 42), Rgb8p::new(100, 0, 50));
+    /// ```
+    pub fn composite_raster_from<R0, R1, S, O>(
+        &mut self,
+        to: R0,
+        src: &Raster<S>,
+        from: R1,
+        op: O,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        S: Pixel<
+            Chan = P::Chan,
+            Model = P::Model,
+            Alpha = Premultiplied,
+            Gamma = Linear,
+        >,
+        O: Blend,
+    {
+        let (to, from) = self.clip_regions(to, src, from);
+        if let (Some(d), Some(s)) =
+            (self.full_width_range(to), src.full_width_range(from))
+        {
+            P::composite_slice_from(&mut self.pixels[d], &src.pixels[s], op);
+            return;
+        }
+        let srows = src.rows(from);
+        let drows = self.rows_mut(to);
+        for (drow, srow) in drows.zip(srows) {
+            P::composite_slice_from(drow, srow, op);
+        }
+    }
+
+    /// Composite one region of `self` onto another region of `self`.
+    ///
+    /// * `to` Destination region.
+    /// * `from` Source region.
+    /// * `op` Compositing operation.
+    ///
+    /// Regions are clipped and aligned as in
+    /// [composite_raster](#method.composite_raster), as if `src` were an
+    /// unmodified copy of `self`.  The regions may overlap; each source row
+    /// is read before any destination pixel it covers is written.
+    ///
+    /// ### Drop shadow from the same raster
+    /// ```
+    /// use pix::ops::SrcOver;
+    /// use pix::rgb::Rgba8p;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_clear(16, 16);
+    /// r.copy_color((2, 2, 8, 8), Rgba8p::new(0, 0, 0, 128));
+    /// r.composite_self((4, 4, 8, 8), (2, 2, 8, 8), SrcOver);
+    /// assert_eq!(r.pixel(3, 3), Rgba8p::new(0, 0, 0, 128));
+    /// assert_eq!(r.pixel(11, 11), Rgba8p::new(0, 0, 0, 128));
+    /// assert_eq!(r.pixel(5, 5), Rgba8p::new(0, 0, 0, 192));
+    /// ```
+    pub fn composite_self<R0, R1, O>(&mut self, to: R0, from: R1, op: O)
+    where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        O: Blend,
+    {
+        let (to, from) = self.clip_regions(to, &*self, from);
+        if to.is_empty() {
+            return;
+        }
+        let width = self.width as usize;
+        let len = to.width as usize;
+        let mut buf = Vec::with_capacity(len);
+        let mut composite_row = |pixels: &mut [P], j: i32| {
+            let s = (from.y + j) as usize * width + from.x as usize;
+            let d = (to.y + j) as usize * width + to.x as usize;
+            buf.clear();
+            buf.extend_from_slice(&pixels[s..s + len]);
+            P::composite_slice(&mut pixels[d..d + len], &buf, op);
+        };
+        // Rows moving down are done bottom-up, so sources are read first
+        if to.y > from.y {
+            for j in (0..to.height).rev() {
+                composite_row(&mut self.pixels, j);
+            }
+        } else {
+            for j in 0..to.height {
+                composite_row(&mut self.pixels, j);
+            }
+        }
+    }
+
+    /// Composite from a source `Raster`, scaling with nearest-neighbor
+    /// sampling.
+    ///
+    /// * `to` Region within `self` (destination).
+    /// * `src` Source `Raster`.
+    /// * `from` Region within source `Raster`.
+    /// * `op` Compositing operation.
+    ///
+    /// Regions are mapped as in
+    /// [copy_raster_scaled](#method.copy_raster_scaled), without allocating
+    /// a scaled copy of the source.
+    ///
+    /// ### Draw a scaled sprite
+    /// ```
+    /// use pix::ops::SrcOver;
+    /// use pix::rgb::Rgba8p;
+    /// use pix::Raster;
+    ///
+    /// let mut r0 = Raster::with_color(100, 100, Rgba8p::new(0, 0, 0, 255));
+    /// let sprite = Raster::with_color(16, 16, Rgba8p::new(80, 0, 80, 255));
+    /// r0.composite_raster_scaled((10, 10, 40, 24), &sprite, (), SrcOver);
+    /// assert_eq!(r0.pixel(49, 33), Rgba8p::new(80, 0, 80, 255));
+    /// ```
+    pub fn composite_raster_scaled<R0, R1, O>(
+        &mut self,
+        to: R0,
+        src: &Raster<P>,
+        from: R1,
+        op: O,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        O: Blend,
+    {
+        self.for_each_scaled(to, src, from, |d, s| d.composite_channels(s, op));
+    }
+
+    /// Composite a color with an operation selected at runtime.
+    ///
+    /// This is the same as [composite_color](#method.composite_color), but
+    /// dispatches on a [BlendOp](ops/enum.BlendOp.html).
+    ///
+    /// ### Select an operation at runtime
+    /// ```
+    /// use pix::ops::BlendOp;
+    /// use pix::rgb::Rgba8p;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::with_color(4, 4, Rgba8p::new(0x40, 0x80, 0, 0xFF));
+    /// let clr = Rgba8p::new(0x80, 0x40, 0, 0xFF);
+    /// r.composite_color_dyn((), clr, BlendOp::Darken);
+    /// assert_eq!(r.pixel(0, 0), Rgba8p::new(0x40, 0x40, 0, 0xFF));
+    /// ```
+    pub fn composite_color_dyn<R>(&mut self, reg: R, clr: P, op: BlendOp)
+    where
+        R: Into<Region>,
+    {
+        with_blend_op!(op, o => self.composite_color(reg, clr, o))
+    }
+
+    /// Composite from a matte `Raster` and color, with an operation selected
+    /// at runtime.
+    ///
+    /// This is the same as [composite_matte](#method.composite_matte), but
+    /// dispatches on a [BlendOp](ops/enum.BlendOp.html).
+    pub fn composite_matte_dyn<R0, R1, M>(
+        &mut self,
+        to: R0,
+        src: &Raster<M>,
+        from: R1,
+        clr: P,
+        op: BlendOp,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+        M: Pixel<Model = Matte>,
+        P::Chan: From<M::Chan>,
+    {
+        with_blend_op!(op, o => self.composite_matte(to, src, from, clr, o))
+    }
+
+    /// Composite from a source `Raster`, with an operation selected at
+    /// runtime.
+    ///
+    /// This is the same as [composite_raster](#method.composite_raster), but
+    /// dispatches on a [BlendOp](ops/enum.BlendOp.html).
+    pub fn composite_raster_dyn<R0, R1>(
+        &mut self,
+        to: R0,
+        src: &Raster<P>,
+        from: R1,
+        op: BlendOp,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+    {
+        with_blend_op!(op, o => self.composite_raster(to, src, from, o))
+    }
+}
+
+impl<C, M, A, G> Raster<Pix1<C, M, A, G>>
+where
+    C: Channel,
+    M: ColorModel,
+    A: Alpha,
+    G: Gamma,
+{
+    /// Map values through a colormap, such as for a heatmap.
+    ///
+    /// * `map` Colormap entries, from `MIN` to `MAX` value.
+    ///
+    /// This works for single-channel rasters, such as `Gray` or `Matte`.
+    /// Values between entries are linearly interpolated, channel by channel
+    /// as encoded in `D`.  An 8-bit raster is mapped through a 256-entry
+    /// lookup table, which is `map` itself if it has 256 entries.  A map
+    /// with one entry produces a solid color.
+    ///
+    /// # Panics
+    ///
+    /// If `map` is empty.
+    ///
+    /// ### Red to blue gradient
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::rgb::SRgb8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::<Gray8>::with_clear(2, 1);
+    /// *r.pixel_mut(1, 0) = Gray8::new(0xFF);
+    /// let map = [SRgb8::new(0xFF, 0, 0), SRgb8::new(0, 0, 0xFF)];
+    /// let heat = r.apply_colormap(&map);
+    /// assert_eq!(heat.pixel(0, 0), SRgb8::new(0xFF, 0, 0));
+    /// assert_eq!(heat.pixel(1, 0), SRgb8::new(0, 0, 0xFF));
+    /// ```
+    pub fn apply_colormap<D: Pixel>(&self, map: &[D]) -> Raster<D> {
+        assert!(!map.is_empty(), "Empty colormap");
+        if C::INTEGER_BITS == 8 {
+            let lut: Vec<D> = if map.len() == 256 {
+                map.to_vec()
+            } else {
+                (0..=255)
+                    .map(|i| colormap_sample(map, f32::from(i as u8) / 255.0))
+                    .collect()
+            };
+            self.map_into((), |p| {
+                lut[(p.one().to_f32() * 255.0).round() as usize]
+            })
+        } else {
+            self.map_into((), |p| colormap_sample(map, p.one().to_f32()))
+        }
+    }
+}
+
+impl<M> Raster<M>
+where
+    M: Pixel<Model = Matte>,
+{
+    /// Draw a line, setting each pixel on it to a value.
+    ///
+    /// * `x0` / `y0` Starting point.
+    /// * `x1` / `y1` Ending point.
+    /// * `value` Matte value to set.
+    ///
+    /// Both endpoints are included.  Pixels outside the `Raster` are
+    /// clipped.
+    ///
+    /// ### Draw a diagonal line
+    /// ```
+    /// use pix::matte::Matte8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::<Matte8>::with_clear(8, 8);
+    /// r.draw_line(0, 0, 7, 7, Matte8::new(0xFF));
+    /// assert_eq!(r.pixel(3, 3), Matte8::new(0xFF));
+    /// assert_eq!(r.pixel(3, 4), Matte8::new(0));
+    /// ```
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, value: M) {
+        let (x0, y0) = (i128::from(x0), i128::from(y0));
+        let (dx, dy) = (i128::from(x1) - x0, i128::from(y1) - y0);
+        let (ax, ay) = (dx.abs(), dy.abs());
+        let n = ax.max(ay);
+        // only step along the part of the major axis within the raster
+        let (lo, hi) = if ax >= ay {
+            line_steps(x0, dx.signum(), n, self.width)
+        } else {
+            line_steps(y0, dy.signum(), n, self.height)
+        };
+        let (w, h) = (i128::from(self.width), i128::from(self.height));
+        for i in lo..=hi {
+            // round to nearest, which matches Bresenham's algorithm
+            let (x, y) = if n > 0 {
+                let x = x0 + dx.signum() * ((2 * i * ax + n) / (2 * n));
+                let y = y0 + dy.signum() * ((2 * i * ay + n) / (2 * n));
+                (x, y)
+            } else {
+                (x0, y0)
+            };
+            if (0..w).contains(&x) && (0..h).contains(&y) {
+                *self.pixel_mut(x as i32, y as i32) = value;
+            }
+        }
+    }
+
+    /// Draw the outline of a rectangle, setting each pixel to a value.
+    ///
+    /// * `reg` Rectangle to outline.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `value` Matte value to set.
+    ///
+    /// The outline is one pixel wide, just inside `reg`.  Pixels outside the
+    /// `Raster` are clipped.
+    ///
+    /// ### Outline a rectangle
+    /// ```
+    /// use pix::matte::Matte8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::<Matte8>::with_clear(8, 8);
+    /// r.draw_rect((2, 2, 4, 4), Matte8::new(0xFF));
+    /// assert_eq!(r.pixel(2, 5), Matte8::new(0xFF));
+    /// assert_eq!(r.pixel(3, 3), Matte8::new(0));
+    /// ```
+    pub fn draw_rect<R>(&mut self, reg: R, value: M)
+    where
+        R: Into<Region>,
+    {
+        let reg = reg.into();
+        if !reg.is_empty() {
+            let (x, y, w, h) = (reg.x, reg.y, reg.width(), reg.height());
+            self.copy_color((x, y, w, 1), value);
+            self.copy_color((x, reg.bottom() - 1, w, 1), value);
+            self.copy_color((x, y, 1, h), value);
+            self.copy_color((reg.right() - 1, y, 1, h), value);
+        }
+    }
+
+    /// Fill a rectangle, setting each pixel to a value.
+    ///
+    /// * `reg` Rectangle to fill.  It can be a `Region` struct, tuple of
+    ///   (*x*, *y*, *width*, *height*) or the unit type `()`.
+    /// * `value` Matte value to set.
+    ///
+    /// This is equivalent to [copy_color](#method.copy_color).
+    pub fn fill_rect<R>(&mut self, reg: R, value: M)
+    where
+        R: Into<Region>,
+    {
+        self.copy_color(reg, value);
+    }
+}
+
+impl Raster<SRgb8> {
+    /// Construct a `Raster` from packed RGB565 pixels.
+    ///
+    /// Each `u16` has *red* in bits 11-15, *green* in bits 5-10 and *blue* in
+    /// bits 0-4.  Channels are expanded to 8 bits by replicating high bits.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `packed` Packed pixel data.
+    ///
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    /// * If `packed` length is not equal to `width` * `height`
+    pub fn with_rgb565(width: u32, height: u32, packed: &[u16]) -> Self {
+        let mut r = Self::with_clear(width, height);
+        assert_eq!(r.pixels.len(), packed.len());
+        for (p, v) in r.pixels.iter_mut().zip(packed) {
+            let red = expand_bits(v >> 11, 5);
+            let green = expand_bits(v >> 5, 6);
+            let blue = expand_bits(*v, 5);
+            *p = SRgb8::new(red, green, blue);
+        }
+        r
+    }
+
+    /// Convert to packed RGB565 pixels.
+    ///
+    /// Channels are rounded to the nearest value.
+    ///
+    /// ## Example
+    /// ```
+    /// use pix::rgb::SRgb8;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::with_color(2, 2, SRgb8::new(0xFF, 0x00, 0xFF));
+    /// assert_eq!(r.to_rgb565(), [0xF81F; 4]);
+    /// ```
+    pub fn to_rgb565(&self) -> Vec<u16> {
+        self.pixels
+            .iter()
+            .map(|p| {
+                let red = reduce_bits(Rgb::red(*p), 5) << 11;
+                let green = reduce_bits(Rgb::green(*p), 6) << 5;
+                let blue = reduce_bits(Rgb::blue(*p), 5);
+                red | green | blue
+            })
+            .collect()
+    }
+
+    /// Blend a *straight* alpha source onto this opaque `Raster`.
+    ///
+    /// * `to` Region within `self` (destination).
+    /// * `src` Source `Raster`, with *straight* alpha.
+    /// * `from` Region within source `Raster`.
+    ///
+    /// Regions are clipped as with [composite_raster].  Each pixel is
+    /// converted to [Rgba8p], blended with [SrcOver], then converted back to
+    /// sRGB.  This gives the same result as converting both rasters,
+    /// compositing and converting back, without allocating any intermediate
+    /// rasters.  Pixels where the source is fully transparent or opaque are
+    /// not round-tripped through linear gamma, so they are exact.
+    ///
+    /// ### Paste a translucent sticker onto a photo
+    /// ```
+    /// use pix::rgb::{SRgb8, SRgba8};
+    /// use pix::Raster;
+    ///
+    /// let mut photo = Raster::with_color(100, 100, SRgb8::new(0, 0, 0xFF));
+    /// let sticker = Raster::with_color(10, 10, SRgba8::new(0xFF, 0, 0, 0x80));
+    /// photo.blend_straight((45, 45), &sticker, ());
+    /// assert_eq!(photo.pixel(50, 50), SRgb8::new(0xBC, 0, 0xBB));
+    /// ```
+    /// [composite_raster]: #method.composite_raster
+    /// [Rgba8p]: rgb/type.Rgba8p.html
+    /// [SrcOver]: ops/struct.SrcOver.html
+    pub fn blend_straight<R0, R1>(
+        &mut self,
+        to: R0,
+        src: &Raster<SRgba8>,
+        from: R1,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+    {
+        let (to, from) = self.clip_regions(to, src, from);
+        if to.is_empty() {
+            return;
+        }
+        let srows = src.rows(from);
+        let drows = self.rows_mut(to);
+        for (drow, srow) in drows.zip(srows) {
+            for (d, s) in drow.iter_mut().zip(srow) {
+                let alpha = s.alpha();
+                if alpha == Ch8::MAX {
+                    *d = s.convert();
+                } else if alpha > Ch8::MIN {
+                    let mut dp: Rgba8p = d.convert();
+                    dp.composite_channels(&s.convert(), SrcOver);
+                    *d = dp.convert();
+                }
+            }
+        }
+    }
+
+    /// Make a translucent `Raster` by chroma keying (green screen).
+    ///
+    /// * `key` Key color, to become transparent.
+    /// * `tolerance` Chroma distance from `key` which is fully transparent.
+    /// * `softness` Chroma distance beyond `tolerance` for a linear ramp up
+    ///   to fully opaque.
+    ///
+    /// Distance is measured in the *Cb* / *Cr* chroma plane of [YCbCr],
+    /// using gamma-encoded values as in JPEG.  It ranges from 0 to about
+    /// 0.7.  To reduce key color spill on the remaining pixels, use
+    /// [despill].
+    ///
+    /// ### Remove a green background
+    /// ```
+    /// use pix::chan::Ch8;
+    /// use pix::el::Pixel;
+    /// use pix::rgb::SRgb8;
+    /// use pix::Raster;
+    ///
+    /// let green = SRgb8::new(0, 0xFF, 0);
+    /// let mut r = Raster::with_color(10, 10, green);
+    /// r.copy_color((2, 2, 6, 6), SRgb8::new(0xC0, 0x40, 0x40));
+    /// let keyed = r.chroma_key(green, 0.1, 0.1);
+    /// assert_eq!(keyed.pixel(0, 0).alpha(), Ch8::new(0));
+    /// assert_eq!(keyed.pixel(5, 5).alpha(), Ch8::new(0xFF));
+    /// ```
+    /// [despill]: #method.despill
+    /// [YCbCr]: ycc/struct.YCbCr.html
+    pub fn chroma_key(
+        &self,
+        key: SRgb8,
+        tolerance: f32,
+        softness: f32,
+    ) -> Raster<SRgba8> {
+        let k = srgb_ycc(key);
+        self.map_into((), |p| {
+            let c = srgb_ycc(p);
+            let d = (YCbCr::cb(c).to_f32() - YCbCr::cb(k).to_f32())
+                .hypot(YCbCr::cr(c).to_f32() - YCbCr::cr(k).to_f32());
+            let alpha = if d <= tolerance {
+                0.0
+            } else if d >= tolerance + softness {
+                1.0
+            } else {
+                (d - tolerance) / softness
+            };
+            SRgba8::new(
+                Rgb::red(p),
+                Rgb::green(p),
+                Rgb::blue(p),
+                Ch8::from(alpha),
+            )
+        })
+    }
+}
+
+impl Raster<SRgba8> {
+    /// Construct a `Raster` from packed RGBA5551 pixels.
+    ///
+    /// Each `u16` has *red* in bits 11-15, *green* in bits 6-10, *blue* in
+    /// bits 1-5 and *alpha* in bit 0.  Channels are expanded to 8 bits by
+    /// replicating high bits.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `packed` Packed pixel data.
+    ///
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    /// * If `packed` length is not equal to `width` * `height`
+    pub fn with_rgba5551(width: u32, height: u32, packed: &[u16]) -> Self {
+        let mut r = Self::with_clear(width, height);
+        assert_eq!(r.pixels.len(), packed.len());
+        for (p, v) in r.pixels.iter_mut().zip(packed) {
+            let red = expand_bits(v >> 11, 5);
+            let green = expand_bits(v >> 6, 5);
+            let blue = expand_bits(v >> 1, 5);
+            let alpha = expand_bits(*v, 1);
+            *p = SRgba8::new(red, green, blue, alpha);
+        }
+        r
+    }
+
+    /// Convert to packed RGBA5551 pixels.
+    ///
+    /// Channels are rounded to the nearest value.
+    pub fn to_rgba5551(&self) -> Vec<u16> {
+        self.pixels
+            .iter()
+            .map(|p| {
+                let red = reduce_bits(Rgb::red(*p), 5) << 11;
+                let green = reduce_bits(Rgb::green(*p), 5) << 6;
+                let blue = reduce_bits(Rgb::blue(*p), 5) << 1;
+                let alpha = reduce_bits(p.alpha(), 1);
+                red | green | blue | alpha
+            })
+            .collect()
+    }
+
+    /// Construct a `Raster` from packed RGBA4444 pixels.
+    ///
+    /// Each `u16` has *red* in bits 12-15, *green* in bits 8-11, *blue* in
+    /// bits 4-7 and *alpha* in bits 0-3.  Channels are expanded to 8 bits by
+    /// replicating high bits.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `packed` Packed pixel data.
+    ///
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    /// * If `packed` length is not equal to `width` * `height`
+    pub fn with_rgba4444(width: u32, height: u32, packed: &[u16]) -> Self {
+        let mut r = Self::with_clear(width, height);
+        assert_eq!(r.pixels.len(), packed.len());
+        for (p, v) in r.pixels.iter_mut().zip(packed) {
+            let red = expand_bits(v >> 12, 4);
+            let green = expand_bits(v >> 8, 4);
+            let blue = expand_bits(v >> 4, 4);
+            let alpha = expand_bits(*v, 4);
+            *p = SRgba8::new(red, green, blue, alpha);
+        }
+        r
+    }
+
+    /// Convert to packed RGBA4444 pixels.
+    ///
+    /// Channels are rounded to the nearest value.
+    pub fn to_rgba4444(&self) -> Vec<u16> {
+        self.pixels
+            .iter()
+            .map(|p| {
+                let red = reduce_bits(Rgb::red(*p), 4) << 12;
+                let green = reduce_bits(Rgb::green(*p), 4) << 8;
+                let blue = reduce_bits(Rgb::blue(*p), 4) << 4;
+                let alpha = reduce_bits(p.alpha(), 4);
+                red | green | blue | alpha
+            })
+            .collect()
+    }
+
+    /// Reduce key color spill, such as after [chroma_key].
+    ///
+    /// * `key` Key color.
+    ///
+    /// For pixels which are not fully transparent, any chroma in the
+    /// direction of `key` is removed, leaving *luma* and *alpha* unchanged.
+    ///
+    /// ### Despill a green-tinted pixel
+    /// ```
+    /// use pix::rgb::{Rgb, SRgb8, SRgba8};
+    /// use pix::Raster;
+    ///
+    /// let clr = SRgba8::new(0x80, 0xA0, 0x80, 0xFF);
+    /// let mut r = Raster::with_color(1, 1, clr);
+    /// r.despill(SRgb8::new(0, 0xFF, 0));
+    /// let p = r.pixel(0, 0);
+    /// assert_eq!(Rgb::red(p), Rgb::green(p));
+    /// assert_eq!(Rgb::green(p), Rgb::blue(p));
+    /// ```
+    /// [chroma_key]: #method.chroma_key
+    pub fn despill(&mut self, key: SRgb8) {
+        let k = srgb_ycc(key);
+        let kb = YCbCr::cb(k).to_f32() - 0.5;
+        let kr = YCbCr::cr(k).to_f32() - 0.5;
+        let k2 = kb * kb + kr * kr;
+        if k2 <= 0.0 {
+            return;
+        }
+        for p in self.pixels.iter_mut() {
+            if p.alpha() == Ch8::MIN {
+                continue;
+            }
+            let c = srgb_ycc(*p);
+            let cb = YCbCr::cb(c).to_f32() - 0.5;
+            let cr = YCbCr::cr(c).to_f32() - 0.5;
+            let t = (cb * kb + cr * kr) / k2;
+            if t > 0.0 {
+                let c = YCbCr32::new(
+                    YCbCr::y(c),
+                    Ch32::new(cb - t * kb + 0.5),
+                    Ch32::new(cr - t * kr + 0.5),
+                );
+                let rgb: Rgb8 = c.convert();
+                let (red, green, blue) =
+                    (Rgb::red(rgb), Rgb::green(rgb), Rgb::blue(rgb));
+                *p = SRgba8::new(red, green, blue, p.alpha());
+            }
+        }
+    }
+}
+
+impl Raster<Matte8> {
+    /// Construct a `Raster` from a 1-bit-per-pixel bitmap.
+    ///
+    /// Each row is packed with the most significant bit first.  Set bits
+    /// become fully opaque (`0xFF`), and clear bits fully transparent.
+    /// Padding bits at the end of each row are ignored.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `stride` Number of bytes per row.
+    /// * `data` Packed bitmap data.
+    ///
+    /// # Panics
+    ///
+    /// * If `width` or `height` is greater than `std::i32::MAX`
+    /// * If `stride` is too small for `width`
+    /// * If `data` is too small for `height` rows
+    ///
+    /// ### Load a glyph bitmap
+    /// ```
+    /// use pix::matte::Matte8;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::<Matte8>::with_bitmap(3, 2, 1, &[0b1010_0000, 0x40]);
+    /// assert_eq!(r.as_u8_slice(), [0xFF, 0, 0xFF, 0, 0xFF, 0]);
+    /// ```
+    pub fn with_bitmap(
+        width: u32,
+        height: u32,
+        stride: usize,
+        data: &[u8],
+    ) -> Self {
+        let mut r = Self::with_clear(width, height);
+        let w = width as usize;
+        assert!(stride >= (w + 7) / 8, "Stride too small: {stride}");
+        if w == 0 {
+            return r;
+        }
+        let len = stride * (height as usize);
+        assert!(data.len() >= len, "Bitmap too small: {}", data.len());
+        for (row, bits) in r.pixels.chunks_exact_mut(w).zip(data.chunks(stride))
+        {
+            for (x, p) in row.iter_mut().enumerate() {
+                if bits[x / 8] & (0x80 >> (x % 8)) != 0 {
+                    *p = Matte8::new(0xFF);
+                }
+            }
+        }
+        r
+    }
+
+    /// Convert to a 1-bit-per-pixel bitmap.
+    ///
+    /// * `threshold` Minimum value for a set bit.
+    ///
+    /// Returns packed data, with the most significant bit first, along with
+    /// the row stride in bytes.  Padding bits at the end of each row are
+    /// zero.
+    ///
+    /// ### Save a glyph bitmap
+    /// ```
+    /// use pix::matte::Matte8;
+    /// use pix::Raster;
+    ///
+    /// let r = Raster::<Matte8>::with_u8_buffer(3, 1, [0x20, 0x80, 0xFF]);
+    /// assert_eq!(r.to_bitmap(0x80), (vec![0b0110_0000], 1));
+    /// ```
+    pub fn to_bitmap(&self, threshold: u8) -> (Vec<u8>, usize) {
+        let w = self.width() as usize;
+        let stride = (w + 7) / 8;
+        let mut data = vec![0; stride * self.height() as usize];
+        if w > 0 {
+            let rows = self.pixels.chunks_exact(w);
+            for (row, bits) in rows.zip(data.chunks_exact_mut(stride)) {
+                for (x, p) in row.iter().enumerate() {
+                    if u8::from(p.alpha()) >= threshold {
+                        bits[x / 8] |= 0x80 >> (x % 8);
+                    }
+                }
+            }
+        }
+        (data, stride)
+    }
+
+    /// Dilate by a square window (maximum filter).
+    ///
+    /// * `radius` Window radius; each pixel becomes the maximum value within
+    ///   a `2 * radius + 1` square centered on it.
+    ///
+    /// Edges are clamped, so pixels outside the raster are ignored.  Cost is
+    /// proportional to the pixel count, regardless of `radius`.
+    ///
+    /// ### Grow a glyph outline
+    /// ```
+    /// use pix::matte::Matte8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::<Matte8>::with_clear(5, 5);
+    /// *r.pixel_mut(2, 2) = Matte8::new(0xFF);
+    /// let r = r.dilate(1);
+    /// assert_eq!(r.pixel(1, 3), Matte8::new(0xFF));
+    /// assert_eq!(r.pixel(0, 2), Matte8::new(0));
+    /// ```
+    pub fn dilate(&self, radius: u32) -> Self {
+        self.morph(radius, true)
+    }
+
+    /// Erode by a square window (minimum filter).
+    ///
+    /// * `radius` Window radius; each pixel becomes the minimum value within
+    ///   a `2 * radius + 1` square centered on it.
+    ///
+    /// Edges are clamped, so pixels outside the raster are ignored.  Cost is
+    /// proportional to the pixel count, regardless of `radius`.
+    ///
+    /// ### Shrink a glyph outline
+    /// ```
+    /// use pix::matte::Matte8;
+    /// use pix::Raster;
+    ///
+    /// let mut r = Raster::<Matte8>::with_clear(5, 5);
+    /// r.fill_rect((1, 1, 3, 3), Matte8::new(0xFF));
+    /// let r = r.erode(1);
+    /// assert_eq!(r.pixel(2, 2), Matte8::new(0xFF));
+    /// assert_eq!(r.pixel(1, 2), Matte8::new(0));
+    /// ```
+    pub fn erode(&self, radius: u32) -> Self {
+        self.morph(radius, false)
+    }
+
+    /// Apply a separable maximum (`dilate`) or minimum filter
+    fn morph(&self, radius: u32, dilate: bool) -> Self {
+        let mut r = self.clone();
+        let (w, h) = (self.width() as usize, self.height() as usize);
+        if w == 0 || h == 0 || radius == 0 {
+            return r;
+        }
+        let radius = radius as usize;
+        let mut line = Vec::with_capacity(w.max(h));
+        let mut out = vec![0; w.max(h)];
+        let mut window = VecDeque::new();
+        for row in r.pixels.chunks_exact_mut(w) {
+            line.clear();
+            line.extend(row.iter().map(|p| u8::from(p.alpha())));
+            window_extreme(&line, &mut out, radius, dilate, &mut window);
+            for (p, v) in row.iter_mut().zip(&out) {
+                *p = Matte8::new(*v);
+            }
+        }
+        for x in 0..w {
+            let col = r.pixels.iter().skip(x).step_by(w);
+            line.clear();
+            line.extend(col.map(|p| u8::from(p.alpha())));
+            window_extreme(&line, &mut out, radius, dilate, &mut window);
+            let col = r.pixels.iter_mut().skip(x).step_by(w);
+            for (p, v) in col.zip(&out) {
+                *p = Matte8::new(*v);
+            }
+        }
+        r
+    }
+}
+
+/// Find the maximum (or minimum) of a sliding window over a line
+///
+/// Uses a monotonic queue of indices, so each value is pushed and popped at
+/// most once.
+fn window_extreme(
+    line: &[u8],
+    out: &mut [u8],
+    radius: usize,
+    max: bool,
+    window: &mut VecDeque<usize>,
+) {
+    let n = line.len();
+    let keeps = |a: u8, b: u8| if max { a < b } else { a > b };
+    window.clear();
+    let mut next = 0;
+    for (i, o) in out[..n].iter_mut().enumerate() {
+        let hi = i.saturating_add(radius).min(n - 1);
+        while next <= hi {
+            while let Some(&b) = window.back() {
+                if keeps(line[next], line[b]) {
+                    break;
+                }
+                window.pop_back();
+            }
+            window.push_back(next);
+            next += 1;
+        }
+        let lo = i.saturating_sub(radius);
+        while window.front().is_some_and(|&f| f < lo) {
+            window.pop_front();
+        }
+        *o = line[window[0]];
+    }
+}
+
+/// Convert gamma-encoded sRGB channels to `YCbCr`, as in JPEG
+fn srgb_ycc<P>(p: P) -> YCbCr32
+where
+    P: Pixel<Chan = Ch8, Model = Rgb>,
+{
+    Rgb8::new(Rgb::red(p), Rgb::green(p), Rgb::blue(p)).convert()
+}
+
+/// Expand the low `bits` of a packed value to 8 bits
+fn expand_bits(v: u16, bits: u32) -> u8 {
+    let v = v & ((1 << bits) - 1);
+    let mut out = 0;
+    let mut shift = 8 - bits as i32;
+    while shift > -(bits as i32) {
+        out |= if shift >= 0 { v << shift } else { v >> -shift };
+        shift -= bits as i32;
+    }
+    out as u8
+}
+
+/// Reduce an 8-bit channel to `bits`, rounding to nearest
+fn reduce_bits(c: Ch8, bits: u32) -> u16 {
+    let max = (1 << bits) - 1;
+    (u16::from(u8::from(c)) * max + 127) / 255
+}
+
+/// Convert raster dimensions to `i32`, panicking if too big
+fn raster_size(width: u32, height: u32) -> (i32, i32) {
+    let w = i32::try_from(width)
+        .unwrap_or_else(|_| panic!("Raster width too big: {width}"));
+    let h = i32::try_from(height)
+        .unwrap_or_else(|_| panic!("Raster height too big: {height}"));
+    (w, h)
+}
+
+/// Get the pixel count of raster dimensions, panicking if too big
+///
+/// The buffer size in bytes must also fit in `isize`, the limit for any
+/// allocation.  That can be exceeded on 32-bit targets with large pixels.
+fn raster_len<P: Pixel>(width: i32, height: i32) -> usize {
+    let size = core::mem::size_of::<P>();
+    let max = isize::MAX as usize;
+    width
+        .checked_mul(height)
+        .and_then(|len| usize::try_from(len).ok())
+        .filter(|len| buffer_bytes(*len, size, max).is_some())
+        .unwrap_or_else(|| panic!("Raster too big: {width} x {height}"))
+}
+
+/// Get the size of a pixel buffer in bytes, if not greater than `max`
+fn buffer_bytes(len: usize, size: usize, max: usize) -> Option<usize> {
+    len.checked_mul(size).filter(|bytes| *bytes <= max)
+}
+
+/// Clip a span of offsets for `to` / `from` regions
+///
+/// Each parameter is (position, length, raster length).  Returns the range of
+/// offsets within both spans and both rasters.
+fn clip_span(to: (i32, i32, i32), from: (i32, i32, i32)) -> (i64, i64) {
+    let (tp, tl, tr) = (i64::from(to.0), i64::from(to.1), i64::from(to.2));
+    let (fp, fl, fr) =
+        (i64::from(from.0), i64::from(from.1), i64::from(from.2));
+    let start = 0.max(-tp).max(-fp);
+    let end = tl.min(fl).min(tr - tp).min(fr - fp);
+    (start, end)
+}
+
+/// Get linear *red*, *green* and *blue* of an opaque pixel
+fn linear_rgb<P: Pixel>(p: P) -> [f32; 3] {
+    let rgba = P::Model::into_rgba::<P>(p);
+    let ch = rgba.channels();
+    [0, 1, 2].map(|i| P::Gamma::to_linear(Ch32::new(ch[i].to_f32())).to_f32())
+}
+
+/// Sample a colormap, interpolating between entries
+///
+/// * `v` Position, from 0.0 to 1.0.
+fn colormap_sample<D: Pixel>(map: &[D], v: f32) -> D {
+    let last = map.len() - 1;
+    let pos = v.clamp(0.0, 1.0) * last as f32;
+    let i = (pos.floor() as usize).min(last);
+    let t = pos - i as f32;
+    if i == last || t <= 0.0 {
+        return map[i];
+    }
+    let (a, b) = (map[i], map[i + 1]);
+    let mut p = a;
+    for (c, (ca, cb)) in p
+        .channels_mut()
+        .iter_mut()
+        .zip(a.channels().iter().zip(b.channels()))
+    {
+        let (ca, cb) = (ca.to_f32(), cb.to_f32());
+        *c = D::Chan::from(ca + (cb - ca) * t);
+    }
+    p
+}
+
+/// Get anchored position along one axis
+///
+/// * `side` Negative for start, positive for end, or zero to center.
+fn anchor_pos(pos: i32, plen: i32, len: i32, margin: i32, side: i8) -> i32 {
+    let (pos, plen, len) = (i64::from(pos), i64::from(plen), i64::from(len));
+    let margin = i64::from(margin);
+    let p = match side {
+        s if s < 0 => pos + margin,
+        0 => pos + (plen - len).div_euclid(2),
+        _ => pos + plen - len - margin,
+    };
+    p.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+}
+
+/// Get number of elements to shift, no more than the length
+fn shift_count<P>(d: i32, len: usize, mode: ShiftMode<P>) -> usize {
+    let n = d.unsigned_abs() as usize;
+    match mode {
+        ShiftMode::Fill(_) => n.min(len),
+        ShiftMode::Wrap => n % len,
+    }
+}
+
+/// Shift elements of a slice, with overlapping source and destination
+///
+/// * `n` Number of elements to shift, no more than the slice length.
+/// * `forward` Shift toward the end of the slice.
+fn shift_slice<P>(s: &mut [P], n: usize, forward: bool, mode: ShiftMode<P>)
+where
+    P: Copy,
+{
+    let len = s.len();
+    match (mode, forward) {
+        (ShiftMode::Wrap, true) => s.rotate_right(n),
+        (ShiftMode::Wrap, false) => s.rotate_left(n),
+        (ShiftMode::Fill(clr), true) => {
+            s.copy_within(..len - n, n);
+            s[..n].fill(clr);
+        }
+        (ShiftMode::Fill(clr), false) => {
+            s.copy_within(n.., 0);
+            s[len - n..].fill(clr);
+        }
+    }
+}
+
+/// Check if a pixel format is `Matte`
+fn is_matte<P: Pixel>() -> bool {
+    TypeId::of::<P::Model>() == TypeId::of::<Matte>()
+}
+
+/// Check if a pixel format is `Gray` with an *alpha* channel
+fn is_gray_alpha<P: Pixel>() -> bool {
+    TypeId::of::<P::Model>() == TypeId::of::<Gray>() && P::CHANNELS == 2
+}
+
+/// Average pixels with a box filter
+///
+/// Color channels of *straight* pixels are weighted by *alpha*.
+fn box_filter<P: Pixel>(px: &[P; 4], light: Light) -> P {
+    let straight = TypeId::of::<P::Alpha>() == TypeId::of::<Straight>();
+    let alpha = px.map(|p| p.alpha().to_f32());
+    let weighted = straight && alpha.iter().any(|a| *a != alpha[0]);
+    let weights = if weighted { alpha } else { [1.0; 4] };
+    let total: f32 = weights.iter().sum();
+    let gamma = light == Light::Linear;
+    let mut d = px[0];
+    for (i, c) in d.channels_mut().iter_mut().enumerate() {
+        let chan = px.map(|p| p.channels()[i]);
+        *c = if P::Model::CIRCULAR.contains(&i) {
+            circular_mean(circular_sums(chan.iter().copied()))
+        } else if P::Model::LINEAR.contains(&i) && (weighted || gamma) {
+            let sum: f32 = chan
+                .iter()
+                .zip(weights)
+                .map(|(c, w)| decode_light::<P>(*c, gamma) * w)
+                .sum();
+            encode_light::<P>(sum / total, gamma)
+        } else {
+            P::Chan::average(&chan)
+        };
+    }
+    d
+}
+
+/// Decode a channel value to `f32`, optionally in linear light
+///
+/// `Ch8` channels are widened to 16 bits, so gamma decoding can use a
+/// look-up table without losing precision.  Other channels are decoded as
+/// `Ch32`.
+fn decode_light<P: Pixel>(c: P::Chan, gamma: bool) -> f32 {
+    if !gamma {
+        c.to_f32()
+    } else if P::Chan::INTEGER_BITS == 8 {
+        P::Gamma::to_linear(Ch16::from(c.to_f32())).to_f32()
+    } else {
+        P::Gamma::to_linear(Ch32::new(c.to_f32())).to_f32()
+    }
+}
+
+/// Encode an `f32` value to a channel, optionally from linear light
+fn encode_light<P: Pixel>(v: f32, gamma: bool) -> P::Chan {
+    if !gamma {
+        P::Chan::from(v)
+    } else if P::Chan::INTEGER_BITS == 8 {
+        P::Chan::from(P::Gamma::from_linear(Ch16::from(v)).to_f32())
+    } else {
+        P::Chan::from(P::Gamma::from_linear(Ch32::new(v)).to_f32())
+    }
+}
+
+/// Check if a pixel format is `Gray` with a *straight* *alpha* channel
+fn is_gray_straight_alpha<P: Pixel>() -> bool {
+    is_gray_alpha::<P>() && TypeId::of::<P::Alpha>() == TypeId::of::<Straight>()
+}
+
+/// Convert a slice of pixels into another format
+///
+/// Same-model, `Matte` and `Gray` *alpha* conversions skip RGBA, matching
+/// `Raster::with_raster`.
+fn convert_slice<P, S>(dst: &mut [P], src: &[S])
+where
+    P: Pixel,
+    S: Pixel,
+    P::Chan: From<S::Chan>,
+{
+    if TypeId::of::<S::Model>() == TypeId::of::<P::Model>()
+        && TypeId::of::<S::Alpha>() == TypeId::of::<P::Alpha>()
+        && TypeId::of::<S::Gamma>() == TypeId::of::<P::Gamma>()
+    {
+        for (d, s) in dst.iter_mut().zip(src) {
+            *d = P::from_bit_depth(*s);
+        }
+    } else if is_matte::<S>() && is_gray_straight_alpha::<P>() {
+        for (d, s) in dst.iter_mut().zip(src) {
+            let alpha = P::Chan::from(s.alpha());
+            *d = P::from_channels(&[P::Chan::MAX, alpha]);
+        }
+    } else if is_gray_alpha::<S>() && is_matte::<P>() {
+        for (d, s) in dst.iter_mut().zip(src) {
+            *d = P::from_channels(&[P::Chan::from(s.alpha())]);
+        }
+    } else {
+        for (d, s) in dst.iter_mut().zip(src) {
+            *d = s.convert();
+        }
+    }
+}
+
+/// Type name, formatted without module paths
+struct TypeName(&'static str);
+
+impl fmt::Debug for TypeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = self.0;
+        while !rest.is_empty() {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+                .unwrap_or(rest.len());
+            let (path, tail) = rest.split_at(end);
+            f.write_str(path.rsplit("::").next().unwrap_or(path))?;
+            let mut chars = tail.chars();
+            if let Some(c) = chars.next() {
+                write!(f, "{c}")?;
+            }
+            rest = chars.as_str();
+        }
+        Ok(())
+    }
+}
+
+/// Sample of pixels from the top-left corner of a raster
+struct Sample<'a, P: Pixel>(&'a Raster<P>);
+
+/// Row of sampled pixels
+struct SampleRow<'a, P>(&'a [P]);
+
+/// Pixel formatted with `Display`
+struct DisplayPixel<P>(P);
+
+impl<P> fmt::Debug for Sample<'_, P>
+where
+    P: Pixel + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.0.width().min(4) as usize;
+        let rows = self.0.rows(()).take(4);
+        f.debug_list()
+            .entries(rows.map(|row| SampleRow(&row[..width])))
+            .finish()
+    }
+}
+
+impl<P> fmt::Debug for SampleRow<'_, P>
+where
+    P: Pixel + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|p| DisplayPixel(*p)))
+            .finish()
+    }
+}
+
+impl<P: fmt::Display> fmt::Debug for DisplayPixel<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// SplitMix64 random number generator
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    /// Get the next random value
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// 64-bit FNV-1a hasher
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xCBF2_9CE4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    /// Add bytes to the hash
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01B3);
+        }
+    }
+}
+
+/// Reverse the byte order of each channel value in a buffer
+fn swap_chan_bytes<C: Channel>(bytes: &mut [u8]) {
+    let n = core::mem::size_of::<C>();
+    if n > 1 {
+        bytes.chunks_exact_mut(n).for_each(|c| c.reverse());
+    }
+}
+
+/// Convert a pixel with ordered dithering.
+///
+/// The pixel is first converted to the destination color model, *alpha* and
+/// *gamma* with `Ch32` channels, so the offset is added right before
+/// quantizing.
+///
+/// * `D` Destination pixel format (with integer channels).
+/// * `S` Source pixel format.
+/// * `src` Source pixel.
+/// * `bayer` Bayer matrix value.
+fn dither_pixel<D, S>(src: S, bayer: u8) -> D
+where
+    D: Pixel,
+    S: Pixel,
+    Ch32: From<S::Chan>,
+{
+    let step = 1.0 / ((1u32 << D::Chan::INTEGER_BITS) - 1) as f32;
+    let offset = ((f32::from(bayer) + 0.5) / 16.0 - 0.5) * step;
+    match D::CHANNELS {
+        1 => dither_quantize(
+            src.convert::<Pix1<Ch32, D::Model, D::Alpha, D::Gamma>>(),
+            offset,
+        ),
+        2 => dither_quantize(
+            src.convert::<Pix2<Ch32, D::Model, D::Alpha, D::Gamma>>(),
+            offset,
+        ),
+        3 => dither_quantize(
+            src.convert::<Pix3<Ch32, D::Model, D::Alpha, D::Gamma>>(),
+            offset,
+        ),
+        _ => dither_quantize(
+            src.convert::<Pix4<Ch32, D::Model, D::Alpha, D::Gamma>>(),
+            offset,
+        ),
+    }
+}
+
+/// Add a dither offset to color channels, then quantize a pixel.
+///
+/// * `D` Destination pixel format.
+/// * `H` Destination format with `Ch32` channels.
+/// * `hi` Pixel to quantize.
+/// * `offset` Offset to add to color channels.
+fn dither_quantize<D, H>(mut hi: H, offset: f32) -> D
+where
+    D: Pixel,
+    H: Pixel<Chan = Ch32>,
+{
+    // premultiplied channels must not exceed alpha
+    let max = if TypeId::of::<H::Alpha>() == TypeId::of::<Premultiplied>() {
+        hi.alpha().to_f32()
+    } else {
+        1.0
+    };
+    for c in hi.channels_mut()[H::Model::LINEAR].iter_mut() {
+        *c = Ch32::new((c.to_f32() + offset).clamp(0.0, max));
+    }
+    let mut channels = [D::Chan::MIN; 4];
+    for (d, c) in channels.iter_mut().zip(hi.channels()) {
+        *d = D::Chan::from(c.to_f32());
+    }
+    D::from_channels(&channels[..D::CHANNELS])
+}
+
+/// Get the period of a circular channel, relative to `MAX`.
+///
+/// Integer channels wrap around one step past `MAX` (256 for `Ch8`), while
+/// floating point channels wrap at `MAX`.
+fn circular_period<C: Channel>() -> f64 {
+    if C::INTEGER_BITS > 0 {
+        let max = ((1u64 << C::INTEGER_BITS) - 1) as f64;
+        (max + 1.0) / max
+    } else {
+        1.0
+    }
+}
+
+/// Sum unit vectors for circular channel values.
+///
+/// Returns sums of (*sin*, *cos*), with values interpreted as angles.
+fn circular_sums<C, I>(values: I) -> (f64, f64)
+where
+    C: Channel,
+    I: Iterator<Item = C>,
+{
+    let period = circular_period::<C>();
+    values.fold((0.0, 0.0), |(sin, cos), c| {
+        let a = f64::from(c.to_f32()) / period * core::f64::consts::TAU;
+        (sin + a.sin(), cos + a.cos())
+    })
+}
+
+/// Get the mean of circular channel values from sums of unit vectors.
+///
+/// * `sums` Sums of (*sin*, *cos*) from `circular_sums`.
+fn circular_mean<C: Channel>((sin, cos): (f64, f64)) -> C {
+    let period = circular_period::<C>();
+    let turns = (sin.atan2(cos) / core::f64::consts::TAU).rem_euclid(1.0);
+    let v = turns * period;
+    // values which round up to the period wrap around
+    if v >= (1.0 + period) / 2.0 {
+        C::MIN
+    } else {
+        C::from(v as f32)
+    }
+}
+
+/// Get range of line steps along the major axis which are within a raster.
+///
+/// * `start` Starting coordinate.
+/// * `dir` Direction of steps (-1, 0 or 1).
+/// * `n` Number of steps.
+/// * `extent` Width or height of raster.
+///
+/// The range is empty (`lo > hi`) if no steps are within the raster.
+fn line_steps(start: i128, dir: i128, n: i128, extent: i32) -> (i128, i128) {
+    let last = i128::from(extent) - 1;
+    if dir < 0 {
+        ((start - last).max(0), n.min(start))
+    } else {
+        ((-start).max(0), n.min(last - start))
+    }
+}
+
+/// Fill a row with alternating bands of two colors.
+///
+/// * `row` Row slice, starting at column `x`.
+/// * `x` Column of first pixel, used to anchor band phase.
+/// * `band` Width of each band.
+/// * `a` Color of even bands.
+/// * `b` Color of odd bands.
+fn fill_bands<P: Pixel>(row: &mut [P], x: usize, band: usize, a: &P, b: &P) {
+    let mut start = 0;
+    let mut i = x / band;
+    while start < row.len() {
+        let end = ((i + 1) * band - x).min(row.len());
+        let clr = if i % 2 == 0 { a } else { b };
+        P::copy_color(&mut row[start..end], clr);
+        start = end;
+        i += 1;
+    }
+}
+
+impl<P: Pixel> RasterBuilder<P> {
+    /// Create a new `RasterBuilder`.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    ///
+    /// # Panics
+    ///
+    /// If `width` or `height` is greater than `std::i32::MAX`, or the
+    /// `Raster` would be too big.
+    pub fn new(width: u32, height: u32) -> Self {
+        let (width, height) = raster_size(width, height);
+        let len = raster_len::<P>(width, height);
+        let pixels = Vec::with_capacity(len);
+        RasterBuilder {
+            width,
+            height,
+            rows: 0,
+            pixels,
+        }
+    }
+
+    /// Get the number of rows pushed.
+    pub fn rows(&self) -> u32 {
+        self.rows as u32
+    }
+
+    /// Push a row of pixels.
+    ///
+    /// * `row` Pixels, with length equal to the `Raster` width.
+    ///
+    /// Returns an error if the row length is wrong, or all rows have
+    /// already been pushed.
+    pub fn push_row(&mut self, row: &[P]) -> Result<(), RasterError> {
+        if row.len() != self.width as usize {
+            return Err(RasterError::RowWidth);
+        }
+        if self.rows == self.height {
+            return Err(RasterError::RowCount);
+        }
+        self.pixels.extend_from_slice(row);
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Finish building the `Raster`.
+    ///
+    /// Returns an error if not all rows were pushed.
+    pub fn finish(self) -> Result<Raster<P>, RasterError> {
+        if self.rows != self.height {
+            return Err(RasterError::RowCount);
+        }
+        Ok(Raster {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels.into_boxed_slice(),
+        })
+    }
+}
+
+impl<'a, P: Pixel> Rows<'a, P> {
+    /// Create a new row `Iterator`.
+    fn new(raster: &'a Raster<P>, reg: Region) -> Self {
+        // regions must be clipped first; empty regions are always default
+        debug_assert_eq!(raster.intersection(reg), reg);
+        let width = raster.width() as usize;
+        let start = reg.y as usize * width;
+        let end = reg.bottom() as usize * width;
+        let slice = &raster.pixels[start..end];
+        // a zero-width raster has no pixels, so it also has no rows
+        let chunks = slice.chunks_exact(width.max(1));
+        let x = reg.x as usize;
+        let w = reg.width as usize;
+        let columns = x..x + w;
+        Rows { chunks, columns }
+    }
+}
+
+impl<'a, P: Pixel> Iterator for Rows<'a, P> {
+    type Item = &'a [P];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(|s| &s[self.columns.clone()])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<'a, P: Pixel> DoubleEndedIterator for Rows<'a, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks.next_back().map(|s| &s[self.columns.clone()])
+    }
+}
+
+impl<'a, P: Pixel> ExactSizeIterator for Rows<'a, P> {}
+
+impl<'a, P: Pixel> Columns<'a, P> {
+    /// Create a new column `Iterator`.
+    fn new(raster: &'a Raster<P>, reg: Region) -> Self {
+        // regions must be clipped first; empty regions are always default
+        debug_assert_eq!(raster.intersection(reg), reg);
+        let width = raster.width() as usize;
+        let start = reg.y as usize * width;
+        let end = reg.bottom() as usize * width;
+        let pixels = &raster.pixels[start..end];
+        let x = reg.x as usize;
+        let columns = x..x + reg.width as usize;
+        Columns {
+            pixels,
+            width,
+            columns,
+        }
+    }
+
+    /// Get one column
+    fn column(&self, x: usize) -> StepBy<Iter<'a, P>> {
+        self.pixels[x..].iter().step_by(self.width)
+    }
+}
+
+impl<'a, P: Pixel> Iterator for Columns<'a, P> {
+    type Item = StepBy<Iter<'a, P>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.columns.next().map(|x| self.column(x))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.columns.size_hint()
+    }
+}
+
+impl<'a, P: Pixel> DoubleEndedIterator for Columns<'a, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.columns.next_back().map(|x| self.column(x))
+    }
+}
+
+impl<'a, P: Pixel> ExactSizeIterator for Columns<'a, P> {}
+
+impl<'a, P: Pixel> Spans<'a, P> {
+    /// Create a new span `Iterator`.
+    fn new(raster: &'a Raster<P>, set: &RegionSet) -> Self {
+        let spans = set.clip_spans(raster.width, raster.height).into_iter();
+        Spans {
+            pixels: &raster.pixels,
+            width: raster.width() as usize,
+            spans,
+        }
+    }
+}
+
+impl<'a, P: Pixel> Iterator for Spans<'a, P> {
+    type Item = (i32, i32, &'a [P]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, y, len) = self.spans.next()?;
+        let start = y as usize * self.width + x as usize;
+        Some((x, y, &self.pixels[start..start + len]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.spans.size_hint()
+    }
+}
+
+impl<'a, P: Pixel> ExactSizeIterator for Spans<'a, P> {}
+
+impl<'a, P: Pixel> SpansMut<'a, P> {
+    /// Create a new mutable span `Iterator`.
+    fn new(raster: &'a mut Raster<P>, set: &RegionSet) -> Self {
+        let spans = set.clip_spans(raster.width, raster.height).into_iter();
+        SpansMut {
+            width: raster.width() as usize,
+            rest: &mut raster.pixels,
+            pos: 0,
+            spans,
+        }
+    }
+}
+
+impl<'a, P: Pixel> Iterator for SpansMut<'a, P> {
+    type Item = (i32, i32, &'a mut [P]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, y, len) = self.spans.next()?;
+        let start = y as usize * self.width + x as usize;
+        // spans are disjoint and in pixel order, so split off each in turn
+        let rest = core::mem::take(&mut self.rest);
+        let (_, rest) = rest.split_at_mut(start - self.pos);
+        let (span, rest) = rest.split_at_mut(len);
+        self.rest = rest;
+        self.pos = start + len;
+        Some((x, y, span))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.spans.size_hint()
+    }
+}
+
+impl<'a, P: Pixel> ExactSizeIterator for SpansMut<'a, P> {}
+
+impl<'a, P: Pixel> RowsMut<'a, P> {
+    /// Create a new mutable row `Iterator`.
+    fn new(raster: &'a mut Raster<P>, reg: Region) -> Self {
+        // regions must be clipped first; empty regions are always default
+        debug_assert_eq!(raster.intersection(reg), reg);
+        let width = raster.width() as usize;
+        let start = reg.y as usize * width;
+        let end = reg.bottom() as usize * width;
+        let slice = &mut raster.pixels[start..end];
+        // a zero-width raster has no pixels, so it also has no rows
+        let chunks = slice.chunks_exact_mut(width.max(1));
+        let x = reg.x as usize;
+        let w = reg.width as usize;
+        let columns = x..x + w;
+        RowsMut { chunks, columns }
+    }
+}
+
+impl<'a, P: Pixel> Iterator for RowsMut<'a, P> {
+    type Item = &'a mut [P];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(|s| &mut s[self.columns.clone()])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<'a, P: Pixel> DoubleEndedIterator for RowsMut<'a, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks
+            .next_back()
+            .map(|s| &mut s[self.columns.clone()])
+    }
+}
+
+impl<'a, P: Pixel> ExactSizeIterator for RowsMut<'a, P> {}
+
+impl From<(i32, i32, u32, u32)> for Region {
+    fn from(r: (i32, i32, u32, u32)) -> Self {
+        Region::new(r.0, r.1, r.2, r.3)
+    }
+}
+
+impl From<(i32, i32)> for Region {
+    fn from(r: (i32, i32)) -> Self {
+        const MAX: u32 = i32::MAX as u32;
+        Region::new(r.0, r.1, MAX, MAX)
+    }
+}
+
+impl From<()> for Region {
+    fn from(_: ()) -> Self {
+        const MAX: u32 = i32::MAX as u32;
+        Region::new(0, 0, MAX, MAX)
+    }
+}
+
+impl Region {
+    /// Create a new `Region`
+    ///
+    /// # Panics
+    ///
+    /// If `width` or `height` is greater than `std::i32::MAX`
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self::checked_new(x, y, width, height)
+            .unwrap_or_else(|| panic!("Region too big: {width} x {height}"))
+    }
+
+    /// Create a new `Region`, checking dimensions
+    ///
+    /// Returns `None` if `width` or `height` is greater than `std::i32::MAX`.
+    ///
+    /// ## Example
+    /// ```
+    /// use pix::Region;
+    ///
+    /// assert!(Region::checked_new(0, 0, 10, 10).is_some());
+    /// assert!(Region::checked_new(0, 0, u32::MAX, 10).is_none());
+    /// ```
+    pub fn checked_new(
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> Option<Self> {
+        let width = i32::try_from(width).ok()?;
+        let height = i32::try_from(height).ok()?;
+        Some(Region {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// Create a `Region` anchored within a parent `Region`
+    ///
+    /// * `parent` Region to anchor within, such as `Raster::region()`.
+    /// * `size` Width and height of the new `Region`.
+    /// * `anchor` Position within the parent.
+    /// * `margin` Distance inward from each anchored edge (ignored for
+    ///   centered axes).
+    ///
+    /// When centering, the position is rounded toward the top-left.  The
+    /// result is not clipped to `parent`; use
+    /// [intersection](#method.intersection) if needed.
+    ///
+    /// # Panics
+    ///
+    /// If `size` width or height is greater than `std::i32::MAX`
+    ///
+    /// ## Example
+    /// ```
+    /// use pix::{Anchor, Region};
+    ///
+    /// let parent = Region::new(0, 0, 640, 480);
+    /// let reg = Region::anchored(parent, (100, 50), Anchor::BottomRight, 10);
+    /// assert_eq!(reg, Region::new(530, 420, 100, 50));
+    /// ```
+    pub fn anchored(
+        parent: Region,
+        size: (u32, u32),
+        anchor: Anchor,
+        margin: i32,
+    ) -> Self {
+        use Anchor::*;
+        let reg = Region::new(0, 0, size.0, size.1);
+        let (h, v) = match anchor {
+            TopLeft => (-1, -1),
+            TopCenter => (0, -1),
+            TopRight => (1, -1),
+            CenterLeft => (-1, 0),
+            Center => (0, 0),
+            CenterRight => (1, 0),
+            BottomLeft => (-1, 1),
+            BottomCenter => (0, 1),
+            BottomRight => (1, 1),
+        };
+        let x = anchor_pos(parent.x, parent.width, reg.width, margin, h);
+        let y = anchor_pos(parent.y, parent.height, reg.height, margin, v);
+        Region::new(x, y, size.0, size.1)
+    }
+
+    /// Check if the `Region` is empty (zero width or height)
+    pub fn is_empty(self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Get intersection with another `Region`
+    pub fn intersection<R>(self, rhs: R) -> Self
+    where
+        R: Into<Self>,
+    {
+        let rhs = rhs.into();
+        let x0 = self.x.max(rhs.x);
+        let x1 = self.right().min(rhs.right());
+        let y0 = self.y.max(rhs.y);
+        let y1 = self.bottom().min(rhs.bottom());
+        if x0 < x1 && y0 < y1 {
+            let w = (x1 - x0) as u32;
+            let h = (y1 - y0) as u32;
+            Region::new(x0, y0, w, h)
+        } else {
+            Region::default()
+        }
+    }
+
+    /// Get the left side
+    pub fn left(self) -> i32 {
+        self.x
+    }
+
+    /// Get the top side
+    pub fn top(self) -> i32 {
+        self.y
+    }
+
+    /// Get the width
+    pub fn width(self) -> u32 {
+        self.width as u32
+    }
+
+    /// Get the height
+    pub fn height(self) -> u32 {
+        self.height as u32
+    }
+
+    /// Get right side
+    pub fn right(self) -> i32 {
+        self.x.saturating_add(self.width)
+    }
+
+    /// Get bottom side
+    pub fn bottom(self) -> i32 {
+        self.y.saturating_add(self.height)
+    }
+}
+
+impl<R> FromIterator<R> for RegionSet
+where
+    R: Into<Region>,
+{
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> Self {
+        let regions: Vec<Region> = iter.into_iter().map(|r| r.into()).collect();
+        RegionSet {
+            bands: Band::decompose(&regions),
+        }
+    }
+}
+
+impl RegionSet {
+    /// Create a new empty `RegionSet`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a `Region` to the set
+    pub fn add<R>(&mut self, reg: R)
+    where
+        R: Into<Region>,
+    {
+        let mut regions: Vec<Region> = self.regions().collect();
+        regions.push(reg.into());
+        self.bands = Band::decompose(&regions);
+    }
+
+    /// Check if the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.bands.is_empty()
+    }
+
+    /// Check if a pixel is in the set
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.bands.iter().any(|b| {
+            b.top <= y
+                && y < b.bottom
+                && b.spans.iter().any(|&(x0, x1)| x0 <= x && x < x1)
+        })
+    }
+
+    /// Get an `Iterator` of disjoint regions covering the set
+    ///
+    /// Regions are ordered from top to bottom, then left to right.
+    pub fn regions(&self) -> impl Iterator<Item = Region> + '_ {
+        self.bands.iter().flat_map(|b| {
+            b.spans.iter().map(move |&(x0, x1)| {
+                let width = (x1 - x0) as u32;
+                let height = (b.bottom - b.top) as u32;
+                Region::new(x0, b.top, width, height)
+            })
+        })
+    }
+
+    /// Get spans clipped to a raster, in pixel order
+    fn clip_spans(&self, width: i32, height: i32) -> Vec<(i32, i32, usize)> {
+        let mut spans = Vec::new();
+        for b in &self.bands {
+            for y in b.top.max(0)..b.bottom.min(height) {
+                for &(x0, x1) in &b.spans {
+                    let (x0, x1) = (x0.max(0), x1.min(width));
+                    if x0 < x1 {
+                        spans.push((x0, y, (x1 - x0) as usize));
+                    }
+                }
+            }
+        }
+        spans
+    }
+}
+
+impl Band {
+    /// Decompose regions into disjoint bands
+    fn decompose(regions: &[Region]) -> Vec<Band> {
+        let regions: Vec<Region> =
+            regions.iter().filter(|r| !r.is_empty()).copied().collect();
+        let mut edges: Vec<i32> =
+            regions.iter().flat_map(|r| [r.top(), r.bottom()]).collect();
+        edges.sort_unstable();
+        edges.dedup();
+        let mut bands: Vec<Band> = Vec::new();
+        for ys in edges.windows(2) {
+            let (top, bottom) = (ys[0], ys[1]);
+            let mut covering: Vec<(i32, i32)> = regions
+                .iter()
+                .filter(|r| r.top() <= top && r.bottom() >= bottom)
+                .map(|r| (r.left(), r.right()))
+                .collect();
+            covering.sort_unstable();
+            let mut spans: Vec<(i32, i32)> = Vec::new();
+            for (x0, x1) in covering {
+                match spans.last_mut() {
+                    // merge overlapping and adjacent spans
+                    Some(last) if x0 <= last.1 => last.1 = last.1.max(x1),
+                    _ => spans.push((x0, x1)),
+                }
+            }
+            if spans.is_empty() {
+                continue;
+            }
+            match bands.last_mut() {
+                // coalesce with the previous band if spans match
+                Some(last) if last.bottom == top && last.spans == spans => {
+                    last.bottom = bottom;
+                }
+                _ => bands.push(Band { top, bottom, spans }),
+            }
+        }
+        bands
+    }
+}
+
+#[cfg(test)]
+#[rustfmt::skip]
+mod test {
+    use crate::chan::{Ch16, Ch8, Channel, Linear, Premultiplied};
+    use crate::el::Pixel;
+    use crate::bgr::*;
+    use crate::gray::*;
+    use crate::hsv::*;
+    use crate::hwb::*;
+    use crate::matte::*;
+    use crate::ops::*;
+    use crate::rgb::*;
+    use crate::*;
+
+    #[test]
+    fn region_size() {
+        assert_eq!(core::mem::size_of::<Region>(), 16);
+    }
+
+    #[test]
+    fn buffers() {
+        let buf = vec![0x80; 64];
+        let mut raster = Raster::<Rgba8>::with_u8_buffer(4, 4, buf);
+        *raster.pixel_mut(1, 1) = Rgba8::new(0x40, 0x60, 0x80, 0xA0);
+        let slice: Box<[u8]> = raster.into();
+        let v: Vec<u8> = slice.into();
+        assert_eq!(v.len(), 64);
+        let buf = vec![0xA0; 16];
+        let mut raster = Raster::<Gray8>::with_u8_buffer(4, 4, buf);
+        *raster.pixel_mut(1, 1) = Gray8::new(0xFF);
+        let slice: Box<[u8]> = raster.into();
+        let v: Vec<u8> = slice.into();
+        let b = vec![
+            0xA0, 0xA0, 0xA0, 0xA0,
+            0xA0, 0xFF, 0xA0, 0xA0,
+            0xA0, 0xA0, 0xA0, 0xA0,
+            0xA0, 0xA0, 0xA0, 0xA0,
+        ];
+        assert_eq!(v, b);
+    }
+
+    #[test]
+    fn buffers_u16() {
+        let buf = vec![0xA0A0; 32];
+        let mut raster = Raster::<Graya16>::with_u16_buffer(4, 4, buf);
+        *raster.pixel_mut(1, 1) = Graya16::new(0xFFFF, 0x1234);
+        assert_eq!(raster.as_u16_slice().len(), 32);
+        assert_eq!(raster.as_u16_slice()[10..12], [0xFFFF, 0x1234]);
+        raster.as_u16_slice_mut()[0] = 0x5678;
+        assert_eq!(raster.pixel(0, 0), Graya16::new(0x5678, 0xA0A0));
+        let slice: Box<[u16]> = raster.into();
+        let v: Vec<u16> = slice.into();
+        assert_eq!(v[0..2], [0x5678, 0xA0A0]);
+        assert_eq!(v[10..12], [0xFFFF, 0x1234]);
+    }
+
+    #[test]
+    fn content_hash() {
+        let empty = Raster::<SRgb8>::with_clear(0, 0);
+        assert_eq!(empty.content_hash(), 0xC536_C420_EF7E_2E8A);
+        let mut r = Raster::<Rgba16>::with_clear(3, 2);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = i as u16 * 0x1111;
+            *p = Rgba16::new(v, v + 1, v + 2, 0xFFFF);
+        }
+        let h = r.content_hash();
+        assert_eq!(h, r.clone().content_hash());
+        for x in 0..3 {
+            for y in 0..2 {
+                let mut r1 = r.clone();
+                let p = r1.pixel_mut(x, y);
+                *p = Rgba16::new(1, 2, 3, 4);
+                assert_ne!(r1.content_hash(), h);
+            }
+        }
+        let r16 = Raster::<Rgba16>::with_u16_buffer(3, 2, r.as_u16_slice());
+        assert_eq!(r16.content_hash(), h);
+        let r8 = Raster::<Rgba16>::from_le_bytes(3, 2, &r.to_le_bytes());
+        assert_eq!(r8.content_hash(), h);
+        // same bytes, different format
+        let b = Raster::<Bgra16>::with_u16_buffer(3, 2, r.as_u16_slice());
+        assert_ne!(b.content_hash(), h);
+        let p = Raster::<Rgba16p>::with_u16_buffer(3, 2, r.as_u16_slice());
+        assert_ne!(p.content_hash(), h);
+        let s = Raster::<SRgba16>::with_u16_buffer(3, 2, r.as_u16_slice());
+        assert_ne!(s.content_hash(), h);
+        // same bytes, different size
+        let t = Raster::<Rgba16>::with_u16_buffer(2, 3, r.as_u16_slice());
+        assert_ne!(t.content_hash(), h);
+    }
+
+    #[test]
+    fn transparent_opaque() {
+        let r = Raster::<Rgba8p>::with_transparent(2, 2);
+        assert_eq!(r.pixel(1, 1), Rgba8p::new(0, 0, 0, 0));
+        let r = Raster::<SGraya16>::with_transparent(2, 2);
+        assert_eq!(r.pixel(1, 1).alpha(), Ch16::MIN);
+        let r = Raster::<Matte8>::with_transparent(2, 2);
+        assert_eq!(r.pixel(0, 0).alpha(), Ch8::MIN);
+        let r = Raster::<Hsva8p>::with_transparent(2, 2);
+        assert_eq!(r.pixel(0, 0).alpha(), Ch8::MIN);
+        let clr = Rgba8p::new(0x10, 0x20, 0x30, 0x40);
+        let r = Raster::<Rgba8p>::with_opaque(2, 2, clr);
+        assert_eq!(r.pixel(1, 0), Rgba8p::new(0x10, 0x20, 0x30, 0xFF));
+        let r = Raster::<SRgba16>::with_opaque(2, 2, SRgba16::new(1, 2, 3, 0));
+        assert_eq!(r.pixel(0, 1).alpha(), Ch16::MAX);
+        let r = Raster::<Matte8>::with_opaque(2, 2, Matte8::new(0x20));
+        assert_eq!(r.pixel(0, 0), Matte8::new(0xFF));
+        let r = Raster::<SRgb8>::with_opaque(2, 2, SRgb8::new(1, 2, 3));
+        assert_eq!(r.pixel(0, 0), SRgb8::new(1, 2, 3));
+        assert_eq!(r.pixel(0, 0).alpha(), Ch8::MAX);
+    }
+
+    #[test]
+    fn premultiply_in_place() {
+        let mut r = Raster::<SRgba8>::with_clear(16, 16);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = i as u8;
+            *p = SRgba8::new(v, v.wrapping_mul(3), 255 - v, v.wrapping_mul(7));
+        }
+        let ptr = r.pixels().as_ptr() as usize;
+        let alloc = Raster::<SRgba8p>::with_raster(&r);
+        let rp = r.premultiply_in_place::<SRgba8p>();
+        assert_eq!(rp.pixels(), alloc.pixels());
+        assert_eq!(rp.pixels().as_ptr() as usize, ptr);
+        let alloc = Raster::<SRgba8>::with_raster(&rp);
+        let rs = rp.unpremultiply_in_place::<SRgba8>();
+        assert_eq!(rs.pixels(), alloc.pixels());
+        assert_eq!(rs.pixels().as_ptr() as usize, ptr);
+        let r = Raster::with_color(2, 2, Hsva8::new(0x40, 0xFF, 0xFF, 0x80));
+        let alloc = Raster::<Hsva8p>::with_raster(&r);
+        let r = r.premultiply_in_place::<Hsva8p>();
+        assert_eq!(r.pixels(), alloc.pixels());
+        assert_eq!(r.pixel(0, 0), Hsva8p::new(0x40, 0x80, 0x80, 0x80));
+    }
+
+    #[test]
+    fn debug_summary() {
+        let mut r = Raster::<SRgb8>::with_clear(6, 5);
+        r.pixels_mut()[1] = SRgb8::new(1, 2, 3);
+        assert_eq!(format!("{r:?}"),
+            "Raster { width: 6, height: 5, \
+            pixel: Pix3<Ch8, Rgb, Straight, Srgb>, sample: [\
+            [rgb(0, 0, 0), rgb(1, 2, 3), \
+            rgb(0, 0, 0), rgb(0, 0, 0)], \
+            [rgb(0, 0, 0), rgb(0, 0, 0), \
+            rgb(0, 0, 0), rgb(0, 0, 0)], \
+            [rgb(0, 0, 0), rgb(0, 0, 0), \
+            rgb(0, 0, 0), rgb(0, 0, 0)], \
+            [rgb(0, 0, 0), rgb(0, 0, 0), \
+            rgb(0, 0, 0), rgb(0, 0, 0)]] }");
+        let r = Raster::<Matte16>::with_clear(1, 2);
+        assert_eq!(format!("{r:?}"),
+            "Raster { width: 1, height: 2, \
+            pixel: Pix1<Ch16, Matte, Premultiplied, Linear>, \
+            sample: [[matte(0)], [matte(0)]] }");
+        let r = Raster::<Gray8>::with_clear(0, 0);
+        assert_eq!(format!("{r:?}"),
+            "Raster { width: 0, height: 0, \
+            pixel: Pix1<Ch8, Gray, Straight, Linear>, sample: [] }");
+    }
+
+    #[test]
+    fn dilate_erode() {
+        let mut r = Raster::<Matte8>::with_clear(9, 9);
+        *r.pixel_mut(4, 5) = Matte8::new(0xFF);
+        let d = r.dilate(2);
+        for y in 0..9 {
+            for x in 0..9 {
+                let v = if (2..=6).contains(&x) && (3..=7).contains(&y) {
+                    0xFF
+                } else {
+                    0
+                };
+                assert_eq!(d.pixel(x, y), Matte8::new(v), "{x},{y}");
+            }
+        }
+        assert_eq!(d.erode(2).pixels(), r.pixels());
+        let mut r = Raster::<Matte8>::with_clear(40, 30);
+        r.fill_rect((10, 8, 16, 12), Matte8::new(0xC0));
+        *r.pixel_mut(1, 1) = Matte8::new(0x40);
+        let d = r.dilate(5);
+        assert_eq!(d.pixel(0, 0), Matte8::new(0x40));
+        assert_eq!(d.pixel(5, 8), Matte8::new(0xC0));
+        assert_eq!(d.pixel(4, 8), Matte8::new(0));
+        let e = d.erode(5);
+        assert_eq!(e.crop((10, 8, 16, 12)).pixels(),
+            r.crop((10, 8, 16, 12)).pixels());
+        assert_eq!(e.pixel(9, 8), Matte8::new(0));
+        // clamped edges: a solid raster is unchanged
+        let r = Raster::with_color(7, 3, Matte8::new(0x80));
+        assert_eq!(r.erode(10).pixels(), r.pixels());
+        assert_eq!(r.dilate(0).pixels(), r.pixels());
+    }
+
+    fn visit_once(rects: &[(i32, i32, u32, u32)]) -> RegionSet {
+        let set: RegionSet = rects.iter().copied().collect();
+        let mut r = Raster::<Gray8>::with_clear(16, 12);
+        for (x, y, span) in r.spans_mut(&set) {
+            for (i, p) in span.iter_mut().enumerate() {
+                assert!(set.contains(x + i as i32, y));
+                *p = Gray8::new(u8::from(Gray::value(*p)) + 1);
+            }
+        }
+        let mut count = 0;
+        for y in 0..12 {
+            for x in 0..16 {
+                let inside = rects.iter().any(|&(rx, ry, w, h)| {
+                    x >= rx && x < rx + w as i32 && y >= ry && y < ry + h as i32
+                });
+                assert_eq!(set.contains(x, y), inside);
+                assert_eq!(r.pixel(x, y), Gray8::new(inside as u8), "{x},{y}");
+                count += inside as usize;
+            }
+        }
+        let n: usize = r.spans(&set).map(|(_, _, s)| s.len()).sum();
+        assert_eq!(n, count);
+        set
+    }
+
+    #[test]
+    fn region_set_overlapping() {
+        let set = visit_once(&[(1, 1, 8, 6), (4, 3, 8, 6), (2, 2, 3, 3)]);
+        assert_eq!(set.regions().collect::<Vec<_>>(), [
+            Region::new(1, 1, 8, 2),
+            Region::new(1, 3, 11, 4),
+            Region::new(4, 7, 8, 2),
+        ]);
+        let mut set2 = RegionSet::new();
+        set2.add((4, 3, 8, 6));
+        set2.add((1, 1, 8, 6));
+        set2.add((2, 2, 3, 3));
+        assert_eq!(set, set2);
+    }
+
+    #[test]
+    fn region_set_adjacent() {
+        let set = visit_once(&[(0, 0, 4, 4), (4, 0, 4, 4), (0, 4, 8, 2)]);
+        assert_eq!(set.regions().collect::<Vec<_>>(), [
+            Region::new(0, 0, 8, 6),
+        ]);
+    }
+
+    #[test]
+    fn region_set_disjoint() {
+        let set = visit_once(&[(0, 0, 2, 2), (5, 1, 3, 2), (10, 8, 9, 9)]);
+        assert_eq!(set.regions().count(), 5);
+        let set = visit_once(&[(-3, -2, 5, 4), (0, 0, 0, 5)]);
+        assert_eq!(set.regions().collect::<Vec<_>>(), [
+            Region::new(-3, -2, 5, 4),
+        ]);
+        assert!(visit_once(&[]).is_empty());
+    }
+
+    #[test]
+    fn clone_raster() {
+        let r = Raster::with_color(3, 2, Rgb8::new(1, 2, 3));
+        let mut c = r.clone();
+        assert_eq!(c, r);
+        *c.pixel_mut(1, 1) = Rgb8::new(4, 5, 6);
+        assert_ne!(c, r);
+        assert_eq!(r.pixel(1, 1), Rgb8::new(1, 2, 3));
+        assert_ne!(Raster::<Rgb8>::with_clear(2, 3),
+            Raster::<Rgb8>::with_clear(3, 2));
+        let r = Raster::<Gray8>::with_u8_buffer(2, 2, vec![1, 2, 3, 4]);
+        let mut c = r.clone();
+        c.pixels_mut()[0] = Gray8::new(9);
+        assert_eq!(r.pixels()[0], Gray8::new(1));
+        assert_ne!(r.pixels().as_ptr(), c.pixels().as_ptr());
+        let rows = r.rows((1, 0, 1, 2));
+        assert_eq!(rows.clone().count(), 2);
+        assert!(rows.eq([[Gray8::new(2)], [Gray8::new(4)]].iter()));
+    }
+
+    #[test]
+    fn noise() {
+        let r = Raster::<SRgba8>::with_noise(4, 2, 0);
+        // first outputs of SplitMix64 with seed 0 are 0xE220A8397B1DCDAF,
+        // 0x6E789E6AA1B965F4, 0x06C45D188009454F, 0xF88BB8A8724C81EC
+        assert_eq!(r.pixel(0, 0), SRgba8::new(0xE2, 0x6E, 0x06, 0xF8));
+        assert_eq!(r.pixel(1, 0), SRgba8::new(0x1B, 0x53, 0x2C, 0xC5));
+        let r = Raster::<Gray16>::with_noise(2, 1, 0);
+        assert_eq!(r.pixels(), [Gray16::new(0xE220), Gray16::new(0x6E78)]);
+        let r = Raster::<Gray32>::with_noise(1, 1, 0);
+        assert_eq!(r.pixel(0, 0), Gray32::new(0xE220A8 as f32 / 16777215.0));
+        let r0 = Raster::<Rgb8>::with_noise(8, 8, 1);
+        let r1 = Raster::<Rgb8>::with_noise(8, 8, 2);
+        assert_ne!(r0, r1);
+        // only the region is filled, starting the sequence at its origin
+        let mut r = Raster::<SRgba8>::with_clear(6, 4);
+        r.fill_noise((2, 1, 9, 9), 0);
+        assert_eq!(r.pixel(1, 1), SRgba8::new(0, 0, 0, 0));
+        assert_eq!(r.pixel(2, 1), SRgba8::new(0xE2, 0x6E, 0x06, 0xF8));
+        assert_eq!(r.pixel(3, 1), SRgba8::new(0x1B, 0x53, 0x2C, 0xC5));
+    }
+
+    #[test]
+    fn rotate_hue() {
+        let clr = Hsv8::new(0x10, 0x80, 0xFF);
+        let mut r = Raster::with_color(4, 4, clr);
+        r.rotate_hue((2, 1, 5, 2), 0x80.into());
+        assert_eq!(r.pixel(1, 1), clr);
+        assert_eq!(r.pixel(2, 1), Hsv8::new(0x90, 0x80, 0xFF));
+        assert_eq!(r.pixel(3, 2), Hsv8::new(0x90, 0x80, 0xFF));
+        assert_eq!(r.pixel(3, 3), clr);
+        r.rotate_hue((2, 1, 5, 2), 0x80.into());
+        assert!(r.pixels().iter().all(|p| *p == clr));
+        let mut r = Raster::with_color(2, 2, Rgb8::new(1, 2, 3));
+        r.rotate_hue((), 0x40.into());
+        assert_eq!(r.pixel(0, 0), Rgb8::new(1, 2, 3));
+    }
+
+    #[test]
+    fn from_f32_slice() {
+        let data = [
+            0.0, 1.0, 0.5, 1.0,
+            -0.5, 1.25, f32::NAN, 1.0,
+            f32::INFINITY, f32::NEG_INFINITY, -0.0, 0.75,
+        ];
+        let (r, clip) = Raster::<Rgba32>::from_f32_slice_clamping(3, 1, &data);
+        assert_eq!(clip, ClipStats { below: 2, above: 2, nan: 1 });
+        assert_eq!(r.pixel(0, 0), Rgba32::new(0.0, 1.0, 0.5, 1.0));
+        assert_eq!(r.pixel(1, 0), Rgba32::new(0.0, 1.0, 0.0, 1.0));
+        assert_eq!(r.pixel(2, 0), Rgba32::new(1.0, 0.0, 0.0, 0.75));
+        assert_eq!(Raster::<Rgba32>::from_f32_slice(3, 1, &data), r);
+        let (_, clip) = Raster::<Gray32>::from_f32_slice_clamping(2, 1,
+            &[0.0, 1.0]);
+        assert_eq!(clip, ClipStats::default());
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_f32_slice_len() {
+        let _ = Raster::<Rgb32>::from_f32_slice(2, 2, &[0.0; 11]);
+    }
+
+    #[test]
+    fn columns() {
+        let r = Raster::<Gray16>::with_noise(5, 4, 3);
+        let cols = r.columns((1, 1, 3, 9));
+        assert_eq!(cols.len(), 3);
+        for (x, col) in (1..).zip(cols) {
+            let col: Vec<_> = col.copied().collect();
+            let expected: Vec<_> = (1..4).map(|y| r.pixel(x, y)).collect();
+            assert_eq!(col, expected);
+            assert_eq!(r.column_to_vec(x, (1, 1, 3, 9)), expected);
+        }
+        let last = r.columns(()).next_back().unwrap();
+        assert!(last.copied().eq((0..4).map(|y| r.pixel(4, y))));
+        assert_eq!(r.columns((5, 0, 2, 2)).count(), 0);
+        assert!(r.column_to_vec(0, (1, 1, 3, 9)).is_empty());
+        assert!(r.column_to_vec(-1, ()).is_empty());
+        assert_eq!(Raster::<Gray8>::with_clear(0, 3).columns(()).count(), 0);
+    }
+
+    #[test]
+    fn set_column() {
+        let mut r = Raster::<Rgb8>::with_noise(4, 5, 7);
+        let orig = r.clone();
+        let col = r.column_to_vec(2, ());
+        r.set_column(2, (), &col);
+        assert_eq!(r, orig);
+        let clr = Rgb8::new(1, 2, 3);
+        r.set_column(3, (0, 3, 9, 9), &[clr, clr]);
+        assert_eq!(r.column_to_vec(3, ())[..3], orig.column_to_vec(3, ())[..3]);
+        assert_eq!(r.pixel(3, 3), clr);
+        assert_eq!(r.pixel(3, 4), clr);
+        assert_eq!(r.pixel(2, 4), orig.pixel(2, 4));
+        r.set_column(9, (), &[]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_column_len() {
+        let mut r = Raster::<Rgb8>::with_clear(4, 5);
+        r.set_column(1, (), &[Rgb8::new(1, 2, 3); 4]);
+    }
+
+    #[test]
+    fn composite_self() {
+        let mut r = Raster::<Rgba8p>::with_noise(12, 10, 7);
+        for (to, from) in [
+            ((3, 2, 6, 5), (1, 1, 6, 5)),
+            ((1, 1, 6, 5), (3, 2, 6, 5)),
+            ((4, 3, 6, 5), (2, 3, 6, 5)),
+            ((2, 3, 6, 5), (4, 3, 6, 5)),
+            ((0, 0, 4, 4), (8, 6, 4, 4)),
+            ((-2, 5, 12, 12), (0, 0, 12, 12)),
+        ] {
+            let src = r.clone();
+            let mut expected = r.clone();
+            expected.composite_raster(to, &src, from, SrcOver);
+            r.composite_self(to, from, SrcOver);
+            assert_eq!(r, expected, "{to:?} {from:?}");
+        }
+    }
+
+    #[test]
+    fn recolor_flat() {
+        let mut r = Raster::<SRgba8>::with_clear(16, 16);
+        for y in 0..16 {
+            for x in 0..16 {
+                let (dx, dy) = (x as f32 - 7.5, y as f32 - 7.5);
+                let a = (8.0 - (dx * dx + dy * dy).sqrt()).clamp(0.0, 1.0);
+                *r.pixel_mut(x, y) =
+                    SRgba8::new(x as u8 * 16, 80, 40, (a * 255.0) as u8);
+            }
+        }
+        let mut rp = Raster::<Rgba8p>::with_raster(&r);
+        let alphas = |r: &Raster<SRgba8>| {
+            r.pixels().iter().map(|p| p.alpha()).collect::<Vec<_>>()
+        };
+        let alphas_p = |r: &Raster<Rgba8p>| {
+            r.pixels().iter().map(|p| p.alpha()).collect::<Vec<_>>()
+        };
+        let (before, before_p) = (alphas(&r), alphas_p(&rp));
+        r.recolor((), SRgba8::new(10, 200, 30, 255), RecolorMode::Flat);
+        rp.recolor((), Rgba8p::new(10, 200, 30, 255), RecolorMode::Flat);
+        assert_eq!(alphas(&r), before);
+        assert_eq!(alphas_p(&rp), before_p);
+        assert!(before.iter().any(|a| *a != Ch8::MAX && *a != Ch8::MIN));
+        assert_eq!(r.pixel(8, 8), SRgba8::new(10, 200, 30, 255));
+        assert_eq!(rp.pixel(8, 8), Rgba8p::new(10, 200, 30, 255));
+        assert_eq!(r.pixel(0, 0), SRgba8::new(10, 200, 30, 0));
+    }
+
+    #[test]
+    fn recolor_luminance() {
+        let mut r = Raster::<SRgba8>::with_clear(2, 1);
+        *r.pixel_mut(0, 0) = SRgba8::new(255, 255, 255, 128);
+        *r.pixel_mut(1, 0) = SRgba8::new(0, 0, 0, 200);
+        let clr = SRgba8::new(200, 40, 90, 255);
+        r.recolor((), clr, RecolorMode::Luminance);
+        assert_eq!(r.pixel(0, 0), SRgba8::new(200, 40, 90, 128));
+        assert_eq!(r.pixel(1, 0), SRgba8::new(0, 0, 0, 200));
+        let mut r = Raster::<Rgba16p>::with_clear(2, 1);
+        *r.pixel_mut(0, 0) = Rgba16p::new(0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF);
+        *r.pixel_mut(1, 0) = Rgba16p::new(0, 0, 0, 0x8000);
+        let clr = Rgba16p::new(0x1234, 0x5678, 0x9ABC, 0xFFFF);
+        r.recolor((), clr, RecolorMode::Luminance);
+        assert_eq!(r.pixel(0, 0), clr);
+        assert_eq!(r.pixel(1, 0), Rgba16p::new(0, 0, 0, 0x8000));
+    }
+
+    #[test]
+    fn non_finite() {
+        let buf: Vec<u8> = [
+            f32::NAN, 0.5, f32::INFINITY, f32::NAN,
+            f32::NEG_INFINITY, 0.25, 2.0, 1.0,
+        ]
+        .iter()
+        .flat_map(|v| v.to_ne_bytes())
+        .collect();
+        let mut r = Raster::<Rgba32p>::with_clear(2, 1);
+        r.as_u8_slice_mut().copy_from_slice(&buf);
+        let chans = r.pixels().iter().flat_map(|p| p.channels().to_vec());
+        assert!(!chans.clone().min().unwrap().is_finite());
+        assert!(!chans.max().unwrap().is_finite());
+        let gray = Rgba32p::new(0.2, 0.2, 0.2, 1.0);
+        let mut dst = Raster::with_color(2, 1, gray);
+        dst.composite_raster((), &r, (), SrcOver);
+        let _r8 = Raster::<Rgba8p>::with_raster(&dst);
+        let _r8 = Raster::<SRgba8>::with_raster(&r);
+        r.sanitize();
+        assert_eq!(r.pixel(0, 0), Rgba32p::new(0.0, 0.5, 0.0, 0.0));
+        assert_eq!(r.pixel(1, 0), Rgba32p::new(0.0, 0.25, 1.0, 1.0));
+        assert!(r.pixels().iter().all(|p| {
+            p.channels().iter().all(|c| c.is_finite())
+        }));
+    }
+
+    #[test]
+    fn composite_opaque_dest() {
+        let src = Raster::<Rgba8>::with_noise(7, 5, 3);
+        let src = Raster::<Rgba8p>::with_raster(&src);
+        let bg = Raster::<Rgb8>::with_noise(9, 6, 4);
+        let ops = [BlendOp::SrcOver, BlendOp::Xor, BlendOp::Darken];
+        for op in ops {
+            let mut rgba = Raster::<Rgba8p>::with_raster(&bg);
+            let mut rgb = Raster::<Rgb8p>::with_raster(&bg);
+            rgba.composite_raster_dyn((1, 2, 8, 4), &src, (), op);
+            with_blend_op!(op, o => {
+                rgb.composite_raster_from((1, 2, 8, 4), &src, (), o)
+            });
+            assert_eq!(Raster::<Rgb8p>::with_raster(&rgba), rgb, "{op:?}");
+        }
+        let mut m = Raster::<Rgb8p>::with_color(2, 1, Rgb8p::new(0, 0, 0));
+        let cov = Raster::<Matte8>::with_color(2, 1, Matte8::new(0x80));
+        m.composite_matte((), &cov, (), Rgb8p::new(0xFF, 0, 0), SrcOver);
+        assert_eq!(m.pixel(1, 0), Rgb8p::new(0x80, 0, 0));
+    }
+
+    #[test]
+    fn apply_colormap() {
+        let gray = [SRgb8::new(0, 0, 0), SRgb8::new(0xFF, 0xFF, 0xFF)];
+        let r = Raster::<Gray8>::with_noise(16, 16, 9);
+        let m = r.apply_colormap(&gray);
+        for (g, p) in r.pixels().iter().zip(m.pixels()) {
+            let v = u8::from(g.one());
+            assert_eq!(*p, SRgb8::new(v, v, v));
+        }
+        let r = Raster::<Gray16>::with_noise(16, 16, 9);
+        let m = r.apply_colormap(&gray);
+        for (g, p) in r.pixels().iter().zip(m.pixels()) {
+            let v = (f32::from(u16::from(g.one())) / 257.0).round() as u8;
+            assert_eq!(*p, SRgb8::new(v, v, v));
+        }
+        let rb = [SRgb8::new(0xFF, 0, 0), SRgb8::new(0, 0, 0xFF)];
+        let r = Raster::<Gray16>::with_pixels(5, 1, vec![
+            Gray16::new(0), Gray16::new(0x4000), Gray16::new(0x8000),
+            Gray16::new(0xC000), Gray16::new(0xFFFF),
+        ]);
+        assert_eq!(r.apply_colormap(&rb).pixels(), [
+            SRgb8::new(0xFF, 0, 0), SRgb8::new(0xBF, 0, 0x40),
+            SRgb8::new(0x7F, 0, 0x80), SRgb8::new(0x40, 0, 0xBF),
+            SRgb8::new(0, 0, 0xFF),
+        ]);
+        let r = Raster::<Gray32>::with_color(2, 2, Gray32::new(0.5));
+        let one = [Rgb8::new(1, 2, 3)];
+        assert_eq!(r.apply_colormap(&one).pixel(1, 1), Rgb8::new(1, 2, 3));
+        let lut: Vec<Rgb8> =
+            (0..=255).map(|i| Rgb8::new(i, 0, 255 - i)).collect();
+        let r = Raster::<Matte8>::with_color(1, 1, Matte8::new(0x12));
+        let m = r.apply_colormap(&lut);
+        assert_eq!(m.pixel(0, 0), Rgb8::new(0x12, 0, 0xED));
+    }
+
+    #[test]
+    #[should_panic(expected = "Empty colormap")]
+    fn apply_colormap_empty() {
+        let r = Raster::<Gray8>::with_clear(1, 1);
+        r.apply_colormap::<Rgb8>(&[]);
+    }
+
+    #[test]
+    fn bounds() {
+        let mut r = Raster::<rgb::SRgba8>::with_clear(20, 12);
+        assert_eq!(r.bounds_nontransparent(), None);
+        for (x, y) in [(0, 0), (19, 11), (7, 0), (0, 5), (13, 9)] {
+            let mut r = r.clone();
+            *r.pixel_mut(x, y) = rgb::SRgba8::new(0, 0, 0, 1);
+            assert_eq!(r.bounds_nontransparent(),
+                Some(Region::new(x, y, 1, 1)));
+        }
+        let clr = rgb::SRgba8::new(0x10, 0x20, 0x30, 0xFF);
+        r.copy_color((5, 3, 4, 2), clr);
+        r.copy_color((2, 7, 2, 1), clr);
+        r.copy_color((14, 6, 1, 3), clr);
+        let reg = r.bounds_nontransparent().unwrap();
+        assert_eq!(reg, Region::new(2, 3, 13, 6));
+        let trimmed = r.crop(reg);
+        assert_eq!((trimmed.width(), trimmed.height()), (13, 6));
+        assert_eq!(trimmed.pixel(0, 4), clr);
+        assert_eq!(trimmed.pixel(12, 5), clr);
+        assert_eq!(trimmed.bounds_nontransparent(), Some(trimmed.region()));
+        assert_eq!(r.bounds_not(clr), Some(r.region()));
+        let r = Raster::with_color(4, 4, clr);
+        assert_eq!(r.bounds_not(clr), None);
+        let r = Raster::<rgb::SRgba8>::with_clear(0, 0);
+        assert_eq!(r.bounds_where(|_| true), None);
+    }
+
+    #[test]
+    fn composite_matte_pair() {
+        fn check<P, M, O>(seed: u64, op: O)
+        where
+            P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+            M: Pixel<Model = Matte>,
+            P::Chan: From<M::Chan>,
+            O: Blend,
+        {
+            let dst = Raster::<P>::with_noise(13, 11, seed);
+            let m = Raster::<M>::with_noise(9, 9, seed + 1);
+            let mut inv = m.clone();
+            for p in inv.pixels_mut() {
+                *p.alpha_mut() = M::Chan::MAX - p.alpha();
+            }
+            let clrs = Raster::<P>::with_noise(2, 1, seed + 2);
+            let (inside, outside) = (clrs.pixel(0, 0), clrs.pixel(1, 0));
+            let mut r0 = dst.clone();
+            r0.composite_matte((2, -1, 9, 9), &m, (1, 0, 8, 9), inside, op);
+            r0.composite_matte((2, -1, 9, 9), &inv, (1, 0, 8, 9), outside,
+                op);
+            let mut r1 = dst;
+            r1.composite_matte_pair((2, -1, 9, 9), &m, (1, 0, 8, 9), inside,
+                outside, op);
+            assert_eq!(r0.pixels(), r1.pixels());
+        }
+        for seed in 0..8 {
+            check::<Rgba8p, Matte8, _>(seed, SrcOver);
+            check::<Rgba8p, Matte8, _>(seed, Xor);
+            check::<Rgba16p, Matte8, _>(seed, Plus);
+            check::<Graya32p, Matte16, _>(seed, DestOut);
+            check::<Rgba8p, Matte32, _>(seed, SrcOver);
+        }
+    }
+
+    #[test]
+    fn full_width_rows() {
+        let mut src = Raster::<Rgba8p>::with_clear(5, 4);
+        for (i, p) in src.pixels_mut().iter_mut().enumerate() {
+            let v = i as u8 * 12;
+            *p = Rgba8p::new(v / 2, v / 3, v / 4, v);
+        }
+        let bg = Rgba8p::new(10, 20, 30, 40);
+        // full width (contiguous) vs. two half-width (per row) regions
+        let mut r0 = Raster::with_color(5, 5, bg);
+        r0.copy_raster((0, 1, 5, 2), &src, (0, 2, 5, 2));
+        let mut r1 = Raster::with_color(5, 5, bg);
+        r1.copy_raster((0, 1, 2, 2), &src, (0, 2, 2, 2));
+        r1.copy_raster((2, 1, 3, 2), &src, (2, 2, 3, 2));
+        assert_eq!(r0.pixels(), r1.pixels());
+        assert_eq!(r0.pixel(4, 2), src.pixel(4, 3));
+        assert_eq!(r0.pixel(0, 3), bg);
+        let mut r0 = Raster::with_color(5, 5, bg);
+        r0.composite_raster((0, 2, 5, 9), &src, (), SrcOver);
+        let mut r1 = Raster::with_color(5, 5, bg);
+        r1.composite_raster((0, 2, 2, 9), &src, (), SrcOver);
+        r1.composite_raster((2, 2, 3, 9), &src, (2, 0, 3, 9), SrcOver);
+        assert_eq!(r0.pixels(), r1.pixels());
+        let mut r0 = Raster::with_color(5, 5, bg);
+        r0.copy_color((0, 3, 5, 1), Rgba8p::new(1, 2, 3, 4));
+        let mut r1 = Raster::with_color(5, 5, bg);
+        r1.copy_color((0, 3, 2, 1), Rgba8p::new(1, 2, 3, 4));
+        r1.copy_color((2, 3, 3, 1), Rgba8p::new(1, 2, 3, 4));
+        assert_eq!(r0.pixels(), r1.pixels());
+    }
+
+    #[test]
+    fn raster_scaled() {
+        let mut sprite = Raster::<Rgba8p>::with_clear(4, 3);
+        for (i, p) in sprite.pixels_mut().iter_mut().enumerate() {
+            let v = i as u8 * 20;
+            *p = Rgba8p::new(v / 2, v / 3, 0, v);
+        }
+        // integer factor: compare with an upscaled copy
+        let mut big = Raster::<Rgba8p>::with_clear(12, 6);
+        for y in 0..6 {
+            for x in 0..12 {
+                *big.pixel_mut(x, y) = sprite.pixel(x / 3, y / 2);
+            }
+        }
+        let bg = Rgba8p::new(10, 20, 30, 40);
+        let mut r0 = Raster::<Rgba8p>::with_color(16, 8, bg);
+        r0.composite_raster((2, 1, 12, 6), &big, (), SrcOver);
+        let mut r1 = Raster::<Rgba8p>::with_color(16, 8, bg);
+        r1.composite_raster_scaled((2, 1, 12, 6), &sprite, (), SrcOver);
+        assert_eq!(r0.pixels(), r1.pixels());
+        let mut r2 = Raster::<Rgba8p>::with_color(16, 8, bg);
+        r2.copy_raster((2, 1, 12, 6), &big, ());
+        let mut r3 = Raster::<Rgba8p>::with_color(16, 8, bg);
+        r3.copy_raster_scaled((2, 1, 12, 6), &sprite, ());
+        assert_eq!(r2.pixels(), r3.pixels());
+        // clipped destination
+        let mut r4 = Raster::<Rgba8p>::with_color(4, 4, bg);
+        r4.copy_raster_scaled((-6, -2, 12, 6), &sprite, ());
+        assert_eq!(r4.pixel(0, 0), big.pixel(6, 2));
+        assert_eq!(r4.pixel(3, 3), big.pixel(9, 5));
+        // non-integer factor
+        let mut r5 = Raster::<Rgba8p>::with_clear(5, 1);
+        r5.copy_raster_scaled((), &sprite, (0, 1, 3, 1));
+        let v: Vec<_> = [0, 0, 1, 2, 2].iter().map(|x| sprite.pixel(*x, 1))
+            .collect();
+        assert_eq!(r5.pixels(), &v[..]);
+        // degenerate regions
+        let mut r6 = Raster::<Rgba8p>::with_color(4, 4, bg);
+        r6.copy_raster_scaled((0, 0, 0, 4), &sprite, ());
+        r6.copy_raster_scaled((), &sprite, (1, 1, 0, 0));
+        r6.copy_raster_scaled((), &sprite, (1, 1, 2, 0));
+        r6.copy_raster_scaled((), &sprite, (8, 8, 2, 2));
+        assert!(r6.pixels().iter().all(|p| *p == bg));
+    }
+
+    #[test]
+    fn convert_region() {
+        let mut src = Raster::<Rgba8p>::with_clear(8, 6);
+        for (i, p) in src.pixels_mut().iter_mut().enumerate() {
+            let v = i as u8 * 5;
+            *p = Rgba8p::new(v / 2, v / 3, v / 4, v);
+        }
+        let full = Raster::<SBgra8>::with_raster(&src);
+        let bg = SBgra8::new(1, 2, 3, 4);
+        let mut r = Raster::<SBgra8>::with_color(8, 6, bg);
+        r.convert_region_from((2, 1, 3, 4), &src, (2, 1, 3, 4));
+        for y in 0..6 {
+            for x in 0..8 {
+                let inside = (2..5).contains(&x) && (1..5).contains(&y);
+                let p = if inside { full.pixel(x, y) } else { bg };
+                assert_eq!(r.pixel(x, y), p);
+            }
+        }
+        let mut r = Raster::<SBgra8>::with_color(4, 4, bg);
+        r.convert_region_from((-1, 2, 8, 8), &src, ());
+        assert_eq!(r.pixel(0, 1), bg);
+        assert_eq!(r.pixel(0, 2), full.pixel(1, 0));
+        assert_eq!(r.pixel(3, 3), full.pixel(4, 1));
+    }
+
+    #[test]
+    fn endian_bytes() {
+        let mut r = Raster::<Rgb16>::with_clear(2, 2);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = i as u16 * 0x1111;
+            *p = Rgb16::new(v, v + 0x0102, 0xFF00 - v);
+        }
+        let be = r.to_be_bytes();
+        let le = r.to_le_bytes();
+        assert_eq!(be[2..4], [0x01, 0x02]);
+        assert_eq!(le[2..4], [0x02, 0x01]);
+        assert_eq!(be[4..6], [0xFF, 0x00]);
+        assert_eq!(le[4..6], [0x00, 0xFF]);
+        let native = if cfg!(target_endian = "little") { &le } else { &be };
+        assert_eq!(native[..], r.as_u8_slice()[..]);
+        let b = Raster::<Rgb16>::from_be_bytes(2, 2, &be);
+        assert_eq!(b.pixels(), r.pixels());
+        let l = Raster::<Rgb16>::from_le_bytes(2, 2, &le);
+        assert_eq!(l.pixels(), r.pixels());
+        let r8 = Raster::<SRgb8>::with_color(2, 1, SRgb8::new(1, 2, 3));
+        assert_eq!(r8.to_be_bytes(), r8.as_u8_slice());
+        assert_eq!(r8.to_le_bytes(), r8.as_u8_slice());
+        let b8 = Raster::<SRgb8>::from_be_bytes(2, 1, &r8.to_be_bytes());
+        assert_eq!(b8.pixels(), r8.pixels());
+    }
+
+    #[test]
+    fn endian_bytes_float() {
+        let r = Raster::with_color(1, 1, Gray32::new(0.5));
+        assert_eq!(r.to_be_bytes(), [0x3F, 0x00, 0x00, 0x00]);
+        assert_eq!(r.to_le_bytes(), [0x00, 0x00, 0x00, 0x3F]);
+        let b = Raster::<Gray32>::from_be_bytes(1, 1, &r.to_be_bytes());
+        assert_eq!(b.pixels(), r.pixels());
+        let l = Raster::<Gray32>::from_le_bytes(1, 1, &r.to_le_bytes());
+        assert_eq!(l.pixels(), r.pixels());
+        let r = Raster::with_color(1, 1, Gray64::new(0.25));
+        assert_eq!(r.to_be_bytes()[..2], [0x3F, 0xD0]);
+        let b = Raster::<Gray64>::from_be_bytes(1, 1, &r.to_be_bytes());
+        assert_eq!(b.pixels(), r.pixels());
+    }
+
+    #[test]
+    fn buffers_f64() {
+        let buf = vec![0.5, 1.5, f64::NAN, -1.0, 0.25, 0.75];
+        let mut raster = Raster::<Graya64>::with_f64_buffer(3, 1, buf);
+        assert_eq!(raster.pixel(0, 0), Graya64::new(0.5, 1.0));
+        assert_eq!(raster.pixel(1, 0), Graya64::new(0.0, 0.0));
+        *raster.pixel_mut(2, 0) = Graya64::new(0.125, 1.0);
+        let slice: Box<[f64]> = raster.into();
+        let v: Vec<f64> = slice.into();
+        assert_eq!(v, [0.5, 1.0, 0.0, 0.0, 0.125, 1.0]);
+        let clr = SRgb8::new(0x40, 0x80, 0xC0);
+        let r = Raster::<SRgb64>::with_color_from(2, 2, clr);
+        let r8 = Raster::<SRgb8>::with_raster(&r);
+        assert_eq!(r8.pixel(1, 1), clr);
+    }
+
+    #[test]
+    fn region_checked() {
+        let reg = Region::new(1, 2, 3, 4);
+        assert_eq!(Region::checked_new(1, 2, 3, 4), Some(reg));
+        assert_eq!(Region::checked_new(0, 0, 1 << 31, 1), None);
+        assert_eq!(Region::checked_new(0, 0, 1, u32::MAX), None);
+        assert!(Region::checked_new(-5, -5, i32::MAX as u32, 0).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Region too big: 4294967295 x 1")]
+    fn region_too_big() {
+        Region::new(0, 0, u32::MAX, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Raster too big: 65536 x 65536")]
+    fn raster_too_big() {
+        Raster::<Gray8>::with_clear(65536, 65536);
+    }
+
+    #[test]
+    #[should_panic(expected = "Raster too big: 65536 x 65536")]
+    fn raster_too_big_buffer() {
+        let _ = Raster::<Rgba16>::with_u16_buffer(65536, 65536, Vec::new());
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "32")]
+    #[should_panic(expected = "Raster too big: 40000 x 40000")]
+    fn raster_too_big_bytes() {
+        // fits in i32 pixels, but not isize bytes
+        Raster::<Rgba16>::with_clear(40000, 40000);
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(Raster::<Gray8>::with_clear(10, 10).bytes(), 100);
+        assert_eq!(Raster::<Rgb16>::with_clear(10, 3).bytes(), 180);
+        assert_eq!(Raster::<Rgba32>::with_clear(7, 5).bytes(), 560);
+        assert_eq!(Raster::<Rgba32>::with_clear(0, 5).bytes(), 0);
+        let r = Raster::<Rgba8>::with_clear(9, 4);
+        assert_eq!(r.bytes(), r.as_u8_slice().len());
+    }
+
+    #[test]
+    fn buffer_bytes() {
+        // limits for a 32-bit target
+        let max = i32::MAX as usize;
+        let bytes = super::buffer_bytes;
+        assert_eq!(bytes(0x07FF_FFFF, 16, max), Some(0x7FFF_FFF0));
+        assert_eq!(bytes(0x0800_0000, 16, max), None);
+        assert_eq!(bytes(0x7FFF_FFFF, 1, max), Some(max));
+        assert_eq!(bytes(usize::MAX, 2, usize::MAX), None);
+        // 40000 x 40000 Rgba16 fits in i32 pixels, but not i32 bytes
+        assert_eq!(bytes(40_000 * 40_000, 8, max), None);
+        // largest pixel count with largest pixels on a 64-bit target
+        assert!(bytes(max, 32, i64::MAX as usize).is_some());
+    }
+
+    #[test]
+    fn region_empty() {
+        assert!(Region::default().is_empty());
+        assert!(Region::new(5, 5, 0, 10).is_empty());
+        assert!(Region::new(5, 5, 10, 0).is_empty());
+        assert!(!Region::new(-5, -5, 1, 1).is_empty());
+        let reg = Region::new(0, 0, 5, 5);
+        assert!(reg.intersection((10, 10, 5, 5)).is_empty());
+    }
+
+    #[test]
+    fn intersect() -> Result<(), ()> {
+        let r = Region::new(0, 0, 5, 5);
+        assert_eq!(r, Region::new(0, 0, 5, 5));
+        assert_eq!(r, r.intersection(Region::new(0, 0, 10, 10)));
+        assert_eq!(r, r.intersection(Region::new(-5, -5, 10, 10)));
+        assert_eq!(Region::new(2, 2, 3, 3), r.intersection((2, 2)));
+        assert_eq!(Region::new(0, 0, 4, 4), r.intersection((-1, -1, 5, 5)));
+        assert_eq!(Region::new(1, 2, 1, 3), r.intersection((1, 2, 1, 100)));
+        assert_eq!(Region::new(2, 1, 3, 1), r.intersection((2, 1, 100, 1)));
+        Ok(())
+    }
+
+    #[test]
+    fn with_buffer_rgb8() {
+        let b = vec![
+            0xAA,0x00,0x00, 0x00,0x11,0x22, 0x33,0x44,0x55,
+            0x00,0xBB,0x00, 0x66,0x77,0x88, 0x99,0xAA,0xBB,
+            0x00,0x00,0xCC, 0xCC,0xDD,0xEE, 0xFF,0x00,0x11,
+        ];
+        let r = Raster::<SRgb8>::with_u8_buffer(3, 3, b);
+        let v = vec![
+            SRgb8::new(0xAA, 0x00, 0x00), SRgb8::new(0x00, 0x11, 0x22),
+            SRgb8::new(0x33, 0x44, 0x55),
+            SRgb8::new(0x00, 0xBB, 0x00), SRgb8::new(0x66, 0x77, 0x88),
+            SRgb8::new(0x99, 0xAA, 0xBB),
+            SRgb8::new(0x00, 0x00, 0xCC), SRgb8::new(0xCC, 0xDD, 0xEE),
+            SRgb8::new(0xFF, 0x00, 0x11),
+        ];
+        assert_eq!(r.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn with_buffer_graya16() {
+        let b = vec![
+            0x1001,0x5005, 0x1000,0x3002, 0x5004,0x7006,
+            0x2002,0x6006, 0x9008,0xB00A, 0xD00C,0xF00E,
+            0x3003,0x7007, 0xE00F,0xC00D, 0xA00B,0x8009,
+        ];
+        let r = Raster::<SGraya16>::with_u16_buffer(3, 3, b);
+        let v = vec![
+            SGraya16::new(0x1001, 0x5005), SGraya16::new(0x1000, 0x3002),
+            SGraya16::new(0x5004, 0x7006),
+            SGraya16::new(0x2002, 0x6006), SGraya16::new(0x9008, 0xB00A),
+            SGraya16::new(0xD00C, 0xF00E),
+            SGraya16::new(0x3003, 0x7007), SGraya16::new(0xE00F, 0xC00D),
+            SGraya16::new(0xA00B, 0x8009),
+        ];
+        assert_eq!(r.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn with_pixels_matte32() {
+        let p = vec![
+            Matte32::new(0.25), Matte32::new(0.5), Matte32::new(0.75),
+            Matte32::new(0.5), Matte32::new(0.6), Matte32::new(0.7),
+            Matte32::new(0.85), Matte32::new(0.65), Matte32::new(0.45),
+        ];
+        let r = Raster::with_pixels(3, 3, p.clone());
+        assert_eq!(r.pixels(), &p[..]);
+    }
+
+    #[test]
+    fn pixel_mut_matte8() {
+        let mut r = Raster::<Matte8>::with_clear(3, 3);
+        *r.pixel_mut(0, 0) = Matte8::new(0xFF);
+        *r.pixel_mut(2, 0) = Matte8::new(0x12);
+        *r.pixel_mut(1, 1) = Matte8::new(0x34);
+        *r.pixel_mut(0, 2) = Matte8::new(0x56);
+        *r.pixel_mut(2, 2) = Matte8::new(0x78);
+        let v = vec![
+            Matte8::new(0xFF), Matte8::new(0x00), Matte8::new(0x12),
+            Matte8::new(0x00), Matte8::new(0x34), Matte8::new(0x00),
+            Matte8::new(0x56), Matte8::new(0x00), Matte8::new(0x78),
+        ];
+        assert_eq!(r.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn pixel_mut_matte16() {
+        let mut r = Raster::<Matte16>::with_clear(3, 3);
+        *r.pixel_mut(2, 0) = Matte16::new(0x9ABC);
+        *r.pixel_mut(1, 1) = Matte16::new(0x5678);
+        *r.pixel_mut(0, 2) = Matte16::new(0x1234);
+        *r.pixel_mut(0, 0) = Matte16::new(1.0);
+        *r.pixel_mut(2, 2) = Matte16::new(0x8080);
+        let v = vec![
+            Matte16::new(0xFFFF), Matte16::new(0x0000), Matte16::new(0x9ABC),
+            Matte16::new(0x0000), Matte16::new(0x5678), Matte16::new(0x0000),
+            Matte16::new(0x1234), Matte16::new(0x0000), Matte16::new(0x8080),
+        ];
+        assert_eq!(r.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn invalid_rows() {
+        let r = Raster::<Matte8>::with_clear(10, 10);
+        let mut rows = r.rows((0, 20, 0, 0));
+        assert_eq!(rows.next(), None);
+    }
+
+    #[test]
+    fn raster_with_color() {
+        let r = Raster::with_color(3, 3, Hwb8::new(0x80, 0, 0));
+        let v = [Hwb8::new(0x80, 0, 0); 9];
+        assert_eq!(r.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn copy_color_gray8() {
+        let mut r = Raster::<SGray8>::with_clear(3, 3);
+        r.copy_color((0, 0, 1, 1), SGray8::new(0x23));
+        r.copy_color((10, 10, 1, 1), SGray8::new(0x45));
+        r.copy_color((1, 1, 10, 10), SGray8::new(0xBB));
+        let v = vec![
+            SGray8::new(0x23), SGray8::new(0), SGray8::new(0),
+            SGray8::new(0), SGray8::new(0xBB), SGray8::new(0xBB),
+            SGray8::new(0), SGray8::new(0xBB), SGray8::new(0xBB),
+        ];
+        assert_eq!(r.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn copy_color_srgb8() {
+        let mut r = Raster::<SRgb8>::with_clear(3, 3);
+        r.copy_color((2, -1, 3, 4), SRgb8::new(0xCC, 0xAA, 0xBB));
+        let v = vec![
+            SRgb8::new(0, 0, 0), SRgb8::new(0, 0, 0),
+            SRgb8::new(0xCC, 0xAA, 0xBB),
+            SRgb8::new(0, 0, 0), SRgb8::new(0, 0, 0),
+            SRgb8::new(0xCC, 0xAA, 0xBB),
+            SRgb8::new(0, 0, 0), SRgb8::new(0, 0, 0),
+            SRgb8::new(0xCC, 0xAA, 0xBB),
+        ];
+        assert_eq!(r.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn copy_raster_gray() {
+        let mut g0 = Raster::<Gray8>::with_clear(3, 3);
+        let g1 = Raster::<Gray8>::with_color(3, 3, Gray8::new(0x40));
+        let g2 = Raster::<Gray8>::with_color(3, 3, Gray8::new(0x60));
+        let g3 = Raster::<Gray8>::with_color(3, 3, Gray8::new(0x80));
+        g0.copy_raster((-1, 2, 3, 3), &g1, ());
+        g0.copy_raster((2, -1, 3, 3), &g2, ());
+        g0.copy_raster((-2, -2, 3, 3), &g3, ());
+        let v = vec![
+            Gray8::new(0x80), Gray8::new(0x00), Gray8::new(0x60),
+            Gray8::new(0x00), Gray8::new(0x00), Gray8::new(0x60),
+            Gray8::new(0x40), Gray8::new(0x40), Gray8::new(0x00),
+        ];
+        assert_eq!(g0.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn composite_color_graya8_over() {
+        let clr = Graya8p::new(0x20, 0x40);
+        let mut r = Raster::<Graya8p>::with_color(2, 2, clr);
+        r.composite_color((0, 0, 3, 1), Graya8p::new(0x60, 0xA0), SrcOver);
+        r.composite_color((1, 1, 4, 4), Graya8p::new(0x80, 0x80), SrcOver);
+        let v = [
+            Graya8p::new(0x6C, 0xB8), Graya8p::new(0x6C, 0xB8),
+            Graya8p::new(0x20, 0x40), Graya8p::new(0x90, 0xA0),
+        ];
+        assert_eq!(r.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn composite_raster_rgb() {
+        let mut rgb = Raster::<Rgba8p>::with_clear(3, 3);
+        let gray = Raster::with_color(3, 3, Gray16::new(0x8000));
+        let r = Raster::with_raster(&gray);
+        rgb.composite_raster((), &r, (0, 1), Src);
+        let mut v = vec![Rgba8p::new(0x80, 0x80, 0x80, 0xFF); 6];
+        v.extend_from_slice(&[Rgba8p::new(0, 0, 0, 0); 3]);
+        assert_eq!(rgb.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn composite_matte_full() {
+        let mut r = Raster::<Rgba8p>::with_clear(2, 2);
+        let mut m = Raster::<Matte8>::with_clear(2, 2);
+        *m.pixel_mut(0, 0) = Matte8::new(0xFF);
+        *m.pixel_mut(1, 1) = Matte8::new(0x80);
+        let v = [
+            Matte8::new(0xFF), Matte8::new(0),
+            Matte8::new(0), Matte8::new(0x80),
+        ];
+        assert_eq!(m.pixels(), &v);
+        let c = Rgba8p::new(0xFF, 0x80, 0x40, 0xFF);
+        r.composite_matte((), &m, (), c, SrcOver);
+        let v = [
+            Rgba8p::new(0xFF, 0x80, 0x40, 0xFF), Rgba8p::new(0, 0, 0, 0),
+            Rgba8p::new(0, 0, 0, 0), Rgba8p::new(0x80, 0x40, 0x20, 0x80),
+        ];
+        assert_eq!(r.pixels(), &v);
+    }
+
+    #[test]
+    fn composite_matte_smaller() {
+        let mut r = Raster::<Rgba8p>::with_clear(3, 3);
+        let m = vec![
+            Matte8::new(0xFF), Matte8::new(0x80),
+            Matte8::new(0x40), Matte8::new(0x20),
+        ];
+        let m = Raster::<Matte8>::with_pixels(2, 2, m);
+        let c = Rgba8p::new(0x40, 0x80, 0x60, 0x80);
+        r.composite_matte((1, 1, 4, 4), &m, (), c, SrcOver);
+        let v = [
+            Rgba8p::new(0, 0, 0, 0),
+            Rgba8p::new(0, 0, 0, 0),
+            Rgba8p::new(0, 0, 0, 0),
+
+            Rgba8p::new(0, 0, 0, 0),
+            Rgba8p::new(0x40, 0x80, 0x60, 0x80),
+            Rgba8p::new(0x20, 0x40, 0x30, 0x40),
+
+            Rgba8p::new(0, 0, 0, 0),
+            Rgba8p::new(0x10, 0x20, 0x18, 0x20),
+            Rgba8p::new(0x08, 0x10, 0x0C, 0x10),
+        ];
+        assert_eq!(r.pixels(), &v);
+    }
+
+    #[test]
+    fn composite_matte_gray() {
+        let mut g0 = Raster::<Graya8p>::with_clear(2, 2);
+        let g1 = Raster::<Matte8>::with_color(3, 3, Matte8::new(0x40));
+        let g2 = Raster::<Matte8>::with_color(3, 3, Matte8::new(0x60));
+        let g3 = Raster::<Matte8>::with_color(3, 3, Matte8::new(0x80));
+        let clr = Graya8p::new(0xFF, 0xFF);
+        g0.composite_matte((1, 1, 3, 3), &g1, (), clr, SrcOver);
+        g0.composite_matte((1, -2, 3, 3), &g2, (), clr, SrcOver);
+        g0.composite_matte((-2, -2, 3, 3), &g3, (), clr, SrcOver);
+        let v = [
+            Graya8p::new(0x80, 0x80), Graya8p::new(0x60, 0x60),
+            Graya8p::new(0x00, 0x00), Graya8p::new(0x40, 0x40),
+        ];
+        assert_eq!(g0.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn planes_rgba8() {
+        let p = vec![
+            Rgba8::new(0x01, 0x02, 0x03, 0x04),
+            Rgba8::new(0x11, 0x12, 0x13, 0x14),
+            Rgba8::new(0x21, 0x22, 0x23, 0x24),
+            Rgba8::new(0x31, 0x32, 0x33, 0x34),
+        ];
+        let r = Raster::with_pixels(2, 2, p);
+        let planes = r.to_planes();
+        assert_eq!(planes.len(), 4);
+        let u8_planes = r.to_u8_planes();
+        assert_eq!(u8_planes[0], [0x01, 0x11, 0x21, 0x31]);
+        assert_eq!(u8_planes[3], [0x04, 0x14, 0x24, 0x34]);
+        let g = Raster::<Gray8>::with_u8_buffer(2, 1, vec![7, 9]);
+        assert_eq!(g.to_u8_planes(), [[7, 9]]);
+        let planes: Vec<_> = planes.iter().map(|p| &p[..]).collect();
+        let r1 = Raster::<Rgba8>::from_planes(2, 2, &planes);
+        assert_eq!(r.pixels(), r1.pixels());
+    }
+
+    #[test]
+    fn planes_graya16() {
+        let p = vec![
+            SGraya16::new(0x1001, 0x5005), SGraya16::new(0x1000, 0x3002),
+            SGraya16::new(0x5004, 0x7006), SGraya16::new(0xFFFF, 0x0000),
+            SGraya16::new(0x2002, 0x6006), SGraya16::new(0x9008, 0xB00A),
+        ];
+        let r = Raster::with_pixels(3, 2, p);
+        let planes = r.to_planes();
+        assert_eq!(planes.len(), 2);
+        assert_eq!(planes[1].len(), 6);
+        let planes: Vec<_> = planes.iter().map(|p| &p[..]).collect();
+        let r1 = Raster::<SGraya16>::from_planes(3, 2, &planes);
+        assert_eq!(r.pixels(), r1.pixels());
+    }
+
+    #[test]
+    #[should_panic]
+    fn planes_wrong_len() {
+        let plane = [chan::Ch8::new(0); 3];
+        let _ = Raster::<Gray8>::from_planes(2, 2, &[&plane[..]]);
+    }
+
+    #[test]
+    fn composite_matte_mixed_depth() {
+        let m = vec![
+            Matte8::new(0xFF), Matte8::new(0x80), Matte8::new(0x40),
+            Matte8::new(0x20), Matte8::new(0x10), Matte8::new(0x00),
+        ];
+        let m = Raster::<Matte8>::with_pixels(3, 2, m);
+        let c = Rgba16p::new(0x4000, 0x8000, 0x6000, 0x8000);
+        let mut r0 = Raster::<Rgba16p>::with_clear(3, 2);
+        r0.composite_matte((), &m, (), c, SrcOver);
+        let m16 = Raster::<Matte16>::with_raster(&m);
+        let mut r1 = Raster::<Rgba16p>::with_clear(3, 2);
+        r1.composite_matte((), &m16, (), c, SrcOver);
+        assert_eq!(r0.pixels(), r1.pixels());
+    }
+
+    #[test]
+    fn composite_matte_masked() {
+        let mut m = Raster::<Matte8>::with_clear(4, 3);
+        for (i, p) in m.pixels_mut().iter_mut().enumerate() {
+            *p = Matte8::new((i * 23) as u8);
+        }
+        let clr = Rgba8p::new(0x20, 0x60, 0x40, 0x80);
+        // two-step: temporary color raster, masked by matte
+        let mut tmp = Raster::<Rgba8p>::with_clear(3, 2);
+        for (i, p) in tmp.pixels_mut().iter_mut().enumerate() {
+            let a = m.pixel(i as i32 % 3 + 1, i as i32 / 3 + 1).alpha();
+            let ch: Vec<_> = clr.channels().iter().map(|c| *c * a).collect();
+            *p = Rgba8p::from_channels(&ch);
+        }
+        let bg = Rgba8p::new(0x50, 0x30, 0x20, 0xC0);
+        let mut r0 = Raster::with_color(5, 4, bg);
+        r0.composite_raster((2, 1, 3, 2), &tmp, (), SrcOver);
+        let mut r1 = Raster::with_color(5, 4, bg);
+        r1.composite_matte((2, 1, 4, 4), &m, (1, 1, 3, 2), clr, SrcOver);
+        assert_eq!(r0.pixels(), r1.pixels());
+        let mut r0 = Raster::with_color(5, 4, bg);
+        r0.composite_raster((2, 1, 3, 2), &tmp, (), Clear);
+        let mut r1 = Raster::with_color(5, 4, bg);
+        r1.composite_matte((2, 1, 4, 4), &m, (1, 1, 3, 2), clr, Clear);
+        assert_eq!(r0.pixels(), r1.pixels());
+    }
+
+    #[test]
+    fn composite_color_masked() {
+        let mut m = Raster::<Matte8>::with_clear(4, 3);
+        for (i, p) in m.pixels_mut().iter_mut().enumerate() {
+            *p = Matte8::new((i * 41) as u8);
+        }
+        let clr = Rgba8p::new(0x40, 0x10, 0x80, 0xA0);
+        // two-step: temporary color raster, masked by matte
+        let mut tmp = Raster::<Rgba8p>::with_clear(3, 2);
+        for (i, p) in tmp.pixels_mut().iter_mut().enumerate() {
+            let a = m.pixel(i as i32 % 3 + 1, i as i32 / 3 + 1).alpha();
+            let ch: Vec<_> = clr.channels().iter().map(|c| *c * a).collect();
+            *p = Rgba8p::from_channels(&ch);
+        }
+        let bg = Rgba8p::new(0x50, 0x30, 0x20, 0xC0);
+        let mut r0 = Raster::with_color(5, 4, bg);
+        r0.composite_raster((2, 1, 3, 2), &tmp, (), SrcOver);
+        let mut r1 = Raster::with_color(5, 4, bg);
+        r1.composite_color_masked((2, 1, 4, 4), clr, &m, (1, 1, 3, 2),
+            SrcOver);
+        assert_eq!(r0.pixels(), r1.pixels());
+        let mut r0 = Raster::with_color(5, 4, bg);
+        r0.composite_raster((2, 1, 3, 2), &tmp, (), Clear);
+        let mut r1 = Raster::with_color(5, 4, bg);
+        r1.composite_color_masked((2, 1, 4, 4), clr, &m, (1, 1, 3, 2),
+            Clear);
+        assert_eq!(r0.pixels(), r1.pixels());
+    }
+
+    #[test]
+    fn composite_matte_srgb_tag() {
+        use crate::chan::{Ch8, Premultiplied, Srgb};
+        use crate::el::Pix1;
+        type SMatte8 = Pix1<Ch8, Matte, Premultiplied, Srgb>;
+        let m = Raster::<Matte8>::with_color(2, 2, Matte8::new(0x60));
+        let sm = Raster::<SMatte8>::with_color(2, 2, SMatte8::new(0x60));
+        let c = Rgba8p::new(0x40, 0x80, 0x60, 0x80);
+        let mut r0 = Raster::<Rgba8p>::with_clear(2, 2);
+        r0.composite_matte((), &m, (), c, SrcOver);
+        let mut r1 = Raster::<Rgba8p>::with_clear(2, 2);
+        r1.composite_matte((), &sm, (), c, SrcOver);
+        assert_eq!(r0.pixels(), r1.pixels());
+    }
+
+    #[test]
+    fn composite_coverage_gray() {
+        let v = [0xFF, 0xC0, 0x80, 0x40, 0x10, 0x00];
+        let m = v.iter().map(|v| Matte8::new(*v)).collect::<Vec<_>>();
+        let m = Raster::<Matte8>::with_pixels(3, 2, m);
+        let g = v.iter().map(|v| SGray8::new(*v)).collect::<Vec<_>>();
+        let g = Raster::<SGray8>::with_pixels(3, 2, g);
+        let c = Rgba8p::new(0x40, 0x80, 0x60, 0x80);
+        let bg = Rgba8p::new(9, 0, 0, 9);
+        let mut r0 = Raster::<Rgba8p>::with_color(3, 2, bg);
+        r0.composite_matte((), &m, (), c, SrcOver);
+        let mut r1 = Raster::<Rgba8p>::with_color(3, 2, bg);
+        r1.composite_coverage((), &g, (), c, SrcOver);
+        assert_eq!(r0.pixels(), r1.pixels());
+        let mut r2 = Raster::<Rgba8p>::with_color(3, 2, bg);
+        r2.composite_coverage((), &m, (), c, SrcOver);
+        assert_eq!(r0.pixels(), r2.pixels());
+    }
+
+    #[test]
+    fn validate_premultiplied() {
+        let mut r = Raster::<Rgba8p>::with_clear(3, 3);
+        assert_eq!(r.validate_premultiplied(), None);
+        *r.pixel_mut(2, 1) = Rgba8p::new(0xFF, 0xFF, 0xFF, 0x00);
+        *r.pixel_mut(1, 2) = Rgba8p::new(0x00, 0x00, 0x20, 0x10);
+        assert_eq!(r.validate_premultiplied(), Some((2, 1)));
+        let mut g = Raster::<Graya16p>::with_clear(2, 2);
+        *g.pixel_mut(1, 1) = Graya16p::new(0x2000, 0x1000);
+        assert_eq!(g.validate_premultiplied(), Some((1, 1)));
+    }
+
+    #[test]
+    fn with_raster_same_format() {
+        let p = vec![
+            SRgba8::new(0x10, 0x20, 0x30, 0x40),
+            SRgba8::new(0x50, 0x60, 0x70, 0x80),
+            SRgba8::new(0x90, 0xA0, 0xB0, 0xC0),
+            SRgba8::new(0xD0, 0xE0, 0xF0, 0xFF),
+        ];
+        let r0 = Raster::with_pixels(2, 2, p);
+        let r1 = Raster::<SRgba8>::with_raster(&r0);
+        assert_eq!(r0.pixels(), r1.pixels());
+        let r2 = Raster::<SRgba16>::with_raster(&r0);
+        let v: Vec<SRgba16> =
+            r0.pixels().iter().map(|p| p.convert()).collect();
+        assert_eq!(r2.pixels(), &v[..]);
+    }
+
+    #[test]
+    fn crop() {
+        let p = vec![
+            Gray8::new(0x01), Gray8::new(0x02), Gray8::new(0x03),
+            Gray8::new(0x04), Gray8::new(0x05), Gray8::new(0x06),
+            Gray8::new(0x07), Gray8::new(0x08), Gray8::new(0x09),
+        ];
+        let r = Raster::with_pixels(3, 3, p);
+        let c = r.crop((1, 1, 5, 5));
+        assert_eq!((c.width(), c.height()), (2, 2));
+        let v = [
+            Gray8::new(0x05), Gray8::new(0x06),
+            Gray8::new(0x08), Gray8::new(0x09),
+        ];
+        assert_eq!(c.pixels(), &v);
+        let c = r.crop((-1, -2, 3, 3));
+        assert_eq!((c.width(), c.height()), (2, 1));
+        assert_eq!(c.pixels(), &[Gray8::new(0x01), Gray8::new(0x02)]);
+        let c = r.crop((3, 0, 2, 2));
+        assert_eq!((c.width(), c.height()), (0, 0));
+        assert!(c.pixels().is_empty());
+    }
+
+    #[test]
+    fn convert_dithered() {
+        // Subtle gradient, less than one 8-bit step
+        let mut r = Raster::<Rgb32>::with_clear(64, 4);
+        for (y, row) in r.rows_mut(()).enumerate() {
+            for (x, p) in row.iter_mut().enumerate() {
+                let v = (x + y) as f32 / 255.0 / 150.0;
+                *p = Rgb32::new(v, v, v);
+            }
+        }
+        let plain = r.convert_dithered::<Rgb8>(DitherMode::None);
+        assert_eq!(plain.pixels(), Raster::<Rgb8>::with_raster(&r).pixels());
+        let dith = r.convert_dithered::<Rgb8>(DitherMode::Ordered);
+        let distinct = |row: &[Rgb8]| {
+            let mut v: Vec<u8> =
+                row.iter().map(|p| u8::from(p.one())).collect();
+            v.sort();
+            v.dedup();
+            v.len()
+        };
+        for (prow, drow) in plain.rows(()).zip(dith.rows(())) {
+            assert!(distinct(drow) > distinct(prow));
+        }
+    }
+
+    #[test]
+    fn convert_dithered_srgb() {
+        // Linear value which encodes to 100.25 in sRGB
+        let e = 100.25 / 255.0;
+        let v = ((e + 0.055) / 1.055_f32).powf(2.4);
+        let r = Raster::with_color(4, 4, Rgba32::new(v, v, v, 1.0));
+        let dith = r.convert_dithered::<SRgba8>(DitherMode::Ordered);
+        let mut sum = 0;
+        for p in dith.pixels() {
+            let red = u8::from(p.one());
+            assert!(red == 100 || red == 101, "{red}");
+            assert_eq!(u8::from(p.alpha()), 255);
+            sum += u32::from(red);
+        }
+        // one quarter of the pixels round up
+        assert_eq!(sum, 100 * 16 + 4);
+    }
+
+    #[test]
+    fn halve() {
+        let mut r = Raster::<Graya8p>::with_clear(2, 2);
+        *r.pixel_mut(0, 0) = Graya8p::new(0xFF, 0xFF);
+        *r.pixel_mut(1, 1) = Graya8p::new(0xFF, 0xFF);
+        *r.pixel_mut(1, 0) = Graya8p::new(0x00, 0xFF);
+        *r.pixel_mut(0, 1) = Graya8p::new(0x00, 0xFF);
+        let h = r.halve(Light::Native);
+        assert_eq!((h.width(), h.height()), (1, 1));
+        assert_eq!(h.pixel(0, 0), Graya8p::new(0x80, 0xFF));
+        let mut r = Raster::<Rgba16p>::with_clear(2, 1);
+        *r.pixel_mut(0, 0) = Rgba16p::new(0x8000, 0x4000, 0, 0x8000);
+        let h = r.halve(Light::Native);
+        assert_eq!(h.pixel(0, 0), Rgba16p::new(0x4000, 0x2000, 0, 0x4000));
+        let r = Raster::<Rgba8p>::with_color(
+            5, 3, Rgba8p::new(0x10, 0x20, 0x30, 0x40),
+        );
+        let h = r.halve(Light::Native);
+        assert_eq!((h.width(), h.height()), (3, 2));
+        assert_eq!(h.pixel(2, 1), Rgba8p::new(0x10, 0x20, 0x30, 0x40));
+        let h = Raster::<Rgba8p>::with_clear(1, 1)
+            .halve(Light::Native);
+        assert_eq!((h.width(), h.height()), (1, 1));
+    }
+
+    #[test]
+    fn halve_checkerboard() {
+        let black = SRgb8::new(0, 0, 0);
+        let white = SRgb8::new(0xFF, 0xFF, 0xFF);
+        let mut r = Raster::<SRgb8>::with_clear(8, 8);
+        r.fill_checkerboard((), black, white, 1);
+        let native = r.halve(Light::Native);
+        let linear = r.halve(Light::Linear);
+        assert_eq!((linear.width(), linear.height()), (4, 4));
+        // 50% linear light is about 188 in sRGB
+        let target = 188.0;
+        let native = f32::from(u8::from(Rgb::red(native.mean(()))));
+        let linear = f32::from(u8::from(Rgb::red(linear.mean(()))));
+        assert!((linear - target).abs() < (native - target).abs());
+        assert!((linear - target).abs() <= 1.0, "{linear}");
+    }
+
+    #[test]
+    fn halve_linear_solid() {
+        for v in 0..=255 {
+            let clr = SGraya8::new(v, 0x80);
+            let r = Raster::with_color(2, 2, clr);
+            assert_eq!(r.halve(Light::Linear).pixel(0, 0), clr);
+        }
+        for v in 0..=0xFFFF {
+            let clr = SRgb16::new(v, v, v);
+            let r = Raster::with_color(2, 2, clr);
+            let h = u16::from(Rgb::red(r.halve(Light::Linear).pixel(0, 0)));
+            assert_eq!(h, v);
+        }
+        let clr = SRgb32::new(0.25, 0.5, 0.75);
+        let r = Raster::with_color(2, 2, clr);
+        let h = r.halve(Light::Linear);
+        assert!(h.pixel(0, 0).approx_eq(clr, 1e-6));
+    }
+
+    #[test]
+    fn halve_hue() {
+        // hue is averaged as an angle, wrapping around
+        let mut r = Raster::<Hsv8>::with_clear(2, 1);
+        *r.pixel_mut(0, 0) = Hsv8::new(0xF0, 0xFF, 0xFF);
+        *r.pixel_mut(1, 0) = Hsv8::new(0x10, 0xFF, 0xFF);
+        let h = r.halve(Light::Native);
+        assert_eq!(h.pixel(0, 0), Hsv8::new(0x00, 0xFF, 0xFF));
+        *r.pixel_mut(0, 0) = Hsv8::new(0xF8, 0x80, 0xFF);
+        *r.pixel_mut(1, 0) = Hsv8::new(0x06, 0x80, 0xFF);
+        let h = r.halve(Light::Native);
+        assert_eq!(h.pixel(0, 0), Hsv8::new(0xFF, 0x80, 0xFF));
+        *r.pixel_mut(0, 0) = Hsv8::new(0x20, 0x80, 0xFF);
+        *r.pixel_mut(1, 0) = Hsv8::new(0x40, 0x80, 0xFF);
+        let h = r.halve(Light::Native);
+        assert_eq!(h.pixel(0, 0), Hsv8::new(0x30, 0x80, 0xFF));
+    }
+
+    #[test]
+    fn halve_straight() {
+        // transparent pixels do not contribute color
+        let mut r = Raster::<Rgba8>::with_clear(2, 2);
+        *r.pixel_mut(0, 0) = Rgba8::new(0xFF, 0x80, 0x00, 0xFF);
+        *r.pixel_mut(1, 1) = Rgba8::new(0x00, 0xFF, 0x00, 0x00);
+        let h = r.halve(Light::Native);
+        assert_eq!(h.pixel(0, 0), Rgba8::new(0xFF, 0x80, 0x00, 0x40));
+    }
+
+    #[test]
+    fn with_raster_matte_gray() {
+        let mut m = Raster::<Matte16>::with_clear(2, 1);
+        *m.pixel_mut(0, 0) = Matte16::new(0xABCD);
+        *m.pixel_mut(1, 0) = Matte16::new(0x1234);
+        let g = Raster::<Graya16>::with_raster(&m);
+        assert_eq!(g.pixel(0, 0), Graya16::new(0xFFFF, 0xABCD));
+        assert_eq!(g.pixel(1, 0), Graya16::new(0xFFFF, 0x1234));
+        let g = Raster::<SGraya8>::with_raster(&m);
+        assert_eq!(g.pixel(0, 0), m.pixel(0, 0).convert());
+        assert_eq!(g.pixel(1, 0), m.pixel(1, 0).convert());
+        let mut g = Raster::<SGraya8>::with_clear(2, 1);
+        *g.pixel_mut(0, 0) = SGraya8::new(0x67, 0x94);
+        *g.pixel_mut(1, 0) = SGraya8::new(0xBA, 0xA2);
+        let m = Raster::<Matte16>::with_raster(&g);
+        assert_eq!(m.pixel(0, 0), Matte16::new(0x9494));
+        assert_eq!(m.pixel(1, 0), Matte16::new(0xA2A2));
+        let g = Raster::<Graya16p>::with_raster(&m);
+        let m2 = Raster::<Matte16>::with_raster(&g);
+        assert_eq!(m.pixels(), m2.pixels());
+    }
+
+    #[test]
+    fn copy_raster_offsets() {
+        let buf = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let src = Raster::<Gray8>::with_u8_buffer(3, 3, buf);
+        for (tx, ty, fx, fy) in (-2..=2).flat_map(|tx| {
+            (-2..=2).flat_map(move |ty| {
+                (-2..=2).flat_map(move |fx| {
+                    (-2..=2).map(move |fy| (tx, ty, fx, fy))
+                })
+            })
+        }) {
+            let mut dst = Raster::<Gray8>::with_clear(3, 3);
+            dst.copy_raster((tx, ty, 3, 3), &src, (fx, fy, 3, 3));
+            for y in 0..3 {
+                for x in 0..3 {
+                    let (i, j) = (x - tx, y - ty);
+                    let (sx, sy) = (fx + i, fy + j);
+                    let expected = if [i, j, sx, sy]
+                        .iter()
+                        .all(|v| (0..3).contains(v))
+                    {
+                        src.pixel(sx, sy)
+                    } else {
+                        Gray8::default()
+                    };
+                    assert_eq!(
+                        dst.pixel(x, y), expected,
+                        "to: ({tx}, {ty}) from: ({fx}, {fy}) at ({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn packed_16() {
+        let exact = [
+            SRgba8::new(0x00, 0x00, 0x00, 0x00),
+            SRgba8::new(0xFF, 0xFF, 0xFF, 0xFF),
+            SRgba8::new(0xFF, 0x00, 0x00, 0xFF),
+            SRgba8::new(0x00, 0xFF, 0x00, 0xFF),
+            SRgba8::new(0x00, 0x00, 0xFF, 0xFF),
+        ];
+        let r = Raster::with_pixels(5, 1, exact.to_vec());
+        let r5551 = Raster::with_rgba5551(5, 1, &r.to_rgba5551());
+        assert_eq!(r.pixels(), r5551.pixels());
+        let r4444 = Raster::with_rgba4444(5, 1, &r.to_rgba4444());
+        assert_eq!(r.pixels(), r4444.pixels());
+        let rgb: Raster<SRgb8> = Raster::with_raster(&r);
+        let r565 = Raster::with_rgb565(5, 1, &rgb.to_rgb565());
+        assert_eq!(rgb.pixels(), r565.pixels());
+        // maximum error is half of one step
+        let mut r = Raster::<SRgba8>::with_clear(256, 1);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = i as u8;
+            *p = SRgba8::new(v, v, v, v);
+        }
+        let r5551 = Raster::with_rgba5551(256, 1, &r.to_rgba5551());
+        let r4444 = Raster::with_rgba4444(256, 1, &r.to_rgba4444());
+        let rgb: Raster<SRgb8> = Raster::with_raster(&r);
+        let r565 = Raster::with_rgb565(256, 1, &rgb.to_rgb565());
+        for i in 0..256 {
+            let v = i as u8;
+            let p = r5551.pixel(i, 0);
+            assert!(v.abs_diff(u8::from(Rgb::red(p))) <= 4);
+            assert!(v.abs_diff(u8::from(p.alpha())) <= 127);
+            let p = r4444.pixel(i, 0);
+            assert!(v.abs_diff(u8::from(Rgb::red(p))) <= 8);
+            assert!(v.abs_diff(u8::from(p.alpha())) <= 8);
+            let p = r565.pixel(i, 0);
+            assert!(v.abs_diff(u8::from(Rgb::red(p))) <= 4);
+            assert!(v.abs_diff(u8::from(Rgb::green(p))) <= 2);
+        }
+    }
+
+    #[test]
+    fn rows_rev() {
+        let buf = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+        let src = Raster::<Gray8>::with_u8_buffer(3, 4, buf);
+        // copy with vertical flip
+        let mut r0 = Raster::<Gray8>::with_clear(4, 4);
+        let to = (1, 0, 2, 3);
+        let from = (0, 1, 2, 3);
+        for (d, s) in r0.rows_mut(to).zip(src.rows(from).rev()) {
+            d.copy_from_slice(s);
+        }
+        // flip then copy
+        let mut flipped = Raster::<Gray8>::with_clear(3, 4);
+        for (d, s) in flipped.rows_mut(()).zip(src.rows(()).rev()) {
+            d.copy_from_slice(s);
+        }
+        let mut r1 = Raster::<Gray8>::with_clear(4, 4);
+        r1.copy_raster(to, &flipped, (0, 0, 2, 3));
+        assert_eq!(r0.pixels(), r1.pixels());
+        assert_eq!(r0.pixel(1, 0), Gray8::new(10));
+        assert_eq!(src.rows((0, 1, 3, 2)).rev().len(), 2);
+        let mut r2 = r0.clone();
+        for row in r2.rows_mut(()).rev().take(1) {
+            row[0] = Gray8::new(0xFF);
+        }
+        assert_eq!(r2.pixel(0, 3), Gray8::new(0xFF));
+    }
+
+    #[test]
+    fn map_channels() {
+        let mut invert = [0; 256];
+        for (i, v) in invert.iter_mut().enumerate() {
+            *v = 255 - i as u8;
+        }
+        let mut r = Raster::<Rgba8>::with_clear(4, 4);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = (i * 16) as u8;
+            *p = Rgba8::new(v, v + 1, v + 2, v + 3);
+        }
+        let mut expected = r.clone();
+        for y in 1..3 {
+            for x in 1..4 {
+                let p = expected.pixel_mut(x, y);
+                for c in p.channels_mut()[0..3].iter_mut() {
+                    *c = Ch8::new(255 - u8::from(*c));
+                }
+            }
+        }
+        let mut r0 = r.clone();
+        r0.map_linear_u8((1, 1, 5, 2), &invert);
+        assert_eq!(r0.pixels(), expected.pixels());
+        let ident: [u8; 256] = std::array::from_fn(|i| i as u8);
+        let mut r1 = r.clone();
+        r1.map_channels_u8((1, 1, 5, 2), &[invert, invert, invert, ident]);
+        assert_eq!(r1.pixels(), expected.pixels());
+        let mut r2 = r.clone();
+        r2.map_channels((1, 1, 5, 2), |i, c| {
+            if i < 3 { Ch8::MAX - c } else { c }
+        });
+        assert_eq!(r2.pixels(), expected.pixels());
+    }
+
+    #[test]
+    fn reinterpret() {
+        let mut r = Raster::<Rgba8>::with_clear(3, 2);
+        *r.pixel_mut(1, 1) = Rgba8::new(0x12, 0x34, 0x56, 0x78);
+        let ptr = r.pixels().as_ptr() as usize;
+        let r = r.reinterpret::<SRgba8>();
+        assert_eq!(r.pixels().as_ptr() as usize, ptr);
+        assert_eq!((r.width(), r.height()), (3, 2));
+        assert_eq!(r.pixel(1, 1), SRgba8::new(0x12, 0x34, 0x56, 0x78));
+        let r = r.reinterpret::<Bgra8>();
+        assert_eq!(r.pixel(1, 1).channels(), [
+            Ch8::new(0x12), Ch8::new(0x34), Ch8::new(0x56), Ch8::new(0x78)
+        ]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn reinterpret_channels() {
+        let r = Raster::<Rgba8>::with_clear(3, 2);
+        r.reinterpret::<Rgb8>();
+    }
+
+    #[test]
+    fn color_from() {
+        let clr = SRgb8::new(0x20, 0x40, 0x80);
+        let r = Raster::<SRgba8>::with_color_from(3, 3, clr);
+        assert_eq!(r.pixels(), Raster::with_color(3, 3, clr.convert())
+            .pixels());
+        let mut r = Raster::<Rgba16>::with_clear(4, 4);
+        r.copy_color_from((1, 1, 2, 2), clr);
+        assert_eq!(r.pixel(0, 0), Rgba16::default());
+        assert_eq!(r.pixel(2, 2), clr.convert());
+        let mut r = Raster::<Rgba8p>::with_color(4, 4, Rgba8p::new(0, 0, 0,
+            0xFF));
+        r.composite_color_from((), SRgba8::new(0xFF, 0, 0, 0x80), SrcOver);
+        let mut s = Raster::<Rgba8p>::with_color(4, 4, Rgba8p::new(0, 0, 0,
+            0xFF));
+        s.composite_color((), SRgba8::new(0xFF, 0, 0, 0x80).convert(),
+            SrcOver);
+        assert_eq!(r.pixels(), s.pixels());
+    }
+
+    #[test]
+    fn shift_fill() {
+        let v: Vec<u8> = (1..=12).collect();
+        let r = Raster::<Gray8>::with_u8_buffer(4, 3, v);
+        let fill = ShiftMode::Fill(Gray8::new(0));
+        let mut s = r.clone();
+        s.shift(1, 1, fill);
+        assert_eq!(s.as_u8_slice(), [
+            0, 0, 0, 0,
+            0, 1, 2, 3,
+            0, 5, 6, 7,
+        ]);
+        let mut s = r.clone();
+        s.shift(-2, -1, fill);
+        assert_eq!(s.as_u8_slice(), [
+            7, 8, 0, 0,
+            11, 12, 0, 0,
+            0, 0, 0, 0,
+        ]);
+        let mut s = r.clone();
+        s.shift(-1, 2, fill);
+        assert_eq!(s.as_u8_slice(), [
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+            2, 3, 4, 0,
+        ]);
+        let mut s = r.clone();
+        s.shift(3, -2, fill);
+        assert_eq!(s.as_u8_slice(), [
+            0, 0, 0, 9,
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+        ]);
+        let mut s = r.clone();
+        s.shift(0, 0, fill);
+        assert_eq!(s.pixels(), r.pixels());
+        s.shift(4, 0, fill);
+        assert_eq!(s.as_u8_slice(), [0; 12]);
+        let mut s = r.clone();
+        s.shift(0, i32::MIN, fill);
+        assert_eq!(s.as_u8_slice(), [0; 12]);
+    }
+
+    #[test]
+    fn shift_wrap() {
+        let v: Vec<u8> = (1..=12).collect();
+        let r = Raster::<Gray8>::with_u8_buffer(4, 3, v);
+        let mut s = r.clone();
+        s.shift(1, -1, ShiftMode::Wrap);
+        assert_eq!(s.as_u8_slice(), [
+            8, 5, 6, 7,
+            12, 9, 10, 11,
+            4, 1, 2, 3,
+        ]);
+        s.shift(-1, 1, ShiftMode::Wrap);
+        assert_eq!(s.pixels(), r.pixels());
+        // full wrap around is the same as no shift
+        s.shift(4, 3, ShiftMode::Wrap);
+        assert_eq!(s.pixels(), r.pixels());
+        s.shift(-8, -6, ShiftMode::Wrap);
+        assert_eq!(s.pixels(), r.pixels());
+        s.shift(5, 4, ShiftMode::Wrap);
+        let mut t = r.clone();
+        t.shift(1, 1, ShiftMode::Wrap);
+        assert_eq!(s.pixels(), t.pixels());
+        s.shift(i32::MAX, i32::MIN, ShiftMode::Wrap);
+        let mut e = Raster::<Gray8>::with_clear(0, 3);
+        e.shift(1, 1, ShiftMode::Wrap);
+    }
+
+    #[test]
+    fn drop_alpha() {
+        let mut r = Raster::<SRgba8>::with_clear(16, 16);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = i as u8;
+            *p = SRgba8::new(v, 255 - v, v.wrapping_mul(7), v / 2);
+        }
+        let d = r.drop_alpha::<SRgb8>();
+        assert_eq!(d.pixels(), Raster::<SRgb8>::with_raster(&r).pixels());
+        let p = r.clone().reinterpret::<Rgba8p>();
+        let d = p.drop_alpha::<Rgb8>();
+        assert_eq!(d.pixels(), Raster::<Rgb8>::with_raster(&p).pixels());
+        let o = d.with_opaque_alpha::<Rgba8>();
+        assert_eq!(o.pixels(), Raster::<Rgba8>::with_raster(&d).pixels());
+        // generic conversion rounds when premultiplying 8-bit channels
+        let o = d.with_opaque_alpha::<Rgba8p>();
+        assert!(o.pixels().iter().zip(d.pixels()).all(|(o, d)| {
+            o.alpha() == Ch8::MAX && o.channels()[..3] == d.channels()[..]
+        }));
+        let g = Raster::with_color(2, 2, SGray16::new(0x1234));
+        let ga = g.with_opaque_alpha::<SGraya16>();
+        assert_eq!(ga.pixel(1, 1), SGraya16::new(0x1234, 0xFFFF));
+        assert_eq!(ga.drop_alpha::<SGray16>().pixels(), g.pixels());
+        let h = Raster::with_color(2, 2, Hsva8::new(0x10, 0x20, 0x30, 0x40));
+        assert_eq!(h.drop_alpha::<Hsv8>().pixel(0, 0),
+            Hsv8::new(0x10, 0x20, 0x30));
+    }
+
+    #[test]
+    #[should_panic(expected = "Pixel formats must differ only by alpha")]
+    fn drop_alpha_mismatch() {
+        let r = Raster::<Rgba8>::with_clear(2, 2);
+        r.with_opaque_alpha::<Rgba8>();
+    }
+
+    #[test]
+    fn srgb_in_place() {
+        let mut r = Raster::<Rgba8>::with_clear(16, 16);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = i as u8;
+            *p = Rgba8::new(v, 255 - v, v.wrapping_mul(7), v / 2);
+        }
+        let mut e = r.clone();
+        e.encode_srgb_in_place();
+        let s = Raster::<SRgba8>::with_raster(&r).reinterpret::<Rgba8>();
+        assert_eq!(e.pixels(), s.pixels());
+        let mut d = r.clone().reinterpret::<SRgba8>();
+        d.decode_srgb_in_place();
+        let l = Raster::<Rgba8>::with_raster(
+            &r.clone().reinterpret::<SRgba8>(),
+        );
+        assert_eq!(d.reinterpret::<Rgba8>().pixels(), l.pixels());
+        let mut r = Raster::with_color(2, 2, Rgb32::new(0.0, 0.2, 0.9));
+        r.encode_srgb_in_place();
+        let s = Raster::<SRgb32>::with_color(2, 2, Rgb32::new(0.0, 0.2, 0.9)
+            .convert()).reinterpret::<Rgb32>();
+        assert_eq!(r.pixels(), s.pixels());
+        r.decode_srgb_in_place();
+        assert!(r.pixel(1, 1).approx_eq(Rgb32::new(0.0, 0.2, 0.9), 0.0001));
+        // hue is not changed
+        let mut r = Raster::with_color(2, 2, Hsv8::new(0x40, 0x80, 0x80));
+        r.encode_srgb_in_place();
+        assert_eq!(Hsv::hue(r.pixel(0, 0)), Ch8::new(0x40));
+    }
+
+    #[test]
+    fn composite_mattes() {
+        let mut r = Raster::<Matte8>::with_clear(4, 1);
+        let m0 = Raster::with_color(2, 1, Matte8::new(0x80));
+        let m1 = Raster::with_color(2, 1, Matte8::new(0x40));
+        r.composite_raster((0, 0, 2, 1), &m0, (), SrcOver);
+        r.composite_raster((1, 0, 2, 1), &m1, (), SrcOver);
+        assert_eq!(r.pixels(), [
+            Matte8::new(0x80), Matte8::new(0xA0), Matte8::new(0x40),
+            Matte8::new(0),
+        ]);
+        r.composite_raster((2, 0, 2, 1), &m0, (), Clear);
+        assert_eq!(r.pixels(), [
+            Matte8::new(0x80), Matte8::new(0xA0), Matte8::new(0),
+            Matte8::new(0),
+        ]);
+        let mut r = Raster::<Matte16>::with_clear(2, 1);
+        let m = Raster::with_color(2, 1, Matte16::new(0x8000));
+        r.composite_raster((), &m, (), SrcOver);
+        r.composite_raster((), &m, (), SrcOver);
+        assert_eq!(r.pixel(0, 0), Matte16::new(0xC000));
+    }
+
+    #[test]
+    fn white_balance() {
+        let gray = SRgb8::new(0x80, 0x80, 0x80);
+        let mut r = Raster::<SRgb8>::with_color(3, 3, gray);
+        r.white_balance((), 1.0, 1.0);
+        assert!(r.pixels().iter().all(|p| *p == gray));
+        r.white_balance((1, 1, 5, 5), 2.0, 1.0);
+        assert_eq!(r.pixel(0, 0), gray);
+        assert_eq!(r.pixel(1, 1), SRgb8::new(0xFF, 0x80, 0x80));
+        assert_eq!(r.pixel(2, 2), SRgb8::new(0xFF, 0x80, 0x80));
+        let mut r = Raster::with_color(2, 2, Rgba32::new(0.25, 0.5, 0.5, 1.0));
+        r.white_balance((), 2.0, 0.5);
+        assert_eq!(r.pixel(1, 1), Rgba32::new(0.5, 0.5, 0.25, 1.0));
+    }
+
+    #[test]
+    fn approx_eq() {
+        let r0 = Raster::with_color(3, 3, Rgba32::new(0.1, 0.2, 0.3, 0.4));
+        let mut r1 = r0.clone();
+        *r1.pixel_mut(1, 1) = Rgba32::new(0.1, 0.2, 0.3, 0.4001);
+        assert!(r0.approx_eq(&r1, 0.001));
+        assert!(!r0.approx_eq(&r1, 0.00001));
+        let r2 = Raster::with_color(3, 2, Rgba32::new(0.1, 0.2, 0.3, 0.4));
+        assert!(!r0.approx_eq(&r2, 0.1));
+    }
+
+    #[test]
+    fn fill_checkerboard() {
+        let (a, b) = (Gray8::new(0xFF), Gray8::new(0x40));
+        let mut r = Raster::with_clear(5, 4);
+        r.fill_checkerboard((), a, b, 2);
+        assert_eq!(r.pixels(), [
+            a, a, b, b, a,
+            a, a, b, b, a,
+            b, b, a, a, b,
+            b, b, a, a, b,
+        ]);
+        // adjacent region fills align with a single fill
+        let mut r1 = Raster::with_clear(5, 4);
+        r1.fill_checkerboard((0, 0, 3, 3), a, b, 2);
+        r1.fill_checkerboard((3, 0, 9, 3), a, b, 2);
+        r1.fill_checkerboard((-2, 3, 9, 9), a, b, 2);
+        assert_eq!(r.pixels(), r1.pixels());
+        let mut r2 = Raster::with_clear(3, 3);
+        r2.fill_checkerboard((1, 1, 2, 2), a, b, 1);
+        let c = Gray8::new(0);
+        assert_eq!(r2.pixels(), [c, c, c, c, a, b, c, b, a]);
+    }
+
+    #[test]
+    fn fill_stripes() {
+        let (a, b) = (Gray8::new(0xFF), Gray8::new(0x40));
+        let mut r = Raster::with_clear(4, 3);
+        r.fill_stripes((), a, b, 1, Axis::Horizontal);
+        assert_eq!(r.pixels(), [a, a, a, a, b, b, b, b, a, a, a, a]);
+        r.fill_stripes((), a, b, 3, Axis::Vertical);
+        assert_eq!(r.pixels(), [a, a, a, b, a, a, a, b, a, a, a, b]);
+        let mut r1 = Raster::with_clear(4, 3);
+        r1.fill_stripes((-5, -5, 7, 20), a, b, 3, Axis::Vertical);
+        r1.fill_stripes((2, 0, 2, 3), a, b, 3, Axis::Vertical);
+        assert_eq!(r.pixels(), r1.pixels());
+        r1.fill_stripes((0, 1, 4, 5), a, b, 1, Axis::Horizontal);
+        assert_eq!(r1.pixels(), [a, a, a, b, b, b, b, b, a, a, a, a]);
+    }
+
+    #[test]
+    fn mean() {
+        let (a, b) = (SRgb8::new(0, 0x40, 0xFF), SRgb8::new(0xFF, 0xC0, 0xFF));
+        let mut r = Raster::with_clear(6, 4);
+        r.fill_checkerboard((), a, b, 1);
+        assert_eq!(r.mean(()), SRgb8::new(0x80, 0x80, 0xFF));
+        assert_eq!(r.mean((0, 0, 1, 1)), a);
+        assert_eq!(r.mean((10, 10, 5, 5)), SRgb8::default());
+        let mut r = Raster::with_clear(2, 2);
+        r.fill_checkerboard((), Hsv32::new(0.9, 0.2, 0.5),
+            Hsv32::new(0.1, 0.4, 0.5), 1);
+        let m = r.mean(());
+        assert!(m.approx_eq(Hsv32::new(0.0, 0.3, 0.5), 0.0001), "{m:?}");
+    }
+
+    #[test]
+    fn variance() {
+        let r = Raster::with_color(5, 5, SRgba16::new(1, 2, 3, 4));
+        assert_eq!(r.variance(()), vec![0.0; 4]);
+        let r = Raster::with_color(5, 5, Hsv32::new(0.3, 0.5, 0.5));
+        assert!(r.variance(()).iter().all(|v| *v < 0.0001));
+        let mut r = Raster::with_clear(4, 4);
+        r.fill_checkerboard((), Hsv32::new(0.0, 0.0, 0.0),
+            Hsv32::new(0.5, 1.0, 0.5), 1);
+        let v = r.variance(());
+        assert!((v[0] - 1.0).abs() < 0.0001, "{v:?}");
+        assert_eq!(&v[1..], [0.25, 0.0625]);
+        assert_eq!(r.variance((9, 9, 1, 1)), vec![0.0; 3]);
+    }
+
+    fn count_set(r: &Raster<Matte8>) -> usize {
+        r.pixels().iter().filter(|p| **p == Matte8::new(0xFF)).count()
+    }
+
+    #[test]
+    fn draw_line() {
+        let v = Matte8::new(0xFF);
+        let mut r = Raster::<Matte8>::with_clear(10, 10);
+        r.draw_line(1, 2, 8, 5, v);
+        assert_eq!(r.pixel(1, 2), v);
+        assert_eq!(r.pixel(8, 5), v);
+        assert_eq!(count_set(&r), 8);
+        let mut r = Raster::<Matte8>::with_clear(10, 10);
+        r.draw_line(9, 0, 0, 9, v);
+        assert_eq!(count_set(&r), 10);
+        assert!((0..10).all(|i| r.pixel(9 - i, i) == v));
+        let mut r = Raster::<Matte8>::with_clear(10, 10);
+        r.draw_line(3, 9, 4, 1, v);
+        assert_eq!(r.pixel(3, 9), v);
+        assert_eq!(r.pixel(4, 1), v);
+        assert_eq!(count_set(&r), 9);
+        let mut r = Raster::<Matte8>::with_clear(10, 10);
+        r.draw_line(5, 5, 5, 5, v);
+        assert_eq!(count_set(&r), 1);
+    }
+
+    #[test]
+    fn draw_line_clip() {
+        let v = Matte8::new(0xFF);
+        let mut r = Raster::<Matte8>::with_clear(4, 4);
+        r.draw_line(-5, -5, 5, 5, v);
+        assert_eq!(count_set(&r), 4);
+        assert!((0..4).all(|i| r.pixel(i, i) == v));
+        let mut r = Raster::<Matte8>::with_clear(4, 4);
+        r.draw_line(2, -100, 2, 100, v);
+        assert_eq!(count_set(&r), 4);
+        r.draw_line(i32::MIN, i32::MAX, i32::MAX, i32::MIN, v);
+        r.draw_line(-10, 10, 10, 10, v);
+        r.draw_line(4, 0, 4, 3, v);
+        assert_eq!(count_set(&r), 4);
+    }
+
+    #[test]
+    fn draw_rect() {
+        let (v, z) = (Matte8::new(0xFF), Matte8::new(0));
+        let mut r = Raster::<Matte8>::with_clear(4, 4);
+        r.draw_rect((0, 0, 3, 3), v);
+        assert_eq!(r.pixels(), [
+            v, v, v, z,
+            v, z, v, z,
+            v, v, v, z,
+            z, z, z, z,
+        ]);
+        let mut r = Raster::<Matte8>::with_clear(4, 4);
+        r.draw_rect((2, -1, 5, 3), v);
+        assert_eq!(r.pixels(), [
+            z, z, v, z,
+            z, z, v, v,
+            z, z, z, z,
+            z, z, z, z,
+        ]);
+        r.fill_rect((1, 3, 9, 9), v);
+        r.draw_rect((1, 1, 0, 2), z);
+        assert_eq!(count_set(&r), 6);
+    }
+
+    #[test]
+    fn map_into() {
+        let v: Vec<u8> = (0..36).map(|i| (i * 7) as u8).collect();
+        let r = Raster::<SGray8>::with_u8_buffer(6, 6, v);
+        let thresh = |p: SGray8| {
+            Matte8::new(if u8::from(p.one()) >= 0x80 { 0xFF } else { 0 })
+        };
+        let mask = r.map_into((1, 2, 9, 3), thresh);
+        assert_eq!((mask.width(), mask.height()), (5, 3));
+        let mut manual = Raster::<Matte8>::with_clear(5, 3);
+        for y in 0..3 {
+            for x in 0..5 {
+                *manual.pixel_mut(x, y) = thresh(r.pixel(x + 1, y + 2));
+            }
+        }
+        assert_eq!(mask.pixels(), manual.pixels());
+        let empty = r.map_into((10, 0, 5, 5), thresh);
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+    }
+
+    #[test]
+    fn map_region_from() {
+        let v: Vec<u8> = (0..36).map(|i| (i * 7) as u8).collect();
+        let src = Raster::<SGray8>::with_u8_buffer(6, 6, v);
+        let thresh = |p: SGray8| {
+            Matte8::new(if u8::from(p.one()) >= 0x80 { 0xFF } else { 0 })
+        };
+        let mut r = Raster::<Matte8>::with_color(4, 4, Matte8::new(1));
+        r.map_region_from((-1, 1), &src, (2, 0, 9, 9), thresh);
+        let mut manual = Raster::<Matte8>::with_color(4, 4, Matte8::new(1));
+        for y in 1..4 {
+            for x in 0..3 {
+                *manual.pixel_mut(x, y) = thresh(src.pixel(x + 3, y - 1));
+            }
+        }
+        assert_eq!(r.pixels(), manual.pixels());
     }
 
-    /// Get the top side
-    pub fn top(self) -> i32 {
-        self.y
+    fn bitmap_round_trip(width: u32) {
+        let v: Vec<u8> = (0..width * 3)
+            .map(|i| if i % 3 == 0 { 0xFF } else { 0 })
+            .collect();
+        let r = Raster::<Matte8>::with_u8_buffer(width, 3, v);
+        let (data, stride) = r.to_bitmap(0x80);
+        assert_eq!(stride, (width as usize + 7) / 8);
+        assert_eq!(data.len(), stride * 3);
+        // padding bits are zero
+        let pad = stride * 8 - width as usize;
+        for row in data.chunks(stride) {
+            assert_eq!(row[stride - 1] & ((1 << pad) - 1) as u8, 0);
+        }
+        let r1 = Raster::<Matte8>::with_bitmap(width, 3, stride, &data);
+        assert_eq!(r.pixels(), r1.pixels());
+        // padding bits are ignored on read, with a wider stride
+        let mut wide = vec![0xFF; (stride + 1) * 3];
+        let rows = wide.chunks_mut(stride + 1);
+        for (row, bits) in rows.zip(data.chunks(stride)) {
+            row[..stride].copy_from_slice(bits);
+            row[stride - 1] |= ((1 << pad) - 1) as u8;
+        }
+        let r2 = Raster::<Matte8>::with_bitmap(width, 3, stride + 1, &wide);
+        assert_eq!(r.pixels(), r2.pixels());
     }
 
-    /// Get the width
-    pub fn width(self) -> u32 {
-        self.width as u32
+    #[test]
+    fn bitmap() {
+        for width in [1, 7, 8, 9, 17] {
+            bitmap_round_trip(width);
+        }
+        let r = Raster::<Matte8>::with_u8_buffer(2, 1, [0x7F, 0x80]);
+        assert_eq!(r.to_bitmap(0x80), (vec![0x40], 1));
+        assert_eq!(r.to_bitmap(0), (vec![0xC0], 1));
+        let e = Raster::<Matte8>::with_clear(0, 4);
+        assert_eq!(e.to_bitmap(1), (vec![], 0));
     }
 
-    /// Get the height
-    pub fn height(self) -> u32 {
-        self.height as u32
+    #[test]
+    #[should_panic(expected = "Bitmap too small")]
+    fn bitmap_too_small() {
+        Raster::<Matte8>::with_bitmap(9, 2, 2, &[0; 3]);
     }
 
-    /// Get right side
-    pub fn right(self) -> i32 {
-        self.x.saturating_add(self.width)
+    #[test]
+    fn swizzle() {
+        let mut r = Raster::<Rgba8>::with_clear(3, 2);
+        r.pixels_mut().iter_mut().enumerate().for_each(|(i, p)| {
+            let i = i as u8;
+            *p = Rgba8::new(i, 0x10 + i, 0x20 + i, 0x30 + i);
+        });
+        r.swizzle([2, 1, 0, 3]);
+        for (i, p) in r.pixels().iter().enumerate() {
+            let i = i as u8;
+            assert_eq!(*p, Rgba8::new(0x20 + i, 0x10 + i, i, 0x30 + i));
+        }
+        r.swizzle([3, 3, 3, 3]);
+        assert_eq!(r.pixel(2, 1), Rgba8::new(0x35, 0x35, 0x35, 0x35));
     }
 
-    /// Get bottom side
-    pub fn bottom(self) -> i32 {
-        self.y.saturating_add(self.height)
+    #[test]
+    #[should_panic(expected = "Swizzle order length")]
+    fn swizzle_length() {
+        Raster::<Rgb8>::with_clear(1, 1).swizzle([0, 1, 2, 0]);
     }
-}
-
-#[cfg(test)]
-#[rustfmt::skip]
-mod test {
-    use crate::gray::*;
-    use crate::hwb::*;
-    use crate::matte::*;
-    use crate::ops::*;
-    use crate::rgb::*;
-    use crate::*;
 
     #[test]
-    fn region_size() {
-        assert_eq!(std::mem::size_of::<Region>(), 16);
+    fn broadcast_channel() {
+        let r = Raster::<Rgba16>::with_pixels(2, 1, vec![
+            Rgba16::new(0x1111, 0x2222, 0x3333, 0x0000),
+            Rgba16::new(0x4444, 0x5555, 0x6666, 0xABCD),
+        ]);
+        let g: Raster<Gray16> = r.broadcast_channel(3);
+        assert_eq!(g.pixels(), [Gray16::new(0x0000), Gray16::new(0xABCD)]);
+        let g: Raster<Gray16> = r.broadcast_channel(1);
+        assert_eq!(g.pixels(), [Gray16::new(0x2222), Gray16::new(0x5555)]);
     }
 
     #[test]
-    fn buffers() {
-        let buf = vec![0x80; 64];
-        let mut raster = Raster::<Rgba8>::with_u8_buffer(4, 4, buf);
-        *raster.pixel_mut(1, 1) = Rgba8::new(0x40, 0x60, 0x80, 0xA0);
-        let slice: Box<[u8]> = raster.into();
-        let v: Vec<u8> = slice.into();
-        assert_eq!(v.len(), 64);
-        let buf = vec![0xA0; 16];
-        let mut raster = Raster::<Gray8>::with_u8_buffer(4, 4, buf);
-        *raster.pixel_mut(1, 1) = Gray8::new(0xFF);
-        let slice: Box<[u8]> = raster.into();
-        let v: Vec<u8> = slice.into();
-        let b = vec![
-            0xA0, 0xA0, 0xA0, 0xA0,
-            0xA0, 0xFF, 0xA0, 0xA0,
-            0xA0, 0xA0, 0xA0, 0xA0,
-            0xA0, 0xA0, 0xA0, 0xA0,
-        ];
-        assert_eq!(v, b);
+    fn threshold() {
+        let mut r = Raster::<Rgb16>::with_clear(16, 2);
+        for (x, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = (x % 16) as u16 * 0x1000;
+            *p = Rgb16::new(0, v, 0xFFFF - v);
+        }
+        let m: Raster<Matte16> = r.threshold((), 1, Ch16::new(0x9000));
+        assert_eq!((m.width(), m.height()), (16, 2));
+        for (x, p) in m.rows(()).flatten().enumerate() {
+            let v = if x % 16 >= 9 { 0xFFFF } else { 0 };
+            assert_eq!(*p, Matte16::new(v), "x = {x}");
+        }
+        let m: Raster<Matte16> =
+            r.threshold((4, 1, 8, 5), 2, Ch16::new(0x9000));
+        assert_eq!((m.width(), m.height()), (8, 1));
+        let step = m.pixels().iter().position(|p| *p == Matte16::new(0));
+        assert_eq!(step, Some(3));
     }
 
     #[test]
-    fn intersect() -> Result<(), ()> {
-        let r = Region::new(0, 0, 5, 5);
-        assert_eq!(r, Region::new(0, 0, 5, 5));
-        assert_eq!(r, r.intersection(Region::new(0, 0, 10, 10)));
-        assert_eq!(r, r.intersection(Region::new(-5, -5, 10, 10)));
-        assert_eq!(Region::new(2, 2, 3, 3), r.intersection((2, 2)));
-        assert_eq!(Region::new(0, 0, 4, 4), r.intersection((-1, -1, 5, 5)));
-        assert_eq!(Region::new(1, 2, 1, 3), r.intersection((1, 2, 1, 100)));
-        assert_eq!(Region::new(2, 1, 3, 1), r.intersection((2, 1, 100, 1)));
-        Ok(())
+    fn clamp_channels() {
+        let mut r = Raster::<Rgba8>::with_clear(4, 4);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let i = i as u8 * 16;
+            *p = Rgba8::new(i, 255 - i, i / 2, 0x80);
+        }
+        let clr = Rgba8::new(0x12, 0x34, 0x56, 0x78);
+        r.clamp_channels((1, 1, 2, 2), clr, clr);
+        assert_eq!(r.pixel(0, 0), Rgba8::new(0, 255, 0, 0x80));
+        assert_eq!(r.pixel(1, 1), clr);
+        assert_eq!(r.pixel(2, 2), clr);
+        assert_eq!(r.pixel(3, 3), Rgba8::new(240, 15, 120, 0x80));
+        let mut s = r.clone();
+        s.clamp_channels((), clr, clr);
+        assert!(s.pixels().iter().all(|p| *p == clr));
+        let min = Rgba8::new(0x20, 0x00, 0x00, 0x00);
+        let max = Rgba8::new(0xFF, 0xFF, 0x10, 0xFF);
+        r.clamp_channels((), min, max);
+        assert_eq!(r.pixel(0, 0), Rgba8::new(0x20, 255, 0, 0x80));
+        assert_eq!(r.pixel(3, 3), Rgba8::new(240, 15, 0x10, 0x80));
     }
 
-    #[test]
-    fn with_buffer_rgb8() {
-        let b = vec![
-            0xAA,0x00,0x00, 0x00,0x11,0x22, 0x33,0x44,0x55,
-            0x00,0xBB,0x00, 0x66,0x77,0x88, 0x99,0xAA,0xBB,
-            0x00,0x00,0xCC, 0xCC,0xDD,0xEE, 0xFF,0x00,0x11,
-        ];
-        let r = Raster::<SRgb8>::with_u8_buffer(3, 3, b);
-        let v = vec![
-            SRgb8::new(0xAA, 0x00, 0x00), SRgb8::new(0x00, 0x11, 0x22),
-            SRgb8::new(0x33, 0x44, 0x55),
-            SRgb8::new(0x00, 0xBB, 0x00), SRgb8::new(0x66, 0x77, 0x88),
-            SRgb8::new(0x99, 0xAA, 0xBB),
-            SRgb8::new(0x00, 0x00, 0xCC), SRgb8::new(0xCC, 0xDD, 0xEE),
-            SRgb8::new(0xFF, 0x00, 0x11),
-        ];
-        assert_eq!(r.pixels(), &v[..]);
+    /// Call every public method on a raster of zero size
+    fn zero_size(width: u32, height: u32) {
+        let r = Raster::<SRgb8>::with_clear(width, height);
+        assert_eq!((r.width(), r.height()), (width, height));
+        assert!(r.pixels().is_empty());
+        assert_eq!(r.rows(()).count(), 0);
+        assert_eq!(r.rows((0, 0, 5, 5)).len(), 0);
+        assert!(r.region().is_empty());
+        assert!(r.intersection((0, 0, 5, 5)).is_empty());
+        let clr = SRgb8::new(0x12, 0x34, 0x56);
+        let r = Raster::<SRgb8>::with_color(width, height, clr);
+        assert!(r.pixels().is_empty());
+        let r = Raster::<SRgb8>::with_pixels(width, height, vec![]);
+        assert!(r.pixels().is_empty());
+        let r = Raster::<SRgb8>::with_u8_buffer(width, height, vec![]);
+        assert!(r.as_u8_slice().is_empty());
+        let r16 = Raster::<Rgba16>::with_u16_buffer(width, height, vec![]);
+        assert!(r16.as_u16_slice().is_empty());
+        let r = Raster::<SRgb8>::from_planes(width, height, &[&[], &[], &[]]);
+        assert_eq!(r.to_planes(), vec![vec![]; 3]);
+        assert_eq!(r.to_u8_planes(), vec![vec![]; 3]);
+        let mut r = Raster::<SRgb8>::with_raster(&r16);
+        assert_eq!((r.width(), r.height()), (width, height));
+        let c: Raster<Gray8> = r.convert_dithered(DitherMode::Ordered);
+        assert_eq!((c.width(), c.height()), (width, height));
+        let c: Raster<SRgba8> = r.map_into((), |p| p.convert());
+        assert!(c.pixels().is_empty());
+        let c: Raster<SBgr8> = r.clone().reinterpret();
+        assert!(c.pixels().is_empty());
+        assert!(r.approx_eq(&r.clone(), 0.0));
+        r.clear();
+        r.pixels_mut().fill(clr);
+        assert_eq!(r.rows_mut(()).count(), 0);
+        r.as_u8_slice_mut().fill(0);
+        r.copy_color((), clr);
+        r.fill_checkerboard((), clr, clr, 2);
+        r.fill_stripes((), clr, clr, 2, Axis::Vertical);
+        let src = Raster::<SRgb8>::with_color(4, 4, clr);
+        r.copy_raster((), &src, ());
+        src.clone().copy_raster((), &r, ());
+        r.map_channels((), |_i, c| c);
+        r.swizzle([2, 1, 0]);
+        let g: Raster<SGray8> = r.broadcast_channel(0);
+        assert!(g.pixels().is_empty());
+        let m: Raster<Matte8> = r.threshold((), 0, Ch8::MAX);
+        assert!(m.pixels().is_empty());
+        r.clamp_channels((), clr, clr);
+        r.map_channels_u8((), &[[0; 256]; 3]);
+        r.map_linear_u8((), &[0; 256]);
+        r.white_balance((), 1.5, 0.5);
+        assert_eq!(r.mean(()), SRgb8::default());
+        assert_eq!(r.variance(()), vec![0.0; 3]);
+        assert!(r.crop(()).pixels().is_empty());
+        r.map_region_from((), &src, (), |p| p);
+        let b = r.with_border(1, 2, 3, 4, clr);
+        assert_eq!((b.width(), b.height()), (width + 4, height + 6));
+        assert!(b.pixels().iter().all(|p| *p == clr));
+        let rot = r.rotate(1.0, clr);
+        assert!(rot.pixels().iter().all(|p| *p == clr));
+        r.blend_straight((), &Raster::with_clear(4, 4), ());
+        assert!(r.to_rgb565().is_empty());
+        let v: Vec<SRgb8> = r.clone().into();
+        assert!(v.is_empty());
+        let v: Box<[u8]> = r.into();
+        assert!(v.is_empty());
+        let mut p = Raster::<Rgba8p>::with_clear(width, height);
+        let p1 = Raster::<Rgba8p>::with_clear(5, 5);
+        assert_eq!(p.validate_premultiplied(), None);
+        p.composite_color((), Rgba8p::new(1, 2, 3, 4), SrcOver);
+        p.composite_raster((), &p1, (), SrcOver);
+        p1.clone().composite_raster((), &p, (), SrcOver);
+        let m = Raster::<Matte8>::with_clear(width, height);
+        p.composite_matte((), &m, (), Rgba8p::default(), SrcOver);
+        let h = p.halve(Light::Native);
+        assert_eq!(h.width(), (width + 1) / 2);
+        assert_eq!(h.height(), (height + 1) / 2);
+        let stride = ((width + 7) / 8) as usize;
+        let mut m = Raster::<Matte8>::with_bitmap(width, height, stride, &[]);
+        m.draw_line(-3, -3, 7, 7, Matte8::new(0xFF));
+        m.draw_rect((), Matte8::new(0xFF));
+        m.fill_rect((), Matte8::new(0xFF));
+        let (bits, stride) = m.to_bitmap(1);
+        assert_eq!(bits.len(), stride * height as usize);
+        assert!(bits.iter().all(|b| *b == 0));
+        let s = Raster::<SRgba8>::with_rgba4444(width, height, &[]);
+        assert!(s.to_rgba4444().is_empty());
+        let s = Raster::<SRgba8>::with_rgba5551(width, height, &[]);
+        assert!(s.to_rgba5551().is_empty());
+        let s = Raster::<SRgb8>::with_rgb565(width, height, &[]);
+        assert!(s.pixels().is_empty());
     }
 
     #[test]
-    fn with_buffer_graya16() {
-        let b = vec![
-            0x1001,0x5005, 0x1000,0x3002, 0x5004,0x7006,
-            0x2002,0x6006, 0x9008,0xB00A, 0xD00C,0xF00E,
-            0x3003,0x7007, 0xE00F,0xC00D, 0xA00B,0x8009,
-        ];
-        let r = Raster::<SGraya16>::with_u16_buffer(3, 3, b);
-        let v = vec![
-            SGraya16::new(0x1001, 0x5005), SGraya16::new(0x1000, 0x3002),
-            SGraya16::new(0x5004, 0x7006),
-            SGraya16::new(0x2002, 0x6006), SGraya16::new(0x9008, 0xB00A),
-            SGraya16::new(0xD00C, 0xF00E),
-            SGraya16::new(0x3003, 0x7007), SGraya16::new(0xE00F, 0xC00D),
-            SGraya16::new(0xA00B, 0x8009),
-        ];
-        assert_eq!(r.pixels(), &v[..]);
+    fn zero_size_0x0() {
+        zero_size(0, 0);
     }
 
     #[test]
-    fn with_pixels_matte32() {
-        let p = vec![
-            Matte32::new(0.25), Matte32::new(0.5), Matte32::new(0.75),
-            Matte32::new(0.5), Matte32::new(0.6), Matte32::new(0.7),
-            Matte32::new(0.85), Matte32::new(0.65), Matte32::new(0.45),
-        ];
-        let r = Raster::with_pixels(3, 3, p.clone());
-        assert_eq!(r.pixels(), &p[..]);
+    fn zero_size_0x5() {
+        zero_size(0, 5);
     }
 
     #[test]
-    fn pixel_mut_matte8() {
-        let mut r = Raster::<Matte8>::with_clear(3, 3);
-        *r.pixel_mut(0, 0) = Matte8::new(0xFF);
-        *r.pixel_mut(2, 0) = Matte8::new(0x12);
-        *r.pixel_mut(1, 1) = Matte8::new(0x34);
-        *r.pixel_mut(0, 2) = Matte8::new(0x56);
-        *r.pixel_mut(2, 2) = Matte8::new(0x78);
-        let v = vec![
-            Matte8::new(0xFF), Matte8::new(0x00), Matte8::new(0x12),
-            Matte8::new(0x00), Matte8::new(0x34), Matte8::new(0x00),
-            Matte8::new(0x56), Matte8::new(0x00), Matte8::new(0x78),
-        ];
-        assert_eq!(r.pixels(), &v[..]);
+    fn zero_size_5x0() {
+        zero_size(5, 0);
     }
 
-    #[test]
-    fn pixel_mut_matte16() {
-        let mut r = Raster::<Matte16>::with_clear(3, 3);
-        *r.pixel_mut(2, 0) = Matte16::new(0x9ABC);
-        *r.pixel_mut(1, 1) = Matte16::new(0x5678);
-        *r.pixel_mut(0, 2) = Matte16::new(0x1234);
-        *r.pixel_mut(0, 0) = Matte16::new(1.0);
-        *r.pixel_mut(2, 2) = Matte16::new(0x8080);
-        let v = vec![
-            Matte16::new(0xFFFF), Matte16::new(0x0000), Matte16::new(0x9ABC),
-            Matte16::new(0x0000), Matte16::new(0x5678), Matte16::new(0x0000),
-            Matte16::new(0x1234), Matte16::new(0x0000), Matte16::new(0x8080),
-        ];
-        assert_eq!(r.pixels(), &v[..]);
+    /// Regions which do not intersect an 8x8 raster
+    const EMPTY_REGIONS: &[(i32, i32, u32, u32)] = &[
+        (0, 0, 0, 0),
+        (3, 3, 0, 5),
+        (3, 3, 5, 0),
+        (0, 20, 0, 0),
+        (0, 1000, 8, 0),
+        (8, 0, 4, 4),
+        (0, 8, 4, 4),
+        (20, 20, 4, 4),
+        (-4, 0, 4, 4),
+        (0, -4, 4, 4),
+        (-100, -100, 50, 50),
+        (-100, 0, 50, 8),
+        (i32::MAX, i32::MAX, 10, 10),
+        (i32::MAX, 0, i32::MAX as u32, 8),
+        (0, i32::MAX, 8, 0),
+        (i32::MIN, i32::MIN, i32::MAX as u32, i32::MAX as u32),
+    ];
+
+    fn pattern_raster() -> Raster<Rgba8p> {
+        let mut r = Raster::with_clear(8, 8);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = (i * 4) as u8;
+            *p = Rgba8p::new(v / 2, v / 3, v / 4, v);
+        }
+        r
     }
 
     #[test]
-    fn invalid_rows() {
-        let r = Raster::<Matte8>::with_clear(10, 10);
-        let mut rows = r.rows((0, 20, 0, 0));
-        assert_eq!(rows.next(), None);
+    fn region_anchored() {
+        use Anchor::*;
+        let p = Region::new(5, 3, 15, 10);
+        let v = [
+            (TopLeft, (7, 5)), (TopCenter, (9, 5)), (TopRight, (12, 5)),
+            (CenterLeft, (7, 6)), (Center, (9, 6)), (CenterRight, (12, 6)),
+            (BottomLeft, (7, 8)), (BottomCenter, (9, 8)),
+            (BottomRight, (12, 8)),
+        ];
+        for (anchor, (x, y)) in v {
+            let reg = Region::anchored(p, (6, 3), anchor, 2);
+            assert_eq!(reg, Region::new(x, y, 6, 3), "{anchor:?}");
+            assert_eq!(p.intersection(reg), reg);
+        }
+        // larger than parent: centering rounds toward top-left
+        let reg = Region::anchored(p, (20, 13), Center, 0);
+        assert_eq!(reg, Region::new(2, 1, 20, 13));
+        assert_eq!(reg.intersection(p), p);
+        // negative margin hangs outside the parent
+        let r = Raster::<Gray8>::with_clear(8, 8);
+        let reg = Region::anchored(r.region(), (4, 4), TopRight, -2);
+        assert_eq!(reg, Region::new(6, -2, 4, 4));
+        assert_eq!(r.intersection(reg), Region::new(6, 0, 2, 2));
     }
 
     #[test]
-    fn raster_with_color() {
-        let r = Raster::with_color(3, 3, Hwb8::new(0x80, 0, 0));
-        let v = vec![Hwb8::new(0x80, 0, 0); 9];
-        assert_eq!(r.pixels(), &v[..]);
+    fn empty_regions() {
+        let src = pattern_raster();
+        let matte = Raster::with_color(8, 8, Matte8::new(0x80));
+        let clr = Rgba8p::new(0x10, 0x20, 0x30, 0x40);
+        let lut = [0xFF; 256];
+        for reg in EMPTY_REGIONS.iter().copied() {
+            let mut r = pattern_raster();
+            assert!(r.intersection(reg).is_empty(), "{reg:?}");
+            assert_eq!(r.rows(reg).count(), 0, "{reg:?}");
+            assert_eq!(r.rows_mut(reg).count(), 0, "{reg:?}");
+            assert_eq!(r.mean(reg), Rgba8p::default());
+            assert_eq!(r.variance(reg), vec![0.0; 4]);
+            let c = r.crop(reg);
+            assert_eq!((c.width(), c.height()), (0, 0));
+            let m = r.map_into(reg, |p| p);
+            assert_eq!((m.width(), m.height()), (0, 0));
+            let t = r.threshold(reg, 0, Ch8::new(0x80));
+            assert_eq!((t.width(), t.height()), (0, 0));
+            r.copy_color(reg, clr);
+            r.fill_checkerboard(reg, clr, Rgba8p::default(), 2);
+            r.fill_stripes(reg, clr, clr, 2, Axis::Horizontal);
+            r.fill_stripes(reg, clr, clr, 2, Axis::Vertical);
+            r.map_channels(reg, |_, _| Ch8::MAX);
+            r.clamp_channels(reg, clr, clr);
+            r.map_channels_u8(reg, &[lut; 4]);
+            r.map_linear_u8(reg, &lut);
+            r.white_balance(reg, 2.0, 0.5);
+            r.composite_color(reg, clr, Plus);
+            r.composite_color_dyn(reg, clr, BlendOp::Plus);
+            let (reg, all) = (Region::from(reg), Region::from(()));
+            for (to, from) in [(reg, all), (all, reg)] {
+                r.copy_raster(to, &src, from);
+                r.map_region_from(to, &src, from, |_| clr);
+                r.composite_raster(to, &src, from, Plus);
+                r.composite_raster_dyn(to, &src, from, BlendOp::Plus);
+                r.composite_matte(to, &matte, from, clr, Plus);
+                r.composite_matte_dyn(to, &matte, from, clr, BlendOp::Plus);
+            }
+            assert_eq!(r.pixels(), src.pixels(), "{reg:?}");
+            let mut m = matte.clone();
+            m.draw_rect(reg, Matte8::new(0));
+            m.fill_rect(reg, Matte8::new(0));
+            assert_eq!(m.pixels(), matte.pixels(), "{reg:?}");
+            let srgb = Raster::with_color(8, 8, SRgb8::new(1, 2, 3));
+            let sticker = Raster::with_color(8, 8, SRgba8::new(9, 9, 9, 0x80));
+            let mut s = srgb.clone();
+            s.blend_straight(reg, &sticker, ());
+            s.blend_straight((), &sticker, reg);
+            assert_eq!(s.pixels(), srgb.pixels(), "{reg:?}");
+        }
     }
 
     #[test]
-    fn copy_color_gray8() {
-        let mut r = Raster::<SGray8>::with_clear(3, 3);
-        r.copy_color((0, 0, 1, 1), SGray8::new(0x23));
-        r.copy_color((10, 10, 1, 1), SGray8::new(0x45));
-        r.copy_color((1, 1, 10, 10), SGray8::new(0xBB));
-        let v = vec![
-            SGray8::new(0x23), SGray8::new(0), SGray8::new(0),
-            SGray8::new(0), SGray8::new(0xBB), SGray8::new(0xBB),
-            SGray8::new(0), SGray8::new(0xBB), SGray8::new(0xBB),
-        ];
-        assert_eq!(r.pixels(), &v[..]);
+    fn chroma_key() {
+        let key = SRgb8::new(0x20, 0xE0, 0x30);
+        let far = SRgb8::new(0xE0, 0x30, 0xC0);
+        let gray = SRgb8::new(0x80, 0x80, 0x80);
+        let r = Raster::with_pixels(3, 1, vec![key, far, gray]);
+        let k = r.chroma_key(key, 0.05, 0.1);
+        assert_eq!(k.pixel(0, 0), SRgba8::new(0x20, 0xE0, 0x30, 0x00));
+        assert_eq!(k.pixel(1, 0), SRgba8::new(0xE0, 0x30, 0xC0, 0xFF));
+        assert_eq!(k.pixel(2, 0).alpha(), Ch8::new(0xFF));
+        // nearby colors are within tolerance
+        let r = Raster::with_color(1, 1, SRgb8::new(0x28, 0xE8, 0x30));
+        assert_eq!(r.chroma_key(key, 0.05, 0.1).pixel(0, 0).alpha(), Ch8::MIN);
+        // ramp midpoint
+        let d = chroma_dist(key, gray);
+        let r = Raster::with_color(1, 1, gray);
+        let a = r.chroma_key(key, d - 0.1, 0.2).pixel(0, 0).alpha();
+        assert!(u8::from(a).abs_diff(0x80) <= 1, "{a:?}");
+        // no softness is a hard edge
+        assert_eq!(r.chroma_key(key, d, 0.0).pixel(0, 0).alpha(), Ch8::MIN);
+        let a = r.chroma_key(key, d - 0.001, 0.0).pixel(0, 0).alpha();
+        assert_eq!(a, Ch8::MAX);
     }
 
-    #[test]
-    fn copy_color_srgb8() {
-        let mut r = Raster::<SRgb8>::with_clear(3, 3);
-        r.copy_color((2, -1, 3, 4), SRgb8::new(0xCC, 0xAA, 0xBB));
-        let v = vec![
-            SRgb8::new(0, 0, 0), SRgb8::new(0, 0, 0),
-            SRgb8::new(0xCC, 0xAA, 0xBB),
-            SRgb8::new(0, 0, 0), SRgb8::new(0, 0, 0),
-            SRgb8::new(0xCC, 0xAA, 0xBB),
-            SRgb8::new(0, 0, 0), SRgb8::new(0, 0, 0),
-            SRgb8::new(0xCC, 0xAA, 0xBB),
-        ];
-        assert_eq!(r.pixels(), &v[..]);
+    fn chroma_dist(a: SRgb8, b: SRgb8) -> f32 {
+        let (a, b) = (super::srgb_ycc(a), super::srgb_ycc(b));
+        let cb = a.two().to_f32() - b.two().to_f32();
+        let cr = a.three().to_f32() - b.three().to_f32();
+        cb.hypot(cr)
     }
 
     #[test]
-    fn copy_raster_gray() {
-        let mut g0 = Raster::<Gray8>::with_clear(3, 3);
-        let g1 = Raster::<Gray8>::with_color(3, 3, Gray8::new(0x40));
-        let g2 = Raster::<Gray8>::with_color(3, 3, Gray8::new(0x60));
-        let g3 = Raster::<Gray8>::with_color(3, 3, Gray8::new(0x80));
-        g0.copy_raster((-1, 2, 3, 3), &g1, ());
-        g0.copy_raster((2, -1, 3, 3), &g2, ());
-        g0.copy_raster((-2, -2, 3, 3), &g3, ());
-        let v = vec![
-            Gray8::new(0x80), Gray8::new(0x00), Gray8::new(0x60),
-            Gray8::new(0x00), Gray8::new(0x00), Gray8::new(0x60),
-            Gray8::new(0x40), Gray8::new(0x40), Gray8::new(0x00),
-        ];
-        assert_eq!(g0.pixels(), &v[..]);
+    fn despill() {
+        let key = SRgb8::new(0, 0xFF, 0);
+        let mut r = Raster::with_pixels(4, 1, vec![
+            SRgba8::new(0x80, 0xA0, 0x80, 0xFF),
+            SRgba8::new(0xC0, 0x40, 0x40, 0xFF),
+            SRgba8::new(0x60, 0xB0, 0x40, 0x00),
+            SRgba8::new(0x50, 0x50, 0x50, 0x80),
+        ]);
+        r.despill(key);
+        let p = r.pixel(0, 0);
+        assert_eq!(p.one(), p.two());
+        assert_eq!(p.two(), p.three());
+        // luma is unchanged
+        let luma = |p: SRgba8| super::srgb_ycc(p).one().to_f32();
+        let before = luma(SRgba8::new(0x80, 0xA0, 0x80, 0xFF));
+        assert!((luma(p) - before).abs() < 0.005);
+        // chroma away from key is kept
+        assert_eq!(r.pixel(1, 0), SRgba8::new(0xC0, 0x40, 0x40, 0xFF));
+        // transparent pixels are not changed
+        assert_eq!(r.pixel(2, 0), SRgba8::new(0x60, 0xB0, 0x40, 0x00));
+        assert_eq!(r.pixel(3, 0), SRgba8::new(0x50, 0x50, 0x50, 0x80));
     }
 
     #[test]
-    fn composite_color_graya8_over() {
-        let clr = Graya8p::new(0x20, 0x40);
-        let mut r = Raster::<Graya8p>::with_color(2, 2, clr);
-        r.composite_color((0, 0, 3, 1), Graya8p::new(0x60, 0xA0), SrcOver);
-        r.composite_color((1, 1, 4, 4), Graya8p::new(0x80, 0x80), SrcOver);
-        let v = vec![
-            Graya8p::new(0x6B, 0xB7), Graya8p::new(0x6B, 0xB7),
-            Graya8p::new(0x20, 0x40), Graya8p::new(0x8F, 0x9F),
+    fn with_rows() {
+        let rows = [
+            [Gray8::new(1), Gray8::new(2)],
+            [Gray8::new(3), Gray8::new(4)],
         ];
-        assert_eq!(r.pixels(), &v[..]);
+        let r = Raster::with_rows(2, 2, rows).unwrap();
+        assert_eq!(r.as_u8_slice(), [1, 2, 3, 4]);
+        let rows = vec![vec![Gray8::new(1); 3]; 2];
+        let r = Raster::<Gray8>::with_rows(3, 2, &rows).unwrap();
+        assert_eq!(r.as_u8_slice(), [1; 6]);
+        let e = Raster::<Gray8>::with_rows(3, 3, &rows);
+        assert_eq!(e.err(), Some(RasterError::RowCount));
+        let e = Raster::<Gray8>::with_rows(3, 1, &rows);
+        assert_eq!(e.err(), Some(RasterError::RowCount));
+        let e = Raster::<Gray8>::with_rows(2, 2, &rows);
+        assert_eq!(e.err(), Some(RasterError::RowWidth));
+        let e = Raster::<Gray8>::with_rows(4, 2, &rows);
+        assert_eq!(e.err(), Some(RasterError::RowWidth));
+        let rows: [&[Gray8]; 3] = [&[], &[], &[]];
+        let r = Raster::<Gray8>::with_rows(0, 3, rows).unwrap();
+        assert_eq!((r.width(), r.height()), (0, 3));
+        let e = Raster::<Gray8>::with_rows(0, 2, rows);
+        assert_eq!(e.err(), Some(RasterError::RowCount));
     }
 
     #[test]
-    fn composite_raster_rgb() {
-        let mut rgb = Raster::<Rgba8p>::with_clear(3, 3);
-        let gray = Raster::with_color(3, 3, Gray16::new(0x8000));
-        let r = Raster::with_raster(&gray);
-        rgb.composite_raster((), &r, (0, 1), Src);
-        let mut v = vec![Rgba8p::new(0x80, 0x80, 0x80, 0xFF); 6];
-        v.extend_from_slice(&vec![Rgba8p::new(0, 0, 0, 0); 3]);
-        assert_eq!(rgb.pixels(), &v[..]);
+    fn raster_builder() {
+        let mut b = RasterBuilder::<Rgb8>::new(2, 2);
+        let row = [Rgb8::new(1, 2, 3), Rgb8::new(4, 5, 6)];
+        assert_eq!(b.push_row(&row[..1]), Err(RasterError::RowWidth));
+        assert_eq!(b.push_row(&row), Ok(()));
+        assert_eq!(b.rows(), 1);
+        assert_eq!(b.push_row(&row), Ok(()));
+        assert_eq!(b.push_row(&row), Err(RasterError::RowCount));
+        assert_eq!(b.rows(), 2);
+        let r = b.finish().unwrap();
+        assert_eq!(r.pixel(1, 1), Rgb8::new(4, 5, 6));
+        let mut b = RasterBuilder::<Rgb8>::new(2, 2);
+        b.push_row(&row).unwrap();
+        assert_eq!(b.finish().err(), Some(RasterError::RowCount));
+        assert_eq!(format!("{}", RasterError::RowWidth),
+            "invalid raster row width");
     }
 
     #[test]
-    fn composite_matte_full() {
-        let mut r = Raster::<Rgba8p>::with_clear(2, 2);
-        let mut m = Raster::<Matte8>::with_clear(2, 2);
-        *m.pixel_mut(0, 0) = Matte8::new(0xFF);
-        *m.pixel_mut(1, 1) = Matte8::new(0x80);
-        let v = [
-            Matte8::new(0xFF), Matte8::new(0),
-            Matte8::new(0), Matte8::new(0x80),
-        ];
-        assert_eq!(m.pixels(), &v);
-        let c = Rgba8p::new(0xFF, 0x80, 0x40, 0xFF);
-        r.composite_matte((), &m, (), c, SrcOver);
+    fn with_border() {
+        let r = Raster::with_color(2, 1, Gray8::new(0x80));
+        let b = r.with_border(1, 2, 0, 1, Gray8::new(0xFF));
+        assert_eq!((b.width(), b.height()), (3, 4));
         let v = [
-            Rgba8p::new(0xFF, 0x80, 0x40, 0xFF), Rgba8p::new(0, 0, 0, 0),
-            Rgba8p::new(0, 0, 0, 0), Rgba8p::new(0x80, 0x40, 0x20, 0x80),
+            Gray8::new(0xFF), Gray8::new(0xFF), Gray8::new(0xFF),
+            Gray8::new(0xFF), Gray8::new(0xFF), Gray8::new(0xFF),
+            Gray8::new(0xFF), Gray8::new(0x80), Gray8::new(0x80),
+            Gray8::new(0xFF), Gray8::new(0xFF), Gray8::new(0xFF),
         ];
-        assert_eq!(r.pixels(), &v);
+        assert_eq!(b.pixels(), &v);
+        let e = r.crop((5, 5, 1, 1)).with_border(1, 1, 1, 1, Gray8::new(1));
+        assert_eq!(e.pixels(), &[Gray8::new(1); 4]);
     }
 
     #[test]
-    fn composite_matte_smaller() {
-        let mut r = Raster::<Rgba8p>::with_clear(3, 3);
-        let m = vec![
-            Matte8::new(0xFF), Matte8::new(0x80),
-            Matte8::new(0x40), Matte8::new(0x20),
-        ];
-        let m = Raster::<Matte8>::with_pixels(2, 2, m);
-        let c = Rgba8p::new(0x40, 0x80, 0x60, 0x80);
-        r.composite_matte((1, 1, 4, 4), &m, (), c, SrcOver);
-        let v = [
-            Rgba8p::new(0, 0, 0, 0),
-            Rgba8p::new(0, 0, 0, 0),
-            Rgba8p::new(0, 0, 0, 0),
-
-            Rgba8p::new(0, 0, 0, 0),
-            Rgba8p::new(0x40, 0x80, 0x60, 0x80),
-            Rgba8p::new(0x20, 0x40, 0x30, 0x40),
-
-            Rgba8p::new(0, 0, 0, 0),
-            Rgba8p::new(0x10, 0x20, 0x18, 0x20),
-            Rgba8p::new(0x08, 0x10, 0x0C, 0x10),
-        ];
-        assert_eq!(r.pixels(), &v);
+    fn rotate() {
+        use core::f32::consts::{FRAC_PI_2, PI};
+        let bg = Gray8::new(0xFF);
+        let r = Raster::<Gray8>::with_u8_buffer(3, 2, [1, 2, 3, 4, 5, 6]);
+        assert_eq!(r.rotate(0.0, bg).pixels(), r.pixels());
+        assert_eq!(r.rotate(1e-6, bg).pixels(), r.pixels());
+        let r90 = r.rotate(FRAC_PI_2, bg);
+        assert_eq!((r90.width(), r90.height()), (2, 3));
+        assert_eq!(r90.as_u8_slice(), [4, 1, 5, 2, 6, 3]);
+        let r90 = r.rotate(FRAC_PI_2 + 1e-6, bg);
+        assert_eq!(r90.as_u8_slice(), [4, 1, 5, 2, 6, 3]);
+        let r180 = r.rotate(PI, bg);
+        assert_eq!(r180.as_u8_slice(), [6, 5, 4, 3, 2, 1]);
+        let r270 = r.rotate(-FRAC_PI_2, bg);
+        assert_eq!(r270.as_u8_slice(), [3, 6, 2, 5, 1, 4]);
+        let r = Raster::with_color(4, 4, Gray8::new(0));
+        let r45 = r.rotate(PI / 4.0, bg);
+        assert_eq!((r45.width(), r45.height()), (6, 6));
+        assert_eq!(r45.pixel(0, 0), bg);
+        assert_eq!(r45.pixel(3, 3), Gray8::new(0));
+        let e = Raster::<Gray8>::with_clear(0, 0).rotate(1.0, bg);
+        assert_eq!((e.width(), e.height()), (0, 0));
     }
 
     #[test]
-    fn composite_matte_gray() {
-        let mut g0 = Raster::<Graya8p>::with_clear(2, 2);
-        let g1 = Raster::<Matte8>::with_color(3, 3, Matte8::new(0x40));
-        let g2 = Raster::<Matte8>::with_color(3, 3, Matte8::new(0x60));
-        let g3 = Raster::<Matte8>::with_color(3, 3, Matte8::new(0x80));
-        let clr = Graya8p::new(0xFF, 0xFF);
-        g0.composite_matte((1, 1, 3, 3), &g1, (), clr, SrcOver);
-        g0.composite_matte((1, -2, 3, 3), &g2, (), clr, SrcOver);
-        g0.composite_matte((-2, -2, 3, 3), &g3, (), clr, SrcOver);
-        let v = vec![
-            Graya8p::new(0x80, 0x80), Graya8p::new(0x60, 0x60),
-            Graya8p::new(0x00, 0x00), Graya8p::new(0x40, 0x40),
-        ];
-        assert_eq!(g0.pixels(), &v[..]);
+    fn blend_straight() {
+        let photo: Vec<u8> = (0..8 * 8 * 3).map(|i| (i * 37 % 256) as u8)
+            .collect();
+        let photo = Raster::<SRgb8>::with_u8_buffer(8, 8, photo);
+        let sticker: Vec<u8> = (0..6 * 5 * 4).map(|i| (i * 91 % 256) as u8)
+            .collect();
+        let sticker = Raster::<SRgba8>::with_u8_buffer(6, 5, sticker);
+        let mut r = photo.clone();
+        r.blend_straight((3, -1), &sticker, (1, 0, 9, 9));
+        // long pipeline, through Rgba8p
+        let mut d = Raster::<Rgba8p>::with_raster(&photo);
+        let s = Raster::<Rgba8p>::with_raster(&sticker);
+        d.composite_raster((3, -1), &s, (1, 0, 9, 9), SrcOver);
+        let expected = Raster::<SRgb8>::with_raster(&d);
+        for y in 0..4 {
+            for x in 3..8 {
+                let (p, e) = (r.pixel(x, y), expected.pixel(x, y));
+                let s = sticker.pixel(x - 2, y + 1);
+                if s.alpha() == Ch8::MIN {
+                    assert_eq!(p, photo.pixel(x, y));
+                } else if s.alpha() == Ch8::MAX {
+                    assert_eq!(p, s.convert());
+                } else {
+                    assert_eq!(p, e);
+                }
+            }
+        }
+        assert_eq!(r.pixel(2, 2), photo.pixel(2, 2));
+        assert_eq!(r.pixel(4, 4), photo.pixel(4, 4));
+        assert_ne!(r.pixel(3, 2), photo.pixel(3, 2));
+        let mut r = photo.clone();
+        r.blend_straight((20, 20), &sticker, ());
+        assert_eq!(r.pixels(), photo.pixels());
     }
 
     #[test]
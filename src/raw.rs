@@ -0,0 +1,371 @@
+// raw.rs       Raw image descriptions
+//
+// Copyright (c) 2024  Douglas P Lau
+//
+use crate::argb::Argb;
+use crate::bgr::Bgr;
+use crate::el::Pixel;
+use crate::gray::Gray;
+use crate::matte::Matte;
+use crate::raster::Raster;
+use crate::rgb::Rgb;
+use core::any::TypeId;
+use core::marker::PhantomData;
+use core::mem::size_of;
+
+/// Channel order and bit depth of a raw image.
+///
+/// This is derived from a [Pixel](el/trait.Pixel.html) type, for passing
+/// images to foreign libraries.  *Alpha* mode and gamma are not included,
+/// since they do not affect memory layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixFormat {
+    /// 8-bit *alpha*
+    Matte8,
+    /// 16-bit *alpha*
+    Matte16,
+    /// 32-bit floating-point *alpha*
+    Matte32,
+    /// 8-bit gray
+    Gray8,
+    /// 16-bit gray
+    Gray16,
+    /// 32-bit floating-point gray
+    Gray32,
+    /// 8-bit gray, *alpha*
+    Graya8,
+    /// 16-bit gray, *alpha*
+    Graya16,
+    /// 32-bit floating-point gray, *alpha*
+    Graya32,
+    /// 8-bit *red*, *green*, *blue*
+    Rgb8,
+    /// 16-bit *red*, *green*, *blue*
+    Rgb16,
+    /// 32-bit floating-point *red*, *green*, *blue*
+    Rgb32,
+    /// 8-bit *red*, *green*, *blue*, *alpha*
+    Rgba8,
+    /// 16-bit *red*, *green*, *blue*, *alpha*
+    Rgba16,
+    /// 32-bit floating-point *red*, *green*, *blue*, *alpha*
+    Rgba32,
+    /// 8-bit *blue*, *green*, *red*
+    Bgr8,
+    /// 16-bit *blue*, *green*, *red*
+    Bgr16,
+    /// 32-bit floating-point *blue*, *green*, *red*
+    Bgr32,
+    /// 8-bit *blue*, *green*, *red*, *alpha*
+    Bgra8,
+    /// 16-bit *blue*, *green*, *red*, *alpha*
+    Bgra16,
+    /// 32-bit floating-point *blue*, *green*, *red*, *alpha*
+    Bgra32,
+    /// 8-bit *alpha*, *red*, *green*, *blue*
+    Argb8,
+    /// 16-bit *alpha*, *red*, *green*, *blue*
+    Argb16,
+    /// 32-bit floating-point *alpha*, *red*, *green*, *blue*
+    Argb32,
+    /// Any other format, such as `Hsv` or `YCbCr`
+    Other,
+}
+
+impl PixFormat {
+    /// Get the format of a `Pixel` type.
+    ///
+    /// ### Format of a pixel type
+    /// ```
+    /// use pix::bgr::SBgra8p;
+    /// use pix::hsv::Hsv8;
+    /// use pix::PixFormat;
+    ///
+    /// assert_eq!(PixFormat::of::<SBgra8p>(), PixFormat::Bgra8);
+    /// assert_eq!(PixFormat::of::<Hsv8>(), PixFormat::Other);
+    /// ```
+    pub fn of<P: Pixel>() -> Self {
+        use PixFormat::*;
+        let model = TypeId::of::<P::Model>();
+        let formats = match P::CHANNELS {
+            1 if model == TypeId::of::<Matte>() => [Matte8, Matte16, Matte32],
+            1 if model == TypeId::of::<Gray>() => [Gray8, Gray16, Gray32],
+            2 if model == TypeId::of::<Gray>() => [Graya8, Graya16, Graya32],
+            3 if model == TypeId::of::<Rgb>() => [Rgb8, Rgb16, Rgb32],
+            4 if model == TypeId::of::<Rgb>() => [Rgba8, Rgba16, Rgba32],
+            3 if model == TypeId::of::<Bgr>() => [Bgr8, Bgr16, Bgr32],
+            4 if model == TypeId::of::<Bgr>() => [Bgra8, Bgra16, Bgra32],
+            4 if model == TypeId::of::<Argb>() => [Argb8, Argb16, Argb32],
+            _ => return Other,
+        };
+        match size_of::<P::Chan>() {
+            1 => formats[0],
+            2 => formats[1],
+            4 => formats[2],
+            _ => Other,
+        }
+    }
+}
+
+/// Raw description of a `Raster`, borrowed for FFI.
+///
+/// Created with [Raster::as_raw_parts](struct.Raster.html#method.as_raw_parts).
+#[derive(Clone, Copy, Debug)]
+pub struct RawImage<'a> {
+    ptr: *const u8,
+    width: u32,
+    height: u32,
+    stride: usize,
+    format: PixFormat,
+    _data: PhantomData<&'a [u8]>,
+}
+
+/// Raw mutable description of a `Raster`, borrowed for FFI.
+///
+/// Created with
+/// [Raster::as_raw_parts_mut](struct.Raster.html#method.as_raw_parts_mut).
+#[derive(Debug)]
+pub struct RawImageMut<'a> {
+    ptr: *mut u8,
+    width: u32,
+    height: u32,
+    stride: usize,
+    format: PixFormat,
+    _data: PhantomData<&'a mut [u8]>,
+}
+
+impl RawImage<'_> {
+    /// Get a pointer to the first pixel.
+    ///
+    /// It is valid for reads of `stride() * height()` bytes, while the
+    /// `Raster` is borrowed.
+    pub fn ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    /// Get the width, in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height, in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the stride (distance between rows), in bytes.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Get the pixel format.
+    pub fn format(&self) -> PixFormat {
+        self.format
+    }
+}
+
+impl RawImageMut<'_> {
+    /// Get a mutable pointer to the first pixel.
+    ///
+    /// It is valid for reads and writes of `stride() * height()` bytes,
+    /// while the `Raster` is borrowed.
+    pub fn ptr(&mut self) -> *mut u8 {
+        self.ptr
+    }
+
+    /// Get the width, in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height, in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the stride (distance between rows), in bytes.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Get the pixel format.
+    pub fn format(&self) -> PixFormat {
+        self.format
+    }
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Get a raw description of the `Raster`, for passing to foreign code.
+    ///
+    /// Rows are tightly packed, so the stride is the width times
+    /// [Pixel::BYTES](el/trait.Pixel.html#associatedconstant.BYTES).
+    /// Multi-byte channels are in native-endian byte order.
+    ///
+    /// ### Describe a raster
+    /// ```
+    /// use pix::rgb::SRgba8;
+    /// use pix::{PixFormat, Raster};
+    ///
+    /// let r = Raster::<SRgba8>::with_clear(10, 5);
+    /// let raw = r.as_raw_parts();
+    /// assert_eq!((raw.width(), raw.height(), raw.stride()), (10, 5, 40));
+    /// assert_eq!(raw.format(), PixFormat::Rgba8);
+    /// ```
+    pub fn as_raw_parts(&self) -> RawImage<'_> {
+        RawImage {
+            ptr: self.as_u8_slice().as_ptr(),
+            width: self.width(),
+            height: self.height(),
+            stride: self.width() as usize * P::BYTES,
+            format: PixFormat::of::<P>(),
+            _data: PhantomData,
+        }
+    }
+
+    /// Get a raw mutable description of the `Raster`, for passing to
+    /// foreign code.
+    ///
+    /// See [as_raw_parts](#method.as_raw_parts).
+    pub fn as_raw_parts_mut(&mut self) -> RawImageMut<'_> {
+        let (width, height) = (self.width(), self.height());
+        RawImageMut {
+            ptr: self.as_u8_slice_mut().as_mut_ptr(),
+            width,
+            height,
+            stride: width as usize * P::BYTES,
+            format: PixFormat::of::<P>(),
+            _data: PhantomData,
+        }
+    }
+
+    /// Construct a `Raster` by copying from a raw image.
+    ///
+    /// * `ptr` Pointer to first pixel.
+    /// * `width` Width in pixels.
+    /// * `height` Height in pixels.
+    /// * `stride` Distance between rows, in bytes.  Any padding at the end
+    ///   of each row is skipped.
+    ///
+    /// Pixels must be in the layout of `P`, with multi-byte channels in
+    /// native-endian byte order.
+    ///
+    /// # Panics
+    ///
+    /// If `stride` is less than `width` times
+    /// [Pixel::BYTES](el/trait.Pixel.html#associatedconstant.BYTES).
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads of `height` rows, each `stride` bytes
+    /// apart (except the last row, which only needs `width` pixels).  It
+    /// does not need to be aligned.
+    ///
+    /// ### Copy from a padded buffer
+    /// ```
+    /// use pix::gray::Gray8;
+    /// use pix::Raster;
+    ///
+    /// let buf = [1, 2, 0, 0, 3, 4, 0, 0, 5, 6];
+    /// let ptr = buf.as_ptr();
+    /// let r = unsafe { Raster::<Gray8>::from_raw_parts(ptr, 2, 3, 4) };
+    /// assert_eq!(r.as_u8_slice(), [1, 2, 3, 4, 5, 6]);
+    /// ```
+    pub unsafe fn from_raw_parts(
+        ptr: *const u8,
+        width: u32,
+        height: u32,
+        stride: usize,
+    ) -> Self {
+        let row_bytes = width as usize * P::BYTES;
+        assert!(stride >= row_bytes, "Stride too small");
+        let mut r = Raster::with_clear(width, height);
+        if row_bytes > 0 {
+            let rows = r.as_u8_slice_mut().chunks_exact_mut(row_bytes);
+            for (y, row) in rows.enumerate() {
+                // SAFETY: caller guarantees each row is readable
+                let src = unsafe {
+                    core::slice::from_raw_parts(ptr.add(y * stride), row_bytes)
+                };
+                row.copy_from_slice(src);
+            }
+        }
+        r
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::argb::Argb16;
+    use crate::bgr::SBgr8;
+    use crate::gray::{Graya32p, SGray16};
+    use crate::matte::Matte8;
+    use crate::oklab::Oklab8;
+    use crate::rgb::{Rgb8, Rgba64, SRgba8};
+    use crate::*;
+
+    #[test]
+    fn formats() {
+        assert_eq!(PixFormat::of::<Matte8>(), PixFormat::Matte8);
+        assert_eq!(PixFormat::of::<SGray16>(), PixFormat::Gray16);
+        assert_eq!(PixFormat::of::<Graya32p>(), PixFormat::Graya32);
+        assert_eq!(PixFormat::of::<Rgb8>(), PixFormat::Rgb8);
+        assert_eq!(PixFormat::of::<SBgr8>(), PixFormat::Bgr8);
+        assert_eq!(PixFormat::of::<Argb16>(), PixFormat::Argb16);
+        assert_eq!(PixFormat::of::<Oklab8>(), PixFormat::Other);
+        assert_eq!(PixFormat::of::<Rgba64>(), PixFormat::Other);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut r = Raster::<SRgba8>::with_clear(3, 2);
+        for (i, p) in r.pixels_mut().iter_mut().enumerate() {
+            let v = i as u8 * 10;
+            *p = SRgba8::new(v, v + 1, v + 2, v + 3);
+        }
+        let raw = r.as_raw_parts();
+        assert_eq!(raw.ptr(), r.as_u8_slice().as_ptr());
+        assert_eq!(raw.stride(), 12);
+        let c = unsafe {
+            Raster::<SRgba8>::from_raw_parts(
+                raw.ptr(),
+                raw.width(),
+                raw.height(),
+                raw.stride(),
+            )
+        };
+        assert_eq!(c.pixels(), r.pixels());
+        let mut d = c.clone();
+        let mut raw = d.as_raw_parts_mut();
+        assert_eq!(raw.format(), PixFormat::Rgba8);
+        unsafe { *raw.ptr() = 0xFF };
+        assert_eq!(d.pixel(0, 0), SRgba8::new(0xFF, 1, 2, 3));
+    }
+
+    #[test]
+    fn padded_stride() {
+        let r = Raster::<SGray16>::with_u16_buffer(3, 2, [1, 2, 3, 4, 5, 6]);
+        // copy rows into a buffer with 5 bytes of padding per row
+        let raw = r.as_raw_parts();
+        let stride = raw.stride() + 5;
+        let mut buf = vec![0xAA; stride * 2];
+        for (y, row) in r.as_u8_slice().chunks(raw.stride()).enumerate() {
+            buf[y * stride..][..row.len()].copy_from_slice(row);
+        }
+        // start at an unaligned address
+        buf.insert(0, 0);
+        let c = unsafe {
+            Raster::<SGray16>::from_raw_parts(buf[1..].as_ptr(), 3, 2, stride)
+        };
+        assert_eq!(c.pixels(), r.pixels());
+        let e =
+            unsafe { Raster::<SGray16>::from_raw_parts(buf.as_ptr(), 0, 0, 0) };
+        assert!(e.pixels().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Stride too small")]
+    fn stride_too_small() {
+        let buf = [0u8; 16];
+        unsafe { Raster::<Rgb8>::from_raw_parts(buf.as_ptr(), 2, 2, 5) };
+    }
+}
@@ -5,11 +5,32 @@
 //
 //! [RGB] color model and types.
 //!
+//! ## Premultiplied sRGB
+//!
+//! Formats such as [SRgba8p] are premultiplied in *linear* light: each
+//! channel is decoded from sRGB, multiplied by *alpha*, then encoded again.
+//! This keeps more precision in dark tones than premultiplying in linear
+//! 8-bit channels, so these formats are useful for storing or transmitting
+//! images.  Converting `SRgba8` to `SRgba8p` and back changes channels by at
+//! most 1 when *alpha* is at least `0x60`; with lower *alpha*, precision is
+//! lost in proportion.
+//!
+//! Compositing requires linear formats, such as [Rgba8p].  Libraries such
+//! as Cairo and Skia premultiply the *encoded* sRGB values instead.  Their
+//! buffers are not compatible with `SRgba8p`, but the same arithmetic is
+//! applied by [Rgba8p] if encoded values are treated as linear.
+//!
 //! [rgb]: https://en.wikipedia.org/wiki/RGB_color_model
-use crate::chan::{Ch16, Ch32, Ch8, Linear, Premultiplied, Srgb, Straight};
+//! [Rgba8p]: type.Rgba8p.html
+//! [SRgba8p]: type.SRgba8p.html
+use crate::chan::{
+    Ch16, Ch32, Ch64, Ch8, Channel, Linear, Premultiplied, Srgb, Straight,
+};
 use crate::el::{Pix3, Pix4, PixRgba, Pixel};
 use crate::ColorModel;
-use std::ops::Range;
+use core::fmt;
+use core::ops::Range;
+use core::str::FromStr;
 
 /// [RGB] additive [color model].
 ///
@@ -158,6 +179,29 @@ impl Rgb {
         P::from_channels(&[red, green, blue, alpha])
     }
 
+    /// Scale *red*, *green* and *blue* channels by separate factors.
+    ///
+    /// Results are clamped to the valid channel range.  *Alpha* is not
+    /// changed.
+    ///
+    /// # Example: Warm a color
+    /// ```
+    /// use pix::rgb::{Rgb, Rgb32};
+    ///
+    /// let p = Rgb32::new(0.25, 0.5, 0.75);
+    /// let p = Rgb::scale_channels(p, 2.0, 1.0, 0.5);
+    /// assert_eq!(p, Rgb32::new(0.5, 0.5, 0.375));
+    /// ```
+    pub fn scale_channels<P>(p: P, red: f32, green: f32, blue: f32) -> P
+    where
+        P: Pixel<Model = Self>,
+    {
+        let red = P::Chan::from(Self::red(p).to_f32() * red);
+        let green = P::Chan::from(Self::green(p).to_f32() * green);
+        let blue = P::Chan::from(Self::blue(p).to_f32() * blue);
+        P::from_channels(&[red, green, blue, p.alpha()])
+    }
+
     /// Check if all `Channel`s are within threshold
     pub fn within_threshold<P>(p: P, rhs: P) -> bool
     where
@@ -174,6 +218,9 @@ impl ColorModel for Rgb {
     const CIRCULAR: Range<usize> = 0..0;
     const LINEAR: Range<usize> = 0..3;
     const ALPHA: usize = 3;
+    const NAME: &'static str = "rgb";
+    const CHANNEL_NAMES: &'static [&'static str] =
+        &["red", "green", "blue", "alpha"];
 
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
@@ -210,6 +257,11 @@ pub type Rgb16 = Pix3<Ch16, Rgb, Straight, Linear>;
 /// format.
 pub type Rgb32 = Pix3<Ch32, Rgb, Straight, Linear>;
 
+/// [Rgb](struct.Rgb.html) 64-bit opaque (no *alpha* channel)
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Rgb64 = Pix3<Ch64, Rgb, Straight, Linear>;
+
 /// [Rgb](struct.Rgb.html) 8-bit [straight](../chan/struct.Straight.html)
 /// alpha [linear](../chan/struct.Linear.html) gamma
 /// [pixel](../el/trait.Pixel.html) format.
@@ -225,6 +277,11 @@ pub type Rgba16 = Pix4<Ch16, Rgb, Straight, Linear>;
 /// [pixel](../el/trait.Pixel.html) format.
 pub type Rgba32 = Pix4<Ch32, Rgb, Straight, Linear>;
 
+/// [Rgb](struct.Rgb.html) 64-bit [straight](../chan/struct.Straight.html)
+/// alpha [linear](../chan/struct.Linear.html) gamma
+/// [pixel](../el/trait.Pixel.html) format.
+pub type Rgba64 = Pix4<Ch64, Rgb, Straight, Linear>;
+
 /// [Rgb](struct.Rgb.html) 8-bit
 /// [premultiplied](../chan/struct.Premultiplied.html) alpha
 /// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
@@ -243,6 +300,36 @@ pub type Rgba16p = Pix4<Ch16, Rgb, Premultiplied, Linear>;
 /// format.
 pub type Rgba32p = Pix4<Ch32, Rgb, Premultiplied, Linear>;
 
+/// [Rgb](struct.Rgb.html) 64-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Rgba64p = Pix4<Ch64, Rgb, Premultiplied, Linear>;
+
+/// [Rgb](struct.Rgb.html) 8-bit opaque (no *alpha* channel)
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Rgb8p = Pix3<Ch8, Rgb, Premultiplied, Linear>;
+
+/// [Rgb](struct.Rgb.html) 16-bit opaque (no *alpha* channel)
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Rgb16p = Pix3<Ch16, Rgb, Premultiplied, Linear>;
+
+/// [Rgb](struct.Rgb.html) 32-bit opaque (no *alpha* channel)
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Rgb32p = Pix3<Ch32, Rgb, Premultiplied, Linear>;
+
+/// [Rgb](struct.Rgb.html) 64-bit opaque (no *alpha* channel)
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type Rgb64p = Pix3<Ch64, Rgb, Premultiplied, Linear>;
+
 /// [Rgb](struct.Rgb.html) 8-bit opaque (no *alpha* channel)
 /// [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
 /// format.
@@ -258,6 +345,11 @@ pub type SRgb16 = Pix3<Ch16, Rgb, Straight, Srgb>;
 /// format.
 pub type SRgb32 = Pix3<Ch32, Rgb, Straight, Srgb>;
 
+/// [Rgb](struct.Rgb.html) 64-bit opaque (no *alpha* channel)
+/// [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type SRgb64 = Pix3<Ch64, Rgb, Straight, Srgb>;
+
 /// [Rgb](struct.Rgb.html) 8-bit [straight](../chan/struct.Straight.html)
 /// alpha [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
 /// format.
@@ -273,6 +365,11 @@ pub type SRgba16 = Pix4<Ch16, Rgb, Straight, Srgb>;
 /// format.
 pub type SRgba32 = Pix4<Ch32, Rgb, Straight, Srgb>;
 
+/// [Rgb](struct.Rgb.html) 64-bit [straight](../chan/struct.Straight.html)
+/// alpha [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type SRgba64 = Pix4<Ch64, Rgb, Straight, Srgb>;
+
 /// [Rgb](struct.Rgb.html) 8-bit
 /// [premultiplied](../chan/struct.Premultiplied.html) alpha
 /// [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
@@ -291,12 +388,199 @@ pub type SRgba16p = Pix4<Ch16, Rgb, Premultiplied, Srgb>;
 /// format.
 pub type SRgba32p = Pix4<Ch32, Rgb, Premultiplied, Srgb>;
 
+/// [Rgb](struct.Rgb.html) 64-bit
+/// [premultiplied](../chan/struct.Premultiplied.html) alpha
+/// [sRGB](../chan/struct.Srgb.html) gamma [pixel](../el/trait.Pixel.html)
+/// format.
+pub type SRgba64p = Pix4<Ch64, Rgb, Premultiplied, Srgb>;
+
+/// Error parsing a hex color string.
+///
+/// Returned from `FromStr` for [SRgb8](type.SRgb8.html) and
+/// [SRgba8](type.SRgba8.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseHexError {
+    /// Invalid number of hex digits
+    Length,
+
+    /// Invalid hex digit
+    Digit,
+}
+
+impl fmt::Display for ParseHexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseHexError::Length => write!(f, "invalid hex color length"),
+            ParseHexError::Digit => write!(f, "invalid hex color digit"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseHexError {}
+
+/// Parse a hex color string into *red*, *green*, *blue* and *alpha*.
+///
+/// Returns the components along with whether *alpha* was specified.
+fn parse_hex(s: &str) -> Result<([u8; 4], bool), ParseHexError> {
+    let s = s.strip_prefix('#').unwrap_or(s);
+    if !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ParseHexError::Digit);
+    }
+    let digit = |i: usize| -> u8 {
+        // all digits have been checked above
+        (s.as_bytes()[i] as char).to_digit(16).unwrap() as u8
+    };
+    let mut rgba = [0xFF; 4];
+    match s.len() {
+        3 | 4 => {
+            for (i, c) in rgba.iter_mut().take(s.len()).enumerate() {
+                *c = digit(i) * 0x11;
+            }
+        }
+        6 | 8 => {
+            for (i, c) in rgba.iter_mut().take(s.len() / 2).enumerate() {
+                *c = (digit(i * 2) << 4) | digit(i * 2 + 1);
+            }
+        }
+        _ => return Err(ParseHexError::Length),
+    }
+    Ok((rgba, s.len() == 4 || s.len() == 8))
+}
+
+impl FromStr for SRgb8 {
+    type Err = ParseHexError;
+
+    /// Parse from `#RGB` or `#RRGGBB` hex string.
+    ///
+    /// The leading `#` is optional, and digits are case-insensitive.
+    ///
+    /// ```
+    /// use pix::rgb::SRgb8;
+    ///
+    /// let clr: SRgb8 = "#40a0ff".parse().unwrap();
+    /// assert_eq!(clr, SRgb8::new(0x40, 0xA0, 0xFF));
+    /// assert_eq!(format!("{clr:x}"), "#40a0ff");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_hex(s)? {
+            ([r, g, b, _], false) => Ok(SRgb8::new(r, g, b)),
+            _ => Err(ParseHexError::Length),
+        }
+    }
+}
+
+impl FromStr for SRgba8 {
+    type Err = ParseHexError;
+
+    /// Parse from `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA` hex string.
+    ///
+    /// The leading `#` is optional, and digits are case-insensitive.  If
+    /// *alpha* is not specified, the color is opaque.
+    ///
+    /// ```
+    /// use pix::rgb::SRgba8;
+    ///
+    /// let clr: SRgba8 = "#4AF8".parse().unwrap();
+    /// assert_eq!(clr, SRgba8::new(0x44, 0xAA, 0xFF, 0x88));
+    /// assert_eq!(format!("{clr:x}"), "#44aaff88");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ([r, g, b, a], _) = parse_hex(s)?;
+        Ok(SRgba8::new(r, g, b, a))
+    }
+}
+
+impl fmt::LowerHex for SRgb8 {
+    /// Format as `#rrggbb`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b] = [Rgb::red(*self), Rgb::green(*self), Rgb::blue(*self)];
+        write!(
+            f,
+            "#{:02x}{:02x}{:02x}",
+            u8::from(r),
+            u8::from(g),
+            u8::from(b)
+        )
+    }
+}
+
+impl fmt::LowerHex for SRgba8 {
+    /// Format as `#rrggbbaa`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b] = [Rgb::red(*self), Rgb::green(*self), Rgb::blue(*self)];
+        let a = self.alpha();
+        write!(
+            f,
+            "#{:02x}{:02x}{:02x}{:02x}",
+            u8::from(r),
+            u8::from(g),
+            u8::from(b),
+            u8::from(a)
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::assert_pixel_approx_eq;
     use crate::el::Pixel;
     use crate::ops::SrcOver;
     use crate::rgb::*;
 
+    #[test]
+    fn parse_hex() {
+        assert_eq!("#123".parse(), Ok(SRgb8::new(0x11, 0x22, 0x33)));
+        assert_eq!("aBcDeF".parse(), Ok(SRgb8::new(0xAB, 0xCD, 0xEF)));
+        assert_eq!("#123".parse(), Ok(SRgba8::new(0x11, 0x22, 0x33, 0xFF)));
+        assert_eq!("#1234".parse(), Ok(SRgba8::new(0x11, 0x22, 0x33, 0x44)));
+        assert_eq!(
+            "#01234567".parse(),
+            Ok(SRgba8::new(0x01, 0x23, 0x45, 0x67))
+        );
+        assert_eq!("0a0B0c".parse(), Ok(SRgba8::new(0x0A, 0x0B, 0x0C, 0xFF)));
+    }
+
+    #[test]
+    fn parse_hex_invalid() {
+        assert_eq!("".parse::<SRgb8>(), Err(ParseHexError::Length));
+        assert_eq!("#".parse::<SRgb8>(), Err(ParseHexError::Length));
+        assert_eq!("#12".parse::<SRgb8>(), Err(ParseHexError::Length));
+        assert_eq!("#1234".parse::<SRgb8>(), Err(ParseHexError::Length));
+        assert_eq!("#12345".parse::<SRgba8>(), Err(ParseHexError::Length));
+        assert_eq!("#123456789".parse::<SRgba8>(), Err(ParseHexError::Length));
+        assert_eq!("#12g".parse::<SRgb8>(), Err(ParseHexError::Digit));
+        assert_eq!("##123".parse::<SRgb8>(), Err(ParseHexError::Digit));
+        assert_eq!("#12345z".parse::<SRgba8>(), Err(ParseHexError::Digit));
+        assert_eq!("+12345".parse::<SRgba8>(), Err(ParseHexError::Digit));
+    }
+
+    #[test]
+    fn scale_channels() {
+        let p = Rgba8::new(0x40, 0x80, 0xC0, 0x55);
+        assert_eq!(Rgb::scale_channels(p, 1.0, 1.0, 1.0), p);
+        assert_eq!(
+            Rgb::scale_channels(p, 2.0, 0.5, 2.0),
+            Rgba8::new(0x80, 0x40, 0xFF, 0x55)
+        );
+        let p = Rgb32::new(0.25, 0.5, 0.75);
+        assert_eq!(
+            Rgb::scale_channels(p, 4.0, 0.0, -1.0),
+            Rgb32::new(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        let clr = SRgb8::new(0x01, 0x9A, 0xFE);
+        assert_eq!(clr.to_string(), "rgb(1, 154, 254)");
+        assert_eq!(format!("{clr:x}"), "#019afe");
+        assert_eq!(format!("{clr:x}").parse(), Ok(clr));
+        let clr = SRgba8::new(0xFF, 0x00, 0x7F, 0x80);
+        assert_eq!(format!("{clr:x}"), "#ff007f80");
+        assert_eq!(format!("{clr:x}").parse(), Ok(clr));
+    }
+
     #[test]
     fn rgba8_transparent() {
         let mut dst = Rgba8p::new(0, 0, 0, 0);
@@ -312,4 +596,53 @@ mod tests {
         dst.composite_channels(&Rgba8p::new(0, 0, 0, 0), SrcOver);
         assert_eq!(dst, Rgba8p::new(0xFF, 0xFF, 0xFF, 0x00));
     }
+    #[test]
+    fn srgb_premultiplied_8() {
+        const LSB: f32 = 1.5 / 255.0;
+        for a in 0x60..=0xFF {
+            for v in 0..=0xFF {
+                let p = SRgba8::new(v, 0xFF - v, v / 2, a);
+                let q: SRgba8 = p.convert::<SRgba8p>().convert();
+                if a == 0xFF {
+                    assert_eq!(q, p);
+                } else {
+                    assert_pixel_approx_eq!(q, p, LSB);
+                }
+                // premultiplied to straight and back
+                let p = p.convert::<SRgba8p>();
+                let q: SRgba8p = p.convert::<SRgba8>().convert();
+                assert_pixel_approx_eq!(q, p, LSB);
+            }
+        }
+    }
+
+    #[test]
+    fn srgb_premultiplied_16() {
+        const LSB: f32 = 1.5 / 65535.0;
+        for a in (0x6000..=0xFFFF).step_by(0x101) {
+            for v in (0..=0xFFFF).step_by(0x33) {
+                let p = SRgba16::new(v, 0xFFFF - v, v / 2, a);
+                let q: SRgba16 = p.convert::<SRgba16p>().convert();
+                assert_pixel_approx_eq!(q, p, LSB);
+                let p = p.convert::<SRgba16p>();
+                let q: SRgba16p = p.convert::<SRgba16>().convert();
+                assert_pixel_approx_eq!(q, p, LSB);
+            }
+        }
+    }
+
+    #[test]
+    fn srgb_premultiplied_32() {
+        for a in 1..=100 {
+            for v in 0..=100 {
+                let (a, v) = (a as f32 / 100.0, v as f32 / 100.0);
+                let p = SRgba32::new(v, 1.0 - v, v / 2.0, a);
+                let q: SRgba32 = p.convert::<SRgba32p>().convert();
+                assert_pixel_approx_eq!(q, p, 1e-5);
+                let p = p.convert::<SRgba32p>();
+                let q: SRgba32p = p.convert::<SRgba32>().convert();
+                assert_pixel_approx_eq!(q, p, 1e-5);
+            }
+        }
+    }
 }
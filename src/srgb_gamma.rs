@@ -4,10 +4,11 @@
 //
 // Functions for encoding and decoding gamma between linear and sRGB.
 //
-// This is a separate file so that it can be includeed by build.rs
+// This is a separate file so that it can be includeed by build.rs.  The
+// functions use f64, so that look-up tables are correctly rounded.
 
 /// Encode an sRGB gamma value from linear intensity
-fn srgb_gamma_encode(v: f32) -> f32 {
+fn srgb_encode_f64(v: f64) -> f64 {
     if v <= 0.0 {
         0.0
     } else if v < 0.003_130_8 {
@@ -20,7 +21,7 @@ fn srgb_gamma_encode(v: f32) -> f32 {
 }
 
 /// Decode an sRGB gamma value into linear intensity
-fn srgb_gamma_decode(v: f32) -> f32 {
+fn srgb_decode_f64(v: f64) -> f64 {
     if v <= 0.0 {
         0.0
     } else if v < 0.04045 {
@@ -10,17 +10,27 @@
 use crate::chan::{Ch16, Ch32, Ch8, Channel, Linear, Premultiplied, Straight};
 use crate::el::{Pix3, Pix4, PixRgba, Pixel};
 use crate::ColorModel;
-use std::ops::Range;
+use core::ops::Range;
 
 /// [Xyz] [color model] with D65 white point.
 ///
 /// The components are *[X]*, *[Y]*, *[Z]* and optional *[alpha]*.
 ///
+/// Conversions use the sRGB primaries with a D65 white point (2° standard
+/// observer), without chromatic adaptation.  *Y* is relative luminance, so
+/// white has *Y* of exactly 1.0.
+///
+/// Like all channels, components are clamped when a pixel is created.  This
+/// limits *X* and *Z* of bright colors, since D65 white is (0.9505, 1.0,
+/// 1.089).  For unclamped values, use [rgb_to_xyz] and [xyz_to_rgb].
+///
 /// [alpha]: ../el/trait.Pixel.html#method.alpha
 /// [color model]: ../trait.ColorModel.html
 /// [x]: #method.x
 /// [y]: #method.y
 /// [z]: #method.z
+/// [rgb_to_xyz]: fn.rgb_to_xyz.html
+/// [xyz_to_rgb]: fn.xyz_to_rgb.html
 /// [XYZ]: https://en.wikipedia.org/wiki/CIE_1931_color_space#Definition_of_the_CIE_XYZ_color_space
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Xyz {}
@@ -136,6 +146,8 @@ impl ColorModel for Xyz {
     const CIRCULAR: Range<usize> = 0..0;
     const LINEAR: Range<usize> = 0..3;
     const ALPHA: usize = 3;
+    const NAME: &'static str = "xyz";
+    const CHANNEL_NAMES: &'static [&'static str] = &["x", "y", "z", "alpha"];
 
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
@@ -145,11 +157,7 @@ impl ColorModel for Xyz {
         let px = Self::x(p).to_f32();
         let py = Self::y(p).to_f32();
         let pz = Self::z(p).to_f32();
-
-        let red = px * 3.2406 + py * -1.5372 + pz * -0.4986;
-        let green = px * -0.9689 + py * 1.8758 + pz * 0.0415;
-        let blue = px * 0.0557 + py * -0.2040 + pz * 1.0570;
-
+        let (red, green, blue) = xyz_to_rgb(px, py, pz);
         PixRgba::<P>::new(red, green, blue, p.alpha().to_f32())
     }
 
@@ -164,14 +172,83 @@ impl ColorModel for Xyz {
         let blue = chan[2].to_f32();
         let alpha = chan[3];
 
-        let x = red * 0.4124 + green * 0.3576 + blue * 0.1805;
-        let y = red * 0.2126 + green * 0.7152 + blue * 0.0722;
-        let z = red * 0.0193 + green * 0.1192 + blue * 0.9505;
-
+        let (x, y, z) = rgb_to_xyz(red, green, blue);
         P::from_channels(&[x.into(), y.into(), z.into(), alpha])
     }
 }
 
+/// Convert linear *red*, *green* and *blue* to *X*, *Y* and *Z*
+///
+/// Values are not clamped.
+///
+/// ## Example
+/// ```
+/// use pix::xyz::rgb_to_xyz;
+///
+/// let (x, y, z) = rgb_to_xyz(1.0, 1.0, 1.0);
+/// assert!((x - 0.9505).abs() < 0.001);
+/// assert_eq!(y, 1.0);
+/// assert!((z - 1.089).abs() < 0.001);
+/// ```
+pub fn rgb_to_xyz(red: f32, green: f32, blue: f32) -> (f32, f32, f32) {
+    let x = red * 0.4124564 + green * 0.3575761 + blue * 0.1804375;
+    // last Y coefficient is rounded down, so white has Y of exactly 1.0
+    let y = red * 0.2126729 + green * 0.7151522 + blue * 0.0721749;
+    let z = red * 0.0193339 + green * 0.1191920 + blue * 0.9503041;
+    (x, y, z)
+}
+
+/// Convert *X*, *Y* and *Z* to linear *red*, *green* and *blue*
+///
+/// Values are not clamped; colors outside the sRGB gamut have components
+/// less than 0.0 or greater than 1.0.
+pub fn xyz_to_rgb(x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+    let red = x * 3.2404542 + y * -1.5371385 + z * -0.4985314;
+    let green = x * -0.9692660 + y * 1.8760108 + z * 0.0415560;
+    let blue = x * 0.0556434 + y * -0.2040259 + z * 1.0572252;
+    (red, green, blue)
+}
+
+/// Convert *X*, *Y* and *Z* to CIE *xyY* chromaticity and luminance
+///
+/// Black has no chromaticity, so the D65 white point is used.
+///
+/// ## Example
+/// ```
+/// use pix::xyz::{rgb_to_xyz, xyz_to_xyy};
+///
+/// let (x, y, lum) = rgb_to_xyz(1.0, 1.0, 1.0);
+/// let (x, y, lum) = xyz_to_xyy(x, y, lum);
+/// assert!((x - 0.3127).abs() < 0.0001);
+/// assert!((y - 0.3290).abs() < 0.0001);
+/// assert_eq!(lum, 1.0);
+/// ```
+pub fn xyz_to_xyy(x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+    let sum = x + y + z;
+    if sum > 0.0 {
+        (x / sum, y / sum, y)
+    } else {
+        (D65_X, D65_Y, 0.0)
+    }
+}
+
+/// Convert CIE *xyY* chromaticity and luminance to *X*, *Y* and *Z*
+///
+/// Chromaticity with *y* of zero is black.
+pub fn xyy_to_xyz(x: f32, y: f32, lum: f32) -> (f32, f32, f32) {
+    if y > 0.0 {
+        (x * lum / y, lum, (1.0 - x - y) * lum / y)
+    } else {
+        (0.0, 0.0, 0.0)
+    }
+}
+
+/// D65 white point *x* chromaticity
+const D65_X: f32 = 0.3127;
+
+/// D65 white point *y* chromaticity
+const D65_Y: f32 = 0.3290;
+
 /// [Xyz](struct.Xyz.html) 8-bit opaque (no *alpha* channel)
 /// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
 /// format.
@@ -225,8 +302,48 @@ pub type Xyza32p = Pix4<Ch32, Xyz, Premultiplied, Linear>;
 
 #[cfg(test)]
 mod test {
+    use crate::el::Pixel;
+    use crate::rgb::*;
+    use crate::xyz::*;
+
+    fn assert_near(a: (f32, f32, f32), b: (f32, f32, f32)) {
+        assert!((a.0 - b.0).abs() < 0.001, "{a:?} != {b:?}");
+        assert!((a.1 - b.1).abs() < 0.001, "{a:?} != {b:?}");
+        assert!((a.2 - b.2).abs() < 0.001, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn srgb_white_to_xyz() {
+        assert_near(rgb_to_xyz(1.0, 1.0, 1.0), (0.9505, 1.0, 1.089));
+        let p: Xyz32 = SRgb8::new(0xFF, 0xFF, 0xFF).convert();
+        assert_eq!(Xyz::y(p), Ch32::new(1.0));
+        // Z is clamped
+        assert_eq!(Xyz::z(p), Ch32::new(1.0));
+    }
+
+    #[test]
+    fn primaries_round_trip() {
+        for clr in [
+            Rgb32::new(1.0, 0.0, 0.0),
+            Rgb32::new(0.0, 1.0, 0.0),
+            Rgb32::new(0.0, 0.0, 1.0),
+            Rgb32::new(0.0, 0.0, 0.0),
+        ] {
+            let p: Xyz32 = clr.convert();
+            let rgb: Rgb32 = p.convert();
+            assert!(rgb.approx_eq(clr, 0.0001), "{clr:?} != {rgb:?}");
+        }
+        let (r, g, b) = xyz_to_rgb(0.9505, 1.0, 1.089);
+        assert_near((r, g, b), (1.0, 1.0, 1.0));
+    }
+
     #[test]
-    fn xyz_to_rgb() {
-        // TODO
+    fn xyy_round_trip() {
+        let (x, y, z) = rgb_to_xyz(0.2, 0.5, 0.8);
+        let (cx, cy, lum) = xyz_to_xyy(x, y, z);
+        assert_eq!(lum, y);
+        assert_near(xyy_to_xyz(cx, cy, lum), (x, y, z));
+        assert_eq!(xyz_to_xyy(0.0, 0.0, 0.0), (0.3127, 0.3290, 0.0));
+        assert_eq!(xyy_to_xyz(0.3127, 0.3290, 0.0), (0.0, 0.0, 0.0));
     }
 }
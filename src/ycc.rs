@@ -8,8 +8,10 @@
 //! [ycbcr]: https://en.wikipedia.org/wiki/YCbCr
 use crate::chan::{Ch16, Ch32, Ch8, Channel, Linear, Premultiplied, Straight};
 use crate::el::{Pix3, Pix4, PixRgba, Pixel};
+use crate::raster::Raster;
 use crate::ColorModel;
-use std::ops::Range;
+use alloc::vec::Vec;
+use core::ops::Range;
 
 /// [YCbCr] [color model] (used in JPEG and other formats).
 ///
@@ -141,19 +143,23 @@ impl ColorModel for YCbCr {
     const CIRCULAR: Range<usize> = 0..0;
     const LINEAR: Range<usize> = 0..3;
     const ALPHA: usize = 3;
+    const NAME: &'static str = "ycbcr";
+    const CHANNEL_NAMES: &'static [&'static str] = &["y", "cb", "cr", "alpha"];
 
     /// Convert into *red*, *green*, *blue* and *alpha* components
     fn into_rgba<P>(p: P) -> PixRgba<P>
     where
         P: Pixel<Model = Self>,
     {
+        // chroma is offset by the channel midpoint, so gray is exact
+        let mid = P::Chan::MID.to_f32();
         let y = Self::y(p).to_f32();
-        let cb = Self::cb(p).to_f32();
-        let cr = Self::cr(p).to_f32();
+        let cb = Self::cb(p).to_f32() - mid;
+        let cr = Self::cr(p).to_f32() - mid;
 
-        let red = y + (cr - 0.5) * 1.402;
-        let green = y - (cb - 0.5) * 0.344_136 - (cr - 0.5) * 0.714_136;
-        let blue = y + (cb - 0.5) * 1.772;
+        let red = y + cr * 1.402;
+        let green = y - cb * 0.344_136 - cr * 0.714_136;
+        let blue = y + cb * 1.772;
         PixRgba::<P>::new(red, green, blue, p.alpha().to_f32())
     }
 
@@ -168,9 +174,10 @@ impl ColorModel for YCbCr {
         let blue = chan[2].to_f32();
         let alpha = chan[3];
 
+        let mid = P::Chan::MID.to_f32();
         let y = (0.299 * red) + (0.587 * green) + (0.114 * blue);
-        let cb = 0.5 - (0.168_736 * red) - (0.331_264 * green) + (0.5 * blue);
-        let cr = 0.5 + (0.5 * red) - (0.418_688 * green) - (0.081_312 * blue);
+        let cb = mid - (0.168_736 * red) - (0.331_264 * green) + (0.5 * blue);
+        let cr = mid + (0.5 * red) - (0.418_688 * green) - (0.081_312 * blue);
 
         P::from_channels(&[y.into(), cb.into(), cr.into(), alpha])
     }
@@ -226,3 +233,285 @@ pub type YCbCra16p = Pix4<Ch16, YCbCr, Premultiplied, Linear>;
 /// [linear](../chan/struct.Linear.html) gamma [pixel](../el/trait.Pixel.html)
 /// format.
 pub type YCbCra32p = Pix4<Ch32, YCbCr, Premultiplied, Linear>;
+
+/// Get chroma plane dimensions for 4:2:0 subsampling
+fn chroma_size(width: u32, height: u32) -> (usize, usize) {
+    (((width + 1) / 2) as usize, ((height + 1) / 2) as usize)
+}
+
+/// Make a [YCbCr8] `Raster` from planar 4:2:0 (I420) data.
+///
+/// Chroma planes have half resolution, rounded up for odd dimensions.
+/// Chroma is upsampled using nearest neighbor, so each *cb* / *cr* sample
+/// is used for a 2x2 block of pixels.
+///
+/// * `width` Width of `Raster`.
+/// * `height` Height of `Raster`.
+/// * `y` Luma plane (`width` * `height`).
+/// * `cb` Blue-difference chroma plane.
+/// * `cr` Red-difference chroma plane.
+///
+/// # Panics
+///
+/// * If `width` or `height` is greater than `std::i32::MAX`
+/// * If any plane length is incorrect
+///
+/// ## Example
+/// ```
+/// use pix::ycc::{self, YCbCr8};
+///
+/// let y = [0x40; 9];
+/// let cb = [0x80; 4];
+/// let cr = [0x90; 4];
+/// let r = ycc::from_i420(3, 3, &y, &cb, &cr);
+/// assert_eq!(r.pixel(2, 2), YCbCr8::new(0x40, 0x80, 0x90));
+/// ```
+///
+/// [YCbCr8]: type.YCbCr8.html
+pub fn from_i420(
+    width: u32,
+    height: u32,
+    y: &[u8],
+    cb: &[u8],
+    cr: &[u8],
+) -> Raster<YCbCr8> {
+    let (cw, ch) = chroma_size(width, height);
+    assert_eq!(cb.len(), cw * ch);
+    assert_eq!(cr.len(), cw * ch);
+    from_420(width, height, y, |c| (cb[c], cr[c]))
+}
+
+/// Make a [YCbCr8] `Raster` from semi-planar 4:2:0 (NV12) data.
+///
+/// This is like [from_i420](fn.from_i420.html), except that *cb* and *cr*
+/// samples are interleaved in a single chroma plane.
+///
+/// * `width` Width of `Raster`.
+/// * `height` Height of `Raster`.
+/// * `y` Luma plane (`width` * `height`).
+/// * `cbcr` Interleaved chroma plane (*cb*, *cr* pairs).
+///
+/// # Panics
+///
+/// * If `width` or `height` is greater than `std::i32::MAX`
+/// * If any plane length is incorrect
+///
+/// ## Example
+/// ```
+/// use pix::ycc::{self, YCbCr8};
+///
+/// let y = [0x40; 9];
+/// let cbcr = [0x80, 0x90, 0x80, 0x90, 0x80, 0x90, 0x70, 0xA0];
+/// let r = ycc::from_nv12(3, 3, &y, &cbcr);
+/// assert_eq!(r.pixel(0, 0), YCbCr8::new(0x40, 0x80, 0x90));
+/// assert_eq!(r.pixel(2, 2), YCbCr8::new(0x40, 0x70, 0xA0));
+/// ```
+///
+/// [YCbCr8]: type.YCbCr8.html
+pub fn from_nv12(
+    width: u32,
+    height: u32,
+    y: &[u8],
+    cbcr: &[u8],
+) -> Raster<YCbCr8> {
+    let (cw, ch) = chroma_size(width, height);
+    assert_eq!(cbcr.len(), cw * ch * 2);
+    from_420(width, height, y, |c| (cbcr[c * 2], cbcr[c * 2 + 1]))
+}
+
+/// Make a [YCbCr8] `Raster` from a luma plane and 4:2:0 chroma samples
+///
+/// * `chroma` Function to get (*cb*, *cr*) for a chroma sample index.
+fn from_420<F>(width: u32, height: u32, y: &[u8], chroma: F) -> Raster<YCbCr8>
+where
+    F: Fn(usize) -> (u8, u8),
+{
+    let mut r = Raster::with_clear(width, height);
+    let (cw, _ch) = chroma_size(width, height);
+    assert_eq!(y.len(), r.pixels().len());
+    let width = width as usize;
+    for (i, (yv, p)) in y.iter().zip(r.pixels_mut()).enumerate() {
+        let (col, row) = (i % width, i / width);
+        let (cb, cr) = chroma((row / 2) * cw + col / 2);
+        *p = YCbCr8::new(*yv, cb, cr);
+    }
+    r
+}
+
+/// Convert a [YCbCr8] `Raster` to planar 4:2:0 (I420) data.
+///
+/// Chroma is downsampled by averaging each 2x2 block.  For odd dimensions,
+/// the last chroma column / row averages only the pixels present.
+///
+/// Returns *y*, *cb* and *cr* planes.
+///
+/// [YCbCr8]: type.YCbCr8.html
+pub fn to_i420(raster: &Raster<YCbCr8>) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let (cw, ch) = chroma_size(raster.width(), raster.height());
+    let mut cb = Vec::with_capacity(cw * ch);
+    let mut cr = Vec::with_capacity(cw * ch);
+    let y = to_420(raster, |b, r| {
+        cb.push(b);
+        cr.push(r);
+    });
+    (y, cb, cr)
+}
+
+/// Convert a [YCbCr8] `Raster` to semi-planar 4:2:0 (NV12) data.
+///
+/// Chroma is downsampled as in [to_i420](fn.to_i420.html).
+///
+/// Returns *y* and interleaved *cb* / *cr* planes.
+///
+/// [YCbCr8]: type.YCbCr8.html
+pub fn to_nv12(raster: &Raster<YCbCr8>) -> (Vec<u8>, Vec<u8>) {
+    let (cw, ch) = chroma_size(raster.width(), raster.height());
+    let mut cbcr = Vec::with_capacity(cw * ch * 2);
+    let y = to_420(raster, |b, r| {
+        cbcr.push(b);
+        cbcr.push(r);
+    });
+    (y, cbcr)
+}
+
+/// Get luma plane of a [YCbCr8] `Raster`, with downsampled 4:2:0 chroma
+///
+/// * `chroma` Function called with (*cb*, *cr*) for each chroma sample, in
+///   row-major order.
+fn to_420<F>(raster: &Raster<YCbCr8>, mut chroma: F) -> Vec<u8>
+where
+    F: FnMut(u8, u8),
+{
+    let (width, height) = (raster.width(), raster.height());
+    let (cw, ch) = chroma_size(width, height);
+    let y = raster
+        .pixels()
+        .iter()
+        .map(|p| u8::from(YCbCr::y(*p)))
+        .collect();
+    for row in 0..ch as i32 {
+        let rows = (row * 2)..(row * 2 + 2).min(height as i32);
+        for col in 0..cw as i32 {
+            let cols = (col * 2)..(col * 2 + 2).min(width as i32);
+            let mut vb = Vec::with_capacity(4);
+            let mut vr = Vec::with_capacity(4);
+            for py in rows.clone() {
+                for px in cols.clone() {
+                    let p = raster.pixel(px, py);
+                    vb.push(YCbCr::cb(p));
+                    vr.push(YCbCr::cr(p));
+                }
+            }
+            chroma(u8::from(Ch8::average(&vb)), u8::from(Ch8::average(&vr)));
+        }
+    }
+    y
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rgb::*;
+    use crate::ycc::*;
+
+    #[test]
+    fn bt601_reference() {
+        for (rgb, ycc) in [
+            (Rgb8::new(0, 0, 0), YCbCr8::new(0, 128, 128)),
+            (Rgb8::new(255, 255, 255), YCbCr8::new(255, 128, 128)),
+            (Rgb8::new(128, 128, 128), YCbCr8::new(128, 128, 128)),
+            (Rgb8::new(255, 0, 0), YCbCr8::new(76, 85, 255)),
+            (Rgb8::new(0, 255, 0), YCbCr8::new(150, 44, 21)),
+            (Rgb8::new(0, 0, 255), YCbCr8::new(29, 255, 107)),
+        ] {
+            assert_eq!(rgb.convert::<YCbCr8>(), ycc, "{rgb}");
+        }
+        // grays round trip exactly
+        for v in 0..=255 {
+            let rgb = Rgb8::new(v, v, v);
+            assert_eq!(rgb.convert::<YCbCr8>().convert::<Rgb8>(), rgb);
+        }
+        let p = YCbCr16::new(0xFFFF, 0x8000, 0x8000);
+        assert_eq!(p.convert::<Rgb16>(), Rgb16::new(0xFFFF, 0xFFFF, 0xFFFF));
+    }
+
+    #[test]
+    fn rgb_round_trip() {
+        let values = (0..=255).step_by(3).chain([254, 255]);
+        for r in values.clone() {
+            for g in values.clone() {
+                for b in values.clone() {
+                    let rgb = Rgb8::new(r, g, b);
+                    let p: Rgb8 = rgb.convert::<YCbCr8>().convert();
+                    for (c0, c1) in rgb.channels().iter().zip(p.channels()) {
+                        let d = u8::from(*c0).abs_diff(u8::from(*c1));
+                        assert!(d <= 1, "{rgb} => {p}");
+                    }
+                }
+            }
+        }
+    }
+
+    fn i420_round_trip(width: u32, height: u32) {
+        let (cw, ch) = chroma_size(width, height);
+        let y: Vec<u8> = (0..width * height).map(|i| (i * 7) as u8).collect();
+        let cb: Vec<u8> = (0..cw * ch).map(|i| (i * 13) as u8).collect();
+        let cr: Vec<u8> = (0..cw * ch).map(|i| (255 - i * 5) as u8).collect();
+        let r = from_i420(width, height, &y, &cb, &cr);
+        let (y1, cb1, cr1) = to_i420(&r);
+        assert_eq!(y, y1);
+        assert_eq!(cb, cb1);
+        assert_eq!(cr, cr1);
+        let r1 = from_i420(width, height, &y1, &cb1, &cr1);
+        assert_eq!(r.pixels(), r1.pixels());
+    }
+
+    #[test]
+    fn i420_even() {
+        i420_round_trip(4, 4);
+        i420_round_trip(16, 8);
+    }
+
+    #[test]
+    fn i420_odd() {
+        i420_round_trip(3, 3);
+        i420_round_trip(5, 2);
+        i420_round_trip(1, 7);
+    }
+
+    #[test]
+    fn i420_average() {
+        let mut r = Raster::<YCbCr8>::with_clear(3, 1);
+        *r.pixel_mut(0, 0) = YCbCr8::new(0, 0x10, 0x20);
+        *r.pixel_mut(1, 0) = YCbCr8::new(0, 0x20, 0x40);
+        *r.pixel_mut(2, 0) = YCbCr8::new(0, 0x33, 0x44);
+        let (_y, cb, cr) = to_i420(&r);
+        assert_eq!(cb, [0x18, 0x33]);
+        assert_eq!(cr, [0x30, 0x44]);
+    }
+
+    fn nv12_round_trip(width: u32, height: u32) {
+        let (cw, ch) = chroma_size(width, height);
+        let y: Vec<u8> = (0..width * height).map(|i| (i * 7) as u8).collect();
+        let cb: Vec<u8> = (0..cw * ch).map(|i| (i * 13) as u8).collect();
+        let cr: Vec<u8> = (0..cw * ch).map(|i| (255 - i * 5) as u8).collect();
+        let cbcr: Vec<u8> =
+            cb.iter().zip(&cr).flat_map(|(b, r)| [*b, *r]).collect();
+        let r = from_nv12(width, height, &y, &cbcr);
+        assert_eq!(r.pixels(), from_i420(width, height, &y, &cb, &cr).pixels());
+        let (y1, cbcr1) = to_nv12(&r);
+        assert_eq!(y, y1);
+        assert_eq!(cbcr, cbcr1);
+        let (_y, cb1, cr1) = to_i420(&r);
+        assert_eq!(cb, cb1);
+        assert_eq!(cr, cr1);
+    }
+
+    #[test]
+    fn nv12() {
+        nv12_round_trip(4, 4);
+        nv12_round_trip(16, 8);
+        nv12_round_trip(3, 3);
+        nv12_round_trip(5, 2);
+        nv12_round_trip(1, 7);
+    }
+}
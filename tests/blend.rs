@@ -0,0 +1,75 @@
+// blend.rs     Custom blend operation tests
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use pix::chan::Channel;
+use pix::el::Pixel;
+use pix::ops::{Blend, SrcOver};
+use pix::rgb::{Rgba32p, Rgba8p};
+use pix::Raster;
+
+/// Soft add: source added to destination, rolling off near the maximum
+#[derive(Clone, Copy)]
+struct SoftAdd;
+
+impl Blend for SoftAdd {
+    fn composite<C: Channel>(&self, dst: &mut C, _da1: C, src: &C, _sa1: C) {
+        *dst = *src + *dst * (C::MAX - *src);
+    }
+}
+
+/// Custom op which keeps destination color, with *alpha* of source
+#[derive(Clone, Copy)]
+struct TakeAlpha;
+
+impl Blend for TakeAlpha {
+    fn composite<C: Channel>(&self, dst: &mut C, _da1: C, src: &C, _sa1: C) {
+        *dst = (*dst).min(*src);
+    }
+
+    fn composite_alpha<C: Channel>(
+        &self,
+        dst: &mut C,
+        _da1: C,
+        src: &C,
+        _sa1: C,
+    ) {
+        *dst = *src;
+    }
+}
+
+#[test]
+fn custom_color() {
+    let mut r = Raster::with_color(3, 3, Rgba8p::new(0x80, 0x40, 0, 0xFF));
+    r.composite_color(
+        (1, 1, 2, 2),
+        Rgba8p::new(0x80, 0x80, 0x80, 0x80),
+        SoftAdd,
+    );
+    assert_eq!(r.pixel(0, 0), Rgba8p::new(0x80, 0x40, 0, 0xFF));
+    assert_eq!(r.pixel(1, 1), Rgba8p::new(0xC0, 0xA0, 0x80, 0xFF));
+    assert_eq!(r.pixel(2, 2), Rgba8p::new(0xC0, 0xA0, 0x80, 0xFF));
+    for p in r.pixels() {
+        assert!(p.is_premultiplied_valid());
+    }
+}
+
+#[test]
+fn custom_raster() {
+    let mut dst = Raster::with_color(4, 4, Rgba32p::new(0.25, 0.5, 0.0, 0.5));
+    let mut src = Raster::<Rgba32p>::with_clear(4, 4);
+    src.copy_color((0, 0, 2, 4), Rgba32p::new(0.5, 0.25, 0.5, 0.5));
+    let mut over = dst.clone();
+    over.composite_raster((), &src, (), SrcOver);
+    dst.composite_raster((), &src, (), SoftAdd);
+    // transparent source leaves destination unchanged
+    assert_eq!(dst.pixel(3, 0), Rgba32p::new(0.25, 0.5, 0.0, 0.5));
+    let p = dst.pixel(0, 0);
+    assert_eq!(p, Rgba32p::new(0.625, 0.625, 0.5, 0.75));
+    // alpha is the same as SrcOver
+    assert_eq!(p.alpha(), over.pixel(0, 0).alpha());
+    let mut dst = Raster::with_color(2, 1, Rgba8p::new(0x40, 0x20, 0x10, 0xFF));
+    let src = Raster::with_color(2, 1, Rgba8p::new(0x20, 0x20, 0x20, 0x20));
+    dst.composite_raster((), &src, (), TakeAlpha);
+    assert_eq!(dst.pixel(1, 0), Rgba8p::new(0x20, 0x20, 0x10, 0x20));
+}